@@ -0,0 +1,35 @@
+-- Create event_outbox table, written in the same transaction as each events insert
+CREATE TABLE event_outbox(
+  id              bigserial                     PRIMARY KEY,
+  aggregate_type  text                          NOT NULL,
+  aggregate_id    text                          NOT NULL,
+  sequence        bigint CHECK (sequence >= 0)  NOT NULL,
+  event_type      text                          NOT NULL,
+  event_version   text                          NOT NULL,
+  payload         json                          NOT NULL,
+  metadata        json                          NOT NULL,
+  created_at      timestamptz                   NOT NULL DEFAULT now(),
+  attempts        integer                       NOT NULL DEFAULT 0,
+  next_attempt_at timestamptz                   NOT NULL DEFAULT now(),
+  last_error      text,
+  published_at    timestamptz
+);
+
+CREATE INDEX event_outbox_unpublished_idx ON event_outbox (id) WHERE published_at IS NULL;
+CREATE INDEX event_outbox_unpublished_aggregate_idx ON event_outbox (aggregate_type, aggregate_id, id) WHERE published_at IS NULL;
+
+CREATE FUNCTION enqueue_event_outbox() RETURNS trigger AS $$
+BEGIN
+  INSERT INTO event_outbox (aggregate_type, aggregate_id, sequence, event_type, event_version, payload, metadata)
+  VALUES (NEW.aggregate_type, NEW.aggregate_id, NEW.sequence, NEW.event_type, NEW.event_version, NEW.payload, NEW.metadata);
+  RETURN NEW;
+END;
+$$ LANGUAGE plpgsql;
+
+-- Queue only account events for external consumers; customer, dispute and standing order events
+-- stay internal. Account events are published with the holder's name, email and mailing address.
+CREATE TRIGGER events_enqueue_outbox
+  AFTER INSERT ON events
+  FOR EACH ROW
+  WHEN (NEW.aggregate_type = 'account')
+  EXECUTE FUNCTION enqueue_event_outbox();
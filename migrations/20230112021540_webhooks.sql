@@ -0,0 +1,34 @@
+-- Create webhook subscription and delivery log tables, dispatched from the event outbox
+CREATE TABLE webhook_subscriptions(
+  id              bigserial                     PRIMARY KEY,
+  target_url      text                          NOT NULL,
+  event_types     text[]                        NOT NULL DEFAULT '{}',
+  account_ids     bigint[]                      NOT NULL DEFAULT '{}',
+  aggregate_ids   text[]                        NOT NULL DEFAULT '{}',
+  secret          text                          NOT NULL,
+  created_at      timestamptz                   NOT NULL DEFAULT now()
+);
+
+CREATE TABLE webhook_deliveries(
+  id                bigserial                   PRIMARY KEY,
+  subscription_id   bigint                      NOT NULL REFERENCES webhook_subscriptions (id) ON DELETE CASCADE,
+  aggregate_type    text                        NOT NULL,
+  aggregate_id      text                        NOT NULL,
+  sequence          bigint CHECK (sequence >= 0) NOT NULL,
+  event_type        text                        NOT NULL,
+  payload           json                        NOT NULL,
+  status            text                        NOT NULL DEFAULT 'pending',
+  attempts          integer                     NOT NULL DEFAULT 0,
+  last_status_code  integer,
+  last_error        text,
+  next_attempt_at   timestamptz                 NOT NULL DEFAULT now(),
+  created_at        timestamptz                 NOT NULL DEFAULT now(),
+  delivered_at      timestamptz,
+  UNIQUE (subscription_id, aggregate_id, sequence)
+);
+
+CREATE INDEX webhook_deliveries_pending_idx ON webhook_deliveries (next_attempt_at) WHERE status = 'pending';
+
+ALTER TABLE event_outbox ADD COLUMN webhooks_dispatched_at timestamptz;
+
+CREATE INDEX event_outbox_undispatched_webhooks_idx ON event_outbox (id) WHERE webhooks_dispatched_at IS NULL;
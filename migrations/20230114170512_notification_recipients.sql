@@ -0,0 +1,10 @@
+-- Create the contact and preference table used by customer notifications
+CREATE TABLE notification_recipients(
+  aggregate_id      text                        PRIMARY KEY,
+  user_name         text,
+  email             text,
+  account_opened    boolean                     NOT NULL DEFAULT true,
+  large_withdrawal  boolean                     NOT NULL DEFAULT true,
+  email_changed     boolean                     NOT NULL DEFAULT true,
+  updated_at        timestamptz                 NOT NULL DEFAULT now()
+);
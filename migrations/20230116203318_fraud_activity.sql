@@ -0,0 +1,11 @@
+-- Create the account activity log consulted by fraud screening
+CREATE TABLE fraud_activity(
+  aggregate_id    text                          NOT NULL,
+  sequence        bigint CHECK (sequence >= 0)  NOT NULL,
+  kind            text                          NOT NULL,
+  atm_id          text,
+  occurred_at     timestamptz                   NOT NULL DEFAULT now(),
+  PRIMARY KEY (aggregate_id, sequence)
+);
+
+CREATE INDEX fraud_activity_recent_idx ON fraud_activity (aggregate_id, occurred_at);
@@ -0,0 +1,9 @@
+-- Create the index of pending holds consulted by the hold expiry task
+CREATE TABLE account_holds(
+  aggregate_id    text          NOT NULL,
+  hold_id         text          NOT NULL,
+  expires_at      timestamptz   NOT NULL,
+  PRIMARY KEY (aggregate_id, hold_id)
+);
+
+CREATE INDEX account_holds_expiry_idx ON account_holds (expires_at);
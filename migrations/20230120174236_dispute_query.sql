@@ -0,0 +1,9 @@
+-- Create dispute_query table
+CREATE TABLE dispute_query(
+  view_id text                        NOT NULL,
+  version bigint CHECK (version >= 0) NOT NULL,
+  payload json                        NOT NULL,
+  PRIMARY KEY (view_id)
+);
+
+CREATE INDEX dispute_query_account_idx ON dispute_query (((payload->>'account_id')::bigint));
@@ -0,0 +1,19 @@
+-- Create standing_order_query table
+CREATE TABLE standing_order_query(
+  view_id text                        NOT NULL,
+  version bigint CHECK (version >= 0) NOT NULL,
+  payload json                        NOT NULL,
+  PRIMARY KEY (view_id)
+);
+
+CREATE INDEX standing_order_query_account_idx
+  ON standing_order_query (((payload->>'account_id')::bigint));
+
+-- Create the index of scheduled standing orders consulted by the standing order scheduler
+CREATE TABLE standing_order_schedule(
+  aggregate_id    text          NOT NULL,
+  next_attempt_at timestamptz   NOT NULL,
+  PRIMARY KEY (aggregate_id)
+);
+
+CREATE INDEX standing_order_schedule_due_idx ON standing_order_schedule (next_attempt_at);
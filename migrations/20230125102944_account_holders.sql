@@ -0,0 +1,10 @@
+-- Create the customer-to-accounts lookup of account holders
+CREATE TABLE account_holders(
+  aggregate_id    text          NOT NULL,
+  account_id      bigint        NOT NULL,
+  customer_id     bigint        NOT NULL,
+  role            text          NOT NULL,
+  PRIMARY KEY (account_id, customer_id)
+);
+
+CREATE INDEX account_holders_customer_id_idx ON account_holders (customer_id);
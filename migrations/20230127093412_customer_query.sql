@@ -0,0 +1,10 @@
+-- Create customer_query table
+CREATE TABLE customer_query(
+  view_id text                        NOT NULL,
+  version bigint CHECK (version >= 0) NOT NULL,
+  payload json                        NOT NULL,
+  PRIMARY KEY (view_id)
+);
+
+CREATE INDEX customer_query_kyc_status_idx ON customer_query ((payload->>'kyc_status'));
+CREATE INDEX customer_query_subject_idx ON customer_query ((payload->>'subject'));
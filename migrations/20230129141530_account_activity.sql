@@ -0,0 +1,49 @@
+-- Create the account activity table consulted by the dormancy task and the escheatment report
+CREATE TABLE account_activity(
+  aggregate_id        text          PRIMARY KEY,
+  account_id          bigint        NOT NULL,
+  user_name           text          NOT NULL,
+  mailing_address     text          NOT NULL,
+  last_activity_at    timestamptz   NOT NULL,
+  dormant_since       timestamptz,
+  escheatable_since   timestamptz
+);
+
+CREATE INDEX account_activity_last_activity_idx ON account_activity (last_activity_at)
+  WHERE dormant_since IS NULL;
+CREATE INDEX account_activity_escheatable_idx ON account_activity (escheatable_since)
+  WHERE escheatable_since IS NOT NULL;
+
+-- Backfill the activity of the accounts opened before the dormancy task maintained it
+WITH account_events AS (
+  SELECT aggregate_id, sequence, event_type, payload,
+    COALESCE((metadata->>'recv_timestamp')::timestamptz, now()) AS recorded_at
+  FROM events
+  WHERE aggregate_type = 'account'
+)
+INSERT INTO account_activity
+  (aggregate_id, account_id, user_name, mailing_address, last_activity_at, dormant_since)
+SELECT opened.aggregate_id,
+  (opened.payload->'AccountOpened'->>'account_id')::bigint,
+  opened.payload->'AccountOpened'->>'user_name',
+  COALESCE(
+    (SELECT e.payload->'MailingAddressUpdated'->>'new_address' FROM account_events e
+      WHERE e.aggregate_id = opened.aggregate_id AND e.event_type = 'mailing_address_updated'
+      ORDER BY e.sequence DESC LIMIT 1),
+    opened.payload->'AccountOpened'->>'mailing_address'
+  ),
+  (SELECT max(e.recorded_at) FROM account_events e
+    WHERE e.aggregate_id = opened.aggregate_id
+    AND e.event_type IN (
+      'account_opened', 'balance_deposited', 'cash_withdrawal', 'check_withdrawal',
+      'mailing_address_updated', 'email_updated', 'hold_placed', 'hold_captured',
+      'account_reactivated', 'account_type_changed'
+    )),
+  (SELECT CASE WHEN e.event_type = 'account_marked_dormant' THEN e.recorded_at END
+    FROM account_events e
+    WHERE e.aggregate_id = opened.aggregate_id
+    AND e.event_type IN ('account_marked_dormant', 'account_reactivated')
+    ORDER BY e.sequence DESC LIMIT 1)
+FROM account_events opened
+WHERE opened.event_type = 'account_opened'
+ON CONFLICT DO NOTHING;
@@ -0,0 +1,10 @@
+-- Create the index of account billing periods consulted by the fee assessment task; accounts that
+-- failed to be assessed are retried at retry_at, without moving the end of their billing period
+CREATE TABLE fee_assessment(
+  aggregate_id        text          NOT NULL,
+  next_assessment_at  timestamptz   NOT NULL,
+  retry_at            timestamptz,
+  PRIMARY KEY (aggregate_id)
+);
+
+CREATE INDEX fee_assessment_due_idx ON fee_assessment (COALESCE(retry_at, next_assessment_at));
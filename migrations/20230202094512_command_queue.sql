@@ -0,0 +1,16 @@
+-- Create the durable queue of bank account commands accepted for asynchronous execution
+CREATE TABLE command_queue(
+  command_id    bigint        NOT NULL,
+  account_id    bigint        NOT NULL,
+  command       jsonb         NOT NULL,
+  status        text          NOT NULL DEFAULT 'pending',
+  error         jsonb,
+  attempts      integer       NOT NULL DEFAULT 0,
+  claimed_until timestamptz,
+  accepted_at   timestamptz   NOT NULL DEFAULT now(),
+  completed_at  timestamptz,
+  PRIMARY KEY (command_id)
+);
+
+CREATE INDEX command_queue_pending_idx
+  ON command_queue (account_id, command_id) WHERE status = 'pending';
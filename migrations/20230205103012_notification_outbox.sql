@@ -0,0 +1,43 @@
+-- Render notifications from the event outbox and queue each message until it is sent
+ALTER TABLE event_outbox ADD COLUMN notifications_dispatched_at timestamptz;
+
+-- events recorded before this migration were already notified
+UPDATE event_outbox SET notifications_dispatched_at = now();
+
+CREATE INDEX event_outbox_undispatched_notifications_idx ON event_outbox (id) WHERE notifications_dispatched_at IS NULL;
+
+CREATE TABLE notification_messages(
+  id                bigserial                   PRIMARY KEY,
+  aggregate_id      text                        NOT NULL,
+  sequence          bigint CHECK (sequence >= 0) NOT NULL,
+  message           json                        NOT NULL,
+  status            text                        NOT NULL DEFAULT 'pending',
+  attempts          integer                     NOT NULL DEFAULT 0,
+  last_error        text,
+  next_attempt_at   timestamptz                 NOT NULL DEFAULT now(),
+  created_at        timestamptz                 NOT NULL DEFAULT now(),
+  sent_at           timestamptz
+);
+
+CREATE INDEX notification_messages_pending_idx ON notification_messages (next_attempt_at) WHERE status = 'pending';
+
+-- Backfill the contact details of every account from its events, keeping any saved preferences
+INSERT INTO notification_recipients (aggregate_id, user_name, email)
+SELECT opened.aggregate_id,
+    opened.payload -> 'AccountOpened' ->> 'user_name',
+    COALESCE(latest.new_email, opened.payload -> 'AccountOpened' ->> 'email')
+FROM events opened
+LEFT JOIN LATERAL (
+    SELECT updated.payload -> 'EmailUpdated' ->> 'new_email' AS new_email
+    FROM events updated
+    WHERE updated.aggregate_type = opened.aggregate_type
+        AND updated.aggregate_id = opened.aggregate_id
+        AND updated.event_type = 'email_updated'
+    ORDER BY updated.sequence DESC
+    LIMIT 1
+) latest ON true
+WHERE opened.aggregate_type = 'account' AND opened.event_type = 'account_opened'
+ON CONFLICT (aggregate_id) DO UPDATE
+SET user_name = EXCLUDED.user_name,
+    email = EXCLUDED.email,
+    updated_at = now();
@@ -1,33 +1,55 @@
 mod app_state;
+mod auth;
 mod bank_routes;
+mod command_routes;
+mod customer_routes;
+mod dispute_routes;
 pub mod errors;
+mod health;
 mod health_routes;
+mod migration;
+mod rate_limit;
 mod result;
+mod standing_order_routes;
+mod webhook_routes;
 
-use crate::settings::HttpApiSettings;
+use crate::commands;
+use crate::dormancy;
+use crate::fees;
+use crate::holds;
+use crate::notifications::{self, NotificationMailer};
+use crate::outbox::{self, OutboxConsumer, OutboxError, OutboxPublisher};
+use crate::settings::{
+    AccountTypeSettings, AuthSettings, CommandQueueSettings, CommandRetrySettings, FeeSettings,
+    FraudSettings, HttpApiSettings, IdentityVerifierSettings, NotificationSettings,
+    StandingOrderSettings,
+};
+use crate::standing_orders;
+use crate::webhooks::{self, WebhookError};
 pub use app_state::{AppState, ACCOUNT_QUERY_VIEW, ACCOUNT_QUERY_VIEW_PAYLOAD};
 pub use errors::ApiError;
-pub use result::HttpResult;
+pub use migration::{migrate_database, MIGRATOR};
+pub use result::{HttpResult, ProblemDetails, PROBLEM_JSON};
 
 use crate::Settings;
 use axum::error_handling::HandleErrorLayer;
-use axum::http::{Response, StatusCode, Uri};
-use axum::{BoxError, Router};
+use axum::http::{StatusCode, Uri};
+use axum::response::{IntoResponse, Response};
+use axum::{middleware, BoxError, Router};
 use serde::Deserialize;
 use settings_loader::common::database::DatabaseSettings;
 use sqlx::PgPool;
-use std::net::TcpListener;
+use std::net::{SocketAddr, TcpListener};
 use strum::Display;
 use tokio::signal;
 use tokio::task::JoinHandle;
 use tower::ServiceBuilder;
-use tower_governor::governor::GovernorConfigBuilder;
-use tower_governor::key_extractor::SmartIpKeyExtractor;
-use tower_governor::GovernorLayer;
+use tower_http::request_id::MakeRequestUuid;
 use tower_http::trace::{DefaultMakeSpan, DefaultOnResponse, TraceLayer};
 use tower_http::ServiceBuilderExt;
-use utoipa::OpenApi;
+use utoipa::{IntoParams, OpenApi};
 use utoipa_swagger_ui::{SwaggerUi, Url as SwaggerUrl};
+use validator::{ValidationError, ValidationErrors};
 
 pub type HttpJoinHandle = JoinHandle<Result<(), ApiError>>;
 
@@ -47,12 +69,19 @@ impl Version {
 pub struct Application {
     port: u16,
     server: HttpJoinHandle,
+    workers: Vec<JoinHandle<()>>,
 }
 
 impl Application {
     #[tracing::instrument(level = "debug", skip(settings))]
     pub async fn build(settings: &Settings) -> Result<Self, ApiError> {
+        settings.correlation.install_id_generator()?;
+
         let connection_pool = get_connection_pool(&settings.database);
+        if settings.migrations.run_on_startup {
+            migrate_database(&connection_pool).await?;
+        }
+
         let address = settings.http_api.server.address();
         let listener = tokio::net::TcpListener::bind(&address).await?;
         tracing::info!(
@@ -62,14 +91,103 @@ impl Application {
         let std_listener = listener.into_std()?;
         let port = std_listener.local_addr()?.port();
 
-        let server = run_http_server(
-            std_listener,
-            connection_pool,
-            &RunParameters::from_settings(settings),
-        )
-        .await?;
+        let mut workers = Vec::new();
+        if settings.outbox.enabled {
+            let publisher =
+                OutboxPublisher::from_settings(&settings.outbox).map_err(OutboxError::from)?;
+            workers.push(outbox::spawn_relay(
+                connection_pool.clone(),
+                settings.outbox.clone(),
+                publisher,
+            ));
+        }
+
+        if settings.webhooks.enabled {
+            let client = reqwest::Client::builder()
+                .timeout(settings.webhooks.request_timeout)
+                .build()
+                .map_err(WebhookError::from)?;
+            workers.push(webhooks::spawn_delivery_worker(
+                connection_pool.clone(),
+                settings.webhooks.clone(),
+                client,
+            ));
+        }
+
+        if settings.notifications.enabled {
+            let mailer = NotificationMailer::from_settings(&settings.notifications.mailer)?;
+            workers.push(notifications::spawn_notification_worker(
+                connection_pool.clone(),
+                settings.notifications.clone(),
+                mailer,
+            ));
+        }
+
+        if settings.outbox.prune_enabled {
+            let consumers = [
+                (settings.outbox.enabled, OutboxConsumer::Relay),
+                (settings.webhooks.enabled, OutboxConsumer::Webhooks),
+                (
+                    settings.notifications.enabled,
+                    OutboxConsumer::Notifications,
+                ),
+            ]
+            .into_iter()
+            .filter_map(|(enabled, consumer)| enabled.then_some(consumer))
+            .collect();
+            workers.push(outbox::spawn_pruner(
+                connection_pool.clone(),
+                settings.outbox.clone(),
+                consumers,
+            ));
+        }
+
+        let params = RunParameters::from_settings(settings);
+        let state = app_state::initialize_app_state(connection_pool.clone(), &params).await?;
+
+        if settings.holds.expiry_enabled {
+            workers.push(holds::spawn_expiry_worker(
+                connection_pool.clone(),
+                state.bank_account_agg.clone(),
+                settings.holds.clone(),
+            ));
+        }
+
+        if settings.standing_orders.scheduler_enabled {
+            workers.push(standing_orders::spawn_scheduler(
+                connection_pool.clone(),
+                state.standing_order_agg.clone(),
+                settings.standing_orders.clone(),
+            ));
+        }
+
+        if settings.dormancy.worker_enabled {
+            workers.push(dormancy::spawn_dormancy_worker(
+                connection_pool.clone(),
+                state.bank_account_agg.clone(),
+                settings.dormancy.clone(),
+            ));
+        }
+
+        if settings.command_queue.enabled {
+            workers.extend(commands::spawn_command_workers(
+                connection_pool.clone(),
+                state.bank_account_agg.clone(),
+                settings.command_queue.clone(),
+            ));
+        }
+
+        if settings.fees.worker_enabled {
+            workers.push(fees::spawn_fee_worker(
+                connection_pool,
+                state.bank_account_agg.clone(),
+                settings.fees.clone(),
+            ));
+        }
 
-        Ok(Self { port, server })
+        let server = run_http_server(std_listener, state, &params).await?;
+
+        Ok(Self { port, server, workers })
     }
 
     pub const fn port(&self) -> u16 {
@@ -77,7 +195,11 @@ impl Application {
     }
 
     pub async fn run_until_stopped(self) -> Result<(), ApiError> {
-        self.server.await?
+        let result = self.server.await;
+        for worker in self.workers {
+            worker.abort();
+        }
+        result?
     }
 }
 
@@ -89,45 +211,62 @@ pub fn get_connection_pool(settings: &DatabaseSettings) -> PgPool {
 #[derive(Debug, Clone)]
 pub struct RunParameters {
     pub http_api: HttpApiSettings,
+    pub notifications: NotificationSettings,
+    pub fraud: FraudSettings,
+    pub auth: AuthSettings,
+    pub standing_orders: StandingOrderSettings,
+    pub account_types: AccountTypeSettings,
+    pub fees: FeeSettings,
+    pub command_retry: CommandRetrySettings,
+    pub command_queue: CommandQueueSettings,
+    pub identity_verifier: IdentityVerifierSettings,
 }
 
 impl RunParameters {
     pub fn from_settings(settings: &Settings) -> Self {
-        Self { http_api: settings.http_api.clone() }
+        Self {
+            http_api: settings.http_api.clone(),
+            notifications: settings.notifications.clone(),
+            fraud: settings.fraud.clone(),
+            auth: settings.auth.clone(),
+            standing_orders: settings.standing_orders.clone(),
+            account_types: settings.account_types.clone(),
+            fees: settings.fees.clone(),
+            command_retry: settings.command_retry,
+            command_queue: settings.command_queue.clone(),
+            identity_verifier: settings.identity_verifier,
+        }
     }
 }
 
 #[tracing::instrument(level = "trace")]
 pub async fn run_http_server(
-    listener: TcpListener, db_pool: PgPool, params: &RunParameters,
+    listener: TcpListener, state: AppState, params: &RunParameters,
 ) -> Result<HttpJoinHandle, ApiError> {
-    let state = app_state::initialize_app_state(db_pool).await?;
-
-    let governor_conf = Box::new(
-        GovernorConfigBuilder::default()
-            .key_extractor(SmartIpKeyExtractor)
-            .finish()
-            .unwrap(),
-    );
-
     let middleware_stack = ServiceBuilder::new()
         .layer(HandleErrorLayer::new(handle_api_error))
-        .layer(GovernorLayer {
-            config: Box::leak(governor_conf) // okay to leak because it is created once and then used by layer
-        })
         .timeout(params.http_api.timeout)
         .compression()
         .layer(
             TraceLayer::new_for_http()
                 .make_span_with(DefaultMakeSpan::new().include_headers(true))
-                .on_response(DefaultOnResponse::new().include_headers(true))
+                .on_response(DefaultOnResponse::new().include_headers(true)),
         )
-        // .set_x_request_id(unimplemented!())
+        .set_x_request_id(MakeRequestUuid)
         .propagate_x_request_id();
 
     let api_routes = Router::new()
-        .nest("/health", health_routes::api())
         .nest("/bank", bank_routes::api())
+        .nest("/commands", command_routes::api())
+        .nest("/customers", customer_routes::api())
+        .nest("/disputes", dispute_routes::api())
+        .nest("/standing_orders", standing_order_routes::api())
+        .nest("/webhooks", webhook_routes::api())
+        .layer(middleware::from_fn_with_state(
+            state.clone(),
+            rate_limit::limit_requests,
+        ))
+        .nest("/health", health_routes::api())
         .with_state(state);
 
     let app = Router::new()
@@ -136,6 +275,26 @@ pub async fn run_http_server(
                 SwaggerUrl::with_primary("bank_api", "/api-doc/bank-openapi.json", true),
                 bank_routes::BankApiDoc::openapi(),
             ),
+            (
+                SwaggerUrl::new("command_api", "/api-doc/command-openapi.json"),
+                command_routes::CommandApiDoc::openapi(),
+            ),
+            (
+                SwaggerUrl::new("customer_api", "/api-doc/customer-openapi.json"),
+                customer_routes::CustomerApiDoc::openapi(),
+            ),
+            (
+                SwaggerUrl::new("dispute_api", "/api-doc/dispute-openapi.json"),
+                dispute_routes::DisputeApiDoc::openapi(),
+            ),
+            (
+                SwaggerUrl::new("standing_order_api", "/api-doc/standing-order-openapi.json"),
+                standing_order_routes::StandingOrderApiDoc::openapi(),
+            ),
+            (
+                SwaggerUrl::new("webhook_api", "/api-doc/webhook-openapi.json"),
+                webhook_routes::WebhookApiDoc::openapi(),
+            ),
             (
                 SwaggerUrl::new("health_api", "/api-doc/health-openapi.json"),
                 health_routes::HealthApiDoc::openapi(),
@@ -143,12 +302,13 @@ pub async fn run_http_server(
         ]))
         .nest("/api/v1", api_routes)
         .fallback(fallback)
+        .layer(middleware::from_fn(result::track_request_context))
         .layer(middleware_stack);
 
     let handle = tokio::spawn(async move {
         tracing::debug!(app_routes=?app, "starting API server...");
         let builder = axum::Server::from_tcp(listener)?;
-        let server = builder.serve(app.into_make_service());
+        let server = builder.serve(app.into_make_service_with_connect_info::<SocketAddr>());
         let graceful = server.with_graceful_shutdown(shutdown_signal());
         graceful.await?;
         tracing::info!("{:?} API shutting down", std::env::current_exe());
@@ -158,32 +318,81 @@ pub async fn run_http_server(
     Ok(handle)
 }
 
-#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Eq, IntoParams, Deserialize)]
+#[into_params(parameter_in = Query)]
 pub struct Pagination {
+    /// Page to return, starting at 1.
+    #[serde(default = "Pagination::default_page")]
     page: usize,
+
+    /// Number of items per page, capped at 500.
+    #[serde(default = "Pagination::default_per_page")]
     per_page: usize,
 }
 
-async fn fallback(uri: Uri) -> (StatusCode, String) {
-    (StatusCode::NOT_FOUND, format!("No route found for {uri}"))
+impl Default for Pagination {
+    fn default() -> Self {
+        Self {
+            page: Self::default_page(),
+            per_page: Self::default_per_page(),
+        }
+    }
 }
 
-async fn handle_api_error(error: BoxError) -> Response<String> {
-    if error.is::<tower::timeout::error::Elapsed>() {
-        let response = Response::new(format!("REQUEST TIMEOUT: {error}"));
-        let (mut parts, body) = response.into_parts();
-        parts.status = StatusCode::REQUEST_TIMEOUT;
-        Response::from_parts(parts, body)
-    } else if error.is::<tower_governor::errors::GovernorError>() {
-        tower_governor::errors::display_error(error)
-    } else {
-        let response = Response::new(format!("INTERNAL SERVER ERROR: {error}"));
-        let (mut parts, body) = response.into_parts();
-        parts.status = StatusCode::INTERNAL_SERVER_ERROR;
-        Response::from_parts(parts, body)
+impl Pagination {
+    const MAX_PER_PAGE: usize = 500;
+
+    const fn default_page() -> usize {
+        1
+    }
+
+    const fn default_per_page() -> usize {
+        50
+    }
+
+    pub fn limit(&self) -> i64 {
+        self.per_page.clamp(1, Self::MAX_PER_PAGE) as i64
+    }
+
+    /// Number of items preceding the page, rejecting a page too far out to be offset.
+    pub fn offset(&self) -> Result<i64, ValidationErrors> {
+        i64::try_from(self.page.saturating_sub(1))
+            .ok()
+            .and_then(|preceding_pages| preceding_pages.checked_mul(self.limit()))
+            .ok_or_else(|| {
+                let mut errors = ValidationErrors::new();
+                errors.add("page", ValidationError::new("range"));
+                errors
+            })
     }
 }
 
+async fn fallback(uri: Uri) -> Response {
+    ProblemDetails::new(
+        StatusCode::NOT_FOUND,
+        "route_not_found",
+        format!("No route found for {uri}"),
+    )
+    .into_response()
+}
+
+async fn handle_api_error(error: BoxError) -> Response {
+    let problem = if error.is::<tower::timeout::error::Elapsed>() {
+        ProblemDetails::new(
+            StatusCode::REQUEST_TIMEOUT,
+            "request_timeout",
+            error.to_string(),
+        )
+    } else {
+        ProblemDetails::new(
+            StatusCode::INTERNAL_SERVER_ERROR,
+            "internal_error",
+            error.to_string(),
+        )
+    };
+    problem.into_response()
+}
+
 async fn shutdown_signal() {
     let ctrl_c = async {
         signal::ctrl_c().await.expect("failed to install Ctrl+C handler");
@@ -1,7 +1,27 @@
-use crate::application::ApiError;
-use crate::model::{BankAccount, BankAccountAggregate};
-use crate::queries::{AccountQuery, BankAccountViewProjection, EventTracingQuery};
-use crate::services::{BankAccountServices, HappyPathBankAccountServices};
+use crate::application::auth::Authenticator;
+use crate::application::health::HealthRegistry;
+use crate::application::rate_limit::RateLimiter;
+use crate::application::{ApiError, RunParameters};
+use crate::commands::CommandQueue;
+use crate::dormancy::AccountActivityQuery;
+use crate::fees::FeeAssessmentQuery;
+use crate::holds::HoldExpiryQuery;
+use crate::model::{
+    BankAccount, BankAccountAggregate, CommandExecutor, Customer, CustomerAggregate, Dispute,
+    DisputeAggregate, StandingOrder, StandingOrderAggregate,
+};
+use crate::queries::{
+    AccountHoldersQuery, AccountQuery, BankAccountViewProjection, CustomerQuery,
+    CustomerViewProjection, DisputeQuery, DisputeViewProjection, EventTracingQuery,
+    StandingOrderQuery, StandingOrderViewProjection, CUSTOMER_QUERY_VIEW, DISPUTE_QUERY_VIEW,
+    STANDING_ORDER_QUERY_VIEW,
+};
+use crate::services::{
+    AccountPolicies, BankAccountServices, CommandClaims, CustomerDirectory, CustomerServices,
+    DisputeServices, FraudActivityQuery, FraudRulesEngine, HappyPathBankAccountServices,
+    IdentityVerifiers, StandingOrderServices, ValidationServices,
+};
+use crate::standing_orders::StandingOrderScheduleQuery;
 use axum::extract::FromRef;
 use cqrs_es::Query;
 use postgres_es::PostgresViewRepository;
@@ -12,8 +32,10 @@ use std::sync::Arc;
 pub const ACCOUNT_QUERY_VIEW: &str = "account_query";
 pub const ACCOUNT_QUERY_VIEW_PAYLOAD: &str = "payload";
 
-#[tracing::instrument(level = "debug")]
-pub async fn initialize_app_state(pool: PgPool) -> Result<AppState, ApiError> {
+#[tracing::instrument(level = "debug", skip(params))]
+pub async fn initialize_app_state(
+    pool: PgPool, params: &RunParameters,
+) -> Result<AppState, ApiError> {
     let tracing_query = EventTracingQuery;
     let account_view_projection = Arc::new(PostgresViewRepository::new(
         ACCOUNT_QUERY_VIEW,
@@ -24,14 +46,100 @@ pub async fn initialize_app_state(pool: PgPool) -> Result<AppState, ApiError> {
         |err| tracing::error!(error=?err, "account query failed"),
     ));
 
-    let queries: Vec<Box<dyn Query<BankAccount>>> =
-        vec![Box::new(tracing_query), Box::new(account_query)];
-    let services = BankAccountServices::HappyPath(HappyPathBankAccountServices);
+    let fraud_activity_query = FraudActivityQuery::new(pool.clone());
+    let hold_expiry_query = HoldExpiryQuery::new(pool.clone());
+    let account_holders_query = AccountHoldersQuery::new(pool.clone());
+    let account_activity_query = AccountActivityQuery::new(pool.clone());
+    let fee_assessment_query = FeeAssessmentQuery::new(pool.clone(), params.fees.billing_period);
+
+    let queries: Vec<Box<dyn Query<BankAccount>>> = vec![
+        Box::new(tracing_query),
+        Box::new(account_query),
+        Box::new(fraud_activity_query),
+        Box::new(hold_expiry_query),
+        Box::new(account_holders_query),
+        Box::new(account_activity_query),
+        Box::new(fee_assessment_query),
+    ];
+
+    let validation = ValidationServices::from(HappyPathBankAccountServices);
+    let services = BankAccountServices::new(
+        validation.clone(),
+        FraudRulesEngine::new(pool.clone(), &params.fraud),
+        CustomerDirectory::new(pool.clone()),
+        CommandClaims::new(pool.clone()),
+        AccountPolicies::new(&params.account_types, &params.fees.schedule),
+    );
+
+    let bank_account_agg: BankAccountAggregate = CommandExecutor::new(
+        postgres_es::postgres_cqrs(pool.clone(), queries, services),
+        params.command_retry,
+    );
+
+    let customer_view_projection = Arc::new(PostgresViewRepository::new(
+        CUSTOMER_QUERY_VIEW,
+        pool.clone(),
+    ));
+    let mut customer_query = CustomerQuery::new(customer_view_projection.clone());
+    customer_query.use_error_handler(Box::new(
+        |err| tracing::error!(error=?err, "customer query failed"),
+    ));
+    let customer_queries: Vec<Box<dyn Query<Customer>>> = vec![Box::new(customer_query)];
+    let customer_services =
+        CustomerServices::new(IdentityVerifiers::from_settings(&params.identity_verifier));
+
+    let dispute_view_projection = Arc::new(PostgresViewRepository::new(
+        DISPUTE_QUERY_VIEW,
+        pool.clone(),
+    ));
+    let mut dispute_query = DisputeQuery::new(dispute_view_projection.clone());
+    dispute_query.use_error_handler(Box::new(
+        |err| tracing::error!(error=?err, "dispute query failed"),
+    ));
+    let dispute_queries: Vec<Box<dyn Query<Dispute>>> = vec![Box::new(dispute_query)];
+    let dispute_services = DisputeServices::new(pool.clone(), bank_account_agg.clone());
+
+    let standing_order_view_projection = Arc::new(PostgresViewRepository::new(
+        STANDING_ORDER_QUERY_VIEW,
+        pool.clone(),
+    ));
+    let mut standing_order_query = StandingOrderQuery::new(standing_order_view_projection.clone());
+    standing_order_query.use_error_handler(Box::new(
+        |err| tracing::error!(error=?err, "standing order query failed"),
+    ));
+    let standing_order_queries: Vec<Box<dyn Query<StandingOrder>>> = vec![
+        Box::new(standing_order_query),
+        Box::new(StandingOrderScheduleQuery::new(pool.clone())),
+    ];
+    let standing_order_services =
+        StandingOrderServices::new(bank_account_agg.clone(), &params.standing_orders);
 
     Ok(AppState {
-        bank_account_agg: Arc::new(postgres_es::postgres_cqrs(pool.clone(), queries, services)),
+        bank_account_agg,
         bank_account_view: account_view_projection,
+        customer_agg: Arc::new(postgres_es::postgres_cqrs(
+            pool.clone(),
+            customer_queries,
+            customer_services,
+        )),
+        customer_view: customer_view_projection,
+        dispute_agg: Arc::new(postgres_es::postgres_cqrs(
+            pool.clone(),
+            dispute_queries,
+            dispute_services,
+        )),
+        dispute_view: dispute_view_projection,
+        standing_order_agg: Arc::new(postgres_es::postgres_cqrs(
+            pool.clone(),
+            standing_order_queries,
+            standing_order_services,
+        )),
+        standing_order_view: standing_order_view_projection,
+        health: HealthRegistry::new(pool.clone(), validation),
+        command_queue: CommandQueue::new(pool.clone(), params.command_queue.enabled),
         db_pool: pool,
+        authenticator: Authenticator::from_settings(&params.auth),
+        rate_limiter: RateLimiter::from_settings(&params.http_api),
     })
 }
 
@@ -39,7 +147,17 @@ pub async fn initialize_app_state(pool: PgPool) -> Result<AppState, ApiError> {
 pub struct AppState {
     pub bank_account_agg: BankAccountAggregate,
     pub bank_account_view: BankAccountViewProjection,
+    pub customer_agg: CustomerAggregate,
+    pub customer_view: CustomerViewProjection,
+    pub dispute_agg: DisputeAggregate,
+    pub dispute_view: DisputeViewProjection,
+    pub standing_order_agg: StandingOrderAggregate,
+    pub standing_order_view: StandingOrderViewProjection,
     pub db_pool: PgPool,
+    pub health: HealthRegistry,
+    pub command_queue: CommandQueue,
+    pub authenticator: Authenticator,
+    pub rate_limiter: RateLimiter,
 }
 
 impl fmt::Debug for AppState {
@@ -60,8 +178,56 @@ impl FromRef<AppState> for BankAccountViewProjection {
     }
 }
 
+impl FromRef<AppState> for CustomerAggregate {
+    fn from_ref(state: &AppState) -> Self {
+        state.customer_agg.clone()
+    }
+}
+
+impl FromRef<AppState> for CustomerViewProjection {
+    fn from_ref(state: &AppState) -> Self {
+        state.customer_view.clone()
+    }
+}
+
+impl FromRef<AppState> for DisputeAggregate {
+    fn from_ref(state: &AppState) -> Self {
+        state.dispute_agg.clone()
+    }
+}
+
+impl FromRef<AppState> for DisputeViewProjection {
+    fn from_ref(state: &AppState) -> Self {
+        state.dispute_view.clone()
+    }
+}
+
+impl FromRef<AppState> for StandingOrderAggregate {
+    fn from_ref(state: &AppState) -> Self {
+        state.standing_order_agg.clone()
+    }
+}
+
+impl FromRef<AppState> for StandingOrderViewProjection {
+    fn from_ref(state: &AppState) -> Self {
+        state.standing_order_view.clone()
+    }
+}
+
 impl FromRef<AppState> for PgPool {
     fn from_ref(state: &AppState) -> Self {
         state.db_pool.clone()
     }
 }
+
+impl FromRef<AppState> for CommandQueue {
+    fn from_ref(state: &AppState) -> Self {
+        state.command_queue.clone()
+    }
+}
+
+impl FromRef<AppState> for Authenticator {
+    fn from_ref(state: &AppState) -> Self {
+        state.authenticator.clone()
+    }
+}
@@ -0,0 +1,126 @@
+use crate::application::ApiError;
+use crate::errors::BankError;
+use crate::settings::AuthSettings;
+use async_trait::async_trait;
+use axum::extract::{FromRef, FromRequestParts};
+use axum::http::header::AUTHORIZATION;
+use axum::http::request::Parts;
+use axum::http::HeaderMap;
+use secrecy::ExposeSecret;
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+use std::sync::Arc;
+
+/// Scope required for administrative account actions, such as freezing or reversing transactions.
+pub const ADMIN_SCOPE: &str = "admin:account";
+
+/// Resolves bearer tokens to the principal configured for them.
+#[derive(Clone, Default)]
+pub struct Authenticator {
+    principals: Arc<HashMap<String, Principal>>,
+}
+
+impl fmt::Debug for Authenticator {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Authenticator")
+            .field("nr_principals", &self.principals.len())
+            .finish()
+    }
+}
+
+impl Authenticator {
+    pub fn from_settings(settings: &AuthSettings) -> Self {
+        let principals = settings
+            .tokens
+            .iter()
+            .map(|token| {
+                let principal = Principal {
+                    subject: token.subject.clone(),
+                    scopes: token.scopes.iter().cloned().collect(),
+                };
+                (token.token.expose_secret().clone(), principal)
+            })
+            .collect();
+
+        Self { principals: Arc::new(principals) }
+    }
+
+    pub fn authenticate(&self, token: &str) -> Option<&Principal> {
+        self.principals.get(token)
+    }
+
+    /// The principal of the bearer token presented in the request headers, if recognized.
+    pub fn authenticate_headers(&self, headers: &HeaderMap) -> Option<&Principal> {
+        bearer_token(headers).and_then(|token| self.authenticate(token))
+    }
+}
+
+fn bearer_token(headers: &HeaderMap) -> Option<&str> {
+    headers
+        .get(AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.strip_prefix("Bearer "))
+        .map(|token| token.trim())
+}
+
+/// The authenticated caller of a request.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Principal {
+    pub subject: String,
+    pub scopes: HashSet<String>,
+}
+
+impl Principal {
+    pub fn require_scope(&self, scope: &str) -> Result<(), ApiError> {
+        if self.scopes.contains(scope) {
+            Ok(())
+        } else {
+            Err(ApiError::Forbidden {
+                subject: self.subject.clone(),
+                scope: scope.to_string(),
+            })
+        }
+    }
+}
+
+#[async_trait]
+impl<S> FromRequestParts<S> for Principal
+where
+    Authenticator: FromRef<S>,
+    S: Send + Sync,
+{
+    type Rejection = BankError;
+
+    async fn from_request_parts(parts: &mut Parts, state: &S) -> Result<Self, Self::Rejection> {
+        let token = bearer_token(&parts.headers).ok_or(ApiError::Unauthenticated)?;
+
+        let authenticator = Authenticator::from_ref(state);
+        let principal = authenticator.authenticate(token).ok_or(ApiError::Unauthenticated)?;
+        tracing::debug!(subject=%principal.subject, "authenticated request principal");
+        Ok(principal.clone())
+    }
+}
+
+/// The caller of a route that was open to anonymous requests before bearer tokens were introduced.
+/// Anonymous requests are still accepted there, but a presented token must be recognized.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Caller(pub Option<Principal>);
+
+#[async_trait]
+impl<S> FromRequestParts<S> for Caller
+where
+    Authenticator: FromRef<S>,
+    S: Send + Sync,
+{
+    type Rejection = BankError;
+
+    async fn from_request_parts(parts: &mut Parts, state: &S) -> Result<Self, Self::Rejection> {
+        if bearer_token(&parts.headers).is_none() {
+            return Ok(Self(None));
+        }
+
+        Principal::from_request_parts(parts, state)
+            .await
+            .map(|principal| Self(Some(principal)))
+    }
+}
@@ -1,19 +1,30 @@
 use crate::application::app_state::AppState;
-use crate::application::result::OptionalResult;
-use crate::application::{ApiError, ACCOUNT_QUERY_VIEW, ACCOUNT_QUERY_VIEW_PAYLOAD};
+use crate::application::auth::{Caller, Principal, ADMIN_SCOPE};
+use crate::application::command_routes::{self, CommandAccepted};
+use crate::application::result::{OptionalResult, ProblemDetails};
+use crate::application::{ApiError, Pagination, ACCOUNT_QUERY_VIEW, ACCOUNT_QUERY_VIEW_PAYLOAD};
+use crate::commands::CommandQueue;
+use crate::dormancy::{self, EscheatmentEntry};
 use crate::errors::BankError;
 use crate::model::{bank_account, BankAccount};
 use crate::model::{
-    AccountId, AtmId, BankAccountAggregate, BankAccountCommand, CheckNumber, EmailAddress,
-    MailingAddress,
+    AccountHolder, AccountId, AccountType, AtmId, BankAccountAggregate, BankAccountCommand,
+    BankAccountError, BankAccountEvent, CheckNumber, CustomerError, CustomerId, EmailAddress,
+    FeeKind, FlagId, FlaggedTransaction, FlaggedTransactionEntry, FundsHold, HoldId, HolderChange,
+    HolderChangeId, HolderRole, MailingAddress, PendingHolderChange,
 };
-use crate::queries::BankAccountViewProjection;
-use crate::BankAccountView;
-use axum::extract::rejection::JsonRejection;
-use axum::extract::{rejection::PathRejection, Path, State};
+use crate::notifications::{self, NotificationPreferences};
+use crate::queries::{
+    self, AccountDormancy, AccountFreeze, AccountHolding, AsOfQuery, BankAccountViewProjection,
+    EventFilter, FreezeAction, FreezeAuditEntry,
+};
+use crate::{BankAccountView, LedgerEntry};
+use axum::extract::rejection::{JsonRejection, QueryRejection};
+use axum::extract::{rejection::PathRejection, Path, Query, State};
 use axum::response::IntoResponse;
 use axum::routing;
 use axum::{Json, Router};
+use chrono::{DateTime, Utc};
 use cqrs_es::persist::ViewRepository;
 use itertools::Itertools;
 use money2::Money;
@@ -92,6 +103,26 @@ impl utoipa::ToSchema for ApiMoney {
     paths(
         create_bank_account,
         serve_bank_account,
+        serve_account_events,
+        serve_notification_preferences,
+        update_notification_preferences,
+        release_flagged_transaction,
+        reject_flagged_transaction,
+        freeze_account,
+        unfreeze_account,
+        reactivate_account,
+        change_account_type,
+        set_fee_waivers,
+        serve_escheatment_report,
+        place_hold,
+        capture_hold,
+        release_hold,
+        reverse_transaction,
+        serve_account_holders,
+        add_account_holder,
+        remove_account_holder,
+        approve_holder_change,
+        serve_customer_accounts,
         update_email,
         update_mailing_address,
         deposit_amount,
@@ -101,9 +132,17 @@ impl utoipa::ToSchema for ApiMoney {
     ),
     components(
         schemas(
-            AccountId, EmailAddress, MailingAddress, AtmId, ApiMoney, CheckNumber,
+            AccountId, CustomerId, EmailAddress, MailingAddress, AtmId, ApiMoney, CheckNumber,
             AccountApplication, CashWithdrawalRequest, CheckWithdrawalRequest,
-            crate::errors::BankError, ApiError,
+            BankAccountEvent, AccountEventEnvelope, BankAccountView, LedgerEntry,
+            NotificationPreferences, FlagId, FlaggedTransaction, FlaggedTransactionEntry,
+            FlagRejection, FreezeRequest, AccountFreeze, FreezeAction, FreezeAuditEntry,
+            AccountDormancy, EscheatmentEntry, AccountType, AccountTypeRequest,
+            FeeKind, FeeWaiverRequest,
+            HoldId, FundsHold, HoldRequest, ReversalRequest,
+            AccountHolder, HolderRole, HolderChange, HolderChangeId, PendingHolderChange,
+            AccountHolding, CommandAccepted,
+            ProblemDetails,
         )
     ),
     modifiers(&SecurityAddon),
@@ -113,7 +152,7 @@ impl utoipa::ToSchema for ApiMoney {
 )]
 pub struct BankApiDoc;
 
-struct SecurityAddon;
+pub struct SecurityAddon;
 
 impl Modify for SecurityAddon {
     fn modify(&self, openapi: &mut utoipa::openapi::OpenApi) {
@@ -128,6 +167,10 @@ impl Modify for SecurityAddon {
                             ("read:account", "view a bank account"),
                             ("deposit:account", "deposit money into an account"),
                             ("withdrawal:account", "withdrawal money from an account"),
+                            (
+                                ADMIN_SCOPE,
+                                "freeze, unfreeze and correct accounts, and work disputes",
+                            ),
                         ]),
                     ),
                 )])),
@@ -143,6 +186,54 @@ pub fn api() -> Router<AppState> {
         // )
         .route("/", routing::post(create_bank_account))
         .route("/:account_id", routing::get(serve_bank_account))
+        .route("/:account_id/events", routing::get(serve_account_events))
+        .route(
+            "/:account_id/notifications",
+            routing::get(serve_notification_preferences).put(update_notification_preferences),
+        )
+        .route(
+            "/:account_id/flags/:flag_id/release",
+            routing::post(release_flagged_transaction),
+        )
+        .route(
+            "/:account_id/flags/:flag_id/reject",
+            routing::post(reject_flagged_transaction),
+        )
+        .route("/:account_id/freeze", routing::post(freeze_account))
+        .route("/:account_id/unfreeze", routing::post(unfreeze_account))
+        .route("/:account_id/reactivate", routing::post(reactivate_account))
+        .route("/:account_id/account_type", routing::post(change_account_type))
+        .route("/:account_id/fee_waivers", routing::post(set_fee_waivers))
+        .route("/escheatment", routing::get(serve_escheatment_report))
+        .route("/:account_id/holds", routing::post(place_hold))
+        .route(
+            "/:account_id/holds/:hold_id/capture",
+            routing::post(capture_hold),
+        )
+        .route(
+            "/:account_id/holds/:hold_id/release",
+            routing::post(release_hold),
+        )
+        .route(
+            "/:account_id/transactions/:sequence/reverse",
+            routing::post(reverse_transaction),
+        )
+        .route(
+            "/:account_id/holders",
+            routing::get(serve_account_holders).post(add_account_holder),
+        )
+        .route(
+            "/:account_id/holders/:customer_id",
+            routing::delete(remove_account_holder),
+        )
+        .route(
+            "/:account_id/holder_changes/:change_id/approve",
+            routing::post(approve_holder_change),
+        )
+        .route(
+            "/customers/:customer_id/accounts",
+            routing::get(serve_customer_accounts),
+        )
         .route("/email/:account_id", routing::post(update_email))
         .route(
             "/address/:account_id",
@@ -162,28 +253,40 @@ pub fn api() -> Router<AppState> {
 
 #[derive(Debug, ToSchema, Validate, Deserialize)]
 #[schema(example = json!({
-    "user_name": "otis",
+    "customer_id": 7021806137352503296_u64,
     "mailing_address": MailingAddress::new("12 Seahawks Way, Renton, WA 98056"),
     "email": EmailAddress::parse("otis@example.com").unwrap(),
+    "joint_holders": [
+        AccountHolder::new(CustomerId::new(7021806137352503297_i64), HolderRole::JointOwner),
+    ],
 }))]
 #[allow(dead_code)]
 struct AccountApplication {
-    user_name: String,
+    /// Customer opening the account, whose identity must be verified. The account is opened in
+    /// the customer's registered name.
+    customer_id: CustomerId,
+    /// Type of account to open; defaults to checking.
+    #[serde(default)]
+    account_type: AccountType,
     mailing_address: MailingAddress,
     #[validate]
     email: EmailAddress,
+    #[serde(default)]
+    joint_holders: Vec<AccountHolder>,
 }
 
 #[allow(dead_code)]
 impl AccountApplication {
     pub fn new(
-        user_name: impl Into<String>, mailing_address: impl Into<MailingAddress>,
+        customer_id: CustomerId, mailing_address: impl Into<MailingAddress>,
         email: impl Into<EmailAddress>,
     ) -> Result<Self, ValidationErrors> {
         let application = Self {
-            user_name: user_name.into(),
+            customer_id,
+            account_type: AccountType::default(),
             mailing_address: mailing_address.into(),
             email: email.into(),
+            joint_holders: Vec::new(),
         };
         application.validate().map(|_| application)
     }
@@ -197,16 +300,18 @@ impl AccountApplication {
     request_body = inline(AccountApplication),
     responses(
         (status = 200, description = "Bank account created", body = AccountId,),
-        (status = 400, description = "bad request",),
-        (status = "5XX", description = "server error", body = BankError),
+        (status = 400, description = "bad request", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 422, description = "Customer is not found or not verified", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = "5XX", description = "server error", body = ProblemDetails, content_type = "application/problem+json"),
     ),
-
 )]
-#[axum::debug_handler]
+#[axum::debug_handler(state = AppState)]
 #[tracing::instrument(level = "debug", skip(agg))]
 async fn create_bank_account(
-    State(agg): State<BankAccountAggregate>, Json(account_application): Json<AccountApplication>,
+    State(agg): State<BankAccountAggregate>,
+    account_application: Result<Json<AccountApplication>, JsonRejection>,
 ) -> impl IntoResponse {
+    let Json(account_application) = account_application?;
     {
         let span = tracing::debug_span!("validating account application", ?account_application);
         let _span_guard = span.enter();
@@ -217,16 +322,18 @@ async fn create_bank_account(
     let account_id: AccountId = aggregate_id.clone().into();
     let command = BankAccountCommand::OpenAccount {
         account_id,
-        user_name: account_application.user_name,
+        customer_id: account_application.customer_id,
+        account_type: account_application.account_type,
         mailing_address: account_application.mailing_address,
         email: account_application.email,
+        joint_holders: account_application.joint_holders,
     };
     let meta: MetaData<BankAccount> = MetaData::default();
 
     agg.execute_with_metadata(aggregate_id.pretty(), command, meta.into())
         .await
-        .map_err::<BankError, _>(|err| err.into())
-        .map(|_| Json(account_id))
+        .map_err::<BankError, _>(|err| err.into())?;
+    Result::<_, BankError>::Ok(Json(account_id))
 }
 
 #[utoipa::path(
@@ -234,29 +341,903 @@ async fn create_bank_account(
     path = "/{account_id}",
     context_path = "/api/v1/bank",
     tag = "bank_account",
-    params(AccountId),
+    params(AccountId, AsOfQuery),
     responses(
         (status = 200, description = "Bank account", body = BankAccountView),
-        (status = 404, description = "No bank account found for account number."),
+        (status = 400, description = "bad request", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 401, description = "Unrecognized bearer token, or none presented for an as_of view"),
+        (status = 403, description = "Caller does not hold the account", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 404, description = "No bank account found for account number.", body = ProblemDetails, content_type = "application/problem+json"),
     ),
 )]
-#[axum::debug_handler]
-#[tracing::instrument(level = "debug", skip(view_repo))]
+#[axum::debug_handler(state = AppState)]
+#[tracing::instrument(level = "debug", skip(view_repo, pool))]
 async fn serve_bank_account(
-    account_id: Result<Path<AccountId>, PathRejection>,
-    State(view_repo): State<BankAccountViewProjection>,
+    account_id: Result<Path<AccountId>, PathRejection>, caller: Caller,
+    as_of: Result<Query<AsOfQuery>, QueryRejection>,
+    State(view_repo): State<BankAccountViewProjection>, State(pool): State<PgPool>,
 ) -> impl IntoResponse {
     let Path(account_id) = account_id?;
+    let Query(as_of) = as_of?;
+    match &caller.0 {
+        Some(principal) => {
+            authorize_holder_roles(&pool, principal, account_id, &HolderRole::ALL).await?;
+        },
+        None if as_of.as_of.is_some() => return Err(BankError::from(ApiError::Unauthenticated)),
+        None => {},
+    }
     let aggregate_id: Id<BankAccount> = account_id.into();
-    tracing::debug!("loading account view for aggregate: {aggregate_id}");
-    let view = view_repo
-        .load(aggregate_id.pretty())
-        .await
-        .map_err::<BankError, _>(|err| err.into())
-        .map(|v| OptionalResult(v.map(Json)));
+
+    let view = match as_of.as_of {
+        None => {
+            tracing::debug!("loading account view for aggregate: {aggregate_id}");
+            view_repo.load(aggregate_id.pretty()).await?
+        },
+        Some(as_of) => {
+            tracing::debug!("replaying account view for aggregate {aggregate_id} as of {as_of}");
+            let events = queries::load_account_events(
+                &pool,
+                aggregate_id.pretty(),
+                &EventFilter::default(),
+                0,
+                None,
+            )
+            .await?;
+            queries::replay_as_of(aggregate_id.pretty(), &events, &as_of)?.map(|(_, view)| view)
+        },
+    };
 
     tracing::debug!("view response: {view:?}");
-    view
+    Result::<_, BankError>::Ok(OptionalResult::new(view.map(Json), "account", account_id))
+}
+
+/// A persisted bank account event, as recorded in the event store.
+#[derive(Debug, Clone, PartialEq, Eq, ToSchema, Serialize)]
+pub struct AccountEventEnvelope {
+    sequence: i64,
+    event_type: String,
+    event_version: String,
+    payload: BankAccountEvent,
+    #[schema(value_type = Object)]
+    metadata: serde_json::Value,
+}
+
+impl TryFrom<queries::PersistedEvent> for AccountEventEnvelope {
+    type Error = serde_json::Error;
+
+    fn try_from(event: queries::PersistedEvent) -> Result<Self, Self::Error> {
+        Ok(Self {
+            sequence: event.sequence,
+            payload: serde_json::from_value(event.payload)?,
+            event_type: event.event_type,
+            event_version: event.event_version,
+            metadata: event.metadata,
+        })
+    }
+}
+
+#[utoipa::path(
+    get,
+    path = "/{account_id}/events",
+    context_path = "/api/v1/bank",
+    tag = "bank_account",
+    params(AccountId, Pagination, EventFilter),
+    responses(
+        (status = 200, description = "Page of the account's persisted events in sequence order", body = [AccountEventEnvelope]),
+        (status = 400, description = "bad request", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 401, description = "Missing or unrecognized bearer token"),
+        (status = 403, description = "Caller does not hold the account", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 404, description = "No bank account found for account number.", body = ProblemDetails, content_type = "application/problem+json"),
+    ),
+)]
+#[axum::debug_handler(state = AppState)]
+#[tracing::instrument(level = "debug", skip(pool))]
+async fn serve_account_events(
+    account_id: Result<Path<AccountId>, PathRejection>, principal: Principal,
+    pagination: Result<Query<Pagination>, QueryRejection>,
+    filter: Result<Query<EventFilter>, QueryRejection>, State(pool): State<PgPool>,
+) -> impl IntoResponse {
+    let Path(account_id) = account_id?;
+    authorize_holder_roles(&pool, &principal, account_id, &HolderRole::ALL).await?;
+    let Query(pagination) = pagination?;
+    let Query(filter) = filter?;
+    let aggregate_id: Id<BankAccount> = account_id.into();
+
+    let events = queries::load_account_events(
+        &pool,
+        aggregate_id.pretty(),
+        &filter,
+        pagination.offset()?,
+        Some(pagination.limit()),
+    )
+    .await?;
+
+    if events.is_empty() && !queries::account_has_events(&pool, aggregate_id.pretty()).await? {
+        return Err(BankError::from(BankAccountError::NotFound(account_id)));
+    }
+
+    let envelopes = events
+        .into_iter()
+        .map(AccountEventEnvelope::try_from)
+        .collect::<Result<Vec<_>, _>>()?;
+
+    Result::<_, BankError>::Ok(Json(envelopes))
+}
+
+#[utoipa::path(
+    get,
+    path = "/{account_id}/notifications",
+    context_path = "/api/v1/bank",
+    tag = "bank_account",
+    params(AccountId),
+    responses(
+        (status = 200, description = "Notifications the account holder receives", body = NotificationPreferences),
+        (status = 401, description = "Missing or unrecognized bearer token"),
+        (status = 403, description = "Caller does not hold the account", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 404, description = "No bank account found for account number.", body = ProblemDetails, content_type = "application/problem+json"),
+    ),
+)]
+#[axum::debug_handler(state = AppState)]
+#[tracing::instrument(level = "debug", skip(pool))]
+async fn serve_notification_preferences(
+    account_id: Result<Path<AccountId>, PathRejection>, principal: Principal,
+    State(pool): State<PgPool>,
+) -> impl IntoResponse {
+    let Path(account_id) = account_id?;
+    authorize_holder_roles(&pool, &principal, account_id, &HolderRole::ALL).await?;
+    let aggregate_id: Id<BankAccount> = account_id.into();
+
+    let preferences = match notifications::load_preferences(&pool, aggregate_id.pretty()).await? {
+        Some(preferences) => preferences,
+        None if queries::account_has_events(&pool, aggregate_id.pretty()).await? => {
+            NotificationPreferences::default()
+        },
+        None => return Err(BankError::from(BankAccountError::NotFound(account_id))),
+    };
+
+    Result::<_, BankError>::Ok(Json(preferences))
+}
+
+#[utoipa::path(
+    put,
+    path = "/{account_id}/notifications",
+    context_path = "/api/v1/bank",
+    tag = "bank_account",
+    params(AccountId),
+    request_body = NotificationPreferences,
+    responses(
+        (status = 200, description = "Updated notification preferences", body = NotificationPreferences),
+        (status = 401, description = "Missing or unrecognized bearer token"),
+        (status = 403, description = "Caller may not manage the account", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 404, description = "No bank account found for account number.", body = ProblemDetails, content_type = "application/problem+json"),
+    ),
+)]
+#[axum::debug_handler(state = AppState)]
+#[tracing::instrument(level = "debug", skip(pool))]
+async fn update_notification_preferences(
+    account_id: Result<Path<AccountId>, PathRejection>, principal: Principal,
+    State(pool): State<PgPool>, preferences: Result<Json<NotificationPreferences>, JsonRejection>,
+) -> impl IntoResponse {
+    let Path(account_id) = account_id?;
+    authorize_holder_roles(&pool, &principal, account_id, &HolderRole::MANAGERS).await?;
+    let aggregate_id: Id<BankAccount> = account_id.into();
+    let Json(preferences) = preferences?;
+
+    if !queries::account_has_events(&pool, aggregate_id.pretty()).await? {
+        return Err(BankError::from(BankAccountError::NotFound(account_id)));
+    }
+
+    let saved = notifications::save_preferences(&pool, aggregate_id.pretty(), preferences).await?;
+    Result::<_, BankError>::Ok(Json(saved))
+}
+
+#[utoipa::path(
+    post,
+    path = "/{account_id}/flags/{flag_id}/release",
+    context_path = "/api/v1/bank",
+    tag = "bank_account",
+    params(AccountId, FlagId),
+    responses(
+        (status = 200, description = "Held transaction released and applied to the account"),
+        (status = 400, description = "Held transaction can no longer be applied", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 401, description = "Missing or unrecognized bearer token"),
+        (status = 403, description = "Caller is not granted the admin:account scope"),
+        (status = 404, description = "No such bank account or held transaction", body = ProblemDetails, content_type = "application/problem+json"),
+    ),
+    security(("api_key" = ["admin:account"])),
+)]
+#[axum::debug_handler(state = AppState)]
+#[tracing::instrument(level = "debug", skip(agg))]
+async fn release_flagged_transaction(
+    path: Result<Path<(AccountId, FlagId)>, PathRejection>, principal: Principal,
+    State(agg): State<BankAccountAggregate>,
+) -> impl IntoResponse {
+    principal.require_scope(ADMIN_SCOPE)?;
+    let Path((account_id, flag_id)) = path?;
+    let aggregate_id: Id<BankAccount> = account_id.into();
+    agg.execute_with_metadata(
+        aggregate_id.pretty(),
+        BankAccountCommand::ReleaseFlaggedTransaction { flag_id },
+        MetaData::<BankAccount>::default().into(),
+    )
+    .await
+    .map_err::<BankError, _>(|err| err.into())
+}
+
+#[derive(Debug, ToSchema, Validate, Deserialize)]
+#[schema(example = json!({ "reason": "confirmed with account holder as not authorized" }))]
+struct FlagRejection {
+    #[validate(length(min = 1))]
+    reason: String,
+}
+
+#[utoipa::path(
+    post,
+    path = "/{account_id}/flags/{flag_id}/reject",
+    context_path = "/api/v1/bank",
+    tag = "bank_account",
+    params(AccountId, FlagId),
+    request_body = FlagRejection,
+    responses(
+        (status = 200, description = "Held transaction rejected; it will not be applied"),
+        (status = 401, description = "Missing or unrecognized bearer token"),
+        (status = 403, description = "Caller is not granted the admin:account scope"),
+        (status = 404, description = "No such bank account or held transaction", body = ProblemDetails, content_type = "application/problem+json"),
+    ),
+    security(("api_key" = ["admin:account"])),
+)]
+#[axum::debug_handler(state = AppState)]
+#[tracing::instrument(level = "debug", skip(agg))]
+async fn reject_flagged_transaction(
+    path: Result<Path<(AccountId, FlagId)>, PathRejection>, principal: Principal,
+    State(agg): State<BankAccountAggregate>, rejection: Result<Json<FlagRejection>, JsonRejection>,
+) -> impl IntoResponse {
+    principal.require_scope(ADMIN_SCOPE)?;
+    let Path((account_id, flag_id)) = path?;
+    let aggregate_id: Id<BankAccount> = account_id.into();
+    let Json(rejection) = rejection?;
+    rejection.validate()?;
+
+    agg.execute_with_metadata(
+        aggregate_id.pretty(),
+        BankAccountCommand::RejectFlaggedTransaction { flag_id, reason: rejection.reason },
+        MetaData::<BankAccount>::default().into(),
+    )
+    .await
+    .map_err::<BankError, _>(|err| err.into())
+}
+
+#[derive(Debug, ToSchema, Validate, Deserialize)]
+#[schema(example = json!({ "reason": "subpoena 2023-117 pending review" }))]
+struct FreezeRequest {
+    #[validate(length(min = 1))]
+    reason: String,
+}
+
+#[utoipa::path(
+    post,
+    path = "/{account_id}/freeze",
+    context_path = "/api/v1/bank",
+    tag = "bank_account",
+    params(AccountId),
+    request_body = FreezeRequest,
+    responses(
+        (status = 200, description = "Account frozen; deposits are accepted but withdrawals and contact changes are rejected"),
+        (status = 400, description = "Account cannot be frozen", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 409, description = "Account is already frozen or is closed", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 401, description = "Missing or unrecognized bearer token"),
+        (status = 403, description = "Caller is not granted the admin:account scope"),
+        (status = 404, description = "No bank account found for account number.", body = ProblemDetails, content_type = "application/problem+json"),
+    ),
+    security(("api_key" = ["admin:account"])),
+)]
+#[axum::debug_handler(state = AppState)]
+#[tracing::instrument(level = "debug", skip(agg))]
+async fn freeze_account(
+    account_id: Result<Path<AccountId>, PathRejection>, principal: Principal,
+    State(agg): State<BankAccountAggregate>, request: Result<Json<FreezeRequest>, JsonRejection>,
+) -> impl IntoResponse {
+    principal.require_scope(ADMIN_SCOPE)?;
+    let Path(account_id) = account_id?;
+    let aggregate_id: Id<BankAccount> = account_id.into();
+    let Json(request) = request?;
+    request.validate()?;
+
+    agg.execute_with_metadata(
+        aggregate_id.pretty(),
+        BankAccountCommand::FreezeAccount {
+            reason: request.reason,
+            authority: principal.subject,
+        },
+        MetaData::<BankAccount>::default().into(),
+    )
+    .await
+    .map_err::<BankError, _>(|err| err.into())
+}
+
+#[utoipa::path(
+    post,
+    path = "/{account_id}/unfreeze",
+    context_path = "/api/v1/bank",
+    tag = "bank_account",
+    params(AccountId),
+    responses(
+        (status = 200, description = "Account returned to active"),
+        (status = 400, description = "Account is not frozen", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 401, description = "Missing or unrecognized bearer token"),
+        (status = 403, description = "Caller is not granted the admin:account scope"),
+        (status = 404, description = "No bank account found for account number.", body = ProblemDetails, content_type = "application/problem+json"),
+    ),
+    security(("api_key" = ["admin:account"])),
+)]
+#[axum::debug_handler(state = AppState)]
+#[tracing::instrument(level = "debug", skip(agg))]
+async fn unfreeze_account(
+    account_id: Result<Path<AccountId>, PathRejection>, principal: Principal,
+    State(agg): State<BankAccountAggregate>,
+) -> impl IntoResponse {
+    principal.require_scope(ADMIN_SCOPE)?;
+    let Path(account_id) = account_id?;
+    let aggregate_id: Id<BankAccount> = account_id.into();
+
+    agg.execute_with_metadata(
+        aggregate_id.pretty(),
+        BankAccountCommand::UnfreezeAccount { authority: principal.subject },
+        MetaData::<BankAccount>::default().into(),
+    )
+    .await
+    .map_err::<BankError, _>(|err| err.into())
+}
+
+#[utoipa::path(
+    post,
+    path = "/{account_id}/reactivate",
+    context_path = "/api/v1/bank",
+    tag = "bank_account",
+    params(AccountId),
+    responses(
+        (status = 200, description = "Dormant account returned to active; withdrawals are accepted again"),
+        (status = 400, description = "Account is not dormant", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 401, description = "Missing or unrecognized bearer token"),
+        (status = 403, description = "Caller's role on the account does not permit the command", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 404, description = "No bank account found for account number.", body = ProblemDetails, content_type = "application/problem+json"),
+    ),
+)]
+#[axum::debug_handler(state = AppState)]
+#[tracing::instrument(level = "debug", skip(agg, pool))]
+async fn reactivate_account(
+    account_id: Result<Path<AccountId>, PathRejection>, State(agg): State<BankAccountAggregate>,
+    principal: Principal, State(pool): State<PgPool>,
+) -> impl IntoResponse {
+    let Path(account_id) = account_id?;
+    let aggregate_id: Id<BankAccount> = account_id.into();
+
+    let command = as_caller(&pool, &principal, BankAccountCommand::ReactivateAccount).await?;
+    agg.execute_with_metadata(
+        aggregate_id.pretty(),
+        command,
+        MetaData::<BankAccount>::default().into(),
+    )
+    .await
+    .map_err::<BankError, _>(|err| err.into())
+}
+
+#[derive(Debug, ToSchema, Deserialize)]
+#[schema(example = json!({ "account_type": "savings" }))]
+struct AccountTypeRequest {
+    account_type: AccountType,
+}
+
+#[utoipa::path(
+    post,
+    path = "/{account_id}/account_type",
+    context_path = "/api/v1/bank",
+    tag = "bank_account",
+    params(AccountId),
+    request_body = AccountTypeRequest,
+    responses(
+        (status = 200, description = "Account changed to the requested type; its policy applies to subsequent transactions"),
+        (status = 400, description = "Account is not eligible for the requested type", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 401, description = "Missing or unrecognized bearer token"),
+        (status = 403, description = "Caller's role on the account does not permit the command", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 404, description = "No bank account found for account number.", body = ProblemDetails, content_type = "application/problem+json"),
+    ),
+)]
+#[axum::debug_handler(state = AppState)]
+#[tracing::instrument(level = "debug", skip(agg, pool))]
+async fn change_account_type(
+    account_id: Result<Path<AccountId>, PathRejection>, State(agg): State<BankAccountAggregate>,
+    principal: Principal, State(pool): State<PgPool>,
+    request: Result<Json<AccountTypeRequest>, JsonRejection>,
+) -> impl IntoResponse {
+    let Path(account_id) = account_id?;
+    let aggregate_id: Id<BankAccount> = account_id.into();
+    let Json(request) = request?;
+
+    let command = as_caller(
+        &pool,
+        &principal,
+        BankAccountCommand::ChangeAccountType { account_type: request.account_type },
+    )
+    .await?;
+    agg.execute_with_metadata(
+        aggregate_id.pretty(),
+        command,
+        MetaData::<BankAccount>::default().into(),
+    )
+    .await
+    .map_err::<BankError, _>(|err| err.into())
+}
+
+#[derive(Debug, ToSchema, Deserialize)]
+#[schema(example = json!({ "waivers": ["monthly_maintenance", "check"] }))]
+struct FeeWaiverRequest {
+    /// Fees no longer charged to the account; fees not listed are charged again.
+    waivers: Vec<FeeKind>,
+}
+
+#[utoipa::path(
+    post,
+    path = "/{account_id}/fee_waivers",
+    context_path = "/api/v1/bank",
+    tag = "bank_account",
+    params(AccountId),
+    request_body = FeeWaiverRequest,
+    responses(
+        (status = 200, description = "Fee waivers replaced; waived fees are no longer charged to the account"),
+        (status = 400, description = "Waivers cannot be set on the account", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 401, description = "Missing or unrecognized bearer token"),
+        (status = 403, description = "Caller is not granted the admin:account scope"),
+        (status = 404, description = "No bank account found for account number.", body = ProblemDetails, content_type = "application/problem+json"),
+    ),
+    security(("api_key" = ["admin:account"])),
+)]
+#[axum::debug_handler(state = AppState)]
+#[tracing::instrument(level = "debug", skip(agg))]
+async fn set_fee_waivers(
+    account_id: Result<Path<AccountId>, PathRejection>, principal: Principal,
+    State(agg): State<BankAccountAggregate>,
+    request: Result<Json<FeeWaiverRequest>, JsonRejection>,
+) -> impl IntoResponse {
+    principal.require_scope(ADMIN_SCOPE)?;
+    let Path(account_id) = account_id?;
+    let aggregate_id: Id<BankAccount> = account_id.into();
+    let Json(request) = request?;
+
+    agg.execute_with_metadata(
+        aggregate_id.pretty(),
+        BankAccountCommand::SetFeeWaivers { waivers: request.waivers },
+        MetaData::<BankAccount>::default().into(),
+    )
+    .await
+    .map_err::<BankError, _>(|err| err.into())
+}
+
+#[utoipa::path(
+    get,
+    path = "/escheatment",
+    context_path = "/api/v1/bank",
+    tag = "bank_account",
+    responses(
+        (status = 200, description = "Balances of long-dormant accounts to be transferred to the state, longest inactive first", body = [EscheatmentEntry]),
+        (status = 401, description = "Missing or unrecognized bearer token"),
+        (status = 403, description = "Caller is not granted the admin:account scope"),
+    ),
+    security(("api_key" = ["admin:account"])),
+)]
+#[axum::debug_handler(state = AppState)]
+#[tracing::instrument(level = "debug", skip(pool))]
+async fn serve_escheatment_report(
+    principal: Principal, State(pool): State<PgPool>,
+) -> impl IntoResponse {
+    principal.require_scope(ADMIN_SCOPE)?;
+    let report = dormancy::escheatment_report(&pool).await?;
+    Result::<_, BankError>::Ok(Json(report))
+}
+
+#[derive(Debug, PartialEq, Eq, ToSchema, Serialize, Deserialize)]
+#[schema(example = json!({
+    "hold_id": "auth_0042",
+    "amount": { "amount": "42.50", "currency": "USD" },
+    "expires_at": "2023-01-25T17:30:00Z",
+}))]
+pub struct HoldRequest {
+    hold_id: HoldId,
+    amount: ApiMoney,
+    expires_at: DateTime<Utc>,
+}
+
+#[utoipa::path(
+    post,
+    path = "/{account_id}/holds",
+    context_path = "/api/v1/bank",
+    tag = "bank_account",
+    params(AccountId),
+    request_body = HoldRequest,
+    responses(
+        (status = 200, description = "Funds reserved against the account's available balance"),
+        (status = 400, description = "Invalid hold", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 401, description = "Missing or unrecognized bearer token"),
+        (status = 403, description = "Caller's role on the account does not permit the command", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 404, description = "No bank account found for account number.", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 409, description = "Account is frozen or closed", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 422, description = "Insufficient available funds", body = ProblemDetails, content_type = "application/problem+json"),
+    ),
+)]
+#[axum::debug_handler(state = AppState)]
+#[tracing::instrument(level = "debug", skip(agg, pool))]
+async fn place_hold(
+    account_id: Result<Path<AccountId>, PathRejection>, State(agg): State<BankAccountAggregate>,
+    principal: Principal, State(pool): State<PgPool>,
+    hold: Result<Json<HoldRequest>, JsonRejection>,
+) -> impl IntoResponse {
+    let Path(account_id) = account_id?;
+    let aggregate_id: Id<BankAccount> = account_id.into();
+    let Json(hold) = hold?;
+
+    let command = as_caller(
+        &pool,
+        &principal,
+        BankAccountCommand::PlaceHold {
+            hold_id: hold.hold_id,
+            amount: hold.amount.into_inner(),
+            expires_at: hold.expires_at,
+        },
+    )
+    .await?;
+    agg.execute_with_metadata(
+        aggregate_id.pretty(),
+        command,
+        MetaData::<BankAccount>::default().into(),
+    )
+    .await
+    .map_err::<BankError, _>(|err| err.into())
+}
+
+#[utoipa::path(
+    post,
+    path = "/{account_id}/holds/{hold_id}/capture",
+    context_path = "/api/v1/bank",
+    tag = "bank_account",
+    params(AccountId, HoldId),
+    responses(
+        (status = 200, description = "Held funds settled against the account's balance"),
+        (status = 401, description = "Missing or unrecognized bearer token"),
+        (status = 403, description = "Caller's role on the account does not permit the command", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 404, description = "No such bank account or hold", body = ProblemDetails, content_type = "application/problem+json"),
+    ),
+)]
+#[axum::debug_handler(state = AppState)]
+#[tracing::instrument(level = "debug", skip(agg, pool))]
+async fn capture_hold(
+    path: Result<Path<(AccountId, HoldId)>, PathRejection>,
+    State(agg): State<BankAccountAggregate>, principal: Principal, State(pool): State<PgPool>,
+) -> impl IntoResponse {
+    let Path((account_id, hold_id)) = path?;
+    let aggregate_id: Id<BankAccount> = account_id.into();
+    let command = as_caller(
+        &pool,
+        &principal,
+        BankAccountCommand::CaptureHold { hold_id },
+    )
+    .await?;
+    agg.execute_with_metadata(
+        aggregate_id.pretty(),
+        command,
+        MetaData::<BankAccount>::default().into(),
+    )
+    .await
+    .map_err::<BankError, _>(|err| err.into())
+}
+
+#[utoipa::path(
+    post,
+    path = "/{account_id}/holds/{hold_id}/release",
+    context_path = "/api/v1/bank",
+    tag = "bank_account",
+    params(AccountId, HoldId),
+    responses(
+        (status = 200, description = "Held funds returned to the account's available balance"),
+        (status = 401, description = "Missing or unrecognized bearer token"),
+        (status = 403, description = "Caller's role on the account does not permit the command", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 404, description = "No such bank account or hold", body = ProblemDetails, content_type = "application/problem+json"),
+    ),
+)]
+#[axum::debug_handler(state = AppState)]
+#[tracing::instrument(level = "debug", skip(agg, pool))]
+async fn release_hold(
+    path: Result<Path<(AccountId, HoldId)>, PathRejection>,
+    State(agg): State<BankAccountAggregate>, principal: Principal, State(pool): State<PgPool>,
+) -> impl IntoResponse {
+    let Path((account_id, hold_id)) = path?;
+    let aggregate_id: Id<BankAccount> = account_id.into();
+    let command = as_caller(
+        &pool,
+        &principal,
+        BankAccountCommand::ReleaseHold { hold_id },
+    )
+    .await?;
+    agg.execute_with_metadata(
+        aggregate_id.pretty(),
+        command,
+        MetaData::<BankAccount>::default().into(),
+    )
+    .await
+    .map_err::<BankError, _>(|err| err.into())
+}
+
+#[derive(Debug, ToSchema, Validate, Deserialize)]
+#[schema(example = json!({ "reason": "deposit posted to the wrong account" }))]
+struct ReversalRequest {
+    #[validate(length(min = 1))]
+    reason: String,
+}
+
+#[utoipa::path(
+    post,
+    path = "/{account_id}/transactions/{sequence}/reverse",
+    context_path = "/api/v1/bank",
+    tag = "bank_account",
+    params(
+        AccountId,
+        ("sequence" = usize, Path, description = "Sequence number of the event that posted the transaction"),
+    ),
+    request_body = ReversalRequest,
+    responses(
+        (status = 200, description = "Compensating reversal posted to the account"),
+        (status = 401, description = "Missing or unrecognized bearer token"),
+        (status = 403, description = "Caller is not granted the admin:account scope"),
+        (status = 404, description = "No such bank account or transaction", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 409, description = "Transaction is already reversed", body = ProblemDetails, content_type = "application/problem+json"),
+    ),
+    security(("api_key" = ["admin:account"])),
+)]
+#[axum::debug_handler(state = AppState)]
+#[tracing::instrument(level = "debug", skip(agg))]
+async fn reverse_transaction(
+    path: Result<Path<(AccountId, usize)>, PathRejection>, principal: Principal,
+    State(agg): State<BankAccountAggregate>, request: Result<Json<ReversalRequest>, JsonRejection>,
+) -> impl IntoResponse {
+    principal.require_scope(ADMIN_SCOPE)?;
+    let Path((account_id, sequence)) = path?;
+    let aggregate_id: Id<BankAccount> = account_id.into();
+    let Json(request) = request?;
+    request.validate()?;
+
+    agg.execute_with_metadata(
+        aggregate_id.pretty(),
+        BankAccountCommand::ReverseTransaction { sequence, reason: request.reason },
+        MetaData::<BankAccount>::default().into(),
+    )
+    .await
+    .map_err::<BankError, _>(|err| err.into())
+}
+
+/// Checks that the caller is an admin or holds the account in one of the roles.
+pub(super) async fn authorize_holder_roles(
+    pool: &PgPool, principal: &Principal, account_id: AccountId, roles: &[HolderRole],
+) -> Result<(), BankError> {
+    if principal.scopes.contains(ADMIN_SCOPE) {
+        return Ok(());
+    }
+
+    let customer_id = caller_customer_id(pool, principal).await?;
+    let holding = queries::list_account_holders(pool, account_id)
+        .await?
+        .into_iter()
+        .find(|holding| holding.customer_id == customer_id)
+        .ok_or(BankAccountError::NotAccountHolder(account_id, customer_id))?;
+
+    if roles.contains(&holding.role) {
+        Ok(())
+    } else {
+        Err(BankAccountError::HolderNotPermitted(account_id, customer_id, holding.role).into())
+    }
+}
+
+/// Resolves the caller to the customer registered for their authenticated subject.
+async fn caller_customer_id(pool: &PgPool, principal: &Principal) -> Result<CustomerId, BankError> {
+    queries::find_customer_id(pool, &principal.subject)
+        .await?
+        .ok_or_else(|| CustomerError::SubjectNotRegistered(principal.subject.clone()).into())
+}
+
+/// Executes the command on behalf of the calling account holder, subject to their role on the
+/// account. Only callers granted the admin scope act with the bank's authority.
+async fn as_caller(
+    pool: &PgPool, principal: &Principal, command: BankAccountCommand,
+) -> Result<BankAccountCommand, BankError> {
+    if principal.scopes.contains(ADMIN_SCOPE) {
+        return Ok(command);
+    }
+
+    Ok(BankAccountCommand::AsHolder {
+        customer_id: caller_customer_id(pool, principal).await?,
+        command: Box::new(command),
+    })
+}
+
+/// Executes the command on behalf of the caller of a route that predates bearer tokens. Anonymous
+/// callers still act with the bank's authority there, while authenticated callers act as
+/// themselves, subject to their role on the account.
+async fn as_optional_caller(
+    pool: &PgPool, caller: &Caller, command: BankAccountCommand,
+) -> Result<BankAccountCommand, BankError> {
+    match &caller.0 {
+        Some(principal) => as_caller(pool, principal, command).await,
+        None => Ok(command),
+    }
+}
+
+#[utoipa::path(
+    get,
+    path = "/{account_id}/holders",
+    context_path = "/api/v1/bank",
+    tag = "bank_account",
+    params(AccountId),
+    responses(
+        (status = 200, description = "Holders of the account, owner first", body = [AccountHolding]),
+        (status = 401, description = "Missing or unrecognized bearer token"),
+        (status = 403, description = "Caller does not hold the account", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 404, description = "No bank account found for account number.", body = ProblemDetails, content_type = "application/problem+json"),
+    ),
+)]
+#[axum::debug_handler(state = AppState)]
+#[tracing::instrument(level = "debug", skip(pool))]
+async fn serve_account_holders(
+    account_id: Result<Path<AccountId>, PathRejection>, principal: Principal,
+    State(pool): State<PgPool>,
+) -> impl IntoResponse {
+    let Path(account_id) = account_id?;
+    authorize_holder_roles(&pool, &principal, account_id, &HolderRole::ALL).await?;
+    let holders = queries::list_account_holders(&pool, account_id).await?;
+    if holders.is_empty() {
+        return Err(BankError::from(BankAccountError::NotFound(account_id)));
+    }
+
+    Result::<_, BankError>::Ok(Json(holders))
+}
+
+#[utoipa::path(
+    post,
+    path = "/{account_id}/holders",
+    context_path = "/api/v1/bank",
+    tag = "bank_account",
+    params(AccountId),
+    request_body = AccountHolder,
+    responses(
+        (status = 200, description = "Holder added, or the addition is pending the owner's approval"),
+        (status = 400, description = "The holder is invalid", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 401, description = "Missing or unrecognized bearer token"),
+        (status = 403, description = "Caller may not add holders", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 404, description = "No bank account found for account number.", body = ProblemDetails, content_type = "application/problem+json"),
+    ),
+)]
+#[axum::debug_handler(state = AppState)]
+#[tracing::instrument(level = "debug", skip(agg, pool))]
+async fn add_account_holder(
+    account_id: Result<Path<AccountId>, PathRejection>, State(agg): State<BankAccountAggregate>,
+    principal: Principal, State(pool): State<PgPool>,
+    holder: Result<Json<AccountHolder>, JsonRejection>,
+) -> impl IntoResponse {
+    let Path(account_id) = account_id?;
+    let aggregate_id: Id<BankAccount> = account_id.into();
+    let Json(holder) = holder?;
+
+    let command = as_caller(
+        &pool,
+        &principal,
+        BankAccountCommand::AddAccountHolder { holder },
+    )
+    .await?;
+    agg.execute_with_metadata(
+        aggregate_id.pretty(),
+        command,
+        MetaData::<BankAccount>::default().into(),
+    )
+    .await
+    .map_err::<BankError, _>(|err| err.into())
+}
+
+#[utoipa::path(
+    delete,
+    path = "/{account_id}/holders/{customer_id}",
+    context_path = "/api/v1/bank",
+    tag = "bank_account",
+    params(AccountId, CustomerId),
+    responses(
+        (status = 200, description = "Holder removed, or the removal is pending the owner's approval"),
+        (status = 400, description = "The customer is not a removable holder", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 401, description = "Missing or unrecognized bearer token"),
+        (status = 403, description = "Caller may not remove holders", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 404, description = "No bank account found for account number.", body = ProblemDetails, content_type = "application/problem+json"),
+    ),
+)]
+#[axum::debug_handler(state = AppState)]
+#[tracing::instrument(level = "debug", skip(agg, pool))]
+async fn remove_account_holder(
+    path: Result<Path<(AccountId, CustomerId)>, PathRejection>,
+    State(agg): State<BankAccountAggregate>, principal: Principal, State(pool): State<PgPool>,
+) -> impl IntoResponse {
+    let Path((account_id, customer_id)) = path?;
+    let aggregate_id: Id<BankAccount> = account_id.into();
+
+    let command = as_caller(
+        &pool,
+        &principal,
+        BankAccountCommand::RemoveAccountHolder { customer_id },
+    )
+    .await?;
+    agg.execute_with_metadata(
+        aggregate_id.pretty(),
+        command,
+        MetaData::<BankAccount>::default().into(),
+    )
+    .await
+    .map_err::<BankError, _>(|err| err.into())
+}
+
+#[utoipa::path(
+    post,
+    path = "/{account_id}/holder_changes/{change_id}/approve",
+    context_path = "/api/v1/bank",
+    tag = "bank_account",
+    params(AccountId, HolderChangeId),
+    responses(
+        (status = 200, description = "Pending holder change approved and applied"),
+        (status = 401, description = "Missing or unrecognized bearer token"),
+        (status = 403, description = "Caller is not the owner", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 404, description = "No such bank account or pending change", body = ProblemDetails, content_type = "application/problem+json"),
+    ),
+)]
+#[axum::debug_handler(state = AppState)]
+#[tracing::instrument(level = "debug", skip(agg, pool))]
+async fn approve_holder_change(
+    path: Result<Path<(AccountId, HolderChangeId)>, PathRejection>,
+    State(agg): State<BankAccountAggregate>, principal: Principal, State(pool): State<PgPool>,
+) -> impl IntoResponse {
+    let Path((account_id, change_id)) = path?;
+    let aggregate_id: Id<BankAccount> = account_id.into();
+
+    let command = as_caller(
+        &pool,
+        &principal,
+        BankAccountCommand::ApproveHolderChange { change_id },
+    )
+    .await?;
+    agg.execute_with_metadata(
+        aggregate_id.pretty(),
+        command,
+        MetaData::<BankAccount>::default().into(),
+    )
+    .await
+    .map_err::<BankError, _>(|err| err.into())
+}
+
+#[utoipa::path(
+    get,
+    path = "/customers/{customer_id}/accounts",
+    context_path = "/api/v1/bank",
+    tag = "bank_account",
+    params(CustomerId),
+    responses(
+        (status = 200, description = "Accounts the customer holds and their role on each", body = [AccountHolding]),
+        (status = 401, description = "Missing or unrecognized bearer token"),
+        (status = 403, description = "Caller is neither the customer nor granted the admin:account scope", body = ProblemDetails, content_type = "application/problem+json"),
+    ),
+)]
+#[axum::debug_handler(state = AppState)]
+#[tracing::instrument(level = "debug", skip(pool))]
+async fn serve_customer_accounts(
+    customer_id: Result<Path<CustomerId>, PathRejection>, principal: Principal,
+    State(pool): State<PgPool>,
+) -> impl IntoResponse {
+    let Path(customer_id) = customer_id?;
+    if queries::find_customer_id(&pool, &principal.subject).await? != Some(customer_id) {
+        principal.require_scope(ADMIN_SCOPE)?;
+    }
+    let accounts = queries::list_customer_accounts(&pool, customer_id).await?;
+    Result::<_, BankError>::Ok(Json(accounts))
 }
 
 #[utoipa::path(
@@ -268,13 +1249,16 @@ async fn serve_bank_account(
     request_body = EmailAddress,
     responses(
         (status = 200, description = "Update email associated with bank account"),
-        (status = 404, description = "No bank account found for account number."),
+        (status = 401, description = "Unrecognized bearer token"),
+        (status = 403, description = "Caller's role on the account does not permit the command", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 404, description = "No bank account found for account number.", body = ProblemDetails, content_type = "application/problem+json"),
     ),
 )]
-#[axum::debug_handler]
-#[tracing::instrument(level = "trace", skip(agg))]
+#[axum::debug_handler(state = AppState)]
+#[tracing::instrument(level = "trace", skip(agg, pool))]
 async fn update_email(
     account_id: Result<Path<AccountId>, PathRejection>, State(agg): State<BankAccountAggregate>,
+    caller: Caller, State(pool): State<PgPool>,
     new_email: Result<Json<EmailAddress>, JsonRejection>,
 ) -> impl IntoResponse {
     let Path(account_id) = account_id?;
@@ -282,9 +1266,15 @@ async fn update_email(
     let Json(new_email) = new_email?;
     new_email.validate()?;
 
+    let command = as_optional_caller(
+        &pool,
+        &caller,
+        BankAccountCommand::ChangeEmail { new_email },
+    )
+    .await?;
     agg.execute_with_metadata(
         aggregate_id.pretty(),
-        BankAccountCommand::ChangeEmail { new_email },
+        command,
         MetaData::<BankAccount>::default().into(),
     )
     .await
@@ -300,21 +1290,30 @@ async fn update_email(
     request_body = MailingAddress,
     responses(
         (status = 200, description = "Update email associated with bank account"),
-        (status = 404, description = "No bank account found for account number."),
+        (status = 401, description = "Unrecognized bearer token"),
+        (status = 403, description = "Caller's role on the account does not permit the command", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 404, description = "No bank account found for account number.", body = ProblemDetails, content_type = "application/problem+json"),
     ),
     )]
-#[axum::debug_handler]
-#[tracing::instrument(level = "trace", skip(agg))]
+#[axum::debug_handler(state = AppState)]
+#[tracing::instrument(level = "trace", skip(agg, pool))]
 async fn update_mailing_address(
     account_id: Result<Path<AccountId>, PathRejection>, State(agg): State<BankAccountAggregate>,
+    caller: Caller, State(pool): State<PgPool>,
     new_mailing_address: Result<Json<MailingAddress>, JsonRejection>,
 ) -> impl IntoResponse {
     let Path(account_id) = account_id?;
     let aggregate_id: Id<BankAccount> = account_id.into();
     let Json(new_address) = new_mailing_address?;
+    let command = as_optional_caller(
+        &pool,
+        &caller,
+        BankAccountCommand::ChangeMailingAddress { new_address },
+    )
+    .await?;
     agg.execute_with_metadata(
         aggregate_id.pretty(),
-        BankAccountCommand::ChangeMailingAddress { new_address },
+        command,
         MetaData::<BankAccount>::default().into(),
     )
     .await
@@ -330,26 +1329,30 @@ async fn update_mailing_address(
     request_body = ApiMoney,
     responses(
         (status = 200, description = "Update email associated with bank account"),
-        (status = 400, description = "bank account error", body = BankError),
-        (status = 404, description = "No bank account found for account number."),
+        (status = 202, description = "Accepted for asynchronous execution with the command queue enabled", body = CommandAccepted),
+        (status = 400, description = "bank account error", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 401, description = "Unrecognized bearer token"),
+        (status = 403, description = "Caller's role on the account does not permit the command", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 404, description = "No bank account found for account number.", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 409, description = "Account is closed", body = ProblemDetails, content_type = "application/problem+json"),
     ),
 )]
-#[axum::debug_handler]
-#[tracing::instrument(level = "trace", skip(agg))]
+#[axum::debug_handler(state = AppState)]
+#[tracing::instrument(level = "trace", skip(agg, queue, pool))]
 async fn deposit_amount(
     account_id: Result<Path<AccountId>, PathRejection>, State(agg): State<BankAccountAggregate>,
+    State(queue): State<CommandQueue>, caller: Caller, State(pool): State<PgPool>,
     amount: Result<Json<ApiMoney>, JsonRejection>,
 ) -> impl IntoResponse {
     let Path(account_id) = account_id?;
-    let aggregate_id: Id<BankAccount> = account_id.into();
     let Json(amount) = amount?;
-    agg.execute_with_metadata(
-        aggregate_id.pretty(),
+    let command = as_optional_caller(
+        &pool,
+        &caller,
         BankAccountCommand::DepositAmount { amount: amount.into_inner() },
-        MetaData::<BankAccount>::default().into(),
     )
-    .await
-    .map_err::<BankError, _>(|err| err.into())
+    .await?;
+    command_routes::execute_or_accept(&agg, &queue, account_id, command).await
 }
 
 #[derive(Debug, PartialEq, Eq, ToSchema, Serialize, Deserialize)]
@@ -366,31 +1369,36 @@ pub struct CashWithdrawalRequest {
     params(AccountId),
     request_body = CashWithdrawalRequest,
     responses(
-        (status = 200, description = "ATM cash withdrawal from bank account"),
-        (status = 400, description = "bank account error", body = BankError),
-        (status = 404, description = "No bank account found for account number."),
+        (status = 200, description = "ATM cash withdrawal from bank account, or held for fraud review"),
+        (status = 202, description = "Accepted for asynchronous execution with the command queue enabled", body = CommandAccepted),
+        (status = 400, description = "bank account error", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 401, description = "Unrecognized bearer token"),
+        (status = 403, description = "Caller's role on the account does not permit the command", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 404, description = "No bank account found for account number.", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 409, description = "Account is frozen, closed or dormant", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 422, description = "Insufficient funds or the withdrawal is not permitted", body = ProblemDetails, content_type = "application/problem+json"),
     ),
 )]
-#[axum::debug_handler]
-#[tracing::instrument(level = "trace", skip(agg))]
+#[axum::debug_handler(state = AppState)]
+#[tracing::instrument(level = "trace", skip(agg, queue, pool))]
 async fn withdrawal_by_atm(
     account_id: Result<Path<AccountId>, PathRejection>, State(agg): State<BankAccountAggregate>,
+    State(queue): State<CommandQueue>, caller: Caller, State(pool): State<PgPool>,
     atm_withdrawal: Result<Json<CashWithdrawalRequest>, JsonRejection>,
 ) -> impl IntoResponse {
     let Path(account_id) = account_id?;
-    let aggregate_id: Id<BankAccount> = account_id.into();
     let Json(atm_withdrawal) = atm_withdrawal?;
 
-    agg.execute_with_metadata(
-        aggregate_id.pretty(),
+    let command = as_optional_caller(
+        &pool,
+        &caller,
         BankAccountCommand::WithdrawCash {
             amount: atm_withdrawal.amount.into_inner(),
             atm_id: atm_withdrawal.atm_id,
         },
-        MetaData::<BankAccount>::default().into(),
     )
-    .await
-    .map_err::<BankError, _>(|err| err.into())
+    .await?;
+    command_routes::execute_or_accept(&agg, &queue, account_id, command).await
 }
 
 #[derive(Debug, PartialEq, Eq, ToSchema, Serialize, Deserialize)]
@@ -408,30 +1416,35 @@ pub struct CheckWithdrawalRequest {
     request_body = CheckWithdrawalRequest,
     responses(
         (status = 200, description = "check withdrawal from bank account"),
-        (status = 400, description = "bank account error", body = BankError),
-        (status = 404, description = "No bank account found for account number."),
+        (status = 202, description = "Accepted for asynchronous execution with the command queue enabled", body = CommandAccepted),
+        (status = 400, description = "bank account error", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 401, description = "Unrecognized bearer token"),
+        (status = 403, description = "Caller's role on the account does not permit the command", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 404, description = "No bank account found for account number.", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 409, description = "Account is frozen, closed or dormant", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 422, description = "Insufficient funds or the check is not permitted", body = ProblemDetails, content_type = "application/problem+json"),
     ),
 )]
-#[axum::debug_handler]
-#[tracing::instrument(level = "trace", skip(agg))]
+#[axum::debug_handler(state = AppState)]
+#[tracing::instrument(level = "trace", skip(agg, queue, pool))]
 async fn withdrawal_by_check(
     account_id: Result<Path<AccountId>, PathRejection>, State(agg): State<BankAccountAggregate>,
+    State(queue): State<CommandQueue>, caller: Caller, State(pool): State<PgPool>,
     check_withdrawal: Result<Json<CheckWithdrawalRequest>, JsonRejection>,
 ) -> impl IntoResponse {
     let Path(account_id) = account_id?;
-    let aggregate_id: Id<BankAccount> = account_id.into();
     let Json(check_withdrawal) = check_withdrawal?;
 
-    agg.execute_with_metadata(
-        aggregate_id.pretty(),
+    let command = as_optional_caller(
+        &pool,
+        &caller,
         BankAccountCommand::DisburseCheck {
             check_nr: check_withdrawal.check_nr,
             amount: check_withdrawal.amount.into_inner(),
         },
-        MetaData::<BankAccount>::default().into(),
     )
-    .await
-    .map_err::<BankError, _>(|err| err.into())
+    .await?;
+    command_routes::execute_or_accept(&agg, &queue, account_id, command).await
 }
 
 #[utoipa::path(
@@ -441,11 +1454,17 @@ async fn withdrawal_by_check(
     tag = "bank_account",
     responses(
         (status = 200, description = "all bank accounts sorted by balance (descending)", body = [BankAccountView]),
+        (status = 401, description = "Missing or unrecognized bearer token"),
+        (status = 403, description = "Caller is not granted the admin:account scope", body = ProblemDetails, content_type = "application/problem+json"),
     ),
+    security(("api_key" = ["admin:account"])),
 )]
-#[axum::debug_handler]
+#[axum::debug_handler(state = AppState)]
 #[tracing::instrument(level = "trace", skip(pool))]
-async fn serve_all_by_balance(State(pool): State<PgPool>) -> impl IntoResponse {
+async fn serve_all_by_balance(
+    principal: Principal, State(pool): State<PgPool>,
+) -> impl IntoResponse {
+    principal.require_scope(ADMIN_SCOPE)?;
     let select_sql = format!("SELECT version, payload FROM {ACCOUNT_QUERY_VIEW}");
     let payloads = sqlx::query(&select_sql).fetch_all(&pool).await?;
 
@@ -484,7 +1503,7 @@ mod tests {
     fn test_cash_withdrawal_request_serde_tokens() {
         let request = CashWithdrawalRequest {
             atm_id: AtmId::new("atm_123_abc"),
-            amount: Money::new(123_56, 2, Currency::Usd).into(),
+            amount: Money::new(12_356, 2, Currency::Usd).into(),
         };
 
         assert_tokens(
@@ -524,7 +1543,7 @@ mod tests {
             actual,
             CashWithdrawalRequest {
                 atm_id: AtmId::new("atm_123_abc"),
-                amount: Money::new(123_56, 2, Currency::Usd).into(),
+                amount: Money::new(12_356, 2, Currency::Usd).into(),
             }
         );
     }
@@ -533,7 +1552,7 @@ mod tests {
     fn test_check_withdrawal_request_serde_tokens() {
         let request = CheckWithdrawalRequest {
             check_nr: CheckNumber::new(8723_u32),
-            amount: Money::new(9834_98, 2, Currency::Usd).into(),
+            amount: Money::new(983_498, 2, Currency::Usd).into(),
         };
 
         assert_tokens(
@@ -573,7 +1592,7 @@ mod tests {
             actual,
             CheckWithdrawalRequest {
                 check_nr: CheckNumber::new(98327_u32),
-                amount: Money::new(34987_34, 2, Currency::Usd).into(),
+                amount: Money::new(3_498_734, 2, Currency::Usd).into(),
             }
         );
     }
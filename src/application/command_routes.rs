@@ -0,0 +1,153 @@
+use crate::application::app_state::AppState;
+use crate::application::auth::Principal;
+use crate::application::bank_routes::{authorize_holder_roles, AccountEventEnvelope};
+use crate::application::result::{OptionalResult, ProblemDetails};
+use crate::commands::{self, CommandQueue, CommandStatus};
+use crate::errors::BankError;
+use crate::model::{
+    AccountId, BankAccount, BankAccountAggregate, BankAccountCommand, BankAccountEvent, CommandId,
+    HolderRole,
+};
+use crate::queries;
+use axum::extract::rejection::PathRejection;
+use axum::extract::{Path, State};
+use axum::http::{header, StatusCode};
+use axum::response::{IntoResponse, Response};
+use axum::{routing, Json, Router};
+use chrono::{DateTime, Utc};
+use pretty_snowflake::envelope::MetaData;
+use pretty_snowflake::Id;
+use serde::{Deserialize, Serialize};
+use sqlx::PgPool;
+use utoipa::{OpenApi, ToSchema};
+
+#[derive(OpenApi)]
+#[openapi(
+    paths(serve_command),
+    components(
+        schemas(
+            CommandId, AccountId, CommandStatus, CommandAccepted, CommandReport,
+            AccountEventEnvelope, BankAccountEvent, ProblemDetails,
+        )
+    ),
+    tags(
+        (name = "commands", description = "Asynchronous Command Status API")
+    )
+)]
+pub struct CommandApiDoc;
+
+pub fn api() -> Router<AppState> {
+    Router::new().route("/:command_id", routing::get(serve_command))
+}
+
+/// Acknowledges a command accepted for asynchronous execution; its outcome is reported at
+/// `/api/v1/commands/{command_id}`.
+#[derive(Debug, Clone, PartialEq, Eq, ToSchema, Serialize, Deserialize)]
+pub struct CommandAccepted {
+    pub command_id: CommandId,
+}
+
+/// Outcome of a command accepted for asynchronous execution.
+#[derive(Debug, Clone, PartialEq, Eq, ToSchema, Serialize)]
+pub struct CommandReport {
+    pub command_id: CommandId,
+    pub account_id: AccountId,
+    pub status: CommandStatus,
+    pub accepted_at: DateTime<Utc>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub completed_at: Option<DateTime<Utc>>,
+
+    /// Events the command produced, once it succeeded.
+    pub events: Vec<AccountEventEnvelope>,
+
+    /// Why the command failed, as the problem details it would have been rejected with.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<ProblemDetails>,
+}
+
+#[utoipa::path(
+    get,
+    path = "/{command_id}",
+    context_path = "/api/v1/commands",
+    tag = "commands",
+    params(CommandId),
+    responses(
+        (status = 200, description = "Status of the accepted command, with its events or error", body = CommandReport),
+        (status = 400, description = "bad request", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 401, description = "Missing or unrecognized bearer token"),
+        (status = 403, description = "Caller does not hold the command's account", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 404, description = "No command accepted under the id.", body = ProblemDetails, content_type = "application/problem+json"),
+    ),
+)]
+#[axum::debug_handler(state = AppState)]
+#[tracing::instrument(level = "debug", skip(pool))]
+async fn serve_command(
+    command_id: Result<Path<CommandId>, PathRejection>, principal: Principal,
+    State(pool): State<PgPool>,
+) -> impl IntoResponse {
+    let Path(command_id) = command_id?;
+    let report = match commands::find_command(&pool, command_id).await? {
+        None => None,
+        Some(command) => {
+            authorize_holder_roles(&pool, &principal, command.account_id, &HolderRole::ALL).await?;
+            Some(report_command(&pool, command).await?)
+        },
+    };
+    Result::<_, BankError>::Ok(OptionalResult::new(report.map(Json), "command", command_id))
+}
+
+async fn report_command(
+    pool: &PgPool, command: commands::QueuedCommand,
+) -> Result<CommandReport, BankError> {
+    let events = if command.status == CommandStatus::Succeeded {
+        let aggregate_id: Id<BankAccount> = command.account_id.into();
+        queries::load_command_events(pool, aggregate_id.pretty(), &command.command_id.to_string())
+            .await?
+            .into_iter()
+            .map(AccountEventEnvelope::try_from)
+            .collect::<Result<Vec<_>, _>>()?
+    } else {
+        Vec::new()
+    };
+
+    let error = command.error.map(serde_json::from_value).transpose()?;
+
+    Ok(CommandReport {
+        command_id: command.command_id,
+        account_id: command.account_id,
+        status: command.status,
+        accepted_at: command.accepted_at,
+        completed_at: command.completed_at,
+        events,
+        error,
+    })
+}
+
+/// Executes the money-moving command on the account, or, with the command queue enabled, accepts
+/// it for asynchronous execution and responds `202 Accepted` with the id to poll its outcome by.
+#[tracing::instrument(level = "trace", skip(agg, queue))]
+pub(super) async fn execute_or_accept(
+    agg: &BankAccountAggregate, queue: &CommandQueue, account_id: AccountId,
+    command: BankAccountCommand,
+) -> Result<Response, BankError> {
+    if queue.is_enabled() {
+        let command_id = queue.enqueue(account_id, &command).await?;
+        let location = format!("/api/v1/commands/{command_id}");
+        return Ok((
+            StatusCode::ACCEPTED,
+            [(header::LOCATION, location)],
+            Json(CommandAccepted { command_id }),
+        )
+            .into_response());
+    }
+
+    let aggregate_id: Id<BankAccount> = account_id.into();
+    agg.execute_with_metadata(
+        aggregate_id.pretty(),
+        command,
+        MetaData::<BankAccount>::default().into(),
+    )
+    .await?;
+    Ok(StatusCode::OK.into_response())
+}
@@ -0,0 +1,262 @@
+use crate::application::app_state::AppState;
+use crate::application::auth::{Principal, ADMIN_SCOPE};
+use crate::application::bank_routes::SecurityAddon;
+use crate::application::result::{OptionalResult, ProblemDetails};
+use crate::application::Pagination;
+use crate::errors::BankError;
+use crate::model::{
+    customer, Customer, CustomerAggregate, CustomerCommand, CustomerError, CustomerEvent,
+    CustomerId, DocumentKind, DocumentReference, EmailAddress, KycStatus, MailingAddress,
+};
+use crate::queries::{self, CustomerFilter, CustomerView, CustomerViewProjection};
+use axum::extract::rejection::{JsonRejection, PathRejection, QueryRejection};
+use axum::extract::{Path, Query, State};
+use axum::response::IntoResponse;
+use axum::{routing, Json, Router};
+use cqrs_es::persist::ViewRepository;
+use pretty_snowflake::envelope::MetaData;
+use pretty_snowflake::Id;
+use serde::{Deserialize, Serialize};
+use sqlx::PgPool;
+use utoipa::{OpenApi, ToSchema};
+use validator::Validate;
+
+#[derive(OpenApi)]
+#[openapi(
+    paths(
+        register_customer,
+        serve_customers,
+        serve_customer,
+        submit_document,
+        verify_identity,
+    ),
+    components(
+        schemas(
+            CustomerId, EmailAddress, MailingAddress, CustomerRegistration, CustomerView,
+            KycStatus, DocumentKind, DocumentReference, CustomerEvent,
+            ProblemDetails,
+        )
+    ),
+    modifiers(&SecurityAddon),
+    tags(
+        (name = "customers", description = "Customer and KYC Verification API")
+    )
+)]
+pub struct CustomerApiDoc;
+
+pub fn api() -> Router<AppState> {
+    Router::new()
+        .route("/", routing::post(register_customer).get(serve_customers))
+        .route("/:customer_id", routing::get(serve_customer))
+        .route("/:customer_id/documents", routing::post(submit_document))
+        .route("/:customer_id/verify", routing::post(verify_identity))
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, ToSchema, Validate, Serialize, Deserialize)]
+#[schema(example = json!({
+    "subject": "otis",
+    "name": "otis",
+    "email": "otis@example.com",
+    "mailing_address": "12 Seahawks Way, Renton, WA 98056",
+}))]
+pub struct CustomerRegistration {
+    /// Authenticated subject the customer signs in as. Customers registering themselves sign in
+    /// as the caller; admins may register a customer for any subject, or none.
+    #[serde(default)]
+    pub subject: Option<String>,
+
+    #[validate(length(min = 1))]
+    pub name: String,
+
+    #[validate]
+    pub email: EmailAddress,
+
+    pub mailing_address: MailingAddress,
+}
+
+#[utoipa::path(
+    post,
+    path = "/",
+    context_path = "/api/v1/customers",
+    tag = "customers",
+    request_body = CustomerRegistration,
+    responses(
+        (status = 200, description = "Customer registered, pending identity verification", body = CustomerId),
+        (status = 400, description = "bad request", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 401, description = "Missing or unrecognized bearer token"),
+        (status = 403, description = "Caller registers another subject without the admin:account scope", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 409, description = "A customer is already registered for the subject", body = ProblemDetails, content_type = "application/problem+json"),
+    ),
+)]
+#[axum::debug_handler(state = AppState)]
+#[tracing::instrument(level = "debug", skip(agg, pool))]
+async fn register_customer(
+    principal: Principal, State(agg): State<CustomerAggregate>, State(pool): State<PgPool>,
+    registration: Result<Json<CustomerRegistration>, JsonRejection>,
+) -> impl IntoResponse {
+    let Json(registration) = registration?;
+    registration.validate()?;
+
+    if matches!(&registration.subject, Some(subject) if subject != &principal.subject) {
+        principal.require_scope(ADMIN_SCOPE)?;
+    }
+    let subject = if principal.scopes.contains(ADMIN_SCOPE) {
+        registration.subject
+    } else {
+        Some(principal.subject.clone())
+    };
+
+    if let Some(subject) = &subject {
+        if queries::find_customer_id(&pool, subject).await?.is_some() {
+            return Err(CustomerError::SubjectAlreadyRegistered(subject.clone()).into());
+        }
+    }
+
+    let aggregate_id = customer::generate_id();
+    let customer_id: CustomerId = aggregate_id.clone().into();
+    let command = CustomerCommand::RegisterCustomer {
+        customer_id,
+        subject,
+        name: registration.name,
+        email: registration.email,
+        mailing_address: registration.mailing_address,
+    };
+
+    agg.execute_with_metadata(
+        aggregate_id.pretty(),
+        command,
+        MetaData::<Customer>::default().into(),
+    )
+    .await
+    .map_err::<BankError, _>(|err| err.into())
+    .map(|_| Json(customer_id))
+}
+
+#[utoipa::path(
+    get,
+    path = "/",
+    context_path = "/api/v1/customers",
+    tag = "customers",
+    params(Pagination, CustomerFilter),
+    responses(
+        (status = 200, description = "Customers, most recently registered first", body = [CustomerView]),
+        (status = 400, description = "bad request", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 401, description = "Missing or unrecognized bearer token"),
+        (status = 403, description = "Caller is not granted the admin:account scope"),
+    ),
+    security(("api_key" = ["admin:account"])),
+)]
+#[axum::debug_handler(state = AppState)]
+#[tracing::instrument(level = "debug", skip(pool))]
+async fn serve_customers(
+    principal: Principal, pagination: Result<Query<Pagination>, QueryRejection>,
+    filter: Result<Query<CustomerFilter>, QueryRejection>, State(pool): State<PgPool>,
+) -> impl IntoResponse {
+    principal.require_scope(ADMIN_SCOPE)?;
+    let Query(pagination) = pagination?;
+    let Query(filter) = filter?;
+    let customers =
+        queries::list_customers(&pool, &filter, pagination.offset()?, pagination.limit()).await?;
+    Result::<_, BankError>::Ok(Json(customers))
+}
+
+#[utoipa::path(
+    get,
+    path = "/{customer_id}",
+    context_path = "/api/v1/customers",
+    tag = "customers",
+    params(CustomerId),
+    responses(
+        (status = 200, description = "Customer", body = CustomerView),
+        (status = 401, description = "Missing or unrecognized bearer token"),
+        (status = 403, description = "Caller is neither the customer nor granted the admin:account scope", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 404, description = "No customer found.", body = ProblemDetails, content_type = "application/problem+json"),
+    ),
+)]
+#[axum::debug_handler(state = AppState)]
+#[tracing::instrument(level = "debug", skip(view_repo))]
+async fn serve_customer(
+    customer_id: Result<Path<CustomerId>, PathRejection>, principal: Principal,
+    State(view_repo): State<CustomerViewProjection>,
+) -> impl IntoResponse {
+    let Path(customer_id) = customer_id?;
+    let aggregate_id: Id<Customer> = customer_id.into();
+    let view = view_repo.load(aggregate_id.pretty()).await?;
+    let is_customer = matches!(
+        &view,
+        Some(view) if view.subject.as_deref() == Some(principal.subject.as_str())
+    );
+    if !is_customer {
+        principal.require_scope(ADMIN_SCOPE)?;
+    }
+    Result::<_, BankError>::Ok(OptionalResult::new(view.map(Json), "customer", customer_id))
+}
+
+#[utoipa::path(
+    post,
+    path = "/{customer_id}/documents",
+    context_path = "/api/v1/customers",
+    tag = "customers",
+    params(CustomerId),
+    request_body = DocumentReference,
+    responses(
+        (status = 200, description = "Identity document submitted for verification"),
+        (status = 400, description = "No such customer or the customer is already verified", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 401, description = "Missing or unrecognized bearer token"),
+        (status = 403, description = "Caller is not granted the admin:account scope"),
+    ),
+    security(("api_key" = ["admin:account"])),
+)]
+#[axum::debug_handler(state = AppState)]
+#[tracing::instrument(level = "debug", skip(agg))]
+async fn submit_document(
+    customer_id: Result<Path<CustomerId>, PathRejection>, principal: Principal,
+    State(agg): State<CustomerAggregate>, document: Result<Json<DocumentReference>, JsonRejection>,
+) -> impl IntoResponse {
+    principal.require_scope(ADMIN_SCOPE)?;
+    let Path(customer_id) = customer_id?;
+    let aggregate_id: Id<Customer> = customer_id.into();
+    let Json(document) = document?;
+
+    agg.execute_with_metadata(
+        aggregate_id.pretty(),
+        CustomerCommand::SubmitDocument { document },
+        MetaData::<Customer>::default().into(),
+    )
+    .await
+    .map_err::<BankError, _>(|err| err.into())
+}
+
+#[utoipa::path(
+    post,
+    path = "/{customer_id}/verify",
+    context_path = "/api/v1/customers",
+    tag = "customers",
+    params(CustomerId),
+    responses(
+        (status = 200, description = "Submitted documents checked; the customer is verified or rejected"),
+        (status = 400, description = "Customer is not pending verification", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 401, description = "Missing or unrecognized bearer token"),
+        (status = 403, description = "Caller is not granted the admin:account scope"),
+        (status = 422, description = "No documents to verify", body = ProblemDetails, content_type = "application/problem+json"),
+    ),
+    security(("api_key" = ["admin:account"])),
+)]
+#[axum::debug_handler(state = AppState)]
+#[tracing::instrument(level = "debug", skip(agg))]
+async fn verify_identity(
+    customer_id: Result<Path<CustomerId>, PathRejection>, principal: Principal,
+    State(agg): State<CustomerAggregate>,
+) -> impl IntoResponse {
+    principal.require_scope(ADMIN_SCOPE)?;
+    let Path(customer_id) = customer_id?;
+    let aggregate_id: Id<Customer> = customer_id.into();
+
+    agg.execute_with_metadata(
+        aggregate_id.pretty(),
+        CustomerCommand::VerifyIdentity,
+        MetaData::<Customer>::default().into(),
+    )
+    .await
+    .map_err::<BankError, _>(|err| err.into())
+}
@@ -0,0 +1,267 @@
+use crate::application::app_state::AppState;
+use crate::application::auth::{Principal, ADMIN_SCOPE};
+use crate::application::bank_routes::{authorize_holder_roles, ApiMoney, SecurityAddon};
+use crate::application::result::{OptionalResult, ProblemDetails};
+use crate::application::Pagination;
+use crate::errors::BankError;
+use crate::model::{
+    dispute, AccountId, AtmId, CheckNumber, Dispute, DisputeAggregate, DisputeCommand,
+    DisputeEvent, DisputeId, DisputeStatus, DisputedTransaction, HolderRole,
+};
+use crate::queries::{self, DisputeFilter, DisputeView, DisputeViewProjection};
+use axum::extract::rejection::{JsonRejection, PathRejection, QueryRejection};
+use axum::extract::{Path, Query, State};
+use axum::response::IntoResponse;
+use axum::{routing, Json, Router};
+use cqrs_es::persist::ViewRepository;
+use pretty_snowflake::envelope::MetaData;
+use pretty_snowflake::Id;
+use serde::{Deserialize, Serialize};
+use sqlx::PgPool;
+use utoipa::{OpenApi, ToSchema};
+use validator::Validate;
+
+#[derive(OpenApi)]
+#[openapi(
+    paths(
+        open_dispute,
+        serve_disputes,
+        serve_dispute,
+        issue_provisional_credit,
+        resolve_dispute,
+    ),
+    components(
+        schemas(
+            DisputeId, AccountId, AtmId, CheckNumber, ApiMoney, DisputeRequest, ResolutionRequest,
+            Resolution, DisputeView, DisputeStatus, DisputedTransaction, DisputeEvent,
+            ProblemDetails,
+        )
+    ),
+    modifiers(&SecurityAddon),
+    tags(
+        (name = "disputes", description = "Customer Dispute API")
+    )
+)]
+pub struct DisputeApiDoc;
+
+pub fn api() -> Router<AppState> {
+    Router::new()
+        .route("/", routing::post(open_dispute).get(serve_disputes))
+        .route("/:dispute_id", routing::get(serve_dispute))
+        .route(
+            "/:dispute_id/provisional_credit",
+            routing::post(issue_provisional_credit),
+        )
+        .route("/:dispute_id/resolve", routing::post(resolve_dispute))
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, ToSchema, Validate, Serialize, Deserialize)]
+#[schema(example = json!({
+    "account_id": 7006077196242653184_u64,
+    "sequence": 3,
+    "reason": "ATM did not dispense cash",
+}))]
+pub struct DisputeRequest {
+    pub account_id: AccountId,
+
+    /// Sequence number of the account event that posted the disputed withdrawal.
+    pub sequence: usize,
+
+    #[validate(length(min = 1))]
+    pub reason: String,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, ToSchema, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Resolution {
+    Customer,
+    Bank,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, ToSchema, Validate, Serialize, Deserialize)]
+#[schema(example = json!({ "resolution": "customer", "note": "ATM journal shows no dispense" }))]
+pub struct ResolutionRequest {
+    pub resolution: Resolution,
+
+    #[validate(length(min = 1))]
+    pub note: String,
+}
+
+#[utoipa::path(
+    post,
+    path = "/",
+    context_path = "/api/v1/disputes",
+    tag = "disputes",
+    request_body = DisputeRequest,
+    responses(
+        (status = 200, description = "Dispute opened", body = DisputeId),
+        (status = 401, description = "Missing or unrecognized bearer token"),
+        (status = 403, description = "Caller does not hold the account as a signer", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 422, description = "Transaction is not a disputable withdrawal", body = ProblemDetails, content_type = "application/problem+json"),
+    ),
+)]
+#[axum::debug_handler(state = AppState)]
+#[tracing::instrument(level = "debug", skip(agg, pool))]
+async fn open_dispute(
+    principal: Principal, State(agg): State<DisputeAggregate>, State(pool): State<PgPool>,
+    request: Result<Json<DisputeRequest>, JsonRejection>,
+) -> impl IntoResponse {
+    let Json(request) = request?;
+    request.validate()?;
+    authorize_holder_roles(&pool, &principal, request.account_id, &HolderRole::SIGNERS).await?;
+
+    let aggregate_id = dispute::generate_id();
+    let dispute_id: DisputeId = aggregate_id.clone().into();
+    let command = DisputeCommand::OpenDispute {
+        dispute_id,
+        account_id: request.account_id,
+        sequence: request.sequence,
+        reason: request.reason,
+    };
+
+    agg.execute_with_metadata(
+        aggregate_id.pretty(),
+        command,
+        MetaData::<Dispute>::default().into(),
+    )
+    .await
+    .map_err::<BankError, _>(|err| err.into())?;
+    Result::<_, BankError>::Ok(Json(dispute_id))
+}
+
+#[utoipa::path(
+    get,
+    path = "/",
+    context_path = "/api/v1/disputes",
+    tag = "disputes",
+    params(Pagination, DisputeFilter),
+    responses(
+        (status = 200, description = "Disputes, most recently opened first", body = [DisputeView]),
+        (status = 400, description = "bad request", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 401, description = "Missing or unrecognized bearer token"),
+        (status = 403, description = "Caller does not hold the filtered account, or lists every account's disputes without the admin:account scope", body = ProblemDetails, content_type = "application/problem+json"),
+    ),
+)]
+#[axum::debug_handler(state = AppState)]
+#[tracing::instrument(level = "debug", skip(pool))]
+async fn serve_disputes(
+    principal: Principal, pagination: Result<Query<Pagination>, QueryRejection>,
+    filter: Result<Query<DisputeFilter>, QueryRejection>, State(pool): State<PgPool>,
+) -> impl IntoResponse {
+    let Query(pagination) = pagination?;
+    let Query(filter) = filter?;
+    match filter.account_id {
+        Some(account_id) => {
+            authorize_holder_roles(&pool, &principal, account_id, &HolderRole::ALL).await?
+        },
+        None => principal.require_scope(ADMIN_SCOPE)?,
+    }
+    let disputes =
+        queries::list_disputes(&pool, &filter, pagination.offset()?, pagination.limit()).await?;
+    Result::<_, BankError>::Ok(Json(disputes))
+}
+
+#[utoipa::path(
+    get,
+    path = "/{dispute_id}",
+    context_path = "/api/v1/disputes",
+    tag = "disputes",
+    params(DisputeId),
+    responses(
+        (status = 200, description = "Dispute", body = DisputeView),
+        (status = 401, description = "Missing or unrecognized bearer token"),
+        (status = 403, description = "Caller does not hold the disputed account", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 404, description = "No dispute found.", body = ProblemDetails, content_type = "application/problem+json"),
+    ),
+)]
+#[axum::debug_handler(state = AppState)]
+#[tracing::instrument(level = "debug", skip(view_repo, pool))]
+async fn serve_dispute(
+    dispute_id: Result<Path<DisputeId>, PathRejection>, principal: Principal,
+    State(view_repo): State<DisputeViewProjection>, State(pool): State<PgPool>,
+) -> impl IntoResponse {
+    let Path(dispute_id) = dispute_id?;
+    let aggregate_id: Id<Dispute> = dispute_id.into();
+    let view = view_repo.load(aggregate_id.pretty()).await?;
+    match view.as_ref().and_then(|view| view.account_id) {
+        Some(account_id) => {
+            authorize_holder_roles(&pool, &principal, account_id, &HolderRole::ALL).await?
+        },
+        None => principal.require_scope(ADMIN_SCOPE)?,
+    }
+    Result::<_, BankError>::Ok(OptionalResult::new(view.map(Json), "dispute", dispute_id))
+}
+
+#[utoipa::path(
+    post,
+    path = "/{dispute_id}/provisional_credit",
+    context_path = "/api/v1/disputes",
+    tag = "disputes",
+    params(DisputeId),
+    responses(
+        (status = 200, description = "Disputed amount provisionally credited to the account"),
+        (status = 400, description = "No open dispute or the transaction can no longer be credited", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 401, description = "Missing or unrecognized bearer token"),
+        (status = 403, description = "Caller is not granted the admin:account scope"),
+    ),
+    security(("api_key" = ["admin:account"])),
+)]
+#[axum::debug_handler(state = AppState)]
+#[tracing::instrument(level = "debug", skip(agg))]
+async fn issue_provisional_credit(
+    dispute_id: Result<Path<DisputeId>, PathRejection>, principal: Principal,
+    State(agg): State<DisputeAggregate>,
+) -> impl IntoResponse {
+    principal.require_scope(ADMIN_SCOPE)?;
+    let Path(dispute_id) = dispute_id?;
+    let aggregate_id: Id<Dispute> = dispute_id.into();
+
+    agg.execute_with_metadata(
+        aggregate_id.pretty(),
+        DisputeCommand::IssueProvisionalCredit,
+        MetaData::<Dispute>::default().into(),
+    )
+    .await
+    .map_err::<BankError, _>(|err| err.into())
+}
+
+#[utoipa::path(
+    post,
+    path = "/{dispute_id}/resolve",
+    context_path = "/api/v1/disputes",
+    tag = "disputes",
+    params(DisputeId),
+    request_body = ResolutionRequest,
+    responses(
+        (status = 200, description = "Dispute resolved and the account adjusted accordingly"),
+        (status = 400, description = "No open dispute to resolve", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 401, description = "Missing or unrecognized bearer token"),
+        (status = 403, description = "Caller is not granted the admin:account scope"),
+    ),
+    security(("api_key" = ["admin:account"])),
+)]
+#[axum::debug_handler(state = AppState)]
+#[tracing::instrument(level = "debug", skip(agg))]
+async fn resolve_dispute(
+    dispute_id: Result<Path<DisputeId>, PathRejection>, principal: Principal,
+    State(agg): State<DisputeAggregate>, request: Result<Json<ResolutionRequest>, JsonRejection>,
+) -> impl IntoResponse {
+    principal.require_scope(ADMIN_SCOPE)?;
+    let Path(dispute_id) = dispute_id?;
+    let aggregate_id: Id<Dispute> = dispute_id.into();
+    let Json(request) = request?;
+    request.validate()?;
+
+    let command = match request.resolution {
+        Resolution::Customer => DisputeCommand::ResolveForCustomer { note: request.note },
+        Resolution::Bank => DisputeCommand::ResolveForBank { note: request.note },
+    };
+
+    agg.execute_with_metadata(
+        aggregate_id.pretty(),
+        command,
+        MetaData::<Dispute>::default().into(),
+    )
+    .await
+    .map_err::<BankError, _>(|err| err.into())
+}
@@ -6,6 +6,9 @@ pub enum ApiError {
     #[error("Invalid URL path input: {0}")]
     Path(#[from] axum::extract::rejection::PathRejection),
 
+    #[error("Invalid query parameters: {0}")]
+    Query(#[from] axum::extract::rejection::QueryRejection),
+
     #[error("Invalid JSON payload: {0}")]
     Json(#[from] axum::extract::rejection::JsonRejection),
 
@@ -30,6 +33,65 @@ pub enum ApiError {
         // backtrace: Backtrace,
     },
 
+    #[error("invalid correlation settings: {0}")]
+    Correlation(#[from] crate::settings::CorrelationIdOutOfRange),
+
+    #[error("{0}")]
+    Outbox(#[from] crate::outbox::OutboxError),
+
+    #[error("{0}")]
+    Webhook(#[from] crate::webhooks::WebhookError),
+
+    #[error("{0}")]
+    Notification(#[from] crate::notifications::NotificationError),
+
+    #[error("{0}")]
+    CommandQueue(#[from] crate::commands::CommandQueueError),
+
+    #[error("request is missing a valid bearer token")]
+    Unauthenticated,
+
+    #[error("{subject} is not granted the {scope} scope")]
+    Forbidden { subject: String, scope: String },
+
+    #[error("too many requests; retry after {retry_after:?}")]
+    RateLimited { retry_after: std::time::Duration },
+
+    #[error("failed to migrate database: {0}")]
+    Migration(#[from] sqlx::migrate::MigrateError),
+
+    #[error(
+        "database schema includes migration {0}, which this build does not know; deploy a build \
+         at least as new as the schema"
+    )]
+    SchemaAhead(i64),
+
     #[error("failed joining with thread: {0}")]
     Join(#[from] tokio::task::JoinError),
 }
+
+impl ApiError {
+    /// Stable machine-readable code identifying the error to API clients.
+    pub const fn error_code(&self) -> &'static str {
+        match self {
+            Self::Path(_) | Self::Query(_) | Self::Json(_) => "invalid_request",
+            Self::Unauthenticated => "unauthenticated",
+            Self::Forbidden { .. } => "forbidden",
+            Self::RateLimited { .. } => "rate_limited",
+            Self::Sql { .. } => "database_unavailable",
+            Self::CommandQueue(crate::commands::CommandQueueError::AttemptsExhausted(_)) => {
+                "command_attempts_exhausted"
+            },
+            Self::CommandQueue(_) => "command_queue_unavailable",
+            Self::IO { .. }
+            | Self::HyperHttp { .. }
+            | Self::Correlation(_)
+            | Self::Outbox(_)
+            | Self::Webhook(_)
+            | Self::Notification(_)
+            | Self::Migration(_)
+            | Self::SchemaAhead(_)
+            | Self::Join(_) => "internal_error",
+        }
+    }
+}
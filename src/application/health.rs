@@ -0,0 +1,230 @@
+//! Health of the service and the dependencies it relies upon.
+//!
+//! The [HealthRegistry] probes each [HealthComponent], timing each probe and remembering each
+//! component's most recent failure, so the report shows a dependency that failed recently even
+//! after it recovers. Readiness only checks the database: the service is ready once the database
+//! answers and its schema includes every migration embedded in the binary.
+
+use crate::application::{ACCOUNT_QUERY_VIEW, MIGRATOR};
+use crate::model;
+use crate::queries::EVENTS_TABLE;
+use crate::services::{BankAccountApi, ValidationServices};
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use sqlx::PgPool;
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use strum_macros::{Display, EnumString, EnumVariantNames};
+use utoipa::ToSchema;
+
+/// Time allowed for a component to answer its probe before it is considered in error.
+const PROBE_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Postgres error code raised when querying a table that does not exist.
+const UNDEFINED_TABLE: &str = "42P01";
+
+#[derive(
+    Debug,
+    Display,
+    Copy,
+    Clone,
+    PartialEq,
+    Eq,
+    Hash,
+    EnumString,
+    EnumVariantNames,
+    ToSchema,
+    Serialize,
+)]
+// #[serde(rename_all = "snake_case")]
+#[strum(serialize_all = "snake_case", ascii_case_insensitive)]
+pub enum HealthStatus {
+    Up,
+    NotReady,
+    Error,
+    Down,
+}
+
+/// A dependency of the service whose health is probed.
+#[derive(
+    Debug, Display, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, ToSchema, Serialize,
+)]
+#[serde(rename_all = "snake_case")]
+#[strum(serialize_all = "snake_case")]
+pub enum HealthComponent {
+    EventStore,
+    ViewStore,
+    ExchangeRates,
+    AtmAndCheckServices,
+}
+
+impl HealthComponent {
+    pub const ALL: [Self; 4] = [
+        Self::EventStore,
+        Self::ViewStore,
+        Self::ExchangeRates,
+        Self::AtmAndCheckServices,
+    ];
+}
+
+#[derive(Debug, Clone, PartialEq, ToSchema, Serialize)]
+pub struct HealthReport {
+    pub status: HealthStatus,
+    pub components: BTreeMap<HealthComponent, ComponentHealth>,
+
+    /// Versions of the embedded migrations not yet applied to the database.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub pending_migrations: Vec<i64>,
+}
+
+/// Whether the service is ready to serve requests, judged by the database alone.
+#[derive(Debug, Clone, PartialEq, Eq, ToSchema, Serialize)]
+pub struct ReadinessReport {
+    pub status: HealthStatus,
+
+    /// Versions of the embedded migrations not yet applied to the database.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub pending_migrations: Vec<i64>,
+}
+
+#[derive(Debug, Clone, PartialEq, ToSchema, Serialize)]
+pub struct ComponentHealth {
+    pub status: HealthStatus,
+
+    /// Time taken by the component to answer the probe.
+    pub latency_ms: f64,
+
+    /// The component's most recent failure, even if it has since recovered.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub last_error: Option<ComponentError>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, ToSchema, Serialize)]
+pub struct ComponentError {
+    pub message: String,
+    pub at: DateTime<Utc>,
+}
+
+#[derive(Clone)]
+pub struct HealthRegistry {
+    pool: PgPool,
+    validation: ValidationServices,
+    last_errors: Arc<Mutex<HashMap<HealthComponent, ComponentError>>>,
+}
+
+impl fmt::Debug for HealthRegistry {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("HealthRegistry").finish()
+    }
+}
+
+impl HealthRegistry {
+    pub fn new(pool: PgPool, validation: ValidationServices) -> Self {
+        Self { pool, validation, last_errors: Arc::default() }
+    }
+
+    /// Probes every component and checks for pending migrations.
+    #[tracing::instrument(level = "trace", skip(self))]
+    pub async fn report(&self) -> HealthReport {
+        let checks = HealthComponent::ALL.map(|component| self.check(component));
+        let components: BTreeMap<_, _> = HealthComponent::ALL
+            .into_iter()
+            .zip(futures::future::join_all(checks).await)
+            .collect();
+
+        let all_components_are_up =
+            components.values().all(|health| health.status == HealthStatus::Up);
+        let readiness = self.readiness().await;
+        let status = if all_components_are_up { readiness.status } else { HealthStatus::Down };
+
+        HealthReport {
+            status,
+            components,
+            pending_migrations: readiness.pending_migrations,
+        }
+    }
+
+    /// Checks the database answers and has no pending migrations, without probing the other
+    /// components.
+    #[tracing::instrument(level = "trace", skip(self))]
+    pub async fn readiness(&self) -> ReadinessReport {
+        let (status, pending_migrations) = match self.pending_migrations().await {
+            Ok(pending) if pending.is_empty() => (HealthStatus::Up, pending),
+            Ok(pending) => (HealthStatus::NotReady, pending),
+            Err(error) => {
+                tracing::error!(?error, "failed to check for pending database migrations");
+                (HealthStatus::Down, Vec::new())
+            },
+        };
+
+        ReadinessReport { status, pending_migrations }
+    }
+
+    async fn check(&self, component: HealthComponent) -> ComponentHealth {
+        let start = Instant::now();
+        let result = tokio::time::timeout(PROBE_TIMEOUT, self.probe(component))
+            .await
+            .unwrap_or_else(|_| Err(anyhow::anyhow!("no answer within {PROBE_TIMEOUT:?}")));
+        let latency = start.elapsed();
+
+        let mut last_errors =
+            self.last_errors.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+        let status = match result {
+            Ok(()) => HealthStatus::Up,
+            Err(error) => {
+                tracing::error!("{component} is down with error: {error:?}");
+                let error = ComponentError { message: error.to_string(), at: Utc::now() };
+                last_errors.insert(component, error);
+                HealthStatus::Error
+            },
+        };
+
+        ComponentHealth {
+            status,
+            latency_ms: latency.as_secs_f64() * 1_000.0,
+            last_error: last_errors.get(&component).cloned(),
+        }
+    }
+
+    async fn probe(&self, component: HealthComponent) -> Result<(), anyhow::Error> {
+        match component {
+            HealthComponent::EventStore => {
+                let select_sql = format!("SELECT event_version FROM {EVENTS_TABLE} LIMIT 1");
+                sqlx::query(&select_sql).fetch_optional(&self.pool).await?;
+            },
+            HealthComponent::ViewStore => {
+                let select_sql = format!("SELECT version FROM {ACCOUNT_QUERY_VIEW} LIMIT 1");
+                sqlx::query(&select_sql).fetch_optional(&self.pool).await?;
+            },
+            HealthComponent::ExchangeRates => model::verify_exchange_rates()?,
+            HealthComponent::AtmAndCheckServices => self.validation.check_availability().await?,
+        }
+
+        Ok(())
+    }
+
+    /// Versions of the embedded migrations the database has not applied.
+    async fn pending_migrations(&self) -> Result<Vec<i64>, sqlx::Error> {
+        let applied: Vec<i64> =
+            match sqlx::query_scalar("SELECT version FROM _sqlx_migrations WHERE success")
+                .fetch_all(&self.pool)
+                .await
+            {
+                Ok(applied) => applied,
+                Err(sqlx::Error::Database(error))
+                    if error.code().as_deref() == Some(UNDEFINED_TABLE) =>
+                {
+                    Vec::new()
+                },
+                Err(error) => return Err(error),
+            };
+
+        Ok(MIGRATOR
+            .iter()
+            .map(|migration| migration.version)
+            .filter(|version| !applied.contains(version))
+            .collect())
+    }
+}
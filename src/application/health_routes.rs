@@ -1,23 +1,23 @@
 use crate::application::app_state::AppState;
-use crate::application::ACCOUNT_QUERY_VIEW;
+use crate::application::health::{
+    ComponentError, ComponentHealth, HealthComponent, HealthReport, HealthStatus, ReadinessReport,
+};
 use axum::extract::State;
 use axum::http::StatusCode;
 use axum::response::IntoResponse;
 use axum::Router;
 use axum::{routing, Json};
-use itertools::Itertools;
 use serde::Serialize;
-use serde_json::json;
-use std::collections::HashMap;
-use std::string::ToString;
-use strum_macros::{Display, EnumString, EnumVariantNames};
 use utoipa::{OpenApi, ToSchema};
 
 #[derive(OpenApi)]
 #[openapi(
     paths(serve_health, serve_readiness, serve_liveness),
     components(
-        schemas(HealthStatus, HealthStatusReport)
+        schemas(
+            HealthStatus, HealthStatusReport, HealthReport, ReadinessReport, HealthComponent,
+            ComponentHealth, ComponentError
+        )
     ),
     tags(
         (name = "health", description = "Bank Account Health API")
@@ -36,28 +36,6 @@ pub fn api() -> Router<AppState> {
         .route("/live", routing::get(serve_liveness))
 }
 
-#[derive(
-    Debug,
-    Display,
-    Copy,
-    Clone,
-    PartialEq,
-    Eq,
-    Hash,
-    EnumString,
-    EnumVariantNames,
-    ToSchema,
-    Serialize,
-)]
-// #[serde(rename_all = "snake_case")]
-#[strum(serialize_all = "snake_case", ascii_case_insensitive)]
-pub enum HealthStatus {
-    Up,
-    NotReady,
-    Error,
-    Down,
-}
-
 #[derive(Debug, Clone, PartialEq, Eq, ToSchema, Serialize)]
 pub struct HealthStatusReport {
     status: HealthStatus,
@@ -85,106 +63,51 @@ impl From<HealthStatus> for StatusCode {
     context_path = "/api/v1/health",
     tag = "health",
     responses(
-        (status = 200, description = "system health"),
-        (status = 5XX, description = "system down")
+        (status = 200, description = "system health", body = HealthReport),
+        (status = 5XX, description = "system down", body = HealthReport)
     )
 )]
 #[axum::debug_handler]
 #[tracing::instrument(level = "trace", skip(app))]
 async fn serve_health(State(app): State<AppState>) -> impl IntoResponse {
-    let (system_health, _health_report) = check_health(app).await;
-    serde_json::to_value::<HealthStatusReport>(system_health.into())
-        .map(|resp| (system_health.into(), Json(resp)))
-        .unwrap_or_else(|error| {
-            (
-                StatusCode::INTERNAL_SERVER_ERROR,
-                Json(json!({ "error": error.to_string() })),
-            )
-        })
+    let report = app.health.report().await;
+    let status_code: StatusCode = report.status.into();
+    (status_code, Json(report))
 }
 
+/// The service is ready once the database answers and its migrations are applied; other
+/// dependencies are reported by the health check instead.
 #[utoipa::path(
     get,
     path = "/ready",
     context_path = "/api/v1/health",
     tag = "health",
     responses(
-        (status = 200, description = "system ready"),
-        (status = 5XX, description = "system not ready")
+        (status = 200, description = "system ready", body = ReadinessReport),
+        (status = 5XX, description = "system not ready, e.g., while database migrations are pending", body = ReadinessReport)
     )
 )]
 #[axum::debug_handler]
 #[tracing::instrument(level = "trace", skip(app))]
 async fn serve_readiness(State(app): State<AppState>) -> impl IntoResponse {
-    let (system_health, _) = check_health(app).await;
-    let status_code: StatusCode = system_health.into();
-    status_code
+    let report = app.health.readiness().await;
+    let status_code: StatusCode = report.status.into();
+    (status_code, Json(report))
 }
 
+/// The service is live while it can answer requests, so liveness does not probe its dependencies.
 #[utoipa::path(
     get,
     path = "/live",
     context_path = "/api/v1/health",
     tag = "health",
     responses(
-        (status = 200, description = "system live"),
-        (status = 5XX, description = "system not live")
+        (status = 200, description = "system live", body = HealthStatusReport)
     )
 )]
-#[axum::debug_handler]
-#[tracing::instrument(level = "trace", skip(app))]
-async fn serve_liveness(State(app): State<AppState>) -> impl IntoResponse {
-    let (system_health, _) = check_health(app).await;
-    let status_code: StatusCode = system_health.into();
-    status_code
-}
-
-#[tracing::instrument(level = "trace", skip(app))]
-async fn check_health(app: AppState) -> (HealthStatus, HashMap<HealthStatus, Vec<&'static str>>) {
-    let view_select_sql = format!("SELECT version FROM {ACCOUNT_QUERY_VIEW}");
-    let view_status: Result<(), anyhow::Error> = sqlx::query(&view_select_sql)
-        .fetch_optional(&app.db_pool)
-        .await
-        .map_err(|err| err.into())
-        .map(|_| ());
-
-    let agg_select_sql = "SELECT event_version FROM events";
-    let agg_status: Result<(), anyhow::Error> = sqlx::query(agg_select_sql)
-        .fetch_optional(&app.db_pool)
-        .await
-        .map_err(|err| err.into())
-        .map(|_| ());
-
-    let service_statuses = vec![
-        ("bank_account_aggregate", agg_status),
-        ("bank_account_view", view_status),
-    ];
-
-    let service_by_status = service_statuses
-        .into_iter()
-        .map(|(service, status)| {
-            let health = match status {
-                Ok(()) => HealthStatus::Up,
-                Err(error) => {
-                    tracing::error!("{service} service is down with error: {error:?}");
-                    HealthStatus::Error
-                },
-            };
-            (service, health)
-        })
-        .into_group_map_by(|(_, health)| *health);
-
-    let health_report: HashMap<_, _> = service_by_status
-        .into_iter()
-        .map(|(status, service_status)| {
-            let services: Vec<_> = service_status.into_iter().map(|s| s.0).collect();
-            (status, services)
-        })
-        .collect();
-
-    let all_services_are_up =
-        health_report.iter().all(|(health, _services)| *health == HealthStatus::Up);
-    let system_health = if all_services_are_up { HealthStatus::Up } else { HealthStatus::Down };
-
-    (system_health, health_report)
+#[tracing::instrument(level = "trace")]
+async fn serve_liveness() -> impl IntoResponse {
+    let status = HealthStatus::Up;
+    let status_code: StatusCode = status.into();
+    (status_code, Json(HealthStatusReport::from(status)))
 }
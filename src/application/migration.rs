@@ -0,0 +1,47 @@
+use crate::application::ApiError;
+use sqlx::migrate::{MigrateError, Migrator};
+use sqlx::PgPool;
+
+/// The database migrations in the `migrations` directory, embedded in the binary.
+pub static MIGRATOR: Migrator = sqlx::migrate!("./migrations");
+
+/// Key of the advisory lock held while migrations are applied.
+const MIGRATION_LOCK_KEY: i64 = 0x4d49_4752_4154_4553; // "MIGRATES"
+
+/// Applies the pending embedded migrations.
+///
+/// Instances migrating concurrently wait on an advisory lock, so the first applies the migrations
+/// and the rest find them applied. Fails if the database records a migration this build does not
+/// embed, i.e., the schema is ahead of the binary.
+#[tracing::instrument(level = "info", skip(pool))]
+pub async fn migrate_database(pool: &PgPool) -> Result<(), ApiError> {
+    let mut conn = pool.acquire().await?;
+    sqlx::query("SELECT pg_advisory_lock($1)")
+        .bind(MIGRATION_LOCK_KEY)
+        .execute(&mut *conn)
+        .await?;
+
+    let result = MIGRATOR.run(&mut *conn).await;
+
+    let unlocked = sqlx::query("SELECT pg_advisory_unlock($1)")
+        .bind(MIGRATION_LOCK_KEY)
+        .execute(&mut *conn)
+        .await;
+    if let Err(error) = unlocked {
+        // closing the connection releases its session lock instead of returning it to the pool
+        tracing::warn!(
+            ?error,
+            "failed to release migration lock -- closing its connection"
+        );
+        drop(conn.detach());
+    }
+
+    match result {
+        Ok(()) => {
+            tracing::info!("database schema is up to date");
+            Ok(())
+        },
+        Err(MigrateError::VersionMissing(version)) => Err(ApiError::SchemaAhead(version)),
+        Err(error) => Err(error.into()),
+    }
+}
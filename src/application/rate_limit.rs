@@ -0,0 +1,300 @@
+//! Per-client request rate limiting.
+//!
+//! Each client is identified by the subject of its bearer token if recognized, or else by its IP
+//! address, which is taken from the forwarding headers only on requests passed on by one of the
+//! [HttpApiSettings::trusted_proxies]. Requests are counted by `governor`'s keyed rate limiters,
+//! one for reads and another for writes, sized by the
+//! [RateLimitSettings](crate::settings::RateLimitSettings). Responses carry the `RateLimit-Limit`,
+//! `RateLimit-Remaining` and `RateLimit-Reset` headers of the client's quota, and a rejected
+//! request also `Retry-After`. The limiters are owned by the [RateLimiter] in the application
+//! state, so they are dropped with it when the server is rebuilt with new limits.
+
+use crate::application::{ApiError, AppState};
+use crate::errors::BankError;
+use crate::settings::{HttpApiSettings, RateLimitQuota};
+use axum::extract::{ConnectInfo, State};
+use axum::http::{HeaderMap, HeaderName, HeaderValue, Method, Request};
+use axum::middleware::Next;
+use axum::response::{IntoResponse, Response};
+use governor::clock::{Clock, DefaultClock};
+use governor::middleware::StateInformationMiddleware;
+use governor::state::keyed::DefaultKeyedStateStore;
+use governor::Quota;
+use std::fmt;
+use std::net::{IpAddr, SocketAddr};
+use std::num::NonZeroU32;
+use std::sync::Arc;
+use std::time::Duration;
+
+pub const RATE_LIMIT_LIMIT: HeaderName = HeaderName::from_static("ratelimit-limit");
+pub const RATE_LIMIT_REMAINING: HeaderName = HeaderName::from_static("ratelimit-remaining");
+pub const RATE_LIMIT_RESET: HeaderName = HeaderName::from_static("ratelimit-reset");
+
+const X_FORWARDED_FOR: &str = "x-forwarded-for";
+const X_REAL_IP: &str = "x-real-ip";
+
+/// Number of tracked clients above which those whose quota has fully replenished are discarded.
+const PRUNE_THRESHOLD: usize = 10_000;
+
+type KeyedLimiter = governor::RateLimiter<
+    String,
+    DefaultKeyedStateStore<String>,
+    DefaultClock,
+    StateInformationMiddleware,
+>;
+
+/// The quota against which a request is counted.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum RouteClass {
+    Read,
+    Write,
+}
+
+impl RouteClass {
+    pub const fn of(method: &Method) -> Self {
+        if matches!(*method, Method::GET | Method::HEAD | Method::OPTIONS) {
+            Self::Read
+        } else {
+            Self::Write
+        }
+    }
+}
+
+/// The outcome of counting a request against its client's quota.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct RateLimitDecision {
+    pub limit: u64,
+    pub remaining: u64,
+    /// Time until the quota is fully replenished.
+    pub reset: Duration,
+    /// Set if the request is rejected, to the time until it may be retried.
+    pub retry_after: Option<Duration>,
+}
+
+impl RateLimitDecision {
+    pub const fn is_allowed(&self) -> bool {
+        self.retry_after.is_none()
+    }
+
+    fn set_headers(&self, headers: &mut HeaderMap) {
+        headers.insert(RATE_LIMIT_LIMIT, HeaderValue::from(self.limit));
+        headers.insert(RATE_LIMIT_REMAINING, HeaderValue::from(self.remaining));
+        headers.insert(RATE_LIMIT_RESET, HeaderValue::from(whole_secs(self.reset)));
+    }
+}
+
+#[derive(Clone)]
+pub struct RateLimiter {
+    reads: Arc<ClientLimiter>,
+    writes: Arc<ClientLimiter>,
+    trusted_proxies: Arc<[IpAddr]>,
+}
+
+impl fmt::Debug for RateLimiter {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("RateLimiter")
+            .field("reads", &self.reads.quota)
+            .field("writes", &self.writes.quota)
+            .field("trusted_proxies", &self.trusted_proxies)
+            .finish()
+    }
+}
+
+impl RateLimiter {
+    pub fn from_settings(settings: &HttpApiSettings) -> Self {
+        Self {
+            reads: Arc::new(ClientLimiter::new(settings.rate_limit.reads)),
+            writes: Arc::new(ClientLimiter::new(settings.rate_limit.writes_quota())),
+            trusted_proxies: settings.trusted_proxies.clone().into(),
+        }
+    }
+
+    /// Counts a request of the client identified by `key`.
+    pub fn check(&self, class: RouteClass, key: &str) -> RateLimitDecision {
+        let limiter = match class {
+            RouteClass::Read => &self.reads,
+            RouteClass::Write => &self.writes,
+        };
+        limiter.check(key)
+    }
+}
+
+/// A keyed rate limiter allowing each client the quota's burst of requests.
+struct ClientLimiter {
+    quota: RateLimitQuota,
+    burst: NonZeroU32,
+    replenish_interval: Duration,
+    limiter: KeyedLimiter,
+}
+
+impl ClientLimiter {
+    fn new(quota: RateLimitQuota) -> Self {
+        let burst = u32::try_from(quota.nr_requests).unwrap_or(u32::MAX);
+        let burst = NonZeroU32::new(burst).expect("rate limit quotas allow at least one request");
+        let period = (quota.per_duration / burst.get()).max(Duration::from_nanos(1));
+        let governed = Quota::with_period(period)
+            .unwrap_or_else(|| Quota::per_second(burst))
+            .allow_burst(burst);
+
+        Self {
+            quota,
+            burst,
+            replenish_interval: governed.replenish_interval(),
+            limiter: governor::RateLimiter::keyed(governed)
+                .with_middleware::<StateInformationMiddleware>(),
+        }
+    }
+
+    fn check(&self, key: &str) -> RateLimitDecision {
+        if PRUNE_THRESHOLD <= self.limiter.len() {
+            self.limiter.retain_recent();
+        }
+
+        let limit = self.burst.get();
+        match self.limiter.check_key(&key.to_string()) {
+            Ok(snapshot) => {
+                let remaining = snapshot.remaining_burst_capacity();
+                RateLimitDecision {
+                    limit: u64::from(limit),
+                    remaining: u64::from(remaining),
+                    reset: self.replenish_interval * (limit - remaining),
+                    retry_after: None,
+                }
+            },
+            Err(not_until) => {
+                let retry_after = not_until.wait_time_from(DefaultClock::default().now());
+                RateLimitDecision {
+                    limit: u64::from(limit),
+                    remaining: 0,
+                    reset: retry_after + self.replenish_interval * (limit - 1),
+                    retry_after: Some(retry_after),
+                }
+            },
+        }
+    }
+}
+
+/// Middleware counting each request against its client's quota for the request's route class.
+pub async fn limit_requests<B>(
+    State(state): State<AppState>, request: Request<B>, next: Next<B>,
+) -> Response {
+    let key = client_key(&state, &request);
+    let decision = state.rate_limiter.check(RouteClass::of(request.method()), &key);
+
+    let mut response = match decision.retry_after {
+        None => next.run(request).await,
+        Some(retry_after) => {
+            tracing::info!(client=%key, ?retry_after, "request rate limited");
+            BankError::from(ApiError::RateLimited { retry_after }).into_response()
+        },
+    };
+    decision.set_headers(response.headers_mut());
+    response
+}
+
+/// Identifies the client by the subject of a recognized bearer token, or else by its IP address.
+fn client_key<B>(state: &AppState, request: &Request<B>) -> String {
+    if let Some(principal) = state.authenticator.authenticate_headers(request.headers()) {
+        return format!("client:{}", principal.subject);
+    }
+
+    let peer = request
+        .extensions()
+        .get::<ConnectInfo<SocketAddr>>()
+        .map(|ConnectInfo(addr)| addr.ip());
+    client_ip(peer, request.headers(), &state.rate_limiter.trusted_proxies)
+        .map(|ip| format!("ip:{ip}"))
+        .unwrap_or_else(|| "ip:unknown".to_string())
+}
+
+/// The address of the client, which is the peer unless the peer is a trusted proxy. Then the
+/// client is the nearest untrusted address in `X-Forwarded-For`, since each proxy appends the
+/// address it received the request from and the entries before it may be set by the client.
+fn client_ip(
+    peer: Option<IpAddr>, headers: &HeaderMap, trusted_proxies: &[IpAddr],
+) -> Option<IpAddr> {
+    let is_trusted = |ip: &IpAddr| trusted_proxies.contains(ip);
+    if !peer.iter().any(is_trusted) {
+        return peer;
+    }
+
+    let header = |name: &str| headers.get(name).and_then(|value| value.to_str().ok());
+    let forwarded_ip = header(X_FORWARDED_FOR).and_then(|forwarded| {
+        let hops: Vec<IpAddr> =
+            forwarded.split(',').filter_map(|ip| ip.trim().parse().ok()).collect();
+        hops.iter()
+            .rev()
+            .find(|ip| !is_trusted(*ip))
+            .or_else(|| hops.first())
+            .copied()
+    });
+
+    forwarded_ip
+        .or_else(|| header(X_REAL_IP).and_then(|ip| ip.trim().parse().ok()))
+        .or(peer)
+}
+
+/// Whole seconds, rounded up so a client waiting that long is not rejected again.
+pub const fn whole_secs(duration: Duration) -> u64 {
+    let secs = duration.as_secs();
+    if duration.subsec_nanos() == 0 {
+        secs
+    } else {
+        secs + 1
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn test_client_limiter_rejects_burst_per_client() {
+        let limiter = ClientLimiter::new(RateLimitQuota {
+            nr_requests: 2,
+            per_duration: Duration::from_secs(10),
+        });
+
+        assert_eq!(limiter.check("ip:10.0.0.1").remaining, 1);
+        assert_eq!(limiter.check("ip:10.0.0.1").remaining, 0);
+        let rejected = limiter.check("ip:10.0.0.1");
+        assert_eq!(rejected.remaining, 0);
+        assert_eq!(rejected.retry_after.map(whole_secs), Some(5));
+        assert_eq!(whole_secs(rejected.reset), 10);
+
+        let other = limiter.check("ip:10.0.0.2");
+        assert!(other.is_allowed());
+        assert_eq!(other.limit, 2);
+        assert_eq!(other.remaining, 1);
+    }
+
+    #[test]
+    fn test_client_ip_trusts_forwarding_headers_only_from_trusted_proxies() {
+        let proxy = IpAddr::from([10, 0, 0, 1]);
+        let peer = IpAddr::from([192, 0, 2, 7]);
+        let mut headers = HeaderMap::new();
+        headers.insert(
+            X_FORWARDED_FOR,
+            HeaderValue::from_static("1.2.3.4, 198.51.100.9, 10.0.0.1"),
+        );
+        headers.insert(X_REAL_IP, HeaderValue::from_static("1.2.3.4"));
+
+        assert_eq!(client_ip(Some(peer), &headers, &[proxy]), Some(peer));
+        assert_eq!(client_ip(Some(peer), &headers, &[]), Some(peer));
+        assert_eq!(
+            client_ip(Some(proxy), &headers, &[proxy]),
+            Some(IpAddr::from([198, 51, 100, 9]))
+        );
+
+        headers.remove(X_FORWARDED_FOR);
+        assert_eq!(
+            client_ip(Some(proxy), &headers, &[proxy]),
+            Some(IpAddr::from([1, 2, 3, 4]))
+        );
+
+        headers.remove(X_REAL_IP);
+        assert_eq!(client_ip(Some(proxy), &headers, &[proxy]), Some(proxy));
+        assert_eq!(client_ip(None, &headers, &[proxy]), None);
+    }
+}
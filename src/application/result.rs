@@ -1,101 +1,349 @@
+use crate::application::rate_limit::whole_secs;
+use crate::application::ApiError;
+use crate::commands::CommandQueueError;
 use crate::errors::BankError;
-use crate::model::BankAccountError;
-use axum::http::StatusCode;
+use crate::model::{BankAccountError, CustomerError, DisputeError, StandingOrderError};
+use crate::services::{BankServiceError, DisputeServiceError};
+use axum::extract::OriginalUri;
+use axum::http::header::{CONTENT_TYPE, RETRY_AFTER};
+use axum::http::{HeaderValue, Request, StatusCode};
+use axum::middleware::Next;
 use axum::response::{IntoResponse, Response};
 use axum::Json;
-use serde::Serialize;
-use std::borrow::Cow;
+use cqrs_es::AggregateError;
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+use utoipa::ToSchema;
 
 pub type HttpResult = Result<Response, BankError>;
 
+/// The resource looked up by a request, responding 404 Not Found with a `<resource>_not_found`
+/// problem if it is missing.
 #[derive(Debug)]
-#[repr(transparent)]
-pub struct OptionalResult<T>(pub Option<T>);
+pub struct OptionalResult<T> {
+    result: Option<T>,
+    resource: &'static str,
+    id: String,
+}
 
-impl<T: IntoResponse> IntoResponse for OptionalResult<T> {
-    fn into_response(self) -> Response {
-        self.0
-            .map(|result| (StatusCode::OK, result).into_response())
-            .unwrap_or_else(|| StatusCode::NOT_FOUND.into_response())
+impl<T> OptionalResult<T> {
+    /// The `resource`, named in snake case such as `standing_order`, identified by `id`.
+    pub fn new(result: Option<T>, resource: &'static str, id: impl std::fmt::Display) -> Self {
+        Self { result, resource, id: id.to_string() }
     }
 }
 
-impl<T: IntoResponse> From<Option<T>> for OptionalResult<T> {
-    fn from(result: Option<T>) -> Self {
-        Self(result)
+impl<T: IntoResponse> IntoResponse for OptionalResult<T> {
+    fn into_response(self) -> Response {
+        match self.result {
+            Some(result) => (StatusCode::OK, result).into_response(),
+            None => ProblemDetails::not_found(self.resource, &self.id).into_response(),
+        }
     }
 }
 
 impl IntoResponse for BankError {
     fn into_response(self) -> Response {
-        let http_error = HttpError::from_error(self.into());
+        let http_error = HttpError::from_error(self);
         http_error.into_response()
     }
 }
 
-#[derive(Debug, Clone, Serialize)]
-pub struct ErrorReport {
-    pub error: String,
+/// Media type of RFC 7807 problem details.
+pub const PROBLEM_JSON: &str = "application/problem+json";
+
+const X_REQUEST_ID: &str = "x-request-id";
+
+/// Hint to clients of a command rejected by a concurrent change to the same aggregate; the command
+/// is safe to retry once that change is committed.
+const CONFLICT_RETRY_AFTER: Duration = Duration::from_secs(1);
+
+tokio::task_local! {
+    /// The request being handled, which problem details identify.
+    static REQUEST_CONTEXT: RequestContext;
+}
+
+#[derive(Debug, Clone, Default)]
+struct RequestContext {
+    instance: String,
+    request_id: Option<String>,
+}
+
+/// Middleware recording the path and `x-request-id` of the request being handled, so an error
+/// response identifies the request that caused it.
+pub async fn track_request_context<B>(request: Request<B>, next: Next<B>) -> Response {
+    let uri = request
+        .extensions()
+        .get::<OriginalUri>()
+        .map_or_else(|| request.uri(), |original| &original.0);
+    let context = RequestContext {
+        instance: uri.path().to_string(),
+        request_id: request
+            .headers()
+            .get(X_REQUEST_ID)
+            .and_then(|value| value.to_str().ok())
+            .map(|value| value.to_string()),
+    };
+
+    REQUEST_CONTEXT.scope(context, next.run(request)).await
+}
+
+/// An RFC 7807 problem detail, the body of every error response.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+pub struct ProblemDetails {
+    /// URI identifying the problem type, `urn:bankaccount:problem:<code>`.
+    #[serde(rename = "type")]
+    pub problem_type: String,
 
+    /// Short summary of the problem type.
+    pub title: String,
+
+    /// HTTP status code of the response.
+    pub status: u16,
+
+    /// Explanation of this occurrence of the problem.
+    pub detail: String,
+
+    /// Path of the request that caused the problem.
     #[serde(skip_serializing_if = "Option::is_none")]
-    pub error_code: Option<String>,
+    pub instance: Option<String>,
+
+    /// Stable machine-readable code of the problem, such as `insufficient_funds` or
+    /// `rejected_command`.
+    pub code: String,
 
+    /// The `x-request-id` of the request that caused the problem.
     #[serde(skip_serializing_if = "Option::is_none")]
-    pub backtrace: Option<String>,
+    pub request_id: Option<String>,
 }
 
-impl From<anyhow::Error> for ErrorReport {
-    fn from(error: anyhow::Error) -> Self {
+impl ProblemDetails {
+    pub fn new(status: StatusCode, code: &str, detail: impl Into<String>) -> Self {
+        let context = REQUEST_CONTEXT.try_with(|context| context.clone()).unwrap_or_default();
         Self {
-            error: error.to_string(),
-            error_code: None,
-            backtrace: None,
+            problem_type: format!("urn:bankaccount:problem:{code}"),
+            title: title_of(code),
+            status: status.as_u16(),
+            detail: detail.into(),
+            instance: Some(context.instance).filter(|instance| !instance.is_empty()),
+            code: code.to_string(),
+            request_id: context.request_id,
         }
     }
+
+    /// The `<resource>_not_found` problem of a missing resource.
+    pub fn not_found(resource: &str, id: &str) -> Self {
+        Self::new(
+            StatusCode::NOT_FOUND,
+            &format!("{resource}_not_found"),
+            format!("No {} found for id: {id}", resource.replace('_', " ")),
+        )
+    }
+}
+
+impl From<&BankError> for ProblemDetails {
+    fn from(error: &BankError) -> Self {
+        let detail = match error {
+            BankError::BankAccount(BankAccountError::NotFound(account_id)) => {
+                format!("No bank account found for account id: {account_id}")
+            },
+            _ => error.to_string(),
+        };
+        Self::new(HttpError::status_of(error), error.error_code(), detail)
+    }
+}
+
+impl IntoResponse for ProblemDetails {
+    fn into_response(self) -> Response {
+        let status = StatusCode::from_u16(self.status).unwrap_or(StatusCode::INTERNAL_SERVER_ERROR);
+        (
+            status,
+            [(CONTENT_TYPE, HeaderValue::from_static(PROBLEM_JSON))],
+            Json(self),
+        )
+            .into_response()
+    }
+}
+
+/// The code read as a sentence, e.g., "Insufficient funds" for `insufficient_funds`.
+fn title_of(code: &str) -> String {
+    let title = code.replace('_', " ");
+    let mut chars = title.chars();
+    chars
+        .next()
+        .map(|first| first.to_uppercase().chain(chars).collect())
+        .unwrap_or_default()
 }
 
 #[derive(Debug, Clone)]
-pub enum HttpError {
-    BadRequest { error: ErrorReport },
-    NotFound { message: Cow<'static, str> },
-    Internal { error: ErrorReport },
+pub struct HttpError {
+    problem: ProblemDetails,
+    retry_after: Option<Duration>,
 }
 
 impl HttpError {
-    fn from_error(error: anyhow::Error) -> Self {
+    fn from_error(error: BankError) -> Self {
         tracing::error!("HTTP handler error: {error}");
-        match error.downcast_ref::<BankError>() {
-            Some(BankError::BankAccount(BankAccountError::NotFound(account_id))) => {
-                Self::NotFound {
-                    message: format!("No bank account found for account id: {account_id}").into(),
-                }
-            },
-            Some(BankError::BankAccount(_)) => Self::BadRequest { error: error.into() },
-            Some(BankError::Api(_)) => Self::Internal { error: error.into() },
-            Some(BankError::Validation(_)) => Self::BadRequest { error: error.into() },
-            Some(BankError::User(_)) => Self::BadRequest { error: error.into() },
+        let retry_after = match &error {
+            BankError::Api(ApiError::RateLimited { retry_after }) => Some(*retry_after),
+            BankError::AggregateConflict => Some(CONFLICT_RETRY_AFTER),
+            _ => None,
+        };
+
+        Self { problem: ProblemDetails::from(&error), retry_after }
+    }
+
+    const fn status_of(error: &BankError) -> StatusCode {
+        match error {
+            BankError::BankAccount(error) => account_status_of(error),
+            BankError::Customer(error) => customer_status_of(error),
+            BankError::Dispute(error) => dispute_status_of(error),
+            BankError::StandingOrder(error) => standing_order_status_of(error),
+            BankError::Api(error) => api_status_of(error),
+            BankError::Validation(_) => StatusCode::BAD_REQUEST,
+            BankError::User(_) => StatusCode::BAD_REQUEST,
+            BankError::AggregateConflict => StatusCode::CONFLICT,
+            BankError::DatabaseConnection { .. } => StatusCode::SERVICE_UNAVAILABLE,
 
             // consideration in explicit list rt. short circuit is compiler-enforced review of how
             // respond to new BankError variants
-            Some(BankError::AggregateConflict)
-            | Some(BankError::DatabaseConnection { .. })
-            | Some(BankError::Deserialization { .. })
-            | Some(BankError::Unexpected { .. }) => Self::Internal { error: error.into() },
-            // Some(_) => Self::Internal { error: error.into() },
-            None => Self::Internal { error: error.into() },
+            BankError::Deserialization { .. } | BankError::Unexpected { .. } => {
+                StatusCode::INTERNAL_SERVER_ERROR
+            },
         }
     }
 }
 
+const fn api_status_of(error: &ApiError) -> StatusCode {
+    match error {
+        ApiError::Path(_) | ApiError::Query(_) | ApiError::Json(_) => StatusCode::BAD_REQUEST,
+        ApiError::Unauthenticated => StatusCode::UNAUTHORIZED,
+        ApiError::Forbidden { .. } => StatusCode::FORBIDDEN,
+        ApiError::RateLimited { .. } => StatusCode::TOO_MANY_REQUESTS,
+        ApiError::CommandQueue(CommandQueueError::AttemptsExhausted(_)) => {
+            StatusCode::INTERNAL_SERVER_ERROR
+        },
+        ApiError::Sql { .. } | ApiError::CommandQueue(_) => StatusCode::SERVICE_UNAVAILABLE,
+        ApiError::IO { .. }
+        | ApiError::HyperHttp { .. }
+        | ApiError::Correlation(_)
+        | ApiError::Outbox(_)
+        | ApiError::Webhook(_)
+        | ApiError::Notification(_)
+        | ApiError::Migration(_)
+        | ApiError::SchemaAhead(_)
+        | ApiError::Join(_) => StatusCode::INTERNAL_SERVER_ERROR,
+    }
+}
+
+const fn account_status_of(error: &BankAccountError) -> StatusCode {
+    match error {
+        BankAccountError::NotFound(_)
+        | BankAccountError::NotOpened(_)
+        | BankAccountError::FlagNotFound(..)
+        | BankAccountError::HoldNotFound(..)
+        | BankAccountError::TransactionNotFound(..)
+        | BankAccountError::ProvisionalCreditNotFound(..)
+        | BankAccountError::HolderChangeNotFound(..) => StatusCode::NOT_FOUND,
+
+        BankAccountError::AccountFrozen(..)
+        | BankAccountError::AccountClosed(..)
+        | BankAccountError::AccountDormant(_)
+        | BankAccountError::TransactionAlreadyReversed(..)
+        | BankAccountError::StandingOrderAlreadyApplied(..)
+        | BankAccountError::FeesAlreadyAssessed(..)
+        | BankAccountError::DisputeAlreadyApplied(..)
+        | BankAccountError::CommandAlreadyApplied(..)
+        | BankAccountError::CommandClaimExpired(..) => StatusCode::CONFLICT,
+
+        BankAccountError::InsufficientFunds(..)
+        | BankAccountError::MinimumBalanceRequired(..)
+        | BankAccountError::WithdrawalLimitExceeded(..)
+        | BankAccountError::MonthlyWithdrawalCapReached(..)
+        | BankAccountError::ChecksNotAllowed(..)
+        | BankAccountError::TransactionDenied(_)
+        | BankAccountError::CustomerNotFound(_)
+        | BankAccountError::CustomerNotVerified(..) => StatusCode::UNPROCESSABLE_ENTITY,
+
+        BankAccountError::NotAccountHolder(..) | BankAccountError::HolderNotPermitted(..) => {
+            StatusCode::FORBIDDEN
+        },
+
+        BankAccountError::BankServiceError(error) => service_status_of(error),
+        BankAccountError::RejectedCommand(_) => StatusCode::BAD_REQUEST,
+    }
+}
+
+const fn service_status_of(error: &BankServiceError) -> StatusCode {
+    match error {
+        BankServiceError::Atm(_) | BankServiceError::InvalidCheck(..) => {
+            StatusCode::UNPROCESSABLE_ENTITY
+        },
+        BankServiceError::Unavailable(_)
+        | BankServiceError::FraudScreening(_)
+        | BankServiceError::CustomerLookup(_)
+        | BankServiceError::CommandClaimLookup(_) => StatusCode::SERVICE_UNAVAILABLE,
+    }
+}
+
+/// Status of a failed command on a bank account, as raised while executing another aggregate's
+/// command.
+const fn account_command_status_of(error: &AggregateError<BankAccountError>) -> StatusCode {
+    match error {
+        AggregateError::UserError(error) => account_status_of(error),
+        AggregateError::AggregateConflict => StatusCode::CONFLICT,
+        AggregateError::DatabaseConnectionError(_) => StatusCode::SERVICE_UNAVAILABLE,
+        AggregateError::DeserializationError(_) | AggregateError::UnexpectedError(_) => {
+            StatusCode::INTERNAL_SERVER_ERROR
+        },
+    }
+}
+
+const fn customer_status_of(error: &CustomerError) -> StatusCode {
+    match error {
+        CustomerError::NoDocuments(_) => StatusCode::UNPROCESSABLE_ENTITY,
+        CustomerError::SubjectAlreadyRegistered(_) => StatusCode::CONFLICT,
+        CustomerError::SubjectNotRegistered(_) => StatusCode::FORBIDDEN,
+        CustomerError::RejectedCommand(_) => StatusCode::BAD_REQUEST,
+        CustomerError::IdentityVerifierError(_) => StatusCode::SERVICE_UNAVAILABLE,
+    }
+}
+
+const fn dispute_status_of(error: &DisputeError) -> StatusCode {
+    match error {
+        DisputeError::TransactionNotDisputable(..) => StatusCode::UNPROCESSABLE_ENTITY,
+        DisputeError::RejectedCommand(_) => StatusCode::BAD_REQUEST,
+        DisputeError::DisputeServiceError(DisputeServiceError::Account(error)) => {
+            account_command_status_of(error)
+        },
+        DisputeError::DisputeServiceError(DisputeServiceError::Load(_)) => {
+            StatusCode::SERVICE_UNAVAILABLE
+        },
+        DisputeError::DisputeServiceError(DisputeServiceError::Deserialization(_)) => {
+            StatusCode::INTERNAL_SERVER_ERROR
+        },
+    }
+}
+
+const fn standing_order_status_of(error: &StandingOrderError) -> StatusCode {
+    match error {
+        StandingOrderError::InvalidSchedule(_) | StandingOrderError::RejectedCommand(_) => {
+            StatusCode::BAD_REQUEST
+        },
+        StandingOrderError::NotDue(..) => StatusCode::CONFLICT,
+        StandingOrderError::Account(error) => account_command_status_of(error),
+    }
+}
+
 impl IntoResponse for HttpError {
     fn into_response(self) -> Response {
-        match self {
-            Self::NotFound { message } => (StatusCode::NOT_FOUND, Json(message)).into_response(),
-            Self::BadRequest { error } => (StatusCode::BAD_REQUEST, Json(error)).into_response(),
-            Self::Internal { error } => {
-                (StatusCode::INTERNAL_SERVER_ERROR, Json(error)).into_response()
-            },
+        let mut response = self.problem.into_response();
+        if let Some(retry_after) = self.retry_after {
+            response
+                .headers_mut()
+                .insert(RETRY_AFTER, HeaderValue::from(whole_secs(retry_after)));
         }
+        response
     }
 }
 
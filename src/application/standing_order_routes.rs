@@ -0,0 +1,261 @@
+use crate::application::app_state::AppState;
+use crate::application::auth::{Principal, ADMIN_SCOPE};
+use crate::application::bank_routes::{authorize_holder_roles, ApiMoney};
+use crate::application::result::{OptionalResult, ProblemDetails};
+use crate::application::Pagination;
+use crate::errors::BankError;
+use crate::model::{
+    standing_order, AccountId, BankAccount, BankAccountError, Beneficiary, HolderRole, Schedule,
+    StandingOrder, StandingOrderAggregate, StandingOrderCommand, StandingOrderError,
+    StandingOrderEvent, StandingOrderId, StandingOrderStatus,
+};
+use crate::queries::{
+    self, BankAccountViewProjection, StandingOrderFilter, StandingOrderView,
+    StandingOrderViewProjection,
+};
+use axum::extract::rejection::{JsonRejection, PathRejection, QueryRejection};
+use axum::extract::{Path, Query, State};
+use axum::response::IntoResponse;
+use axum::{routing, Json, Router};
+use cqrs_es::persist::ViewRepository;
+use pretty_snowflake::envelope::MetaData;
+use pretty_snowflake::Id;
+use serde::{Deserialize, Serialize};
+use sqlx::PgPool;
+use utoipa::{OpenApi, ToSchema};
+
+#[derive(OpenApi)]
+#[openapi(
+    paths(
+        create_standing_order,
+        serve_standing_orders,
+        serve_standing_order,
+        cancel_standing_order,
+    ),
+    components(
+        schemas(
+            StandingOrderId, AccountId, ApiMoney, StandingOrderRequest, Beneficiary, Schedule,
+            StandingOrderView, StandingOrderStatus, StandingOrderEvent, ProblemDetails,
+        )
+    ),
+    tags(
+        (name = "standing_orders", description = "Scheduled and Recurring Payment API")
+    )
+)]
+pub struct StandingOrderApiDoc;
+
+pub fn api() -> Router<AppState> {
+    Router::new()
+        .route(
+            "/",
+            routing::post(create_standing_order).get(serve_standing_orders),
+        )
+        .route("/:order_id", routing::get(serve_standing_order))
+        .route("/:order_id/cancel", routing::post(cancel_standing_order))
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, ToSchema, Serialize, Deserialize)]
+#[schema(example = json!({
+    "account_id": 7006077196242653184_u64,
+    "amount": { "amount": "125.00", "currency": "USD" },
+    "beneficiary": { "payee": { "name": "Renton Water District" } },
+    "schedule": { "monthly": { "starting": "2023-02-01T09:00:00Z" } },
+}))]
+pub struct StandingOrderRequest {
+    /// Account the payments are made from.
+    pub account_id: AccountId,
+    pub amount: ApiMoney,
+    pub beneficiary: Beneficiary,
+    pub schedule: Schedule,
+}
+
+#[utoipa::path(
+    post,
+    path = "/",
+    context_path = "/api/v1/standing_orders",
+    tag = "standing_orders",
+    request_body = StandingOrderRequest,
+    responses(
+        (status = 200, description = "Standing order scheduled", body = StandingOrderId),
+        (status = 400, description = "Invalid schedule or beneficiary", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 401, description = "Missing or unrecognized bearer token"),
+        (status = 403, description = "Caller may not pay from the account", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 404, description = "No bank account found for the paying or beneficiary account", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 409, description = "Paying account is frozen or dormant", body = ProblemDetails, content_type = "application/problem+json"),
+    ),
+)]
+#[axum::debug_handler(state = AppState)]
+#[tracing::instrument(level = "debug", skip(agg, pool, account_repo))]
+async fn create_standing_order(
+    principal: Principal, State(agg): State<StandingOrderAggregate>, State(pool): State<PgPool>,
+    State(account_repo): State<BankAccountViewProjection>,
+    request: Result<Json<StandingOrderRequest>, JsonRejection>,
+) -> impl IntoResponse {
+    let Json(request) = request?;
+    authorize_holder_roles(&pool, &principal, request.account_id, &HolderRole::SIGNERS).await?;
+    check_account_open(&account_repo, request.account_id, true).await?;
+    if let Beneficiary::Account { account_id } = request.beneficiary {
+        check_account_open(&account_repo, account_id, false).await?;
+    }
+
+    let aggregate_id = standing_order::generate_id();
+    let order_id: StandingOrderId = aggregate_id.clone().into();
+    let command = StandingOrderCommand::CreateStandingOrder {
+        order_id,
+        account_id: request.account_id,
+        amount: request.amount.into_inner(),
+        beneficiary: request.beneficiary,
+        schedule: request.schedule,
+    };
+
+    agg.execute_with_metadata(
+        aggregate_id.pretty(),
+        command,
+        MetaData::<StandingOrder>::default().into(),
+    )
+    .await
+    .map_err::<BankError, _>(|err| err.into())
+    .map(|_| Json(order_id))
+}
+
+/// Checks the account is open and, if it is `paying` the order, that it may make payments.
+async fn check_account_open(
+    account_repo: &BankAccountViewProjection, account_id: AccountId, paying: bool,
+) -> Result<(), BankError> {
+    let aggregate_id: Id<BankAccount> = account_id.into();
+    let view = account_repo
+        .load(aggregate_id.pretty())
+        .await?
+        .filter(|view| view.account_id.is_some())
+        .ok_or(BankAccountError::NotFound(account_id))?;
+
+    if !paying {
+        return Ok(());
+    }
+
+    if let Some(freeze) = view.freeze {
+        return Err(BankAccountError::AccountFrozen(
+            account_id,
+            freeze.reason,
+            "CreateStandingOrder".to_string(),
+        )
+        .into());
+    }
+
+    if view.dormancy.is_some() {
+        return Err(BankAccountError::AccountDormant(account_id).into());
+    }
+
+    Ok(())
+}
+
+#[utoipa::path(
+    get,
+    path = "/",
+    context_path = "/api/v1/standing_orders",
+    tag = "standing_orders",
+    params(Pagination, StandingOrderFilter),
+    responses(
+        (status = 200, description = "Standing orders, most recently created first", body = [StandingOrderView]),
+        (status = 400, description = "bad request", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 401, description = "Missing or unrecognized bearer token"),
+        (status = 403, description = "Caller does not hold the filtered account, or lists every account's standing orders without the admin:account scope", body = ProblemDetails, content_type = "application/problem+json"),
+    ),
+)]
+#[axum::debug_handler(state = AppState)]
+#[tracing::instrument(level = "debug", skip(pool))]
+async fn serve_standing_orders(
+    principal: Principal, pagination: Result<Query<Pagination>, QueryRejection>,
+    filter: Result<Query<StandingOrderFilter>, QueryRejection>, State(pool): State<PgPool>,
+) -> impl IntoResponse {
+    let Query(pagination) = pagination?;
+    let Query(filter) = filter?;
+    match filter.account_id {
+        Some(account_id) => {
+            authorize_holder_roles(&pool, &principal, account_id, &HolderRole::ALL).await?
+        },
+        None => principal.require_scope(ADMIN_SCOPE)?,
+    }
+    let orders =
+        queries::list_standing_orders(&pool, &filter, pagination.offset()?, pagination.limit())
+            .await?;
+    Result::<_, BankError>::Ok(Json(orders))
+}
+
+#[utoipa::path(
+    get,
+    path = "/{order_id}",
+    context_path = "/api/v1/standing_orders",
+    tag = "standing_orders",
+    params(StandingOrderId),
+    responses(
+        (status = 200, description = "Standing order", body = StandingOrderView),
+        (status = 401, description = "Missing or unrecognized bearer token"),
+        (status = 403, description = "Caller does not hold the paying account", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 404, description = "No standing order found.", body = ProblemDetails, content_type = "application/problem+json"),
+    ),
+)]
+#[axum::debug_handler(state = AppState)]
+#[tracing::instrument(level = "debug", skip(view_repo, pool))]
+async fn serve_standing_order(
+    order_id: Result<Path<StandingOrderId>, PathRejection>, principal: Principal,
+    State(view_repo): State<StandingOrderViewProjection>, State(pool): State<PgPool>,
+) -> impl IntoResponse {
+    let Path(order_id) = order_id?;
+    let aggregate_id: Id<StandingOrder> = order_id.into();
+    let view = view_repo.load(aggregate_id.pretty()).await?;
+    match view.as_ref().and_then(|view| view.account_id) {
+        Some(account_id) => {
+            authorize_holder_roles(&pool, &principal, account_id, &HolderRole::ALL).await?
+        },
+        None => principal.require_scope(ADMIN_SCOPE)?,
+    }
+    Result::<_, BankError>::Ok(OptionalResult::new(
+        view.map(Json),
+        "standing_order",
+        order_id,
+    ))
+}
+
+#[utoipa::path(
+    post,
+    path = "/{order_id}/cancel",
+    context_path = "/api/v1/standing_orders",
+    tag = "standing_orders",
+    params(StandingOrderId),
+    responses(
+        (status = 200, description = "Standing order cancelled; no further occurrences are paid"),
+        (status = 400, description = "No scheduled standing order to cancel", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 401, description = "Missing or unrecognized bearer token"),
+        (status = 403, description = "Caller may not pay from the order's account", body = ProblemDetails, content_type = "application/problem+json"),
+    ),
+)]
+#[axum::debug_handler(state = AppState)]
+#[tracing::instrument(level = "debug", skip(agg, view_repo, pool))]
+async fn cancel_standing_order(
+    order_id: Result<Path<StandingOrderId>, PathRejection>, principal: Principal,
+    State(agg): State<StandingOrderAggregate>,
+    State(view_repo): State<StandingOrderViewProjection>, State(pool): State<PgPool>,
+) -> impl IntoResponse {
+    let Path(order_id) = order_id?;
+    let aggregate_id: Id<StandingOrder> = order_id.into();
+
+    let account_id = view_repo
+        .load(aggregate_id.pretty())
+        .await?
+        .and_then(|view| view.account_id)
+        .ok_or_else(|| {
+            StandingOrderError::RejectedCommand(format!(
+                "No standing order {order_id} is created to cancel."
+            ))
+        })?;
+    authorize_holder_roles(&pool, &principal, account_id, &HolderRole::SIGNERS).await?;
+
+    agg.execute_with_metadata(
+        aggregate_id.pretty(),
+        StandingOrderCommand::CancelStandingOrder,
+        MetaData::<StandingOrder>::default().into(),
+    )
+    .await
+    .map_err::<BankError, _>(|err| err.into())
+}
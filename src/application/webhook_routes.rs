@@ -0,0 +1,277 @@
+use crate::application::app_state::AppState;
+use crate::application::auth::{Principal, ADMIN_SCOPE};
+use crate::application::bank_routes::SecurityAddon;
+use crate::application::result::{OptionalResult, ProblemDetails};
+use crate::application::Pagination;
+use crate::errors::BankError;
+use crate::webhooks::{
+    self, Delivery, DeliveryFilter, DeliveryId, DeliveryStatus, Subscription, SubscriptionId,
+    SubscriptionRequest,
+};
+use axum::extract::rejection::{JsonRejection, PathRejection, QueryRejection};
+use axum::extract::{Path, Query, State};
+use axum::http::StatusCode;
+use axum::response::IntoResponse;
+use axum::{routing, Json, Router};
+use sqlx::PgPool;
+use utoipa::OpenApi;
+use validator::Validate;
+
+#[derive(OpenApi)]
+#[openapi(
+    paths(
+        create_subscription,
+        serve_subscriptions,
+        serve_subscription,
+        delete_subscription,
+        serve_deliveries,
+        serve_dead_letters,
+        redeliver,
+    ),
+    components(
+        schemas(
+            SubscriptionId, SubscriptionRequest, Subscription, DeliveryId, DeliveryStatus, Delivery,
+            ProblemDetails,
+        )
+    ),
+    modifiers(&SecurityAddon),
+    tags(
+        (name = "webhooks", description = "Account Event Webhook Subscription API")
+    )
+)]
+pub struct WebhookApiDoc;
+
+pub fn api() -> Router<AppState> {
+    Router::new()
+        .route(
+            "/",
+            routing::post(create_subscription).get(serve_subscriptions),
+        )
+        .route(
+            "/:subscription_id",
+            routing::get(serve_subscription).delete(delete_subscription),
+        )
+        .route(
+            "/:subscription_id/deliveries",
+            routing::get(serve_deliveries),
+        )
+        .route("/dead_letters", routing::get(serve_dead_letters))
+        .route(
+            "/deliveries/:delivery_id/redeliver",
+            routing::post(redeliver),
+        )
+}
+
+#[utoipa::path(
+    post,
+    path = "/",
+    context_path = "/api/v1/webhooks",
+    tag = "webhooks",
+    request_body = SubscriptionRequest,
+    responses(
+        (status = 201, description = "Webhook subscription created", body = Subscription),
+        (status = 400, description = "bad request", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 401, description = "Missing or unrecognized bearer token"),
+        (status = 403, description = "Caller is not granted the admin:account scope"),
+    ),
+    security(("api_key" = ["admin:account"])),
+)]
+#[axum::debug_handler(state = AppState)]
+#[tracing::instrument(level = "debug", skip(pool, request))]
+async fn create_subscription(
+    principal: Principal, State(pool): State<PgPool>,
+    request: Result<Json<SubscriptionRequest>, JsonRejection>,
+) -> impl IntoResponse {
+    principal.require_scope(ADMIN_SCOPE)?;
+    let Json(request) = request?;
+    request.validate()?;
+    let subscription = webhooks::create_subscription(&pool, request).await?;
+    Result::<_, BankError>::Ok((StatusCode::CREATED, Json(subscription)))
+}
+
+#[utoipa::path(
+    get,
+    path = "/",
+    context_path = "/api/v1/webhooks",
+    tag = "webhooks",
+    responses(
+        (status = 200, description = "All webhook subscriptions", body = [Subscription]),
+        (status = 401, description = "Missing or unrecognized bearer token"),
+        (status = 403, description = "Caller is not granted the admin:account scope"),
+    ),
+    security(("api_key" = ["admin:account"])),
+)]
+#[axum::debug_handler(state = AppState)]
+#[tracing::instrument(level = "debug", skip(pool))]
+async fn serve_subscriptions(
+    principal: Principal, State(pool): State<PgPool>,
+) -> impl IntoResponse {
+    principal.require_scope(ADMIN_SCOPE)?;
+    let subscriptions = webhooks::list_subscriptions(&pool).await?;
+    Result::<_, BankError>::Ok(Json(subscriptions))
+}
+
+#[utoipa::path(
+    get,
+    path = "/{subscription_id}",
+    context_path = "/api/v1/webhooks",
+    tag = "webhooks",
+    params(SubscriptionId),
+    responses(
+        (status = 200, description = "Webhook subscription", body = Subscription),
+        (status = 404, description = "No webhook subscription found.", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 401, description = "Missing or unrecognized bearer token"),
+        (status = 403, description = "Caller is not granted the admin:account scope"),
+    ),
+    security(("api_key" = ["admin:account"])),
+)]
+#[axum::debug_handler(state = AppState)]
+#[tracing::instrument(level = "debug", skip(pool))]
+async fn serve_subscription(
+    subscription_id: Result<Path<SubscriptionId>, PathRejection>, principal: Principal,
+    State(pool): State<PgPool>,
+) -> impl IntoResponse {
+    principal.require_scope(ADMIN_SCOPE)?;
+    let Path(subscription_id) = subscription_id?;
+    let subscription = webhooks::find_subscription(&pool, subscription_id).await?;
+    Result::<_, BankError>::Ok(OptionalResult::new(
+        subscription.map(Json),
+        "webhook_subscription",
+        subscription_id,
+    ))
+}
+
+#[utoipa::path(
+    delete,
+    path = "/{subscription_id}",
+    context_path = "/api/v1/webhooks",
+    tag = "webhooks",
+    params(SubscriptionId),
+    responses(
+        (status = 204, description = "Webhook subscription and its delivery log deleted"),
+        (status = 404, description = "No webhook subscription found.", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 401, description = "Missing or unrecognized bearer token"),
+        (status = 403, description = "Caller is not granted the admin:account scope"),
+    ),
+    security(("api_key" = ["admin:account"])),
+)]
+#[axum::debug_handler(state = AppState)]
+#[tracing::instrument(level = "debug", skip(pool))]
+async fn delete_subscription(
+    subscription_id: Result<Path<SubscriptionId>, PathRejection>, principal: Principal,
+    State(pool): State<PgPool>,
+) -> impl IntoResponse {
+    principal.require_scope(ADMIN_SCOPE)?;
+    let Path(subscription_id) = subscription_id?;
+    let response = if webhooks::delete_subscription(&pool, subscription_id).await? {
+        StatusCode::NO_CONTENT.into_response()
+    } else {
+        ProblemDetails::not_found("webhook_subscription", &subscription_id.to_string())
+            .into_response()
+    };
+    Result::<_, BankError>::Ok(response)
+}
+
+#[utoipa::path(
+    get,
+    path = "/{subscription_id}/deliveries",
+    context_path = "/api/v1/webhooks",
+    tag = "webhooks",
+    params(SubscriptionId, Pagination, DeliveryFilter),
+    responses(
+        (status = 200, description = "Delivery log of the subscription, most recent first", body = [Delivery]),
+        (status = 404, description = "No webhook subscription found.", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 401, description = "Missing or unrecognized bearer token"),
+        (status = 403, description = "Caller is not granted the admin:account scope"),
+    ),
+    security(("api_key" = ["admin:account"])),
+)]
+#[axum::debug_handler(state = AppState)]
+#[tracing::instrument(level = "debug", skip(pool))]
+async fn serve_deliveries(
+    subscription_id: Result<Path<SubscriptionId>, PathRejection>,
+    pagination: Result<Query<Pagination>, QueryRejection>,
+    filter: Result<Query<DeliveryFilter>, QueryRejection>, principal: Principal,
+    State(pool): State<PgPool>,
+) -> impl IntoResponse {
+    principal.require_scope(ADMIN_SCOPE)?;
+    let Path(subscription_id) = subscription_id?;
+    let Query(pagination) = pagination?;
+    let Query(filter) = filter?;
+
+    let deliveries = match webhooks::find_subscription(&pool, subscription_id).await? {
+        None => None,
+        Some(_) => Some(
+            webhooks::list_deliveries(
+                &pool,
+                subscription_id,
+                &filter,
+                pagination.offset()?,
+                pagination.limit(),
+            )
+            .await?,
+        ),
+    };
+
+    Result::<_, BankError>::Ok(OptionalResult::new(
+        deliveries.map(Json),
+        "webhook_subscription",
+        subscription_id,
+    ))
+}
+
+#[utoipa::path(
+    get,
+    path = "/dead_letters",
+    context_path = "/api/v1/webhooks",
+    tag = "webhooks",
+    params(Pagination),
+    responses(
+        (status = 200, description = "Deliveries that exhausted their retries, most recent first", body = [Delivery]),
+        (status = 401, description = "Missing or unrecognized bearer token"),
+        (status = 403, description = "Caller is not granted the admin:account scope"),
+    ),
+    security(("api_key" = ["admin:account"])),
+)]
+#[axum::debug_handler(state = AppState)]
+#[tracing::instrument(level = "debug", skip(pool))]
+async fn serve_dead_letters(
+    pagination: Result<Query<Pagination>, QueryRejection>, principal: Principal,
+    State(pool): State<PgPool>,
+) -> impl IntoResponse {
+    principal.require_scope(ADMIN_SCOPE)?;
+    let Query(pagination) = pagination?;
+    let dead_letters =
+        webhooks::list_dead_letters(&pool, pagination.offset()?, pagination.limit()).await?;
+    Result::<_, BankError>::Ok(Json(dead_letters))
+}
+
+#[utoipa::path(
+    post,
+    path = "/deliveries/{delivery_id}/redeliver",
+    context_path = "/api/v1/webhooks",
+    tag = "webhooks",
+    params(DeliveryId),
+    responses(
+        (status = 200, description = "Dead-lettered delivery queued for redelivery", body = Delivery),
+        (status = 404, description = "No dead-lettered delivery found.", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 401, description = "Missing or unrecognized bearer token"),
+        (status = 403, description = "Caller is not granted the admin:account scope"),
+    ),
+    security(("api_key" = ["admin:account"])),
+)]
+#[axum::debug_handler(state = AppState)]
+#[tracing::instrument(level = "debug", skip(pool))]
+async fn redeliver(
+    delivery_id: Result<Path<DeliveryId>, PathRejection>, principal: Principal,
+    State(pool): State<PgPool>,
+) -> impl IntoResponse {
+    principal.require_scope(ADMIN_SCOPE)?;
+    let Path(delivery_id) = delivery_id?;
+    let delivery = webhooks::requeue_delivery(&pool, delivery_id).await?;
+    Result::<_, BankError>::Ok(OptionalResult::new(
+        delivery.map(Json),
+        "webhook_delivery",
+        delivery_id,
+    ))
+}
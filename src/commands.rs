@@ -0,0 +1,334 @@
+//! Asynchronous execution of money-moving bank account commands.
+//!
+//! With the command queue enabled, the deposit and withdrawal routes record the command in the
+//! durable command queue and respond `202 Accepted` with its [CommandId], rather than holding the
+//! connection open through slow ATM and check validation. A pool of workers claims queued commands
+//! in acceptance order, holding back an account's later commands until its earlier ones complete,
+//! and executes each with its id in the event metadata, so the outcome reports the events the
+//! command produced. A command whose claim expired before its outcome was recorded -- because its
+//! worker stopped, or is still executing it -- is claimed again by another worker. The account
+//! rejects a queued command it already applied, and one whose worker's claim in the queue is no
+//! longer current, so of the workers executing the same command only one applies it, and the
+//! others record it as succeeded or leave it to the newer claim. A command claimed `max_attempts`
+//! times without its outcome being recorded is failed rather than claimed again, so it no longer
+//! holds back the account's later commands.
+
+use crate::application::ProblemDetails;
+use crate::errors::BankError;
+use crate::model::{
+    AccountId, BankAccount, BankAccountAggregate, BankAccountCommand, BankAccountError, CommandId,
+};
+use crate::queries;
+use crate::settings::CommandQueueSettings;
+use chrono::{DateTime, Utc};
+use cqrs_es::AggregateError;
+use pretty_snowflake::envelope::MetaData;
+use pretty_snowflake::Id;
+use serde::{Deserialize, Serialize};
+use sqlx::postgres::PgRow;
+use sqlx::{PgPool, Row};
+use std::collections::HashMap;
+use std::str::FromStr;
+use strum_macros::{Display, EnumString};
+use thiserror::Error;
+use tokio::task::JoinHandle;
+use tokio::time::MissedTickBehavior;
+use utoipa::ToSchema;
+
+pub const COMMAND_QUEUE_TABLE: &str = "command_queue";
+
+#[derive(Debug, Error)]
+pub enum CommandQueueError {
+    #[error("failed command queue database operation: {0}")]
+    Sql(#[from] sqlx::Error),
+
+    #[error("invalid queued command data: {0}")]
+    Serialization(#[from] serde_json::Error),
+
+    #[error("command was claimed {0} times without its outcome being recorded")]
+    AttemptsExhausted(u32),
+}
+
+#[derive(
+    Debug, Display, Copy, Clone, PartialEq, Eq, EnumString, ToSchema, Serialize, Deserialize,
+)]
+#[strum(serialize_all = "snake_case")]
+#[serde(rename_all = "snake_case")]
+pub enum CommandStatus {
+    Pending,
+    Succeeded,
+    Failed,
+}
+
+/// A command accepted for asynchronous execution, as recorded in the command queue.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct QueuedCommand {
+    pub command_id: CommandId,
+    pub account_id: AccountId,
+    pub status: CommandStatus,
+
+    /// Problem details of the failure, once the command failed.
+    pub error: Option<serde_json::Value>,
+
+    pub accepted_at: DateTime<Utc>,
+    pub completed_at: Option<DateTime<Utc>>,
+}
+
+impl QueuedCommand {
+    fn from_row(row: &PgRow) -> Result<Self, sqlx::Error> {
+        let status: String = row.try_get("status")?;
+        let status = CommandStatus::from_str(&status).map_err(|err| sqlx::Error::ColumnDecode {
+            index: "status".to_string(),
+            source: err.into(),
+        })?;
+
+        Ok(Self {
+            command_id: CommandId::new(row.try_get::<i64, _>("command_id")?),
+            account_id: AccountId::new(row.try_get::<i64, _>("account_id")?),
+            status,
+            error: row.try_get("error")?,
+            accepted_at: row.try_get("accepted_at")?,
+            completed_at: row.try_get("completed_at")?,
+        })
+    }
+}
+
+/// Accepts commands into the queue, when it is enabled.
+#[derive(Debug, Clone)]
+pub struct CommandQueue {
+    pool: PgPool,
+    enabled: bool,
+}
+
+impl CommandQueue {
+    pub const fn new(pool: PgPool, enabled: bool) -> Self {
+        Self { pool, enabled }
+    }
+
+    pub const fn is_enabled(&self) -> bool {
+        self.enabled
+    }
+
+    /// Records the command for execution on the account, returning the id its outcome is
+    /// reported under.
+    #[tracing::instrument(level = "debug", skip(self))]
+    pub async fn enqueue(
+        &self, account_id: AccountId, command: &BankAccountCommand,
+    ) -> Result<CommandId, CommandQueueError> {
+        let command_id = CommandId::generate();
+        let insert_sql = format!(
+            r##"INSERT INTO {COMMAND_QUEUE_TABLE} (command_id, account_id, command)
+            VALUES ($1, $2, $3)"##
+        );
+        sqlx::query(&insert_sql)
+            .bind(command_id.as_num())
+            .bind(account_id.as_num())
+            .bind(serde_json::to_value(command)?)
+            .execute(&self.pool)
+            .await?;
+        Ok(command_id)
+    }
+}
+
+#[tracing::instrument(level = "debug", skip(pool))]
+pub async fn find_command(
+    pool: &PgPool, command_id: CommandId,
+) -> Result<Option<QueuedCommand>, sqlx::Error> {
+    let select_sql = format!(
+        r##"SELECT command_id, account_id, status, error, accepted_at, completed_at
+        FROM {COMMAND_QUEUE_TABLE}
+        WHERE command_id = $1"##
+    );
+    let row = sqlx::query(&select_sql)
+        .bind(command_id.as_num())
+        .fetch_optional(pool)
+        .await?;
+    row.as_ref().map(QueuedCommand::from_row).transpose()
+}
+
+pub fn spawn_command_workers(
+    pool: PgPool, agg: BankAccountAggregate, settings: CommandQueueSettings,
+) -> Vec<JoinHandle<()>> {
+    (0..settings.nr_workers)
+        .map(|worker| {
+            let pool = pool.clone();
+            let agg = agg.clone();
+            let settings = settings.clone();
+            tokio::spawn(async move {
+                tracing::info!(%worker, "starting command queue worker...");
+                let mut interval = tokio::time::interval(settings.poll_interval);
+                interval.set_missed_tick_behavior(MissedTickBehavior::Delay);
+
+                loop {
+                    interval.tick().await;
+                    loop {
+                        match execute_next(&pool, &agg, &settings).await {
+                            Ok(Some(command_id)) => {
+                                tracing::debug!(%worker, %command_id, "executed queued command")
+                            },
+                            Ok(None) => break,
+                            Err(error) => {
+                                tracing::error!(%worker, ?error, "command queue pass failed");
+                                break;
+                            },
+                        }
+                    }
+                }
+            })
+        })
+        .collect()
+}
+
+/// Claims the next queued command whose account has no earlier command outstanding, executes it
+/// and records its outcome. Returns the id of the command executed, if one was ready.
+#[tracing::instrument(level = "debug", skip(pool, agg, settings))]
+pub async fn execute_next(
+    pool: &PgPool, agg: &BankAccountAggregate, settings: &CommandQueueSettings,
+) -> Result<Option<CommandId>, CommandQueueError> {
+    fail_exhausted(pool, settings.max_attempts).await?;
+
+    let update_sql = format!(
+        r##"UPDATE {COMMAND_QUEUE_TABLE}
+        SET attempts = attempts + 1, claimed_until = now() + make_interval(secs => $1)
+        WHERE command_id = (
+            SELECT queued.command_id
+            FROM {COMMAND_QUEUE_TABLE} queued
+            WHERE queued.status = 'pending'
+              AND (queued.claimed_until IS NULL OR queued.claimed_until < now())
+              AND queued.attempts < $2
+              AND NOT EXISTS (
+                SELECT 1 FROM {COMMAND_QUEUE_TABLE} earlier
+                WHERE earlier.account_id = queued.account_id
+                  AND earlier.status = 'pending'
+                  AND earlier.command_id < queued.command_id
+              )
+            ORDER BY queued.command_id
+            LIMIT 1
+            FOR UPDATE SKIP LOCKED
+        )
+        RETURNING command_id, account_id, command, claimed_until"##
+    );
+    let claimed = sqlx::query(&update_sql)
+        .bind(settings.claim_timeout.as_secs_f64())
+        .bind(i64::from(settings.max_attempts))
+        .fetch_optional(pool)
+        .await?;
+
+    let row = match claimed {
+        Some(row) => row,
+        None => return Ok(None),
+    };
+    let command_id = CommandId::new(row.try_get::<i64, _>("command_id")?);
+    let account_id = AccountId::new(row.try_get::<i64, _>("account_id")?);
+    let claimed_until: DateTime<Utc> = row.try_get("claimed_until")?;
+    let aggregate_id: Id<BankAccount> = account_id.into();
+
+    let outcome = match serde_json::from_value::<BankAccountCommand>(row.try_get("command")?) {
+        Err(error) => Err(BankError::from(error)),
+        Ok(command) => {
+            let command = BankAccountCommand::Queued {
+                command_id,
+                claimed_until,
+                command: Box::new(command),
+            };
+            let mut metadata: HashMap<String, String> = MetaData::<BankAccount>::default().into();
+            metadata.insert(queries::COMMAND_ID.to_string(), command_id.to_string());
+            let result = agg.execute_with_metadata(aggregate_id.pretty(), command, metadata).await;
+            skip_applied(result).map_err(BankError::from)
+        },
+    };
+
+    let is_recorded = match outcome {
+        Ok(()) => {
+            complete(
+                pool,
+                command_id,
+                claimed_until,
+                CommandStatus::Succeeded,
+                None,
+            )
+            .await?
+        },
+        Err(error) => {
+            tracing::info!(%command_id, %error, "queued command failed");
+            let problem = serde_json::to_value(ProblemDetails::from(&error))?;
+            complete(
+                pool,
+                command_id,
+                claimed_until,
+                CommandStatus::Failed,
+                Some(problem),
+            )
+            .await?
+        },
+    };
+
+    if !is_recorded {
+        tracing::warn!(
+            %command_id, %claimed_until,
+            "claim on command expired before its outcome was recorded -- left to its new claim"
+        );
+    }
+
+    Ok(Some(command_id))
+}
+
+/// Fails the pending commands whose last claim expired after they were claimed `max_attempts`
+/// times, reporting the exhausted attempts as their problem.
+async fn fail_exhausted(pool: &PgPool, max_attempts: u32) -> Result<(), CommandQueueError> {
+    let error = BankError::from(CommandQueueError::AttemptsExhausted(max_attempts));
+    let problem = serde_json::to_value(ProblemDetails::from(&error))?;
+    let update_sql = format!(
+        r##"UPDATE {COMMAND_QUEUE_TABLE}
+        SET status = 'failed', error = $2, claimed_until = NULL, completed_at = now()
+        WHERE status = 'pending' AND claimed_until < now() AND $1 <= attempts
+        RETURNING command_id"##
+    );
+    let failed: Vec<i64> = sqlx::query_scalar(&update_sql)
+        .bind(i64::from(max_attempts))
+        .bind(problem)
+        .fetch_all(pool)
+        .await?;
+    for command_id in failed.into_iter().map(CommandId::new) {
+        tracing::warn!(
+            %command_id, %max_attempts,
+            "queued command exhausted its attempts -- failed without executing it again"
+        );
+    }
+    Ok(())
+}
+
+/// A command already applied by another worker holding an earlier claim succeeded.
+fn skip_applied(
+    result: Result<(), AggregateError<BankAccountError>>,
+) -> Result<(), AggregateError<BankAccountError>> {
+    match result {
+        Err(AggregateError::UserError(BankAccountError::CommandAlreadyApplied(_, command_id))) => {
+            tracing::info!(%command_id, "command was already applied under an earlier claim");
+            Ok(())
+        },
+        result => result,
+    }
+}
+
+/// Records the outcome of the command under the worker's claim, returning whether it was recorded.
+/// An outcome is not recorded once the claim expired and the command was claimed again, so a
+/// stalled worker does not overwrite the outcome recorded under the newer claim.
+async fn complete(
+    pool: &PgPool, command_id: CommandId, claimed_until: DateTime<Utc>, status: CommandStatus,
+    error: Option<serde_json::Value>,
+) -> Result<bool, sqlx::Error> {
+    let update_sql = format!(
+        r##"UPDATE {COMMAND_QUEUE_TABLE}
+        SET status = $3, error = $4, claimed_until = NULL, completed_at = now()
+        WHERE command_id = $1 AND status = 'pending' AND claimed_until = $2"##
+    );
+    let result = sqlx::query(&update_sql)
+        .bind(command_id.as_num())
+        .bind(claimed_until)
+        .bind(status.to_string())
+        .bind(error)
+        .execute(pool)
+        .await?;
+    Ok(0 < result.rows_affected())
+}
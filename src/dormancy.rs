@@ -0,0 +1,291 @@
+//! Dormancy of accounts without customer-initiated activity, and escheatment of their balances.
+//!
+//! The [AccountActivityQuery] tracks when each account last saw customer-initiated activity. The
+//! dormancy task periodically issues a `MarkDormant` command for accounts inactive longer than the
+//! dormancy period, which blocks withdrawals until the account is reactivated, and flags dormant
+//! accounts inactive past the escheatment period. The flagged accounts make up the escheatment
+//! report of balances to be transferred to the state as unclaimed property.
+
+use crate::application::{ACCOUNT_QUERY_VIEW, ACCOUNT_QUERY_VIEW_PAYLOAD};
+use crate::model::{AccountId, BankAccount, BankAccountAggregate, BankAccountCommand};
+use crate::model::{BankAccountError, BankAccountEvent, MailingAddress, ZERO_MONEY};
+use crate::queries::{BankAccountView, RECV_TIMESTAMP};
+use crate::settings::DormancySettings;
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use cqrs_es::{AggregateError, EventEnvelope, Query};
+use money2::Money;
+use pretty_snowflake::envelope::MetaData;
+use serde::{Deserialize, Serialize};
+use sqlx::{PgPool, Row};
+use tokio::task::JoinHandle;
+use tokio::time::MissedTickBehavior;
+use utoipa::ToSchema;
+
+pub const ACTIVITY_TABLE: &str = "account_activity";
+
+/// Maintains each account's latest customer-initiated activity from committed account events.
+#[derive(Debug, Clone)]
+pub struct AccountActivityQuery {
+    pool: PgPool,
+}
+
+impl AccountActivityQuery {
+    pub const fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+
+    /// Records the event's activity as of the time the event was recorded, so a replay of the
+    /// event store restores the original activity times.
+    async fn record(
+        &self, aggregate_id: &str, event: &EventEnvelope<BankAccount>,
+    ) -> Result<(), sqlx::Error> {
+        let recorded_at: Option<DateTime<Utc>> =
+            event.metadata.get(RECV_TIMESTAMP).and_then(|ts| ts.parse().ok());
+
+        match &event.payload {
+            BankAccountEvent::AccountOpened { account_id, user_name, mailing_address, .. } => {
+                let insert_sql = format!(
+                    r##"INSERT INTO {ACTIVITY_TABLE}
+                    (aggregate_id, account_id, user_name, mailing_address, last_activity_at)
+                    VALUES ($1, $2, $3, $4, COALESCE($5, now()))
+                    ON CONFLICT DO NOTHING"##
+                );
+                sqlx::query(&insert_sql)
+                    .bind(aggregate_id)
+                    .bind(account_id.as_num())
+                    .bind(user_name)
+                    .bind(mailing_address.as_str())
+                    .bind(recorded_at)
+                    .execute(&self.pool)
+                    .await?;
+            },
+
+            BankAccountEvent::MailingAddressUpdated { new_address } => {
+                let update_sql = format!(
+                    r##"UPDATE {ACTIVITY_TABLE}
+                    SET mailing_address = $2, last_activity_at = COALESCE($3, now())
+                    WHERE aggregate_id = $1"##
+                );
+                sqlx::query(&update_sql)
+                    .bind(aggregate_id)
+                    .bind(new_address.as_str())
+                    .bind(recorded_at)
+                    .execute(&self.pool)
+                    .await?;
+            },
+
+            BankAccountEvent::AccountMarkedDormant { .. } => {
+                let update_sql = format!(
+                    r##"UPDATE {ACTIVITY_TABLE} SET dormant_since = COALESCE($2, now())
+                    WHERE aggregate_id = $1"##
+                );
+                sqlx::query(&update_sql)
+                    .bind(aggregate_id)
+                    .bind(recorded_at)
+                    .execute(&self.pool)
+                    .await?;
+            },
+
+            BankAccountEvent::AccountReactivated { .. } => {
+                let update_sql = format!(
+                    r##"UPDATE {ACTIVITY_TABLE}
+                    SET last_activity_at = COALESCE($2, now()), dormant_since = NULL,
+                        escheatable_since = NULL
+                    WHERE aggregate_id = $1"##
+                );
+                sqlx::query(&update_sql)
+                    .bind(aggregate_id)
+                    .bind(recorded_at)
+                    .execute(&self.pool)
+                    .await?;
+            },
+
+            event if event.is_customer_activity() => {
+                let update_sql = format!(
+                    r##"UPDATE {ACTIVITY_TABLE} SET last_activity_at = COALESCE($2, now())
+                    WHERE aggregate_id = $1"##
+                );
+                sqlx::query(&update_sql)
+                    .bind(aggregate_id)
+                    .bind(recorded_at)
+                    .execute(&self.pool)
+                    .await?;
+            },
+
+            _ => {},
+        }
+
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl Query<BankAccount> for AccountActivityQuery {
+    #[tracing::instrument(level = "debug", skip(self, events))]
+    async fn dispatch(&self, aggregate_id: &str, events: &[EventEnvelope<BankAccount>]) {
+        for event in events {
+            if let Err(error) = self.record(aggregate_id, event).await {
+                tracing::error!(
+                    ?error, sequence=%event.sequence,
+                    "failed to record account activity"
+                );
+            }
+        }
+    }
+}
+
+pub fn spawn_dormancy_worker(
+    pool: PgPool, agg: BankAccountAggregate, settings: DormancySettings,
+) -> JoinHandle<()> {
+    tokio::spawn(async move {
+        tracing::info!("starting account dormancy task...");
+        let mut interval = tokio::time::interval(settings.poll_interval);
+        interval.set_missed_tick_behavior(MissedTickBehavior::Delay);
+
+        loop {
+            interval.tick().await;
+            match mark_dormant_batch(&pool, &agg, &settings).await {
+                Ok(0) => {},
+                Ok(nr_dormant) => tracing::info!(%nr_dormant, "marked inactive accounts dormant"),
+                Err(error) => tracing::error!(?error, "account dormancy pass failed"),
+            }
+
+            match flag_escheatable(&pool, &settings).await {
+                Ok(0) => {},
+                Ok(nr_flagged) => {
+                    tracing::info!(%nr_flagged, "flagged dormant accounts for escheatment")
+                },
+                Err(error) => tracing::error!(?error, "account escheatment pass failed"),
+            }
+        }
+    })
+}
+
+/// Marks one batch of accounts inactive past the dormancy period dormant, returning the number
+/// marked.
+#[tracing::instrument(level = "debug", skip(pool, agg))]
+pub async fn mark_dormant_batch(
+    pool: &PgPool, agg: &BankAccountAggregate, settings: &DormancySettings,
+) -> Result<usize, sqlx::Error> {
+    let select_sql = format!(
+        r##"SELECT aggregate_id, last_activity_at FROM {ACTIVITY_TABLE}
+        WHERE dormant_since IS NULL AND last_activity_at <= now() - make_interval(secs => $1)
+        ORDER BY last_activity_at
+        LIMIT $2"##
+    );
+    let rows = sqlx::query(&select_sql)
+        .bind(settings.dormant_after.as_secs_f64())
+        .bind(settings.batch_size)
+        .fetch_all(pool)
+        .await?;
+
+    let mut nr_dormant = 0;
+    for row in rows {
+        let aggregate_id: String = row.try_get("aggregate_id")?;
+        let last_activity_at: DateTime<Utc> = row.try_get("last_activity_at")?;
+
+        let result = agg
+            .execute_with_metadata(
+                &aggregate_id,
+                BankAccountCommand::MarkDormant { last_activity_at },
+                MetaData::<BankAccount>::default().into(),
+            )
+            .await;
+
+        match result {
+            Ok(()) => nr_dormant += 1,
+            Err(AggregateError::UserError(reason @ BankAccountError::RejectedCommand(_))) => {
+                tracing::debug!(%aggregate_id, %reason, "account already dormant -- skipped");
+                mark_skipped_dormant(pool, &aggregate_id).await?;
+            },
+            Err(error) => {
+                tracing::warn!(?error, %aggregate_id, "failed to mark account dormant");
+            },
+        }
+    }
+
+    Ok(nr_dormant)
+}
+
+/// Records an account the aggregate already holds dormant, so the dormancy selection no longer
+/// picks it up ahead of the accounts still to be marked.
+async fn mark_skipped_dormant(pool: &PgPool, aggregate_id: &str) -> Result<(), sqlx::Error> {
+    let update_sql = format!(
+        r##"UPDATE {ACTIVITY_TABLE} SET dormant_since = now()
+        WHERE aggregate_id = $1 AND dormant_since IS NULL"##
+    );
+    sqlx::query(&update_sql).bind(aggregate_id).execute(pool).await?;
+    Ok(())
+}
+
+/// Flags dormant accounts inactive past the escheatment period for the escheatment report,
+/// returning the number newly flagged.
+#[tracing::instrument(level = "debug", skip(pool))]
+pub async fn flag_escheatable(
+    pool: &PgPool, settings: &DormancySettings,
+) -> Result<u64, sqlx::Error> {
+    let update_sql = format!(
+        r##"UPDATE {ACTIVITY_TABLE} SET escheatable_since = now()
+        WHERE dormant_since IS NOT NULL
+        AND escheatable_since IS NULL
+        AND last_activity_at <= now() - make_interval(secs => $1)"##
+    );
+    let result = sqlx::query(&update_sql)
+        .bind(settings.escheat_after.as_secs_f64())
+        .execute(pool)
+        .await?;
+    Ok(result.rows_affected())
+}
+
+/// A dormant account's balance to be transferred to the state as unclaimed property.
+#[derive(Debug, Clone, PartialEq, Eq, ToSchema, Serialize, Deserialize)]
+pub struct EscheatmentEntry {
+    pub account_id: AccountId,
+    pub owner: String,
+    pub mailing_address: MailingAddress,
+    #[schema(value_type = ApiMoney)]
+    pub balance: Money,
+    pub last_activity_at: DateTime<Utc>,
+    pub dormant_since: DateTime<Utc>,
+    pub escheatable_since: DateTime<Utc>,
+}
+
+/// Lists the dormant accounts flagged for escheatment that hold a balance, longest inactive
+/// first.
+#[tracing::instrument(level = "debug", skip(pool))]
+pub async fn escheatment_report(pool: &PgPool) -> Result<Vec<EscheatmentEntry>, sqlx::Error> {
+    let select_sql = format!(
+        r##"SELECT activity.account_id, activity.user_name, activity.mailing_address,
+        activity.last_activity_at, activity.dormant_since, activity.escheatable_since,
+        account_view.{ACCOUNT_QUERY_VIEW_PAYLOAD}
+        FROM {ACTIVITY_TABLE} activity
+        INNER JOIN {ACCOUNT_QUERY_VIEW} account_view
+        ON account_view.view_id = activity.aggregate_id
+        WHERE activity.escheatable_since IS NOT NULL
+        ORDER BY activity.last_activity_at"##
+    );
+    let rows = sqlx::query(&select_sql).fetch_all(pool).await?;
+
+    let mut report = Vec::with_capacity(rows.len());
+    for row in rows {
+        let view: BankAccountView =
+            serde_json::from_value(row.try_get(ACCOUNT_QUERY_VIEW_PAYLOAD)?)
+                .map_err(|err| sqlx::Error::Decode(err.into()))?;
+        if view.balance <= *ZERO_MONEY {
+            continue;
+        }
+
+        report.push(EscheatmentEntry {
+            account_id: AccountId::new(row.try_get::<i64, _>("account_id")?),
+            owner: row.try_get("user_name")?,
+            mailing_address: MailingAddress::new(row.try_get::<String, _>("mailing_address")?),
+            balance: view.balance,
+            last_activity_at: row.try_get("last_activity_at")?,
+            dormant_since: row.try_get("dormant_since")?,
+            escheatable_since: row.try_get("escheatable_since")?,
+        });
+    }
+
+    Ok(report)
+}
@@ -1,4 +1,4 @@
-use crate::{application, model};
+use crate::{application, commands, model};
 use anyhow::anyhow;
 use cqrs_es::persist::PersistenceError;
 use cqrs_es::AggregateError;
@@ -18,6 +18,15 @@ pub enum BankError {
     #[error("{0}")]
     BankAccount(#[from] model::BankAccountError),
 
+    #[error("{0}")]
+    Customer(#[from] model::CustomerError),
+
+    #[error("{0}")]
+    Dispute(#[from] model::DisputeError),
+
+    #[error("{0}")]
+    StandingOrder(#[from] model::StandingOrderError),
+
     #[error("User violated bank service business rules: {0}")]
     User(#[from] anyhow::Error),
 
@@ -34,13 +43,34 @@ pub enum BankError {
     Unexpected { source: anyhow::Error },
 }
 
+impl BankError {
+    /// Stable machine-readable code identifying the error to API clients, so they may branch on,
+    /// e.g., `insufficient_funds` rather than parse the error message.
+    pub const fn error_code(&self) -> &'static str {
+        match self {
+            Self::Api(error) => error.error_code(),
+            Self::Validation(_) => "invalid_request",
+            Self::BankAccount(error) => error.error_code(),
+            Self::Customer(error) => error.error_code(),
+            Self::Dispute(error) => error.error_code(),
+            Self::StandingOrder(error) => error.error_code(),
+            Self::User(_) => "rejected_command",
+            Self::AggregateConflict => "aggregate_conflict",
+            Self::DatabaseConnection { .. } => "database_unavailable",
+            Self::Deserialization { .. } | Self::Unexpected { .. } => "internal_error",
+        }
+    }
+}
+
+/// Domain errors keep their type rather than being flattened, so the API can report the precise
+/// status and code for each rejection.
 impl<E> From<AggregateError<E>> for BankError
 where
-    E: std::error::Error + Send + Sync + 'static,
+    E: std::error::Error + Into<Self> + Send + Sync + 'static,
 {
     fn from(error: AggregateError<E>) -> Self {
         match error {
-            AggregateError::UserError(err) => Self::User(anyhow!(err)),
+            AggregateError::UserError(err) => err.into(),
             AggregateError::AggregateConflict => Self::AggregateConflict,
             AggregateError::DatabaseConnectionError(err) => {
                 Self::DatabaseConnection { source: anyhow!(err) }
@@ -65,6 +95,12 @@ impl From<axum::extract::rejection::PathRejection> for BankError {
     }
 }
 
+impl From<axum::extract::rejection::QueryRejection> for BankError {
+    fn from(error: axum::extract::rejection::QueryRejection) -> Self {
+        application::ApiError::Query(error).into()
+    }
+}
+
 impl From<axum::extract::rejection::JsonRejection> for BankError {
     fn from(error: axum::extract::rejection::JsonRejection) -> Self {
         application::ApiError::Json(error).into()
@@ -76,3 +112,15 @@ impl From<sqlx::Error> for BankError {
         application::ApiError::Sql { source }.into()
     }
 }
+
+impl From<commands::CommandQueueError> for BankError {
+    fn from(error: commands::CommandQueueError) -> Self {
+        application::ApiError::CommandQueue(error).into()
+    }
+}
+
+impl From<serde_json::Error> for BankError {
+    fn from(error: serde_json::Error) -> Self {
+        Self::Deserialization { source: error.into() }
+    }
+}
@@ -0,0 +1,236 @@
+//! Periodic assessment of account fees.
+//!
+//! The [FeeAssessmentQuery] indexes each account by the end of its current billing period, and the
+//! fee task periodically issues an `AssessFees` command for accounts whose billing period has
+//! ended. The account charges the monthly maintenance and below-minimum-balance fees due under the
+//! fee schedule, unless waived, and rejects a period already assessed, so a pass interrupted by a
+//! restart does not charge the fees twice. A period the account already assessed is skipped, and
+//! an account that otherwise fails to be assessed is retried after the retry interval, so it does
+//! not hold back the rest of the batch. Per-check and foreign ATM fees are charged by the account
+//! as the transactions are made.
+
+use crate::model::{bank_account, BankAccount, BankAccountAggregate, BankAccountCommand};
+use crate::model::{BankAccountError, BankAccountEvent};
+use crate::queries::{EVENTS_TABLE, RECV_TIMESTAMP};
+use crate::settings::FeeSettings;
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use cqrs_es::{AggregateError, EventEnvelope, Query};
+use pretty_snowflake::envelope::MetaData;
+use sqlx::{PgPool, Row};
+use std::time::Duration;
+use tokio::task::JoinHandle;
+use tokio::time::MissedTickBehavior;
+
+pub const ASSESSMENT_TABLE: &str = "fee_assessment";
+
+/// Maintains the end of each account's current billing period from committed account events.
+#[derive(Debug, Clone)]
+pub struct FeeAssessmentQuery {
+    pool: PgPool,
+    billing_period: Duration,
+}
+
+impl FeeAssessmentQuery {
+    pub const fn new(pool: PgPool, billing_period: Duration) -> Self {
+        Self { pool, billing_period }
+    }
+
+    async fn record(
+        &self, aggregate_id: &str, event: &EventEnvelope<BankAccount>,
+    ) -> Result<(), sqlx::Error> {
+        match &event.payload {
+            BankAccountEvent::AccountOpened { .. } => {
+                // the first period runs from when the account opening was recorded, so a replay of
+                // the event store keeps the account's billing periods
+                let opened_at: Option<DateTime<Utc>> =
+                    event.metadata.get(RECV_TIMESTAMP).and_then(|ts| ts.parse().ok());
+                let insert_sql = format!(
+                    r##"INSERT INTO {ASSESSMENT_TABLE} (aggregate_id, next_assessment_at)
+                    VALUES ($1, COALESCE($3, now()) + make_interval(secs => $2))
+                    ON CONFLICT DO NOTHING"##
+                );
+                sqlx::query(&insert_sql)
+                    .bind(aggregate_id)
+                    .bind(self.billing_period.as_secs_f64())
+                    .bind(opened_at)
+                    .execute(&self.pool)
+                    .await?;
+            },
+
+            BankAccountEvent::FeesAssessed { period_ending } => {
+                let update_sql = format!(
+                    r##"UPDATE {ASSESSMENT_TABLE}
+                    SET next_assessment_at = $2 + make_interval(secs => $3), retry_at = NULL
+                    WHERE aggregate_id = $1"##
+                );
+                sqlx::query(&update_sql)
+                    .bind(aggregate_id)
+                    .bind(period_ending)
+                    .bind(self.billing_period.as_secs_f64())
+                    .execute(&self.pool)
+                    .await?;
+            },
+
+            _ => {},
+        }
+
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl Query<BankAccount> for FeeAssessmentQuery {
+    #[tracing::instrument(level = "debug", skip(self, events))]
+    async fn dispatch(&self, aggregate_id: &str, events: &[EventEnvelope<BankAccount>]) {
+        for event in events {
+            if let Err(error) = self.record(aggregate_id, event).await {
+                tracing::error!(
+                    ?error, sequence=%event.sequence,
+                    "failed to index account billing period"
+                );
+            }
+        }
+    }
+}
+
+pub fn spawn_fee_worker(
+    pool: PgPool, agg: BankAccountAggregate, settings: FeeSettings,
+) -> JoinHandle<()> {
+    tokio::spawn(async move {
+        tracing::info!("starting fee assessment task...");
+        match backfill_billing_periods(&pool, settings.billing_period).await {
+            Ok(0) => {},
+            Ok(nr_indexed) => {
+                tracing::info!(%nr_indexed, "indexed billing periods of existing accounts")
+            },
+            Err(error) => tracing::error!(?error, "billing period backfill failed"),
+        }
+
+        let mut interval = tokio::time::interval(settings.poll_interval);
+        interval.set_missed_tick_behavior(MissedTickBehavior::Delay);
+
+        loop {
+            interval.tick().await;
+            match assess_due_batch(&pool, &agg, &settings).await {
+                Ok(0) => {},
+                Ok(nr_assessed) => tracing::info!(%nr_assessed, "assessed periodic account fees"),
+                Err(error) => tracing::error!(?error, "fee assessment pass failed"),
+            }
+        }
+    })
+}
+
+/// Indexes the billing period of accounts missing from the index, such as those opened before the
+/// fee task maintained it, from each account's opening or latest assessment in the event store.
+/// Returns the number of accounts indexed.
+#[tracing::instrument(level = "debug", skip(pool))]
+pub async fn backfill_billing_periods(
+    pool: &PgPool, billing_period: Duration,
+) -> Result<u64, sqlx::Error> {
+    let insert_sql = format!(
+        r##"INSERT INTO {ASSESSMENT_TABLE} (aggregate_id, next_assessment_at)
+        SELECT opened.aggregate_id, COALESCE(
+            (SELECT (assessed.payload->'FeesAssessed'->>'period_ending')::timestamptz
+                FROM {EVENTS_TABLE} assessed
+                WHERE assessed.aggregate_type = opened.aggregate_type
+                AND assessed.aggregate_id = opened.aggregate_id
+                AND assessed.event_type = 'fees_assessed'
+                ORDER BY assessed.sequence DESC LIMIT 1),
+            (opened.metadata->>'{RECV_TIMESTAMP}')::timestamptz,
+            now()
+        ) + make_interval(secs => $2)
+        FROM {EVENTS_TABLE} opened
+        WHERE opened.aggregate_type = $1 AND opened.event_type = 'account_opened'
+        ON CONFLICT DO NOTHING"##
+    );
+    let result = sqlx::query(&insert_sql)
+        .bind(bank_account::AGGREGATE_TYPE)
+        .bind(billing_period.as_secs_f64())
+        .execute(pool)
+        .await?;
+    Ok(result.rows_affected())
+}
+
+/// Assesses the periodic fees of one batch of accounts whose billing period has ended, returning
+/// the number assessed.
+#[tracing::instrument(level = "debug", skip(pool, agg, settings))]
+pub async fn assess_due_batch(
+    pool: &PgPool, agg: &BankAccountAggregate, settings: &FeeSettings,
+) -> Result<usize, sqlx::Error> {
+    let select_sql = format!(
+        r##"SELECT aggregate_id, next_assessment_at FROM {ASSESSMENT_TABLE}
+        WHERE COALESCE(retry_at, next_assessment_at) <= now()
+        ORDER BY COALESCE(retry_at, next_assessment_at)
+        LIMIT $1"##
+    );
+    let rows = sqlx::query(&select_sql).bind(settings.batch_size).fetch_all(pool).await?;
+
+    let mut nr_assessed = 0;
+    for row in rows {
+        let aggregate_id: String = row.try_get("aggregate_id")?;
+        let period_ending: DateTime<Utc> = row.try_get("next_assessment_at")?;
+
+        let result = agg
+            .execute_with_metadata(
+                &aggregate_id,
+                BankAccountCommand::AssessFees { period_ending },
+                MetaData::<BankAccount>::default().into(),
+            )
+            .await;
+
+        match result {
+            Ok(()) => nr_assessed += 1,
+            Err(AggregateError::UserError(reason @ BankAccountError::FeesAlreadyAssessed(..))) => {
+                tracing::debug!(%aggregate_id, %reason, "account fees already assessed -- skipped");
+                advance_assessment(pool, &aggregate_id, period_ending, settings.billing_period)
+                    .await?;
+            },
+            Err(error) => {
+                tracing::warn!(
+                    ?error, %aggregate_id, retry_interval=?settings.retry_interval,
+                    "failed to assess account fees -- will retry"
+                );
+                defer_assessment(pool, &aggregate_id, settings.retry_interval).await?;
+            },
+        }
+    }
+
+    Ok(nr_assessed)
+}
+
+/// Moves the account's next assessment past a period it has already assessed.
+async fn advance_assessment(
+    pool: &PgPool, aggregate_id: &str, period_ending: DateTime<Utc>, billing_period: Duration,
+) -> Result<(), sqlx::Error> {
+    let update_sql = format!(
+        r##"UPDATE {ASSESSMENT_TABLE}
+        SET next_assessment_at = $2 + make_interval(secs => $3), retry_at = NULL
+        WHERE aggregate_id = $1 AND next_assessment_at = $2"##
+    );
+    sqlx::query(&update_sql)
+        .bind(aggregate_id)
+        .bind(period_ending)
+        .bind(billing_period.as_secs_f64())
+        .execute(pool)
+        .await?;
+    Ok(())
+}
+
+/// Retries the assessment of the account's period after the retry interval, behind the other due
+/// accounts.
+async fn defer_assessment(
+    pool: &PgPool, aggregate_id: &str, retry_interval: Duration,
+) -> Result<(), sqlx::Error> {
+    let update_sql = format!(
+        r##"UPDATE {ASSESSMENT_TABLE}
+        SET retry_at = now() + make_interval(secs => $2)
+        WHERE aggregate_id = $1"##
+    );
+    sqlx::query(&update_sql)
+        .bind(aggregate_id)
+        .bind(retry_interval.as_secs_f64())
+        .execute(pool)
+        .await?;
+    Ok(())
+}
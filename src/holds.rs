@@ -0,0 +1,146 @@
+//! Expiry of holds placed on accounts for pending authorizations.
+//!
+//! The [HoldExpiryQuery] indexes each account's pending holds by expiry time, and the expiry task
+//! periodically issues an `ExpireHold` command for holds past their expiry, which returns the
+//! reserved funds to the account's available balance.
+
+use crate::model::{BankAccount, BankAccountAggregate, BankAccountCommand, BankAccountError};
+use crate::model::{BankAccountEvent, HoldId};
+use crate::settings::HoldSettings;
+use async_trait::async_trait;
+use cqrs_es::{AggregateError, EventEnvelope, Query};
+use pretty_snowflake::envelope::MetaData;
+use sqlx::{PgPool, Row};
+use tokio::task::JoinHandle;
+use tokio::time::MissedTickBehavior;
+
+pub const HOLDS_TABLE: &str = "account_holds";
+
+/// Maintains the index of pending holds from committed account events.
+#[derive(Debug, Clone)]
+pub struct HoldExpiryQuery {
+    pool: PgPool,
+}
+
+impl HoldExpiryQuery {
+    pub const fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+
+    async fn record(
+        &self, aggregate_id: &str, event: &EventEnvelope<BankAccount>,
+    ) -> Result<(), sqlx::Error> {
+        match &event.payload {
+            BankAccountEvent::HoldPlaced { hold_id, expires_at, .. } => {
+                let insert_sql = format!(
+                    r##"INSERT INTO {HOLDS_TABLE} (aggregate_id, hold_id, expires_at)
+                    VALUES ($1, $2, $3)
+                    ON CONFLICT DO NOTHING"##
+                );
+                sqlx::query(&insert_sql)
+                    .bind(aggregate_id)
+                    .bind(hold_id.as_str())
+                    .bind(expires_at)
+                    .execute(&self.pool)
+                    .await?;
+            },
+
+            BankAccountEvent::HoldCaptured { hold_id, .. }
+            | BankAccountEvent::HoldReleased { hold_id }
+            | BankAccountEvent::HoldExpired { hold_id } => {
+                remove_hold(&self.pool, aggregate_id, hold_id).await?;
+            },
+
+            _ => {},
+        }
+
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl Query<BankAccount> for HoldExpiryQuery {
+    #[tracing::instrument(level = "debug", skip(self, events))]
+    async fn dispatch(&self, aggregate_id: &str, events: &[EventEnvelope<BankAccount>]) {
+        for event in events {
+            if let Err(error) = self.record(aggregate_id, event).await {
+                tracing::error!(
+                    ?error, sequence=%event.sequence,
+                    "failed to index account hold for expiry"
+                );
+            }
+        }
+    }
+}
+
+async fn remove_hold(
+    pool: &PgPool, aggregate_id: &str, hold_id: &HoldId,
+) -> Result<(), sqlx::Error> {
+    let delete_sql = format!("DELETE FROM {HOLDS_TABLE} WHERE aggregate_id = $1 AND hold_id = $2");
+    sqlx::query(&delete_sql)
+        .bind(aggregate_id)
+        .bind(hold_id.as_str())
+        .execute(pool)
+        .await?;
+    Ok(())
+}
+
+pub fn spawn_expiry_worker(
+    pool: PgPool, agg: BankAccountAggregate, settings: HoldSettings,
+) -> JoinHandle<()> {
+    tokio::spawn(async move {
+        tracing::info!("starting hold expiry task...");
+        let mut interval = tokio::time::interval(settings.poll_interval);
+        interval.set_missed_tick_behavior(MissedTickBehavior::Delay);
+
+        loop {
+            interval.tick().await;
+            match expire_batch(&pool, &agg, settings.batch_size).await {
+                Ok(0) => {},
+                Ok(nr_expired) => tracing::debug!(%nr_expired, "expired account holds"),
+                Err(error) => tracing::error!(?error, "hold expiry pass failed"),
+            }
+        }
+    })
+}
+
+/// Expires one batch of holds past their expiry time, returning the number expired.
+#[tracing::instrument(level = "debug", skip(pool, agg))]
+pub async fn expire_batch(
+    pool: &PgPool, agg: &BankAccountAggregate, batch_size: i64,
+) -> Result<usize, sqlx::Error> {
+    let select_sql = format!(
+        r##"SELECT aggregate_id, hold_id FROM {HOLDS_TABLE}
+        WHERE expires_at <= now()
+        ORDER BY expires_at
+        LIMIT $1"##
+    );
+    let rows = sqlx::query(&select_sql).bind(batch_size).fetch_all(pool).await?;
+
+    let mut nr_expired = 0;
+    for row in rows {
+        let aggregate_id: String = row.try_get("aggregate_id")?;
+        let hold_id = HoldId::new(row.try_get::<String, _>("hold_id")?);
+
+        let result = agg
+            .execute_with_metadata(
+                &aggregate_id,
+                BankAccountCommand::ExpireHold { hold_id: hold_id.clone() },
+                MetaData::<BankAccount>::default().into(),
+            )
+            .await;
+
+        match result {
+            Ok(()) => nr_expired += 1,
+            Err(AggregateError::UserError(BankAccountError::HoldNotFound(..))) => {
+                tracing::debug!(%aggregate_id, %hold_id, "hold already settled -- removing from index");
+                remove_hold(pool, &aggregate_id, &hold_id).await?;
+            },
+            Err(error) => {
+                tracing::warn!(?error, %aggregate_id, %hold_id, "failed to expire account hold");
+            },
+        }
+    }
+
+    Ok(nr_expired)
+}
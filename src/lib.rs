@@ -8,14 +8,44 @@
 )]
 
 pub mod application;
+mod commands;
+mod dormancy;
 mod errors;
+mod fees;
+mod holds;
 mod model;
+mod notifications;
+mod outbox;
 mod queries;
+mod replay;
 mod services;
 mod settings;
+mod standing_orders;
 pub mod tracing;
+mod webhooks;
 
 pub use application::{ApiError, Application};
-pub use model::{AccountId, AtmId, BankAccount, CheckNumber, EmailAddress, MailingAddress};
-pub use queries::{BankAccountView, LedgerEntry};
-pub use settings::{CliOptions, CorrelationSettings, Settings};
+pub use commands::{CommandQueueError, CommandStatus};
+pub use dormancy::EscheatmentEntry;
+pub use model::{
+    AccountHolder, AccountId, AccountType, AtmId, BankAccount, Beneficiary, CheckNumber, CommandId,
+    CustomerId, DisputeId, DisputeStatus, DisputedTransaction, DocumentKind, DocumentReference,
+    EmailAddress, FeeKind, FundsHold, HoldId, HolderChangeId, HolderRole, KycStatus,
+    MailingAddress, Schedule, StandingOrderId, StandingOrderStatus, COMMAND_CONFLICTS_EXHAUSTED,
+    COMMAND_CONFLICT_RETRIES,
+};
+pub use notifications::{EmailMessage, InMemoryMailer, Mailer, NotificationError};
+pub use queries::{
+    AccountDormancy, AccountFreeze, AccountHolding, BankAccountView, CustomerView, DisputeView,
+    FreezeAction, FreezeAuditEntry, LedgerEntry, StandingOrderView,
+};
+pub use replay::{replay_projections, ReplayError};
+pub use services::FraudOutcome;
+pub use settings::{
+    AccountTypePolicy, AccountTypeSettings, ApiTokenSettings, CliCommand, CliOptions,
+    CommandQueueSettings, CommandRetrySettings, CorrelationIdOutOfRange, CorrelationSettings,
+    DormancySettings, FeeSchedule, FeeSettings, FraudRule, IdentityVerifierSettings,
+    MailerSettings, PublisherSettings, RateLimitQuota, RateLimitSettings, Settings,
+    StandingOrderSettings,
+};
+pub use webhooks::{sign_payload, SIGNATURE_HEADER, TIMESTAMP_HEADER};
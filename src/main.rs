@@ -1,3 +1,5 @@
+use bankaccount::application;
+use bankaccount::CliCommand;
 use clap::Parser;
 use settings_loader::{LoadingOptions, SettingsLoader};
 
@@ -6,8 +8,6 @@ async fn main() -> anyhow::Result<()> {
     let subscriber = bankaccount::tracing::get_tracing_subscriber("info");
     bankaccount::tracing::init_subscriber(subscriber);
 
-    //todo consider: pretty_snowflake::generator::set_id_generator(...);
-
     let app_environment = std::env::var(bankaccount::CliOptions::env_app_environment()).ok();
     if app_environment.is_none() {
         tracing::info!("No environment configuration override provided.");
@@ -22,8 +22,30 @@ async fn main() -> anyhow::Result<()> {
         "loaded settings via CLI options and environment"
     );
 
-    let application = bankaccount::Application::build(&settings).await?;
-    application.run_until_stopped().await.map_err(|err| err.into())
+    match options.command.unwrap_or_default() {
+        CliCommand::Serve => {
+            let application = bankaccount::Application::build(&settings).await?;
+            application.run_until_stopped().await.map_err(|err| err.into())
+        },
+
+        CliCommand::Migrate => {
+            let pool = application::get_connection_pool(&settings.database);
+            application::migrate_database(&pool).await?;
+            Ok(())
+        },
+
+        CliCommand::Replay => {
+            let pool = application::get_connection_pool(&settings.database);
+            bankaccount::replay_projections(&pool, &settings.fees).await?;
+            tracing::info!("rebuilt view projections from the event store");
+            Ok(())
+        },
+
+        CliCommand::CheckConfig => {
+            println!("{settings:#?}");
+            Ok(())
+        },
+    }
 }
 
 fn parse_options() -> bankaccount::CliOptions {
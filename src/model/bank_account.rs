@@ -1,25 +1,41 @@
 use super::AccountId;
 use crate::model;
-use crate::model::{AtmId, CheckNumber, EmailAddress, MailingAddress, ZERO_MONEY};
+use crate::model::{
+    AtmId, CheckNumber, CommandExecutor, CommandId, CustomerId, DisputeId, EmailAddress, FlagId,
+    HoldId, HolderChangeId, KycStatus, MailingAddress, StandingOrderId, ZERO_MONEY,
+};
 use async_trait::async_trait;
+use chrono::{DateTime, Datelike, TimeZone, Utc};
 use cqrs_es::{Aggregate, DomainEvent};
 use money2::Money;
-use postgres_es::PostgresCqrs;
 use pretty_snowflake::{Id, Label};
 use serde::{Deserialize, Serialize};
-use std::sync::Arc;
+use std::collections::VecDeque;
 
 mod errors;
 mod protocol;
 
-use crate::services::{BankAccountApi, BankAccountServices};
-pub use errors::BankAccountError;
-pub use protocol::{BankAccountCommand, BankAccountEvent};
+use crate::services::{
+    AccountPolicyApi, BankAccountApi, BankAccountServices, CommandClaimApi, CustomerKycApi,
+    FraudOutcome, FraudScreeningApi, ScreenedActivity,
+};
+pub use errors::{account_error_code, BankAccountError};
+pub use protocol::{
+    AccountHolder, AccountType, BankAccountCommand, BankAccountEvent, FeeKind, FlaggedTransaction,
+    FlaggedTransactionEntry, FundsHold, HolderChange, HolderRole, PendingHolderChange,
+};
 
-pub type BankAccountAggregate = Arc<PostgresCqrs<BankAccount>>;
+pub type BankAccountAggregate = CommandExecutor<BankAccount>;
 
 pub const AGGREGATE_TYPE: &str = "account";
 
+/// Number of the latest queued commands an account remembers having applied. The window only
+/// needs to cover the command claimed again after its worker applied it but before the outcome
+/// was recorded: until it is recorded, the command holds back the account's later queued
+/// commands, so it is the latest applied. Workers whose claim is no longer current are rejected
+/// against the command queue instead, however many commands were applied since.
+const APPLIED_COMMANDS_WINDOW: usize = 256;
+
 #[inline]
 pub fn generate_id() -> Id<BankAccount> {
     pretty_snowflake::generator::next_id()
@@ -28,6 +44,10 @@ pub fn generate_id() -> Id<BankAccount> {
 #[derive(Debug, Default, Clone, Label, PartialEq, Serialize, Deserialize)]
 pub struct BankAccount {
     state: BankAccountState,
+
+    /// Sequence number of the last event applied to the account.
+    #[serde(default)]
+    sequence: usize,
 }
 
 #[async_trait]
@@ -49,7 +69,8 @@ impl Aggregate for BankAccount {
     }
 
     fn apply(&mut self, event: Self::Event) {
-        if let Some(new_state) = self.state.apply(event) {
+        self.sequence += 1;
+        if let Some(new_state) = self.state.apply(event, self.sequence) {
             self.state = new_state;
         }
     }
@@ -68,19 +89,21 @@ trait AggregateState {
         &self, command: Self::Command, services: &Self::Services,
     ) -> Result<Vec<Self::Event>, Self::Error>;
 
-    fn apply(&self, event: Self::Event) -> Option<Self::State>;
+    /// Applies the event, which has the `sequence` number in the account's event stream.
+    fn apply(&self, event: Self::Event, sequence: usize) -> Option<Self::State>;
 }
 
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 enum BankAccountState {
     Quiescent(QuiescentBankAccount),
     Active(ActiveBankAccount),
+    Frozen(FrozenBankAccount),
     Closed(ClosedBankAccount),
 }
 
 impl Default for BankAccountState {
     fn default() -> Self {
-        Self::Quiescent(QuiescentBankAccount::default())
+        Self::Quiescent(QuiescentBankAccount)
     }
 }
 
@@ -98,15 +121,17 @@ impl AggregateState for BankAccountState {
         match self {
             Self::Quiescent(state) => state.handle(command, services).await,
             Self::Active(state) => state.handle(command, services).await,
+            Self::Frozen(state) => state.handle(command, services).await,
             Self::Closed(state) => state.handle(command, services).await,
         }
     }
 
-    fn apply(&self, event: Self::Event) -> Option<Self::State> {
+    fn apply(&self, event: Self::Event, sequence: usize) -> Option<Self::State> {
         match self {
-            Self::Quiescent(state) => state.apply(event),
-            Self::Active(state) => state.apply(event),
-            Self::Closed(state) => state.apply(event),
+            Self::Quiescent(state) => state.apply(event, sequence),
+            Self::Active(state) => state.apply(event, sequence),
+            Self::Frozen(state) => state.apply(event, sequence),
+            Self::Closed(state) => state.apply(event, sequence),
         }
     }
 }
@@ -123,33 +148,86 @@ impl AggregateState for QuiescentBankAccount {
     type Services = <BankAccount as Aggregate>::Services;
 
     async fn handle(
-        &self, command: Self::Command, _services: &Self::Services,
+        &self, command: Self::Command, services: &Self::Services,
     ) -> Result<Vec<Self::Event>, Self::Error> {
         match command {
-            BankAccountCommand::OpenAccount { account_id, user_name, mailing_address, email } => {
+            BankAccountCommand::OpenAccount {
+                account_id,
+                customer_id,
+                account_type,
+                mailing_address,
+                email,
+                joint_holders,
+            } => {
+                let customer = services
+                    .load_customer(customer_id)
+                    .await?
+                    .ok_or(BankAccountError::CustomerNotFound(customer_id))?;
+                if customer.kyc_status != KycStatus::Verified {
+                    return Err(BankAccountError::CustomerNotVerified(
+                        customer_id,
+                        customer.kyc_status,
+                    ));
+                }
+
+                let mut holders = vec![AccountHolder::new(customer_id, HolderRole::Owner)];
+                for holder in joint_holders.iter() {
+                    check_holder_addition(&holders, holder)?;
+                    holders.push(holder.clone());
+                }
+
                 Ok(vec![BankAccountEvent::AccountOpened {
                     account_id,
-                    user_name,
+                    customer_id: Some(customer_id),
+                    account_type,
+                    user_name: customer.name,
                     mailing_address,
                     email,
+                    joint_holders,
                 }])
             },
-            cmd => Err(BankAccountError::RejectedCommand(format!(
-                "Unopened account cannot process command: {cmd:?}"
-            ))),
+            cmd => Err(BankAccountError::NotOpened(format!("{cmd:?}"))),
         }
     }
 
-    fn apply(&self, event: Self::Event) -> Option<Self::State> {
+    fn apply(&self, event: Self::Event, _sequence: usize) -> Option<Self::State> {
         match event {
-            BankAccountEvent::AccountOpened { account_id, user_name, mailing_address, email } => {
+            BankAccountEvent::AccountOpened {
+                account_id,
+                customer_id,
+                account_type,
+                user_name,
+                mailing_address,
+                email,
+                joint_holders,
+            } => {
+                // accounts opened before KYC have no owning customer to hold them
+                let owner = customer_id.map(|id| AccountHolder::new(id, HolderRole::Owner));
+                let holders = owner.into_iter().chain(joint_holders).collect();
+
                 Some(BankAccountState::Active(ActiveBankAccount {
                     id: account_id.into(),
                     account_id,
+                    customer_id,
+                    account_type,
                     user_name,
                     balance: Money::default(),
                     mailing_address,
                     email,
+                    flagged: Vec::new(),
+                    last_flag_id: None,
+                    holds: Vec::new(),
+                    postings: Vec::new(),
+                    provisional_credits: Vec::new(),
+                    standing_orders: Vec::new(),
+                    holders,
+                    pending_holder_changes: Vec::new(),
+                    last_holder_change_id: None,
+                    dormant: false,
+                    fee_waivers: Vec::new(),
+                    fees_assessed_through: None,
+                    monthly_withdrawals: MonthlyWithdrawals::default(),
+                    applied_commands: VecDeque::new(),
                 }))
             },
 
@@ -165,10 +243,145 @@ impl AggregateState for QuiescentBankAccount {
 struct ActiveBankAccount {
     id: Id<BankAccount>,
     account_id: AccountId,
+
+    /// Verified customer who owns the account.
+    #[serde(default)]
+    customer_id: Option<CustomerId>,
+
+    /// Selects the policy governing the account's transactions.
+    #[serde(default)]
+    account_type: AccountType,
+
     user_name: String,
     balance: Money,
     mailing_address: MailingAddress,
     email: EmailAddress,
+
+    /// Transactions held for fraud review.
+    #[serde(default)]
+    flagged: Vec<FlaggedTransactionEntry>,
+
+    #[serde(default)]
+    last_flag_id: Option<FlagId>,
+
+    /// Funds reserved for pending authorizations, which are not yet settled against the balance.
+    #[serde(default)]
+    holds: Vec<FundsHold>,
+
+    /// Settled transactions, which may be reversed.
+    #[serde(default)]
+    postings: Vec<Posting>,
+
+    /// Credits issued while disputes are investigated, which are taken back if the bank prevails.
+    /// Settled credits are kept, so a dispute retrying its settlement does not settle it twice.
+    #[serde(default)]
+    provisional_credits: Vec<ProvisionalCredit>,
+
+    /// The latest occurrence of each standing order applied to the account, so an occurrence is
+    /// never paid twice.
+    #[serde(default)]
+    standing_orders: Vec<AppliedStandingOrder>,
+
+    /// Customers holding the account, including its owner.
+    #[serde(default)]
+    holders: Vec<AccountHolder>,
+
+    /// Holder changes requested by joint owners, awaiting the owner's approval.
+    #[serde(default)]
+    pending_holder_changes: Vec<PendingHolderChange>,
+
+    #[serde(default)]
+    last_holder_change_id: Option<HolderChangeId>,
+
+    /// Set when the account goes without customer-initiated activity; withdrawals are blocked
+    /// until it is reactivated.
+    #[serde(default)]
+    dormant: bool,
+
+    /// Fees not charged to the account.
+    #[serde(default)]
+    fee_waivers: Vec<FeeKind>,
+
+    /// End of the latest billing period whose periodic fees are assessed.
+    #[serde(default)]
+    fees_assessed_through: Option<DateTime<Utc>>,
+
+    /// Cash and check withdrawals made in the month of the latest one, checked against the
+    /// account type's monthly withdrawal cap.
+    #[serde(default)]
+    monthly_withdrawals: MonthlyWithdrawals,
+
+    /// The latest queued commands applied to the account, oldest first, so a command claimed again
+    /// by another worker while it is still executing is not applied twice.
+    #[serde(default)]
+    applied_commands: VecDeque<CommandId>,
+}
+
+/// The effect of a settled transaction on the account balance, in the currency it was transacted
+/// in, identified by the sequence number of the event that posted it.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+struct Posting {
+    sequence: usize,
+    amount: Money,
+    reversed: bool,
+}
+
+/// A provisional credit of the disputed transaction posted by the event with the `sequence`
+/// number; the credit itself was posted by the event with the `credit_sequence` number.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+struct ProvisionalCredit {
+    dispute_id: DisputeId,
+    sequence: usize,
+    credit_sequence: usize,
+    amount: Money,
+
+    /// How the dispute settled the credit; outstanding credits are not yet settled.
+    #[serde(default)]
+    settlement: Option<CreditSettlement>,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize, Deserialize)]
+enum CreditSettlement {
+    Finalized,
+    Reversed,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+struct AppliedStandingOrder {
+    order_id: StandingOrderId,
+    due_at: DateTime<Utc>,
+
+    /// Sequence number of the event that posted the occurrence.
+    #[serde(default)]
+    sequence: usize,
+}
+
+/// The number of withdrawals made in a calendar month.
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq, Serialize, Deserialize)]
+struct MonthlyWithdrawals {
+    /// Start of the month the withdrawals are counted in.
+    month: Option<DateTime<Utc>>,
+    count: usize,
+}
+
+impl MonthlyWithdrawals {
+    fn month_of(at: DateTime<Utc>) -> Option<DateTime<Utc>> {
+        Utc.with_ymd_and_hms(at.year(), at.month(), 1, 0, 0, 0).single()
+    }
+
+    /// The number of withdrawals made in the month of `at`.
+    fn made_in_month_of(&self, at: DateTime<Utc>) -> usize {
+        if self.month.is_some() && self.month == Self::month_of(at) {
+            self.count
+        } else {
+            0
+        }
+    }
+
+    fn record(&mut self, withdrawn_at: DateTime<Utc>) {
+        self.count = self.made_in_month_of(withdrawn_at) + 1;
+        self.month = Self::month_of(withdrawn_at);
+    }
 }
 
 #[async_trait]
@@ -182,6 +395,9 @@ impl AggregateState for ActiveBankAccount {
     async fn handle(
         &self, command: Self::Command, services: &Self::Services,
     ) -> Result<Vec<Self::Event>, Self::Error> {
+        self.check_withdrawals_allowed(&command)?;
+        self.check_account_policy(&command, services)?;
+
         match command {
             BankAccountCommand::OpenAccount { .. } => Err(BankAccountError::RejectedCommand(
                 format!("Active account {} cannot be reopened.", self.account_id),
@@ -200,35 +416,263 @@ impl AggregateState for ActiveBankAccount {
             },
 
             BankAccountCommand::ChangeMailingAddress { new_address } => {
-                Ok(vec![BankAccountEvent::MailingAddressUpdated {
-                    new_address,
-                }])
+                let transaction = FlaggedTransaction::MailingAddressChange { new_address };
+                self.do_handle_screened(transaction, ScreenedActivity::ContactChange, services)
+                    .await
             },
 
             BankAccountCommand::ChangeEmail { new_email } => {
-                Ok(vec![BankAccountEvent::EmailUpdated { new_email }])
+                let transaction = FlaggedTransaction::EmailChange { new_email };
+                self.do_handle_screened(transaction, ScreenedActivity::ContactChange, services)
+                    .await
+            },
+
+            BankAccountCommand::ReleaseFlaggedTransaction { flag_id } => {
+                self.do_handle_flag_release(flag_id, services).await
+            },
+
+            BankAccountCommand::RejectFlaggedTransaction { flag_id, reason } => {
+                self.find_flagged(flag_id)?;
+                Ok(vec![BankAccountEvent::FlaggedTransactionRejected {
+                    flag_id,
+                    reason,
+                }])
+            },
+
+            BankAccountCommand::FreezeAccount { reason, authority } => {
+                Ok(vec![BankAccountEvent::AccountFrozen { reason, authority }])
+            },
+
+            BankAccountCommand::UnfreezeAccount { .. } => Err(BankAccountError::RejectedCommand(
+                format!("Active account {} is not frozen.", self.account_id),
+            )),
+
+            BankAccountCommand::PlaceHold { hold_id, amount, expires_at } => {
+                self.do_handle_place_hold(hold_id, amount, expires_at)
+            },
+
+            BankAccountCommand::CaptureHold { hold_id } => {
+                let amount = self.find_hold(&hold_id)?.amount;
+                Ok(vec![BankAccountEvent::HoldCaptured { hold_id, amount }])
+            },
+
+            BankAccountCommand::ReleaseHold { hold_id } => {
+                self.find_hold(&hold_id)?;
+                Ok(vec![BankAccountEvent::HoldReleased { hold_id }])
+            },
+
+            BankAccountCommand::ExpireHold { hold_id } => self.do_handle_hold_expiry(hold_id),
+
+            BankAccountCommand::ReverseTransaction { sequence, reason } => {
+                self.do_handle_reversal(sequence, reason)
+            },
+
+            BankAccountCommand::IssueProvisionalCredit { dispute_id, sequence } => {
+                self.do_handle_provisional_credit(dispute_id, sequence)
+            },
+
+            BankAccountCommand::FinalizeProvisionalCredit { dispute_id } => {
+                self.check_credit_unsettled(dispute_id, CreditSettlement::Finalized)?;
+                self.find_provisional_credit(dispute_id)?;
+                Ok(vec![BankAccountEvent::ProvisionalCreditFinalized {
+                    dispute_id,
+                }])
+            },
+
+            BankAccountCommand::ReverseProvisionalCredit { dispute_id } => {
+                self.check_credit_unsettled(dispute_id, CreditSettlement::Reversed)?;
+                let credit = self.find_provisional_credit(dispute_id)?;
+                Ok(vec![BankAccountEvent::ProvisionalCreditReversed {
+                    dispute_id,
+                    sequence: credit.sequence,
+                    credit_sequence: credit.credit_sequence,
+                    amount: model::negate(credit.amount),
+                }])
+            },
+
+            BankAccountCommand::DebitStandingOrder { order_id, due_at, amount, beneficiary } => {
+                self.check_standing_order_pending(order_id, due_at)?;
+                self.check_funds_available(amount)?;
+                Ok(vec![BankAccountEvent::StandingOrderDebited {
+                    order_id,
+                    due_at,
+                    amount,
+                    beneficiary,
+                }])
+            },
+
+            BankAccountCommand::CreditStandingOrder { order_id, due_at, amount, from_account } => {
+                self.check_standing_order_pending(order_id, due_at)?;
+                Ok(vec![BankAccountEvent::StandingOrderCredited {
+                    order_id,
+                    due_at,
+                    amount,
+                    from_account,
+                }])
+            },
+
+            BankAccountCommand::ReverseStandingOrderDebit { order_id, due_at, reason } => {
+                let sequence = self.find_standing_order_debit(order_id, due_at)?;
+                self.do_handle_reversal(sequence, reason)
+            },
+
+            BankAccountCommand::AddAccountHolder { holder } => {
+                self.do_handle_holder_change(HolderChange::Add { holder }, None)
+            },
+
+            BankAccountCommand::RemoveAccountHolder { customer_id } => {
+                self.do_handle_holder_change(HolderChange::Remove { customer_id }, None)
+            },
+
+            BankAccountCommand::ApproveHolderChange { change_id } => {
+                self.do_handle_holder_approval(change_id, None)
+            },
+
+            BankAccountCommand::MarkDormant { last_activity_at } => {
+                if self.dormant {
+                    return Err(BankAccountError::RejectedCommand(format!(
+                        "Account {} is already dormant.",
+                        self.account_id
+                    )));
+                }
+
+                Ok(vec![BankAccountEvent::AccountMarkedDormant {
+                    last_activity_at,
+                }])
+            },
+
+            BankAccountCommand::ReactivateAccount => self.do_handle_reactivation(None),
+
+            BankAccountCommand::ChangeAccountType { account_type } => {
+                self.do_handle_account_type_change(account_type, services)
+            },
+
+            BankAccountCommand::AssessFees { period_ending } => {
+                self.do_handle_fee_assessment(period_ending, services)
+            },
+
+            BankAccountCommand::SetFeeWaivers { waivers } => {
+                Ok(vec![BankAccountEvent::FeeWaiversSet { waivers }])
+            },
+
+            BankAccountCommand::AsHolder { customer_id, command } => {
+                let role = self.authorize_holder(customer_id, &command)?;
+                let requested_by = Some((customer_id, role));
+                match *command {
+                    BankAccountCommand::AddAccountHolder { holder } => {
+                        self.do_handle_holder_change(HolderChange::Add { holder }, requested_by)
+                    },
+                    BankAccountCommand::RemoveAccountHolder { customer_id } => {
+                        let change = HolderChange::Remove { customer_id };
+                        self.do_handle_holder_change(change, requested_by)
+                    },
+                    BankAccountCommand::ApproveHolderChange { change_id } => {
+                        self.do_handle_holder_approval(change_id, Some(customer_id))
+                    },
+                    BankAccountCommand::ReactivateAccount => {
+                        self.do_handle_reactivation(Some(customer_id))
+                    },
+                    command => self.handle(command, services).await,
+                }
+            },
+
+            BankAccountCommand::Queued { command_id, claimed_until, command } => {
+                self.check_command_pending(command_id)?;
+                self.check_command_claimed(command_id, claimed_until, services).await?;
+                let mut events = self.handle(*command, services).await?;
+                events.push(BankAccountEvent::QueuedCommandApplied { command_id });
+                Ok(events)
             },
         }
     }
 
-    fn apply(&self, event: Self::Event) -> Option<Self::State> {
+    fn apply(&self, event: Self::Event, sequence: usize) -> Option<Self::State> {
         match event {
             BankAccountEvent::BalanceDeposited { amount } => {
                 let mut updated = self.clone();
                 let converted = model::convert_amount(self.balance.currency, amount);
                 updated.balance += converted;
+                updated.post(sequence, amount);
                 Some(BankAccountState::Active(updated))
             },
-            BankAccountEvent::CashWithdrawal { amount } => {
+            BankAccountEvent::CashWithdrawal { amount, withdrawn_at, .. } => {
                 let mut updated = self.clone();
                 let converted = model::convert_amount(self.balance.currency, amount);
                 updated.balance -= converted; // ignoring negative balance here
+                updated.post(sequence, model::negate(amount));
+                if let Some(withdrawn_at) = withdrawn_at {
+                    updated.monthly_withdrawals.record(withdrawn_at);
+                }
+                Some(BankAccountState::Active(updated))
+            },
+            BankAccountEvent::CheckWithdrawal { amount, withdrawn_at, .. } => {
+                let mut updated = self.clone();
+                let converted = model::convert_amount(self.balance.currency, amount);
+                updated.balance -= converted;
+                updated.post(sequence, model::negate(amount));
+                if let Some(withdrawn_at) = withdrawn_at {
+                    updated.monthly_withdrawals.record(withdrawn_at);
+                }
+                Some(BankAccountState::Active(updated))
+            },
+            BankAccountEvent::TransactionReversed { sequence: reversed, amount, .. } => {
+                let mut updated = self.clone();
+                let converted = model::convert_amount(self.balance.currency, amount);
+                updated.balance += converted;
+                updated.mark_reversed(reversed, true);
+                updated.standing_orders.retain(|applied| applied.sequence != reversed);
+                Some(BankAccountState::Active(updated))
+            },
+            BankAccountEvent::ProvisionalCreditIssued {
+                dispute_id,
+                sequence: disputed,
+                amount,
+            } => {
+                let mut updated = self.clone();
+                let converted = model::convert_amount(self.balance.currency, amount);
+                updated.balance += converted;
+                updated.mark_reversed(disputed, true);
+                updated.provisional_credits.push(ProvisionalCredit {
+                    dispute_id,
+                    sequence: disputed,
+                    credit_sequence: sequence,
+                    amount,
+                    settlement: None,
+                });
+                Some(BankAccountState::Active(updated))
+            },
+            BankAccountEvent::ProvisionalCreditFinalized { dispute_id } => {
+                let mut updated = self.clone();
+                updated.settle_provisional_credit(dispute_id, CreditSettlement::Finalized);
+                Some(BankAccountState::Active(updated))
+            },
+            BankAccountEvent::ProvisionalCreditReversed {
+                dispute_id,
+                sequence: disputed,
+                amount,
+                ..
+            } => {
+                let mut updated = self.clone();
+                let converted = model::convert_amount(self.balance.currency, amount);
+                updated.balance += converted;
+                updated.mark_reversed(disputed, false);
+                updated.settle_provisional_credit(dispute_id, CreditSettlement::Reversed);
                 Some(BankAccountState::Active(updated))
             },
-            BankAccountEvent::CheckWithdrawal { amount, .. } => {
+            BankAccountEvent::StandingOrderDebited { order_id, due_at, amount, .. } => {
                 let mut updated = self.clone();
                 let converted = model::convert_amount(self.balance.currency, amount);
                 updated.balance -= converted;
+                updated.post(sequence, model::negate(amount));
+                updated.record_standing_order(order_id, due_at, sequence);
+                Some(BankAccountState::Active(updated))
+            },
+            BankAccountEvent::StandingOrderCredited { order_id, due_at, amount, .. } => {
+                let mut updated = self.clone();
+                let converted = model::convert_amount(self.balance.currency, amount);
+                updated.balance += converted;
+                updated.post(sequence, amount);
+                updated.record_standing_order(order_id, due_at, sequence);
                 Some(BankAccountState::Active(updated))
             },
             BankAccountEvent::MailingAddressUpdated { new_address } => {
@@ -241,6 +685,110 @@ impl AggregateState for ActiveBankAccount {
                 updated.email = new_email;
                 Some(BankAccountState::Active(updated))
             },
+            BankAccountEvent::TransactionFlagged { flag_id, transaction, reasons } => {
+                let mut updated = self.clone();
+                updated.last_flag_id = updated.last_flag_id.max(Some(flag_id));
+                updated
+                    .flagged
+                    .push(FlaggedTransactionEntry { flag_id, transaction, reasons });
+                Some(BankAccountState::Active(updated))
+            },
+            BankAccountEvent::FlaggedTransactionReleased { flag_id }
+            | BankAccountEvent::FlaggedTransactionRejected { flag_id, .. } => {
+                let mut updated = self.clone();
+                updated.flagged.retain(|entry| entry.flag_id != flag_id);
+                Some(BankAccountState::Active(updated))
+            },
+            BankAccountEvent::HoldPlaced { hold_id, amount, expires_at } => {
+                let mut updated = self.clone();
+                updated.holds.push(FundsHold { hold_id, amount, expires_at });
+                Some(BankAccountState::Active(updated))
+            },
+            BankAccountEvent::HoldCaptured { hold_id, amount } => {
+                let mut updated = self.clone();
+                updated.holds.retain(|hold| hold.hold_id != hold_id);
+                let converted = model::convert_amount(self.balance.currency, amount);
+                updated.balance -= converted;
+                updated.post(sequence, model::negate(amount));
+                Some(BankAccountState::Active(updated))
+            },
+            BankAccountEvent::HoldReleased { hold_id }
+            | BankAccountEvent::HoldExpired { hold_id } => {
+                let mut updated = self.clone();
+                updated.holds.retain(|hold| hold.hold_id != hold_id);
+                Some(BankAccountState::Active(updated))
+            },
+            BankAccountEvent::HolderChangeRequested { change_id, change, requested_by } => {
+                let mut updated = self.clone();
+                updated.last_holder_change_id = updated.last_holder_change_id.max(Some(change_id));
+                updated.pending_holder_changes.push(PendingHolderChange {
+                    change_id,
+                    change,
+                    requested_by,
+                });
+                Some(BankAccountState::Active(updated))
+            },
+            BankAccountEvent::HolderChangeApproved { change_id, .. } => {
+                let mut updated = self.clone();
+                updated
+                    .pending_holder_changes
+                    .retain(|pending| pending.change_id != change_id);
+                Some(BankAccountState::Active(updated))
+            },
+            BankAccountEvent::AccountHolderAdded { holder, .. } => {
+                let mut updated = self.clone();
+                updated.holders.push(holder);
+                Some(BankAccountState::Active(updated))
+            },
+            BankAccountEvent::AccountHolderRemoved { customer_id, .. } => {
+                let mut updated = self.clone();
+                updated.holders.retain(|holder| holder.customer_id != customer_id);
+                Some(BankAccountState::Active(updated))
+            },
+            BankAccountEvent::AccountMarkedDormant { .. } => {
+                let mut updated = self.clone();
+                updated.dormant = true;
+                Some(BankAccountState::Active(updated))
+            },
+            BankAccountEvent::AccountReactivated { .. } => {
+                let mut updated = self.clone();
+                updated.dormant = false;
+                Some(BankAccountState::Active(updated))
+            },
+            BankAccountEvent::AccountTypeChanged { to, .. } => {
+                let mut updated = self.clone();
+                updated.account_type = to;
+                Some(BankAccountState::Active(updated))
+            },
+            BankAccountEvent::FeeCharged { amount, .. } => {
+                let mut updated = self.clone();
+                let converted = model::convert_amount(self.balance.currency, amount);
+                updated.balance -= converted;
+                updated.post(sequence, model::negate(amount));
+                Some(BankAccountState::Active(updated))
+            },
+            BankAccountEvent::FeesAssessed { period_ending } => {
+                let mut updated = self.clone();
+                updated.fees_assessed_through = Some(period_ending);
+                Some(BankAccountState::Active(updated))
+            },
+            BankAccountEvent::FeeWaiversSet { waivers } => {
+                let mut updated = self.clone();
+                updated.fee_waivers = waivers;
+                Some(BankAccountState::Active(updated))
+            },
+            BankAccountEvent::QueuedCommandApplied { command_id } => {
+                let mut updated = self.clone();
+                updated.record_applied_command(command_id);
+                Some(BankAccountState::Active(updated))
+            },
+            BankAccountEvent::AccountFrozen { reason, authority } => {
+                Some(BankAccountState::Frozen(FrozenBankAccount {
+                    account: self.clone(),
+                    reason,
+                    authority,
+                }))
+            },
             event => {
                 tracing::warn!(?event, "unrecognized bank account event -- ignored");
                 None
@@ -254,26 +802,655 @@ impl ActiveBankAccount {
     async fn do_handle_cash_withdrawal(
         &self, amount: Money, atm_id: AtmId, services: &<Self as AggregateState>::Services,
     ) -> Result<Vec<<Self as AggregateState>::Event>, <Self as AggregateState>::Error> {
-        let remaining_balance = self.check_funds_available(amount)?;
+        let activity = ScreenedActivity::CashWithdrawal { amount, atm_id: atm_id.clone() };
+        let transaction = FlaggedTransaction::CashWithdrawal { amount, atm_id: atm_id.clone() };
+        let fee = self.transaction_fee(&transaction, services);
+        let remaining_balance = self.check_funds_available(Self::with_fee(amount, fee))?;
         services.validate_atm_withdrawal(&atm_id, amount).await?;
         tracing::debug!(
             "cash withdrawal from ATM {atm_id} will leave {remaining_balance} in account {}",
             self.account_id
         );
-        Ok(vec![BankAccountEvent::CashWithdrawal { amount }])
+        self.do_handle_screened(transaction, activity, services).await
     }
 
     #[tracing::instrument(level = "trace", skip(self, services))]
     async fn do_handle_check_disbursement(
         &self, check_nr: CheckNumber, amount: Money, services: &<Self as AggregateState>::Services,
     ) -> Result<Vec<<Self as AggregateState>::Event>, <Self as AggregateState>::Error> {
-        let remaining_balance = self.check_funds_available(amount)?;
+        let activity = ScreenedActivity::CheckWithdrawal { check_nr, amount };
+        let transaction = FlaggedTransaction::CheckWithdrawal { check_nr, amount };
+        let fee = self.transaction_fee(&transaction, services);
+        let remaining_balance = self.check_funds_available(Self::with_fee(amount, fee))?;
         services.validate_check(&self.account_id, check_nr).await?;
         tracing::debug!(
             "disbursement of check {check_nr} will leave {remaining_balance} in account {}",
             self.account_id
         );
-        Ok(vec![BankAccountEvent::CheckWithdrawal { check_nr, amount }])
+        self.do_handle_screened(transaction, activity, services).await
+    }
+
+    /// Screens the transaction for fraud, applying it if allowed or holding it for review.
+    #[tracing::instrument(level = "trace", skip(self, services))]
+    async fn do_handle_screened(
+        &self, transaction: FlaggedTransaction, activity: ScreenedActivity,
+        services: &<Self as AggregateState>::Services,
+    ) -> Result<Vec<<Self as AggregateState>::Event>, <Self as AggregateState>::Error> {
+        let screening = services.screen(&self.account_id, &activity).await?;
+        match screening.outcome {
+            FraudOutcome::Allow => {
+                let fee = self.transaction_fee(&transaction, services);
+                let mut events = vec![Self::transaction_event(transaction, Utc::now())];
+                events.extend(fee.map(Self::fee_event));
+                Ok(events)
+            },
+            FraudOutcome::Hold => Ok(vec![BankAccountEvent::TransactionFlagged {
+                flag_id: self.last_flag_id.map_or_else(|| FlagId::new(1), |id| id.next()),
+                transaction,
+                reasons: screening.reasons,
+            }]),
+            FraudOutcome::Deny => Err(BankAccountError::TransactionDenied(screening.reasons)),
+        }
+    }
+
+    #[tracing::instrument(level = "trace", skip(self, services))]
+    async fn do_handle_flag_release(
+        &self, flag_id: FlagId, services: &<Self as AggregateState>::Services,
+    ) -> Result<Vec<<Self as AggregateState>::Event>, <Self as AggregateState>::Error> {
+        let transaction = self.find_flagged(flag_id)?.transaction.clone();
+        let fee = self.transaction_fee(&transaction, services);
+        let withdrawal = match &transaction {
+            FlaggedTransaction::CashWithdrawal { amount, atm_id } => {
+                self.check_funds_available(Self::with_fee(*amount, fee))?;
+                services.validate_atm_withdrawal(atm_id, *amount).await?;
+                Some(BankAccountCommand::WithdrawCash { amount: *amount, atm_id: atm_id.clone() })
+            },
+            FlaggedTransaction::CheckWithdrawal { check_nr, amount } => {
+                self.check_funds_available(Self::with_fee(*amount, fee))?;
+                services.validate_check(&self.account_id, *check_nr).await?;
+                Some(BankAccountCommand::DisburseCheck { check_nr: *check_nr, amount: *amount })
+            },
+            FlaggedTransaction::MailingAddressChange { .. }
+            | FlaggedTransaction::EmailChange { .. } => None,
+        };
+
+        // the account may have gone dormant or used up its limits since the withdrawal was held
+        if let Some(withdrawal) = withdrawal {
+            self.check_withdrawals_allowed(&withdrawal)?;
+            self.check_account_policy(&withdrawal, services)?;
+        }
+
+        let mut events = vec![
+            BankAccountEvent::FlaggedTransactionReleased { flag_id },
+            Self::transaction_event(transaction, Utc::now()),
+        ];
+        events.extend(fee.map(Self::fee_event));
+        Ok(events)
+    }
+
+    #[tracing::instrument(level = "trace", skip(self))]
+    fn do_handle_place_hold(
+        &self, hold_id: HoldId, amount: Money, expires_at: DateTime<Utc>,
+    ) -> Result<Vec<<Self as AggregateState>::Event>, <Self as AggregateState>::Error> {
+        if self.holds.iter().any(|hold| hold.hold_id == hold_id) {
+            return Err(BankAccountError::RejectedCommand(format!(
+                "hold {hold_id} is already placed on account {}",
+                self.account_id
+            )));
+        }
+
+        if expires_at <= Utc::now() {
+            return Err(BankAccountError::RejectedCommand(format!(
+                "hold {hold_id} would already be expired at {expires_at}"
+            )));
+        }
+
+        let remaining_balance = self.check_funds_available(amount)?;
+        tracing::debug!(
+            "hold {hold_id} will leave {remaining_balance} available in account {}",
+            self.account_id
+        );
+        Ok(vec![BankAccountEvent::HoldPlaced {
+            hold_id,
+            amount,
+            expires_at,
+        }])
+    }
+
+    #[tracing::instrument(level = "trace", skip(self))]
+    fn do_handle_hold_expiry(
+        &self, hold_id: HoldId,
+    ) -> Result<Vec<<Self as AggregateState>::Event>, <Self as AggregateState>::Error> {
+        let hold = self.find_hold(&hold_id)?;
+        if Utc::now() < hold.expires_at {
+            return Err(BankAccountError::RejectedCommand(format!(
+                "hold {hold_id} does not expire until {}",
+                hold.expires_at
+            )));
+        }
+
+        Ok(vec![BankAccountEvent::HoldExpired { hold_id }])
+    }
+
+    #[tracing::instrument(level = "trace", skip(self))]
+    fn do_handle_reversal(
+        &self, sequence: usize, reason: String,
+    ) -> Result<Vec<<Self as AggregateState>::Event>, <Self as AggregateState>::Error> {
+        let posting = self.find_reversible_posting(sequence)?;
+        if *ZERO_MONEY < posting.amount {
+            // reversing a credit debits the account, which must not overdraw it
+            self.check_funds_available(posting.amount)?;
+        }
+
+        Ok(vec![BankAccountEvent::TransactionReversed {
+            sequence,
+            amount: model::negate(posting.amount),
+            reason,
+        }])
+    }
+
+    #[tracing::instrument(level = "trace", skip(self))]
+    fn do_handle_provisional_credit(
+        &self, dispute_id: DisputeId, sequence: usize,
+    ) -> Result<Vec<<Self as AggregateState>::Event>, <Self as AggregateState>::Error> {
+        if self
+            .provisional_credits
+            .iter()
+            .any(|credit| credit.dispute_id == dispute_id)
+        {
+            return Err(BankAccountError::DisputeAlreadyApplied(
+                self.account_id,
+                dispute_id,
+            ));
+        }
+
+        let posting = self.find_reversible_posting(sequence)?;
+        Ok(vec![BankAccountEvent::ProvisionalCreditIssued {
+            dispute_id,
+            sequence,
+            amount: model::negate(posting.amount),
+        }])
+    }
+
+    fn post(&mut self, sequence: usize, amount: Money) {
+        self.postings.push(Posting { sequence, amount, reversed: false });
+    }
+
+    fn mark_reversed(&mut self, sequence: usize, reversed: bool) {
+        let posting = self.postings.iter_mut().find(|posting| posting.sequence == sequence);
+        if let Some(posting) = posting {
+            posting.reversed = reversed;
+        }
+    }
+
+    fn find_reversible_posting(&self, sequence: usize) -> Result<&Posting, BankAccountError> {
+        let posting = self.postings.iter().find(|posting| posting.sequence == sequence).ok_or(
+            BankAccountError::TransactionNotFound(self.account_id, sequence),
+        )?;
+
+        if posting.reversed {
+            return Err(BankAccountError::TransactionAlreadyReversed(
+                self.account_id,
+                sequence,
+            ));
+        }
+
+        Ok(posting)
+    }
+
+    /// Checks that the customer holds the account in a role permitted to execute the command.
+    fn authorize_holder(
+        &self, customer_id: CustomerId, command: &BankAccountCommand,
+    ) -> Result<HolderRole, BankAccountError> {
+        let role = self
+            .holders
+            .iter()
+            .find(|holder| holder.customer_id == customer_id)
+            .map(|holder| holder.role)
+            .ok_or(BankAccountError::NotAccountHolder(
+                self.account_id,
+                customer_id,
+            ))?;
+
+        if role.permits(command) {
+            Ok(role)
+        } else {
+            Err(BankAccountError::HolderNotPermitted(
+                self.account_id,
+                customer_id,
+                role,
+            ))
+        }
+    }
+
+    /// Applies the holder change on the bank's authority or the owner's, or else records it for
+    /// the owner's approval.
+    fn do_handle_holder_change(
+        &self, change: HolderChange, requested_by: Option<(CustomerId, HolderRole)>,
+    ) -> Result<Vec<BankAccountEvent>, BankAccountError> {
+        self.check_holder_change(&change)?;
+
+        match requested_by {
+            Some((requested_by, role)) if role != HolderRole::Owner => {
+                let change_id = self
+                    .last_holder_change_id
+                    .map_or_else(|| HolderChangeId::new(1), |id| id.next());
+                Ok(vec![BankAccountEvent::HolderChangeRequested {
+                    change_id,
+                    change,
+                    requested_by,
+                }])
+            },
+            requested_by => Ok(vec![
+                change.into_event(requested_by.map(|(customer, _)| customer))
+            ]),
+        }
+    }
+
+    fn do_handle_holder_approval(
+        &self, change_id: HolderChangeId, approved_by: Option<CustomerId>,
+    ) -> Result<Vec<BankAccountEvent>, BankAccountError> {
+        let pending = self
+            .pending_holder_changes
+            .iter()
+            .find(|pending| pending.change_id == change_id)
+            .ok_or(BankAccountError::HolderChangeNotFound(
+                self.account_id,
+                change_id,
+            ))?;
+        self.check_holder_change(&pending.change)?;
+
+        Ok(vec![
+            BankAccountEvent::HolderChangeApproved { change_id, approved_by },
+            pending.change.clone().into_event(approved_by),
+        ])
+    }
+
+    fn do_handle_reactivation(
+        &self, reactivated_by: Option<CustomerId>,
+    ) -> Result<Vec<BankAccountEvent>, BankAccountError> {
+        if !self.dormant {
+            return Err(BankAccountError::RejectedCommand(format!(
+                "Account {} is not dormant.",
+                self.account_id
+            )));
+        }
+
+        Ok(vec![BankAccountEvent::AccountReactivated {
+            reactivated_by,
+        }])
+    }
+
+    /// Checks that the command does not take funds out of a dormant account.
+    const fn check_withdrawals_allowed(
+        &self, command: &BankAccountCommand,
+    ) -> Result<(), BankAccountError> {
+        let is_withdrawal = matches!(
+            command,
+            BankAccountCommand::WithdrawCash { .. }
+                | BankAccountCommand::DisburseCheck { .. }
+                | BankAccountCommand::PlaceHold { .. }
+                | BankAccountCommand::CaptureHold { .. }
+                | BankAccountCommand::DebitStandingOrder { .. }
+        );
+
+        if self.dormant && is_withdrawal {
+            Err(BankAccountError::AccountDormant(self.account_id))
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Checks the withdrawal against the policy for the account's type.
+    fn check_account_policy(
+        &self, command: &BankAccountCommand, services: &<Self as AggregateState>::Services,
+    ) -> Result<(), BankAccountError> {
+        let policy = services.policy(self.account_type);
+        let (amount, fee, capped) = match command {
+            BankAccountCommand::WithdrawCash { amount, atm_id } => {
+                let transaction =
+                    FlaggedTransaction::CashWithdrawal { amount: *amount, atm_id: atm_id.clone() };
+                (*amount, self.transaction_fee(&transaction, services), true)
+            },
+            BankAccountCommand::DisburseCheck { check_nr, amount } => {
+                if !policy.checks_allowed {
+                    return Err(BankAccountError::ChecksNotAllowed(
+                        self.account_id,
+                        self.account_type,
+                    ));
+                }
+                let transaction =
+                    FlaggedTransaction::CheckWithdrawal { check_nr: *check_nr, amount: *amount };
+                (*amount, self.transaction_fee(&transaction, services), true)
+            },
+            BankAccountCommand::PlaceHold { amount, .. }
+            | BankAccountCommand::DebitStandingOrder { amount, .. } => (*amount, None, false),
+            _ => return Ok(()),
+        };
+
+        if let Some(max_withdrawal) = policy.max_withdrawal {
+            if max_withdrawal < model::convert_amount(max_withdrawal.currency, amount) {
+                return Err(BankAccountError::WithdrawalLimitExceeded(
+                    self.account_id,
+                    max_withdrawal,
+                ));
+            }
+        }
+
+        if let Some(minimum_balance) = policy.minimum_balance {
+            let withdrawn = Self::with_fee(amount, fee);
+            let remaining =
+                self.available_balance() - model::convert_amount(self.balance.currency, withdrawn);
+            if remaining < model::convert_amount(self.balance.currency, minimum_balance) {
+                return Err(BankAccountError::MinimumBalanceRequired(
+                    self.account_id,
+                    minimum_balance,
+                ));
+            }
+        }
+
+        if let Some(cap) = policy.monthly_withdrawal_cap.filter(|_| capped) {
+            if cap <= self.monthly_withdrawals.made_in_month_of(Utc::now()) {
+                return Err(BankAccountError::MonthlyWithdrawalCapReached(
+                    self.account_id,
+                    cap,
+                ));
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Changes the account to the type if it is eligible under the new type's policy.
+    fn do_handle_account_type_change(
+        &self, account_type: AccountType, services: &<Self as AggregateState>::Services,
+    ) -> Result<Vec<BankAccountEvent>, BankAccountError> {
+        if account_type == self.account_type {
+            return Err(BankAccountError::RejectedCommand(format!(
+                "Account {} is already a {account_type} account.",
+                self.account_id
+            )));
+        }
+
+        let policy = services.policy(account_type);
+        if let Some(minimum_balance) = policy.minimum_balance {
+            let minimum = model::convert_amount(self.balance.currency, minimum_balance);
+            if self.available_balance() < minimum {
+                return Err(BankAccountError::MinimumBalanceRequired(
+                    self.account_id,
+                    minimum_balance,
+                ));
+            }
+        }
+
+        let pending_check = self.flagged.iter().any(|entry| {
+            matches!(
+                entry.transaction,
+                FlaggedTransaction::CheckWithdrawal { .. }
+            )
+        });
+        if !policy.checks_allowed && pending_check {
+            return Err(BankAccountError::RejectedCommand(format!(
+                "Account {} has a check withdrawal held for review and cannot become a \
+                 {account_type} account.",
+                self.account_id
+            )));
+        }
+
+        Ok(vec![BankAccountEvent::AccountTypeChanged {
+            from: self.account_type,
+            to: account_type,
+        }])
+    }
+
+    fn check_holder_change(&self, change: &HolderChange) -> Result<(), BankAccountError> {
+        match change {
+            HolderChange::Add { holder } => check_holder_addition(&self.holders, holder),
+            HolderChange::Remove { customer_id } => {
+                match self.holders.iter().find(|holder| &holder.customer_id == customer_id) {
+                    None => Err(BankAccountError::NotAccountHolder(
+                        self.account_id,
+                        *customer_id,
+                    )),
+                    Some(holder) if holder.role == HolderRole::Owner => {
+                        Err(BankAccountError::RejectedCommand(format!(
+                            "Owner {customer_id} cannot be removed from account {}.",
+                            self.account_id
+                        )))
+                    },
+                    Some(_) => Ok(()),
+                }
+            },
+        }
+    }
+
+    fn record_applied_command(&mut self, command_id: CommandId) {
+        if APPLIED_COMMANDS_WINDOW <= self.applied_commands.len() {
+            self.applied_commands.pop_front();
+        }
+        self.applied_commands.push_back(command_id);
+    }
+
+    fn check_command_pending(&self, command_id: CommandId) -> Result<(), BankAccountError> {
+        if self.applied_commands.contains(&command_id) {
+            Err(BankAccountError::CommandAlreadyApplied(
+                self.account_id,
+                command_id,
+            ))
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Checks that the worker executing the queued command still holds its claim, so a worker
+    /// resuming after its claim expired does not apply a command another worker completed or one
+    /// failed meanwhile.
+    async fn check_command_claimed(
+        &self, command_id: CommandId, claimed_until: DateTime<Utc>,
+        services: &<Self as AggregateState>::Services,
+    ) -> Result<(), BankAccountError> {
+        if services.holds_claim(command_id, claimed_until).await? {
+            Ok(())
+        } else {
+            Err(BankAccountError::CommandClaimExpired(
+                self.account_id,
+                command_id,
+            ))
+        }
+    }
+
+    fn check_standing_order_pending(
+        &self, order_id: StandingOrderId, due_at: DateTime<Utc>,
+    ) -> Result<(), BankAccountError> {
+        let applied = self
+            .standing_orders
+            .iter()
+            .any(|applied| applied.order_id == order_id && due_at <= applied.due_at);
+        if applied {
+            Err(BankAccountError::StandingOrderAlreadyApplied(
+                self.account_id,
+                order_id,
+                due_at,
+            ))
+        } else {
+            Ok(())
+        }
+    }
+
+    fn record_standing_order(
+        &mut self, order_id: StandingOrderId, due_at: DateTime<Utc>, sequence: usize,
+    ) {
+        self.standing_orders.retain(|applied| applied.order_id != order_id);
+        self.standing_orders
+            .push(AppliedStandingOrder { order_id, due_at, sequence });
+    }
+
+    /// Finds the sequence number of the event that debited the standing order's occurrence.
+    fn find_standing_order_debit(
+        &self, order_id: StandingOrderId, due_at: DateTime<Utc>,
+    ) -> Result<usize, BankAccountError> {
+        self.standing_orders
+            .iter()
+            .find(|applied| applied.order_id == order_id && applied.due_at == due_at)
+            .map(|applied| applied.sequence)
+            .ok_or_else(|| {
+                BankAccountError::RejectedCommand(format!(
+                    "standing order {order_id} occurrence due at {due_at} is not debited from account {}",
+                    self.account_id
+                ))
+            })
+    }
+
+    fn find_provisional_credit(
+        &self, dispute_id: DisputeId,
+    ) -> Result<&ProvisionalCredit, BankAccountError> {
+        self.provisional_credits
+            .iter()
+            .find(|credit| credit.dispute_id == dispute_id && credit.settlement.is_none())
+            .ok_or(BankAccountError::ProvisionalCreditNotFound(
+                self.account_id,
+                dispute_id,
+            ))
+    }
+
+    /// Rejects settling the dispute's provisional credit as it is already settled.
+    fn check_credit_unsettled(
+        &self, dispute_id: DisputeId, settlement: CreditSettlement,
+    ) -> Result<(), BankAccountError> {
+        let settled = self
+            .provisional_credits
+            .iter()
+            .any(|credit| credit.dispute_id == dispute_id && credit.settlement == Some(settlement));
+        if settled {
+            Err(BankAccountError::DisputeAlreadyApplied(
+                self.account_id,
+                dispute_id,
+            ))
+        } else {
+            Ok(())
+        }
+    }
+
+    fn settle_provisional_credit(&mut self, dispute_id: DisputeId, settlement: CreditSettlement) {
+        let credit = self
+            .provisional_credits
+            .iter_mut()
+            .find(|credit| credit.dispute_id == dispute_id && credit.settlement.is_none());
+        if let Some(credit) = credit {
+            credit.settlement = Some(settlement);
+        }
+    }
+
+    fn find_hold(&self, hold_id: &HoldId) -> Result<&FundsHold, BankAccountError> {
+        self.holds
+            .iter()
+            .find(|hold| &hold.hold_id == hold_id)
+            .ok_or_else(|| BankAccountError::HoldNotFound(self.account_id, hold_id.clone()))
+    }
+
+    fn find_flagged(&self, flag_id: FlagId) -> Result<&FlaggedTransactionEntry, BankAccountError> {
+        self.flagged
+            .iter()
+            .find(|entry| entry.flag_id == flag_id)
+            .ok_or(BankAccountError::FlagNotFound(self.account_id, flag_id))
+    }
+
+    /// The event applying the transaction, made at `now`.
+    fn transaction_event(
+        transaction: FlaggedTransaction, now: DateTime<Utc>,
+    ) -> <Self as AggregateState>::Event {
+        match transaction {
+            FlaggedTransaction::CashWithdrawal { amount, atm_id } => {
+                BankAccountEvent::CashWithdrawal {
+                    amount,
+                    atm_id: Some(atm_id),
+                    withdrawn_at: Some(now),
+                }
+            },
+            FlaggedTransaction::CheckWithdrawal { check_nr, amount } => {
+                BankAccountEvent::CheckWithdrawal { check_nr, amount, withdrawn_at: Some(now) }
+            },
+            FlaggedTransaction::MailingAddressChange { new_address } => {
+                BankAccountEvent::MailingAddressUpdated { new_address }
+            },
+            FlaggedTransaction::EmailChange { new_email } => {
+                BankAccountEvent::EmailUpdated { new_email }
+            },
+        }
+    }
+
+    /// The fee charged for the transaction under the fee schedule, unless waived for the account.
+    fn transaction_fee(
+        &self, transaction: &FlaggedTransaction, services: &<Self as AggregateState>::Services,
+    ) -> Option<(FeeKind, Money)> {
+        let schedule = services.fee_schedule();
+        let (kind, fee) = match transaction {
+            FlaggedTransaction::CashWithdrawal { atm_id, .. } if !schedule.is_home_atm(atm_id) => {
+                (FeeKind::ForeignAtm, schedule.foreign_atm?)
+            },
+            FlaggedTransaction::CheckWithdrawal { .. } => (FeeKind::Check, schedule.per_check?),
+            _ => return None,
+        };
+
+        self.charges(kind).then_some((kind, fee))
+    }
+
+    fn charges(&self, kind: FeeKind) -> bool {
+        !self.fee_waivers.contains(&kind)
+    }
+
+    fn with_fee(amount: Money, fee: Option<(FeeKind, Money)>) -> Money {
+        fee.map_or(amount, |(_, fee)| {
+            amount + model::convert_amount(amount.currency, fee)
+        })
+    }
+
+    const fn fee_event((kind, amount): (FeeKind, Money)) -> <Self as AggregateState>::Event {
+        BankAccountEvent::FeeCharged { kind, amount }
+    }
+
+    /// Charges the periodic fees due under the fee schedule for the billing period, unless waived.
+    /// Periodic fees are charged even if they overdraw the account.
+    fn do_handle_fee_assessment(
+        &self, period_ending: DateTime<Utc>, services: &<Self as AggregateState>::Services,
+    ) -> Result<Vec<<Self as AggregateState>::Event>, <Self as AggregateState>::Error> {
+        if matches!(self.fees_assessed_through, Some(through) if period_ending <= through) {
+            return Err(BankAccountError::FeesAlreadyAssessed(
+                self.account_id,
+                period_ending,
+            ));
+        }
+
+        let schedule = services.fee_schedule();
+        let mut events = Vec::new();
+        if let Some(amount) = schedule.monthly_maintenance {
+            if self.charges(FeeKind::MonthlyMaintenance) {
+                events.push(BankAccountEvent::FeeCharged {
+                    kind: FeeKind::MonthlyMaintenance,
+                    amount,
+                });
+            }
+        }
+
+        if let Some(amount) = schedule.below_minimum_balance {
+            let minimum = model::convert_amount(self.balance.currency, schedule.minimum_balance);
+            if self.charges(FeeKind::BelowMinimumBalance) && self.balance < minimum {
+                events.push(BankAccountEvent::FeeCharged {
+                    kind: FeeKind::BelowMinimumBalance,
+                    amount,
+                });
+            }
+        }
+
+        events.push(BankAccountEvent::FeesAssessed { period_ending });
+        Ok(events)
+    }
+
+    /// The ledger balance less funds reserved by holds.
+    fn available_balance(&self) -> Money {
+        self.holds.iter().fold(self.balance, |available, hold| {
+            available - model::convert_amount(self.balance.currency, hold.amount)
+        })
     }
 
     #[tracing::instrument(
@@ -283,7 +1460,7 @@ impl ActiveBankAccount {
     )]
     fn check_funds_available(&self, amount: Money) -> Result<Money, BankAccountError> {
         let converted = model::convert_amount(self.balance.currency, amount);
-        let balance = self.balance - converted;
+        let balance = self.available_balance() - converted;
         if *ZERO_MONEY <= balance {
             Ok(balance)
         } else {
@@ -292,6 +1469,126 @@ impl ActiveBankAccount {
     }
 }
 
+fn check_holder_addition(
+    holders: &[AccountHolder], holder: &AccountHolder,
+) -> Result<(), BankAccountError> {
+    if holder.role == HolderRole::Owner {
+        Err(BankAccountError::RejectedCommand(format!(
+            "Customer {} cannot be added as owner; an account has a single owner.",
+            holder.customer_id
+        )))
+    } else if holders.iter().any(|existing| existing.customer_id == holder.customer_id) {
+        Err(BankAccountError::RejectedCommand(format!(
+            "Customer {} already holds the account.",
+            holder.customer_id
+        )))
+    } else {
+        Ok(())
+    }
+}
+
+/// A compliance freeze: the account continues to accept deposits, but rejects withdrawals and
+/// contact changes until it is unfrozen.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+struct FrozenBankAccount {
+    account: ActiveBankAccount,
+    reason: String,
+    authority: String,
+}
+
+impl FrozenBankAccount {
+    /// Whether the frozen account still accepts the command, which may not take funds out or
+    /// change the account's contacts.
+    const fn is_allowed(command: &BankAccountCommand) -> bool {
+        matches!(
+            command,
+            BankAccountCommand::DepositAmount { .. }
+                | BankAccountCommand::RejectFlaggedTransaction { .. }
+                | BankAccountCommand::ReleaseHold { .. }
+                | BankAccountCommand::ExpireHold { .. }
+                | BankAccountCommand::ReverseTransaction { .. }
+                | BankAccountCommand::IssueProvisionalCredit { .. }
+                | BankAccountCommand::FinalizeProvisionalCredit { .. }
+                | BankAccountCommand::ReverseProvisionalCredit { .. }
+                | BankAccountCommand::CreditStandingOrder { .. }
+                | BankAccountCommand::ReverseStandingOrderDebit { .. }
+                | BankAccountCommand::MarkDormant { .. }
+                | BankAccountCommand::ReactivateAccount
+                | BankAccountCommand::AssessFees { .. }
+                | BankAccountCommand::SetFeeWaivers { .. }
+        )
+    }
+
+    fn frozen_error(&self, command: &BankAccountCommand) -> BankAccountError {
+        BankAccountError::AccountFrozen(
+            self.account.account_id,
+            self.reason.clone(),
+            format!("{command:?}"),
+        )
+    }
+}
+
+#[async_trait]
+impl AggregateState for FrozenBankAccount {
+    type State = BankAccountState;
+    type Command = <BankAccount as Aggregate>::Command;
+    type Event = <BankAccount as Aggregate>::Event;
+    type Error = <BankAccount as Aggregate>::Error;
+    type Services = <BankAccount as Aggregate>::Services;
+
+    #[tracing::instrument(level = "trace", skip(services))]
+    async fn handle(
+        &self, command: Self::Command, services: &Self::Services,
+    ) -> Result<Vec<Self::Event>, Self::Error> {
+        match command {
+            BankAccountCommand::UnfreezeAccount { authority } => {
+                Ok(vec![BankAccountEvent::AccountUnfrozen { authority }])
+            },
+
+            BankAccountCommand::AsHolder { customer_id, command } => {
+                self.account.authorize_holder(customer_id, &command)?;
+                if Self::is_allowed(&command) {
+                    // the active account handles the holder's command as the holder's, e.g.,
+                    // recording who reactivated the account
+                    let command = BankAccountCommand::AsHolder { customer_id, command };
+                    self.account.handle(command, services).await
+                } else {
+                    Err(self.frozen_error(&command))
+                }
+            },
+
+            BankAccountCommand::Queued { command_id, claimed_until, command } => {
+                self.account.check_command_pending(command_id)?;
+                self.account
+                    .check_command_claimed(command_id, claimed_until, services)
+                    .await?;
+                let mut events = self.handle(*command, services).await?;
+                events.push(BankAccountEvent::QueuedCommandApplied { command_id });
+                Ok(events)
+            },
+
+            cmd if Self::is_allowed(&cmd) => self.account.handle(cmd, services).await,
+
+            cmd => Err(self.frozen_error(&cmd)),
+        }
+    }
+
+    fn apply(&self, event: Self::Event, sequence: usize) -> Option<Self::State> {
+        match event {
+            BankAccountEvent::AccountUnfrozen { .. } => {
+                Some(BankAccountState::Active(self.account.clone()))
+            },
+
+            event => match self.account.apply(event, sequence) {
+                Some(BankAccountState::Active(account)) => {
+                    Some(BankAccountState::Frozen(Self { account, ..self.clone() }))
+                },
+                _ => None,
+            },
+        }
+    }
+}
+
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 struct ClosedBankAccount {
     id: Id<BankAccount>,
@@ -313,12 +1610,13 @@ impl AggregateState for ClosedBankAccount {
     async fn handle(
         &self, command: Self::Command, _services: &Self::Services,
     ) -> Result<Vec<Self::Event>, Self::Error> {
-        Err(BankAccountError::RejectedCommand(format!(
-            "Closed account will not accept command: {command:?}"
-        )))
+        Err(BankAccountError::AccountClosed(
+            self.account_id,
+            format!("{command:?}"),
+        ))
     }
 
-    fn apply(&self, event: Self::Event) -> Option<Self::State> {
+    fn apply(&self, event: Self::Event, _sequence: usize) -> Option<Self::State> {
         tracing::warn!("no events possible dead-end closed account state: {event:?}");
         None
     }
@@ -1,5 +1,10 @@
-use crate::model::AccountId;
+use crate::model::{
+    AccountId, AccountType, CommandId, CustomerId, DisputeId, FlagId, HoldId, HolderChangeId,
+    HolderRole, KycStatus, StandingOrderId,
+};
 use crate::services::BankServiceError;
+use chrono::{DateTime, Utc};
+use cqrs_es::AggregateError;
 use money2::Money;
 use thiserror::Error;
 
@@ -8,6 +13,15 @@ pub enum BankAccountError {
     #[error("bank account not found id: {0}")]
     NotFound(AccountId),
 
+    #[error("Unopened account cannot process command: {0}")]
+    NotOpened(String),
+
+    #[error("Account {0} is frozen ({1}) and will not accept command: {2}")]
+    AccountFrozen(AccountId, String, String),
+
+    #[error("Closed account {0} will not accept command: {1}")]
+    AccountClosed(AccountId, String),
+
     #[error("{1} funds not available in account, {0}")]
     InsufficientFunds(AccountId, Money),
 
@@ -16,4 +30,114 @@ pub enum BankAccountError {
 
     #[error("Rejected command: {0}")]
     RejectedCommand(String),
+
+    #[error("transaction denied by fraud screening: {}", .0.join("; "))]
+    TransactionDenied(Vec<String>),
+
+    #[error("no flagged transaction {1} is held for account {0}")]
+    FlagNotFound(AccountId, FlagId),
+
+    #[error("no hold {1} is placed on account {0}")]
+    HoldNotFound(AccountId, HoldId),
+
+    #[error("no reversible transaction was posted by event {1} on account {0}")]
+    TransactionNotFound(AccountId, usize),
+
+    #[error("transaction posted by event {1} on account {0} is already reversed")]
+    TransactionAlreadyReversed(AccountId, usize),
+
+    #[error("no provisional credit for dispute {1} is outstanding on account {0}")]
+    ProvisionalCreditNotFound(AccountId, DisputeId),
+
+    #[error("dispute {1} is already applied to account {0}")]
+    DisputeAlreadyApplied(AccountId, DisputeId),
+
+    #[error("standing order {1} occurrence due at {2} is already applied to account {0}")]
+    StandingOrderAlreadyApplied(AccountId, StandingOrderId, DateTime<Utc>),
+
+    #[error("fees of account {0} are already assessed for the period ending {1}")]
+    FeesAlreadyAssessed(AccountId, DateTime<Utc>),
+
+    #[error("customer {1} is not a holder of account {0}")]
+    NotAccountHolder(AccountId, CustomerId),
+
+    #[error("customer {1}, as {2} of account {0}, is not permitted to execute the command")]
+    HolderNotPermitted(AccountId, CustomerId, HolderRole),
+
+    #[error("no holder change {1} is pending approval on account {0}")]
+    HolderChangeNotFound(AccountId, HolderChangeId),
+
+    #[error("no customer found id: {0}")]
+    CustomerNotFound(CustomerId),
+
+    #[error("account {0} is dormant; withdrawals are blocked until it is reactivated")]
+    AccountDormant(AccountId),
+
+    #[error("checks may not be disbursed from {1} account {0}")]
+    ChecksNotAllowed(AccountId, AccountType),
+
+    #[error("withdrawals from account {0} are limited to {1} per transaction")]
+    WithdrawalLimitExceeded(AccountId, Money),
+
+    #[error("account {0} already made its {1} withdrawals allowed this month")]
+    MonthlyWithdrawalCapReached(AccountId, usize),
+
+    #[error("account {0} must keep a minimum balance of {1}")]
+    MinimumBalanceRequired(AccountId, Money),
+
+    #[error("customer {0} is {1} and cannot open an account until their identity is verified")]
+    CustomerNotVerified(CustomerId, KycStatus),
+
+    #[error("queued command {1} is already applied to account {0}")]
+    CommandAlreadyApplied(AccountId, CommandId),
+
+    #[error("claim on queued command {1} for account {0} expired before it was applied")]
+    CommandClaimExpired(AccountId, CommandId),
+}
+
+impl BankAccountError {
+    /// Stable machine-readable code identifying the error to API clients.
+    pub const fn error_code(&self) -> &'static str {
+        match self {
+            Self::NotFound(_) | Self::NotOpened(_) => "account_not_found",
+            Self::AccountFrozen(..) => "account_frozen",
+            Self::AccountClosed(..) => "account_closed",
+            Self::InsufficientFunds(..) => "insufficient_funds",
+            Self::BankServiceError(error) => error.error_code(),
+            Self::RejectedCommand(_) => "rejected_command",
+            Self::TransactionDenied(_) => "transaction_denied",
+            Self::FlagNotFound(..) => "flag_not_found",
+            Self::HoldNotFound(..) => "hold_not_found",
+            Self::TransactionNotFound(..) => "transaction_not_found",
+            Self::TransactionAlreadyReversed(..) => "transaction_already_reversed",
+            Self::ProvisionalCreditNotFound(..) => "provisional_credit_not_found",
+            Self::DisputeAlreadyApplied(..) => "dispute_already_applied",
+            Self::StandingOrderAlreadyApplied(..) => "standing_order_already_applied",
+            Self::FeesAlreadyAssessed(..) => "fees_already_assessed",
+            Self::NotAccountHolder(..) => "not_account_holder",
+            Self::HolderNotPermitted(..) => "holder_not_permitted",
+            Self::HolderChangeNotFound(..) => "holder_change_not_found",
+            Self::CustomerNotFound(_) => "customer_not_found",
+            Self::AccountDormant(_) => "account_dormant",
+            Self::ChecksNotAllowed(..) => "checks_not_allowed",
+            Self::WithdrawalLimitExceeded(..) => "withdrawal_limit_exceeded",
+            Self::MonthlyWithdrawalCapReached(..) => "monthly_withdrawal_cap_reached",
+            Self::MinimumBalanceRequired(..) => "minimum_balance_required",
+            Self::CustomerNotVerified(..) => "customer_not_verified",
+            Self::CommandAlreadyApplied(..) => "command_already_applied",
+            Self::CommandClaimExpired(..) => "command_claim_expired",
+        }
+    }
+}
+
+/// Code of an error raised while executing a command on a bank account.
+pub const fn account_error_code(error: &AggregateError<BankAccountError>) -> &'static str {
+    match error {
+        AggregateError::UserError(error) => error.error_code(),
+        AggregateError::AggregateConflict => "aggregate_conflict",
+        AggregateError::DatabaseConnectionError(_) => "database_unavailable",
+        AggregateError::DeserializationError(_) | AggregateError::UnexpectedError(_) => {
+            "internal_error"
+        },
+    }
 }
@@ -1,16 +1,25 @@
-use crate::model::{AccountId, AtmId, CheckNumber, EmailAddress, MailingAddress};
+use crate::model::{
+    AccountId, AtmId, Beneficiary, CheckNumber, CommandId, CustomerId, DisputeId, EmailAddress,
+    FlagId, HoldId, HolderChangeId, MailingAddress, StandingOrderId,
+};
+use chrono::{DateTime, Utc};
 use cqrs_es::DomainEvent;
 use money2::Money;
 use serde::{Deserialize, Serialize};
 use strum::Display;
+use strum_macros::EnumString;
+use utoipa::ToSchema;
 
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub enum BankAccountCommand {
+    /// Opens the account for the verified customer, who owns it under their registered name.
     OpenAccount {
         account_id: AccountId,
-        user_name: String,
+        customer_id: CustomerId,
+        account_type: AccountType,
         mailing_address: MailingAddress,
         email: EmailAddress,
+        joint_holders: Vec<AccountHolder>,
     },
     DepositAmount {
         amount: Money,
@@ -29,26 +38,147 @@ pub enum BankAccountCommand {
     ChangeEmail {
         new_email: EmailAddress,
     },
+    ReleaseFlaggedTransaction {
+        flag_id: FlagId,
+    },
+    RejectFlaggedTransaction {
+        flag_id: FlagId,
+        reason: String,
+    },
+    FreezeAccount {
+        reason: String,
+        authority: String,
+    },
+    UnfreezeAccount {
+        authority: String,
+    },
+    PlaceHold {
+        hold_id: HoldId,
+        amount: Money,
+        expires_at: DateTime<Utc>,
+    },
+    CaptureHold {
+        hold_id: HoldId,
+    },
+    ReleaseHold {
+        hold_id: HoldId,
+    },
+    ExpireHold {
+        hold_id: HoldId,
+    },
+    ReverseTransaction {
+        sequence: usize,
+        reason: String,
+    },
+    IssueProvisionalCredit {
+        dispute_id: DisputeId,
+        sequence: usize,
+    },
+    FinalizeProvisionalCredit {
+        dispute_id: DisputeId,
+    },
+    ReverseProvisionalCredit {
+        dispute_id: DisputeId,
+    },
+    DebitStandingOrder {
+        order_id: StandingOrderId,
+        due_at: DateTime<Utc>,
+        amount: Money,
+        beneficiary: Beneficiary,
+    },
+    CreditStandingOrder {
+        order_id: StandingOrderId,
+        due_at: DateTime<Utc>,
+        amount: Money,
+        from_account: AccountId,
+    },
+    /// Takes back the debit paying the standing order's occurrence due at `due_at`, after its
+    /// transfer could not be credited to the beneficiary.
+    ReverseStandingOrderDebit {
+        order_id: StandingOrderId,
+        due_at: DateTime<Utc>,
+        reason: String,
+    },
+    AddAccountHolder {
+        holder: AccountHolder,
+    },
+    RemoveAccountHolder {
+        customer_id: CustomerId,
+    },
+    ApproveHolderChange {
+        change_id: HolderChangeId,
+    },
+    /// Marks the account dormant after no customer-initiated activity since `last_activity_at`.
+    MarkDormant {
+        last_activity_at: DateTime<Utc>,
+    },
+    ReactivateAccount,
+    ChangeAccountType {
+        account_type: AccountType,
+    },
+    /// Assesses the periodic fees for the billing period ending at `period_ending`.
+    AssessFees {
+        period_ending: DateTime<Utc>,
+    },
+    /// Replaces the fees waived for the account.
+    SetFeeWaivers {
+        waivers: Vec<FeeKind>,
+    },
+    /// Executes the command on behalf of one of the account's holders, subject to the permissions
+    /// of the holder's role. Commands not wrapped are executed with the bank's authority.
+    AsHolder {
+        customer_id: CustomerId,
+        command: Box<Self>,
+    },
+    /// Executes the command accepted into the command queue under `command_id`, unless it is
+    /// already applied to the account or the executing worker's claim on it, lasting until
+    /// `claimed_until`, is no longer current.
+    Queued {
+        command_id: CommandId,
+        claimed_until: DateTime<Utc>,
+        command: Box<Self>,
+    },
 }
 
-#[derive(Debug, Display, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[derive(Debug, Display, Clone, PartialEq, Eq, ToSchema, Serialize, Deserialize)]
 #[strum(serialize_all = "snake_case")]
 pub enum BankAccountEvent {
+    /// The account is opened in its owning customer's registered `user_name`, held by the customer
+    /// as owner along with any joint holders.
     AccountOpened {
         account_id: AccountId,
+        /// Verified customer who owns the account; absent for accounts opened before KYC.
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        customer_id: Option<CustomerId>,
+        /// Absent for accounts opened before account types, which are checking accounts.
+        #[serde(default)]
+        account_type: AccountType,
         user_name: String,
         mailing_address: MailingAddress,
         email: EmailAddress,
+        #[serde(default, skip_serializing_if = "Vec::is_empty")]
+        joint_holders: Vec<AccountHolder>,
     },
     BalanceDeposited {
+        #[schema(value_type = ApiMoney)]
         amount: Money,
     },
     CashWithdrawal {
+        #[schema(value_type = ApiMoney)]
         amount: Money,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        atm_id: Option<AtmId>,
+        /// Counts the withdrawal against the cap for its month; absent for withdrawals made
+        /// before the cap was tracked by the account.
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        withdrawn_at: Option<DateTime<Utc>>,
     },
     CheckWithdrawal {
         check_nr: CheckNumber,
+        #[schema(value_type = ApiMoney)]
         amount: Money,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        withdrawn_at: Option<DateTime<Utc>>,
     },
     MailingAddressUpdated {
         new_address: MailingAddress,
@@ -56,6 +186,338 @@ pub enum BankAccountEvent {
     EmailUpdated {
         new_email: EmailAddress,
     },
+    TransactionFlagged {
+        flag_id: FlagId,
+        transaction: FlaggedTransaction,
+        reasons: Vec<String>,
+    },
+    FlaggedTransactionReleased {
+        flag_id: FlagId,
+    },
+    FlaggedTransactionRejected {
+        flag_id: FlagId,
+        reason: String,
+    },
+    AccountFrozen {
+        reason: String,
+        authority: String,
+    },
+    AccountUnfrozen {
+        authority: String,
+    },
+    HoldPlaced {
+        hold_id: HoldId,
+        #[schema(value_type = ApiMoney)]
+        amount: Money,
+        expires_at: DateTime<Utc>,
+    },
+    HoldCaptured {
+        hold_id: HoldId,
+        #[schema(value_type = ApiMoney)]
+        amount: Money,
+    },
+    HoldReleased {
+        hold_id: HoldId,
+    },
+    HoldExpired {
+        hold_id: HoldId,
+    },
+    /// Compensates the transaction posted by the event with the `sequence` number by adjusting the
+    /// balance by `amount`.
+    TransactionReversed {
+        sequence: usize,
+        #[schema(value_type = ApiMoney)]
+        amount: Money,
+        reason: String,
+    },
+    /// Credits back the disputed transaction posted by the event with the `sequence` number while
+    /// the dispute is investigated.
+    ProvisionalCreditIssued {
+        dispute_id: DisputeId,
+        sequence: usize,
+        #[schema(value_type = ApiMoney)]
+        amount: Money,
+    },
+    /// The dispute was resolved for the customer, so the provisional credit stands.
+    ProvisionalCreditFinalized {
+        dispute_id: DisputeId,
+    },
+    /// The dispute was resolved for the bank, so the provisional credit posted by the event with
+    /// the `credit_sequence` number is taken back.
+    ProvisionalCreditReversed {
+        dispute_id: DisputeId,
+        sequence: usize,
+        credit_sequence: usize,
+        #[schema(value_type = ApiMoney)]
+        amount: Money,
+    },
+    /// Payment of the standing order's occurrence due at `due_at`.
+    StandingOrderDebited {
+        order_id: StandingOrderId,
+        due_at: DateTime<Utc>,
+        #[schema(value_type = ApiMoney)]
+        amount: Money,
+        beneficiary: Beneficiary,
+    },
+    /// Receipt of a standing order transfer from another account.
+    StandingOrderCredited {
+        order_id: StandingOrderId,
+        due_at: DateTime<Utc>,
+        #[schema(value_type = ApiMoney)]
+        amount: Money,
+        from_account: AccountId,
+    },
+    /// A holder other than the owner requested the change, which awaits the owner's approval.
+    HolderChangeRequested {
+        change_id: HolderChangeId,
+        change: HolderChange,
+        requested_by: CustomerId,
+    },
+    HolderChangeApproved {
+        change_id: HolderChangeId,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        approved_by: Option<CustomerId>,
+    },
+    /// The holder is added, on the authority of the `authorized_by` holder or else the bank's.
+    AccountHolderAdded {
+        holder: AccountHolder,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        authorized_by: Option<CustomerId>,
+    },
+    AccountHolderRemoved {
+        customer_id: CustomerId,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        authorized_by: Option<CustomerId>,
+    },
+    /// No customer-initiated activity since `last_activity_at`; withdrawals are blocked until the
+    /// account is reactivated.
+    AccountMarkedDormant {
+        last_activity_at: DateTime<Utc>,
+    },
+    /// The dormant account is reactivated by the `reactivated_by` holder or else by the bank.
+    AccountReactivated {
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        reactivated_by: Option<CustomerId>,
+    },
+    AccountTypeChanged {
+        from: AccountType,
+        to: AccountType,
+    },
+    FeeCharged {
+        kind: FeeKind,
+        #[schema(value_type = ApiMoney)]
+        amount: Money,
+    },
+    /// Periodic fees are assessed for the billing period ending at `period_ending`.
+    FeesAssessed {
+        period_ending: DateTime<Utc>,
+    },
+    FeeWaiversSet {
+        waivers: Vec<FeeKind>,
+    },
+    /// The events committed with it were produced by the queued command, which is therefore not
+    /// applied again.
+    QueuedCommandApplied {
+        command_id: CommandId,
+    },
+}
+
+impl BankAccountEvent {
+    /// Whether the event records activity initiated by the customer, as opposed to bank
+    /// operations or scheduled payments. Customer activity keeps the account from going dormant.
+    pub const fn is_customer_activity(&self) -> bool {
+        matches!(
+            self,
+            Self::AccountOpened { .. }
+                | Self::BalanceDeposited { .. }
+                | Self::CashWithdrawal { .. }
+                | Self::CheckWithdrawal { .. }
+                | Self::MailingAddressUpdated { .. }
+                | Self::EmailUpdated { .. }
+                | Self::HoldPlaced { .. }
+                | Self::HoldCaptured { .. }
+                | Self::AccountReactivated { .. }
+                | Self::AccountTypeChanged { .. }
+        )
+    }
+}
+
+/// The kind of account, which determines the policy of rules governing its transactions.
+#[derive(
+    Debug,
+    Display,
+    Default,
+    Copy,
+    Clone,
+    PartialEq,
+    Eq,
+    Hash,
+    EnumString,
+    ToSchema,
+    Serialize,
+    Deserialize,
+)]
+#[strum(serialize_all = "snake_case")]
+#[serde(rename_all = "snake_case")]
+pub enum AccountType {
+    #[default]
+    Checking,
+    Savings,
+    Business,
+}
+
+/// The kind of fee charged to an account under the fee schedule.
+#[derive(
+    Debug, Display, Copy, Clone, PartialEq, Eq, Hash, EnumString, ToSchema, Serialize, Deserialize,
+)]
+#[strum(serialize_all = "snake_case")]
+#[serde(rename_all = "snake_case")]
+pub enum FeeKind {
+    MonthlyMaintenance,
+    BelowMinimumBalance,
+    Check,
+    ForeignAtm,
+}
+
+/// A customer holding the account in a role.
+#[derive(Debug, Clone, PartialEq, Eq, ToSchema, Serialize, Deserialize)]
+pub struct AccountHolder {
+    pub customer_id: CustomerId,
+    pub role: HolderRole,
+}
+
+impl AccountHolder {
+    pub const fn new(customer_id: CustomerId, role: HolderRole) -> Self {
+        Self { customer_id, role }
+    }
+}
+
+/// The part a holder plays on the account, which determines the commands they may execute.
+#[derive(
+    Debug, Display, Copy, Clone, PartialEq, Eq, Hash, EnumString, ToSchema, Serialize, Deserialize,
+)]
+#[strum(serialize_all = "snake_case")]
+#[serde(rename_all = "snake_case")]
+pub enum HolderRole {
+    /// Opened the account; may transact, manage the account and approve holder changes.
+    Owner,
+    /// May transact and manage the account; holder changes require the owner's approval.
+    JointOwner,
+    /// May transact on the account.
+    AuthorizedSigner,
+    /// May only view the account.
+    Viewer,
+}
+
+impl HolderRole {
+    pub const ALL: [Self; 4] = [
+        Self::Owner,
+        Self::JointOwner,
+        Self::AuthorizedSigner,
+        Self::Viewer,
+    ];
+
+    /// Roles that may manage the account, such as its contact details and notifications.
+    pub const MANAGERS: [Self; 2] = [Self::Owner, Self::JointOwner];
+
+    /// Roles that may pay out of the account, such as by standing orders.
+    pub const SIGNERS: [Self; 3] = [Self::Owner, Self::JointOwner, Self::AuthorizedSigner];
+
+    /// Whether a holder in this role may execute the command on the account.
+    pub const fn permits(&self, command: &BankAccountCommand) -> bool {
+        match command {
+            BankAccountCommand::DepositAmount { .. }
+            | BankAccountCommand::WithdrawCash { .. }
+            | BankAccountCommand::DisburseCheck { .. }
+            | BankAccountCommand::PlaceHold { .. }
+            | BankAccountCommand::CaptureHold { .. }
+            | BankAccountCommand::ReleaseHold { .. }
+            | BankAccountCommand::ReactivateAccount => {
+                matches!(
+                    self,
+                    Self::Owner | Self::JointOwner | Self::AuthorizedSigner
+                )
+            },
+
+            BankAccountCommand::ChangeMailingAddress { .. }
+            | BankAccountCommand::ChangeEmail { .. }
+            | BankAccountCommand::AddAccountHolder { .. }
+            | BankAccountCommand::RemoveAccountHolder { .. }
+            | BankAccountCommand::ChangeAccountType { .. } => {
+                matches!(self, Self::Owner | Self::JointOwner)
+            },
+
+            BankAccountCommand::ApproveHolderChange { .. } => matches!(self, Self::Owner),
+
+            _ => false,
+        }
+    }
+}
+
+/// A change to the account's holders.
+#[derive(Debug, Clone, PartialEq, Eq, ToSchema, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum HolderChange {
+    Add { holder: AccountHolder },
+    Remove { customer_id: CustomerId },
+}
+
+impl HolderChange {
+    /// The event applying the change on the authority of the `authorized_by` holder.
+    pub fn into_event(self, authorized_by: Option<CustomerId>) -> BankAccountEvent {
+        match self {
+            Self::Add { holder } => BankAccountEvent::AccountHolderAdded { holder, authorized_by },
+            Self::Remove { customer_id } => {
+                BankAccountEvent::AccountHolderRemoved { customer_id, authorized_by }
+            },
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, ToSchema, Serialize, Deserialize)]
+pub struct PendingHolderChange {
+    pub change_id: HolderChangeId,
+    pub change: HolderChange,
+    pub requested_by: CustomerId,
+}
+
+/// A transaction held for fraud review; it is applied only if released by a reviewer.
+#[derive(Debug, Clone, PartialEq, Eq, ToSchema, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FlaggedTransaction {
+    CashWithdrawal {
+        #[schema(value_type = ApiMoney)]
+        amount: Money,
+        atm_id: AtmId,
+    },
+    CheckWithdrawal {
+        check_nr: CheckNumber,
+        #[schema(value_type = ApiMoney)]
+        amount: Money,
+    },
+    MailingAddressChange {
+        new_address: MailingAddress,
+    },
+    EmailChange {
+        new_email: EmailAddress,
+    },
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, ToSchema, Serialize, Deserialize)]
+pub struct FlaggedTransactionEntry {
+    pub flag_id: FlagId,
+    pub transaction: FlaggedTransaction,
+    pub reasons: Vec<String>,
+}
+
+/// Funds reserved for a pending authorization. They count against the available balance until
+/// the hold is captured, released or expires.
+#[derive(Debug, Clone, PartialEq, Eq, ToSchema, Serialize, Deserialize)]
+pub struct FundsHold {
+    pub hold_id: HoldId,
+    #[schema(value_type = ApiMoney)]
+    pub amount: Money,
+    pub expires_at: DateTime<Utc>,
 }
 
 const VERSION: &str = "1.0";
@@ -0,0 +1,125 @@
+use crate::settings::CommandRetrySettings;
+use cqrs_es::{Aggregate, AggregateError};
+use once_cell::sync::Lazy;
+use postgres_es::PostgresCqrs;
+use prometheus::IntCounterVec;
+use rand::Rng;
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+
+pub static COMMAND_CONFLICT_RETRIES: Lazy<IntCounterVec> = Lazy::new(|| {
+    prometheus::register_int_counter_vec!(
+        "command_conflict_retries_total",
+        "Commands retried after a conflict with a concurrent command on the same aggregate",
+        &["aggregate_type"]
+    )
+    .expect("failed to register command_conflict_retries_total metric")
+});
+
+pub static COMMAND_CONFLICTS_EXHAUSTED: Lazy<IntCounterVec> = Lazy::new(|| {
+    prometheus::register_int_counter_vec!(
+        "command_conflicts_exhausted_total",
+        "Commands returned to the caller still conflicting once their retries are spent",
+        &["aggregate_type"]
+    )
+    .expect("failed to register command_conflicts_exhausted_total metric")
+});
+
+/// Executes commands on an aggregate, retrying a command rejected on an
+/// [AggregateConflict](AggregateError::AggregateConflict) with jittered backoff until the retry
+/// budget is spent. A conflict only means another command on the same aggregate instance committed
+/// first, so the retry is handled against the state that includes it.
+pub struct CommandExecutor<A: Aggregate> {
+    cqrs: Arc<PostgresCqrs<A>>,
+    retry: CommandRetrySettings,
+    jitter: Jitter,
+}
+
+/// Draws the delay before a retry from the backoff delay.
+pub type Jitter = fn(Duration) -> Duration;
+
+impl<A: Aggregate> CommandExecutor<A> {
+    pub fn new(cqrs: PostgresCqrs<A>, retry: CommandRetrySettings) -> Self {
+        Self::with_jitter(cqrs, retry, jittered)
+    }
+
+    pub fn with_jitter(cqrs: PostgresCqrs<A>, retry: CommandRetrySettings, jitter: Jitter) -> Self {
+        Self { cqrs: Arc::new(cqrs), retry, jitter }
+    }
+
+    #[tracing::instrument(level = "trace", skip(self, command, metadata))]
+    pub async fn execute_with_metadata(
+        &self, aggregate_id: &str, command: A::Command, metadata: HashMap<String, String>,
+    ) -> Result<(), AggregateError<A::Error>>
+    where
+        A::Command: Clone,
+    {
+        let mut retries = 0;
+        loop {
+            let result = self
+                .cqrs
+                .execute_with_metadata(aggregate_id, command.clone(), metadata.clone())
+                .await;
+
+            match result {
+                Err(AggregateError::AggregateConflict) if retries < self.retry.max_retries => {
+                    retries += 1;
+                    let delay = (self.jitter)(self.retry.backoff.delay_for(retries));
+                    COMMAND_CONFLICT_RETRIES
+                        .with_label_values(&[A::aggregate_type().as_str()])
+                        .inc();
+                    tracing::info!(
+                        %aggregate_id, %retries, ?delay,
+                        "command conflicted with a concurrent command -- retrying"
+                    );
+                    tokio::time::sleep(delay).await;
+                },
+
+                Err(AggregateError::AggregateConflict) => {
+                    COMMAND_CONFLICTS_EXHAUSTED
+                        .with_label_values(&[A::aggregate_type().as_str()])
+                        .inc();
+                    tracing::warn!(
+                        %aggregate_id, %retries,
+                        "command still conflicts after its retries -- returning conflict"
+                    );
+                    return Err(AggregateError::AggregateConflict);
+                },
+
+                result => return result,
+            }
+        }
+    }
+}
+
+impl<A: Aggregate> Clone for CommandExecutor<A> {
+    fn clone(&self) -> Self {
+        Self {
+            cqrs: self.cqrs.clone(),
+            retry: self.retry,
+            jitter: self.jitter,
+        }
+    }
+}
+
+/// Draws the delay at random from its upper half, so retries of commands that conflicted together
+/// are spread apart.
+pub fn jittered(delay: Duration) -> Duration {
+    rand::thread_rng().gen_range(delay / 2..=delay)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_jittered_delay_is_within_upper_half() {
+        let delay = Duration::from_millis(100);
+        for _ in 0..1_000 {
+            let jittered = jittered(delay);
+            assert!(delay / 2 <= jittered && jittered <= delay, "{jittered:?}");
+        }
+        assert_eq!(jittered(Duration::ZERO), Duration::ZERO);
+    }
+}
@@ -0,0 +1,157 @@
+use crate::model::CustomerId;
+use crate::services::{CustomerServices, IdentityVerification, IdentityVerifier};
+use async_trait::async_trait;
+use cqrs_es::Aggregate;
+use postgres_es::PostgresCqrs;
+use pretty_snowflake::{Id, Label};
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+
+mod errors;
+mod protocol;
+
+pub use errors::CustomerError;
+pub use protocol::{CustomerCommand, CustomerEvent, DocumentKind, DocumentReference, KycStatus};
+
+pub type CustomerAggregate = Arc<PostgresCqrs<Customer>>;
+
+pub const AGGREGATE_TYPE: &str = "customer";
+
+#[inline]
+pub fn generate_id() -> Id<Customer> {
+    pretty_snowflake::generator::next_id()
+}
+
+/// A customer of the bank. The customer's identity is verified from submitted documents before
+/// accounts may be opened for them.
+#[derive(Debug, Default, Clone, Label, PartialEq, Serialize, Deserialize)]
+pub struct Customer {
+    profile: Option<CustomerProfile>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+struct CustomerProfile {
+    customer_id: CustomerId,
+    name: String,
+    status: KycStatus,
+    documents: Vec<DocumentReference>,
+}
+
+#[async_trait]
+impl Aggregate for Customer {
+    type Command = CustomerCommand;
+    type Event = CustomerEvent;
+    type Error = CustomerError;
+    type Services = CustomerServices;
+
+    fn aggregate_type() -> String {
+        AGGREGATE_TYPE.to_string()
+    }
+
+    #[tracing::instrument(level = "trace", skip(services))]
+    async fn handle(
+        &self, command: Self::Command, services: &Self::Services,
+    ) -> Result<Vec<Self::Event>, Self::Error> {
+        match (&self.profile, command) {
+            (
+                None,
+                CustomerCommand::RegisterCustomer {
+                    customer_id,
+                    subject,
+                    name,
+                    email,
+                    mailing_address,
+                },
+            ) => Ok(vec![CustomerEvent::CustomerRegistered {
+                customer_id,
+                subject,
+                name,
+                email,
+                mailing_address,
+            }]),
+
+            (Some(profile), CustomerCommand::RegisterCustomer { .. }) => {
+                Err(CustomerError::RejectedCommand(format!(
+                    "Customer {} is already registered.",
+                    profile.customer_id
+                )))
+            },
+
+            (None, cmd) => Err(CustomerError::RejectedCommand(format!(
+                "No customer is registered to accept command: {cmd:?}"
+            ))),
+
+            (Some(profile), cmd) => profile.handle(cmd, services).await,
+        }
+    }
+
+    fn apply(&mut self, event: Self::Event) {
+        match event {
+            CustomerEvent::CustomerRegistered { customer_id, name, .. } => {
+                self.profile = Some(CustomerProfile {
+                    customer_id,
+                    name,
+                    status: KycStatus::Pending,
+                    documents: Vec::new(),
+                });
+            },
+
+            CustomerEvent::DocumentSubmitted { document } => match self.profile.as_mut() {
+                Some(profile) => {
+                    profile.documents.push(document);
+                    profile.status = KycStatus::Pending;
+                },
+                None => tracing::warn!(?document, "no customer registered for document -- ignored"),
+            },
+
+            CustomerEvent::IdentityVerified { .. } => self.update_status(KycStatus::Verified),
+
+            CustomerEvent::IdentityRejected { .. } => self.update_status(KycStatus::Rejected),
+        }
+    }
+}
+
+impl Customer {
+    fn update_status(&mut self, status: KycStatus) {
+        match self.profile.as_mut() {
+            Some(profile) => profile.status = status,
+            None => tracing::warn!(%status, "no customer registered to update -- ignored"),
+        }
+    }
+}
+
+impl CustomerProfile {
+    #[tracing::instrument(level = "trace", skip(services))]
+    async fn handle(
+        &self, command: CustomerCommand, services: &CustomerServices,
+    ) -> Result<Vec<CustomerEvent>, CustomerError> {
+        match (self.status, command) {
+            (
+                KycStatus::Pending | KycStatus::Rejected,
+                CustomerCommand::SubmitDocument { document },
+            ) => Ok(vec![CustomerEvent::DocumentSubmitted { document }]),
+
+            (KycStatus::Pending, CustomerCommand::VerifyIdentity) => {
+                if self.documents.is_empty() {
+                    return Err(CustomerError::NoDocuments(self.customer_id));
+                }
+
+                let verifier = services.name().to_string();
+                let verification =
+                    services.verify(self.customer_id, &self.name, &self.documents).await?;
+                let event = match verification {
+                    IdentityVerification::Verified => CustomerEvent::IdentityVerified { verifier },
+                    IdentityVerification::Rejected { reason } => {
+                        CustomerEvent::IdentityRejected { verifier, reason }
+                    },
+                };
+                Ok(vec![event])
+            },
+
+            (status, cmd) => Err(CustomerError::RejectedCommand(format!(
+                "Customer {} is {status} and will not accept command: {cmd:?}",
+                self.customer_id
+            ))),
+        }
+    }
+}
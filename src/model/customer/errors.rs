@@ -0,0 +1,34 @@
+use crate::model::CustomerId;
+use crate::services::IdentityVerifierError;
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+pub enum CustomerError {
+    #[error("customer {0} has not submitted any identity documents")]
+    NoDocuments(CustomerId),
+
+    #[error("a customer is already registered for subject {0}")]
+    SubjectAlreadyRegistered(String),
+
+    #[error("no customer is registered for subject {0}")]
+    SubjectNotRegistered(String),
+
+    #[error("Rejected command: {0}")]
+    RejectedCommand(String),
+
+    #[error("{0}")]
+    IdentityVerifierError(#[from] IdentityVerifierError),
+}
+
+impl CustomerError {
+    /// Stable machine-readable code identifying the error to API clients.
+    pub const fn error_code(&self) -> &'static str {
+        match self {
+            Self::NoDocuments(_) => "no_identity_documents",
+            Self::SubjectAlreadyRegistered(_) => "customer_already_registered",
+            Self::SubjectNotRegistered(_) => "customer_not_registered",
+            Self::RejectedCommand(_) => "rejected_command",
+            Self::IdentityVerifierError(_) => "identity_verifier_unavailable",
+        }
+    }
+}
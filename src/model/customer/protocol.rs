@@ -0,0 +1,100 @@
+use crate::model::{CustomerId, EmailAddress, MailingAddress};
+use cqrs_es::DomainEvent;
+use serde::{Deserialize, Serialize};
+use strum::Display;
+use utoipa::ToSchema;
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum CustomerCommand {
+    RegisterCustomer {
+        customer_id: CustomerId,
+        subject: Option<String>,
+        name: String,
+        email: EmailAddress,
+        mailing_address: MailingAddress,
+    },
+    SubmitDocument {
+        document: DocumentReference,
+    },
+    VerifyIdentity,
+}
+
+#[derive(Debug, Display, Clone, PartialEq, Eq, ToSchema, Serialize, Deserialize)]
+#[strum(serialize_all = "snake_case")]
+pub enum CustomerEvent {
+    /// The customer is registered under `name`. The customer signs in as the authenticated
+    /// `subject`, if any, which is absent for customers registered before it was recorded.
+    CustomerRegistered {
+        customer_id: CustomerId,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        subject: Option<String>,
+        name: String,
+        email: EmailAddress,
+        mailing_address: MailingAddress,
+    },
+    /// An identity document is submitted for verification. Submitting a document after the
+    /// customer is rejected returns them to pending.
+    DocumentSubmitted { document: DocumentReference },
+    /// The `verifier` confirmed the customer's identity from the submitted documents.
+    IdentityVerified { verifier: String },
+    /// The `verifier` could not confirm the customer's identity from the submitted documents.
+    IdentityRejected { verifier: String, reason: String },
+}
+
+/// Know-your-customer standing; only verified customers may open accounts.
+#[derive(Debug, Display, Default, Copy, Clone, PartialEq, Eq, ToSchema, Serialize, Deserialize)]
+#[strum(serialize_all = "snake_case")]
+#[serde(rename_all = "snake_case")]
+pub enum KycStatus {
+    #[default]
+    Pending,
+    Verified,
+    Rejected,
+}
+
+#[derive(Debug, Display, Copy, Clone, PartialEq, Eq, ToSchema, Serialize, Deserialize)]
+#[strum(serialize_all = "snake_case")]
+#[serde(rename_all = "snake_case")]
+pub enum DocumentKind {
+    Passport,
+    DriversLicense,
+    NationalId,
+    UtilityBill,
+}
+
+impl DocumentKind {
+    /// Whether the document is government-issued photo identification.
+    pub const fn is_photo_id(&self) -> bool {
+        matches!(
+            self,
+            Self::Passport | Self::DriversLicense | Self::NationalId
+        )
+    }
+}
+
+/// Reference to an identity document held in the document store; the bank does not keep the
+/// document itself.
+#[derive(Debug, Clone, PartialEq, Eq, ToSchema, Serialize, Deserialize)]
+#[schema(example = json!({ "kind": "passport", "reference": "docs/7021806137352503296/passport.pdf" }))]
+pub struct DocumentReference {
+    pub kind: DocumentKind,
+    pub reference: String,
+}
+
+impl DocumentReference {
+    pub fn new(kind: DocumentKind, reference: impl Into<String>) -> Self {
+        Self { kind, reference: reference.into() }
+    }
+}
+
+const VERSION: &str = "1.0";
+
+impl DomainEvent for CustomerEvent {
+    fn event_type(&self) -> String {
+        self.to_string()
+    }
+
+    fn event_version(&self) -> String {
+        VERSION.to_string()
+    }
+}
@@ -0,0 +1,193 @@
+use crate::model::{AccountId, BankAccountCommand, BankAccountError, DisputeId};
+use crate::services::{AccountLedgerApi, DisputeServiceError, DisputeServices};
+use async_trait::async_trait;
+use cqrs_es::{Aggregate, AggregateError};
+use postgres_es::PostgresCqrs;
+use pretty_snowflake::{Id, Label};
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+
+mod errors;
+mod protocol;
+
+pub use errors::DisputeError;
+pub use protocol::{DisputeCommand, DisputeEvent, DisputeStatus, DisputedTransaction};
+
+pub type DisputeAggregate = Arc<PostgresCqrs<Dispute>>;
+
+pub const AGGREGATE_TYPE: &str = "dispute";
+
+#[inline]
+pub fn generate_id() -> Id<Dispute> {
+    pretty_snowflake::generator::next_id()
+}
+
+/// A customer's dispute of a cash or check withdrawal. The dispute drives provisional credit and
+/// reversal of the withdrawal on the bank account as the case is worked.
+#[derive(Debug, Default, Clone, Label, PartialEq, Serialize, Deserialize)]
+pub struct Dispute {
+    case: Option<DisputeCase>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+struct DisputeCase {
+    dispute_id: DisputeId,
+    account_id: AccountId,
+    sequence: usize,
+    transaction: DisputedTransaction,
+    status: DisputeStatus,
+}
+
+#[async_trait]
+impl Aggregate for Dispute {
+    type Command = DisputeCommand;
+    type Event = DisputeEvent;
+    type Error = DisputeError;
+    type Services = DisputeServices;
+
+    fn aggregate_type() -> String {
+        AGGREGATE_TYPE.to_string()
+    }
+
+    #[tracing::instrument(level = "trace", skip(services))]
+    async fn handle(
+        &self, command: Self::Command, services: &Self::Services,
+    ) -> Result<Vec<Self::Event>, Self::Error> {
+        match (&self.case, command) {
+            (None, DisputeCommand::OpenDispute { dispute_id, account_id, sequence, reason }) => {
+                let transaction = services
+                    .find_transaction(account_id, sequence)
+                    .await?
+                    .and_then(DisputedTransaction::from_event)
+                    .ok_or(DisputeError::TransactionNotDisputable(account_id, sequence))?;
+
+                Ok(vec![DisputeEvent::DisputeOpened {
+                    dispute_id,
+                    account_id,
+                    sequence,
+                    transaction,
+                    reason,
+                }])
+            },
+
+            (Some(case), DisputeCommand::OpenDispute { .. }) => Err(DisputeError::RejectedCommand(
+                format!("Dispute {} is already opened.", case.dispute_id),
+            )),
+
+            (None, cmd) => Err(DisputeError::RejectedCommand(format!(
+                "No dispute is opened to accept command: {cmd:?}"
+            ))),
+
+            (Some(case), cmd) => case.handle(cmd, services).await,
+        }
+    }
+
+    fn apply(&mut self, event: Self::Event) {
+        match event {
+            DisputeEvent::DisputeOpened {
+                dispute_id, account_id, sequence, transaction, ..
+            } => {
+                self.case = Some(DisputeCase {
+                    dispute_id,
+                    account_id,
+                    sequence,
+                    transaction,
+                    status: DisputeStatus::Opened,
+                });
+            },
+
+            DisputeEvent::ProvisionalCreditIssued { .. } => {
+                self.update_status(DisputeStatus::ProvisionalCreditIssued)
+            },
+
+            DisputeEvent::ResolvedForCustomer { .. } => {
+                self.update_status(DisputeStatus::ResolvedForCustomer)
+            },
+
+            DisputeEvent::ResolvedForBank { .. } => {
+                self.update_status(DisputeStatus::ResolvedForBank)
+            },
+        }
+    }
+}
+
+impl Dispute {
+    fn update_status(&mut self, status: DisputeStatus) {
+        match self.case.as_mut() {
+            Some(case) => case.status = status,
+            None => tracing::warn!(%status, "no dispute opened to update -- ignored"),
+        }
+    }
+}
+
+impl DisputeCase {
+    #[tracing::instrument(level = "trace", skip(services))]
+    async fn handle(
+        &self, command: DisputeCommand, services: &DisputeServices,
+    ) -> Result<Vec<DisputeEvent>, DisputeError> {
+        let dispute_id = self.dispute_id;
+        let sequence = self.sequence;
+
+        match (self.status, command) {
+            (DisputeStatus::Opened, DisputeCommand::IssueProvisionalCredit) => {
+                let command = BankAccountCommand::IssueProvisionalCredit { dispute_id, sequence };
+                skip_applied(services.execute(self.account_id, command).await)?;
+                Ok(vec![DisputeEvent::ProvisionalCreditIssued {
+                    amount: self.transaction.amount(),
+                }])
+            },
+
+            (DisputeStatus::Opened, DisputeCommand::ResolveForCustomer { note }) => {
+                let reason = format!("dispute {dispute_id} resolved for customer");
+                let command = BankAccountCommand::ReverseTransaction { sequence, reason };
+                // a reversal already posted, e.g., by an earlier attempt whose dispute event
+                // failed to commit, stands for the customer
+                match services.execute(self.account_id, command).await {
+                    Err(DisputeServiceError::Account(AggregateError::UserError(
+                        BankAccountError::TransactionAlreadyReversed(..),
+                    ))) => tracing::info!(%dispute_id, "disputed transaction is already reversed"),
+                    result => result?,
+                }
+                Ok(vec![DisputeEvent::ResolvedForCustomer { note }])
+            },
+
+            (
+                DisputeStatus::ProvisionalCreditIssued,
+                DisputeCommand::ResolveForCustomer { note },
+            ) => {
+                let command = BankAccountCommand::FinalizeProvisionalCredit { dispute_id };
+                skip_applied(services.execute(self.account_id, command).await)?;
+                Ok(vec![DisputeEvent::ResolvedForCustomer { note }])
+            },
+
+            (DisputeStatus::Opened, DisputeCommand::ResolveForBank { note }) => {
+                Ok(vec![DisputeEvent::ResolvedForBank { note }])
+            },
+
+            (DisputeStatus::ProvisionalCreditIssued, DisputeCommand::ResolveForBank { note }) => {
+                let command = BankAccountCommand::ReverseProvisionalCredit { dispute_id };
+                skip_applied(services.execute(self.account_id, command).await)?;
+                Ok(vec![DisputeEvent::ResolvedForBank { note }])
+            },
+
+            (status, cmd) => Err(DisputeError::RejectedCommand(format!(
+                "Dispute {dispute_id} is {status} and will not accept command: {cmd:?}"
+            ))),
+        }
+    }
+}
+
+/// The account commands are committed before the dispute's event, so a dispute command retried
+/// after its event failed to commit finds its account command already applied, and goes on to
+/// record the event.
+fn skip_applied(result: Result<(), DisputeServiceError>) -> Result<(), DisputeServiceError> {
+    match result {
+        Err(DisputeServiceError::Account(AggregateError::UserError(
+            BankAccountError::DisputeAlreadyApplied(account_id, dispute_id),
+        ))) => {
+            tracing::info!(%account_id, %dispute_id, "dispute is already applied to the account");
+            Ok(())
+        },
+        result => result,
+    }
+}
@@ -0,0 +1,29 @@
+use crate::model::{account_error_code, AccountId};
+use crate::services::DisputeServiceError;
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+pub enum DisputeError {
+    #[error("event {1} on account {0} is not a disputable withdrawal")]
+    TransactionNotDisputable(AccountId, usize),
+
+    #[error("Rejected command: {0}")]
+    RejectedCommand(String),
+
+    #[error("{0}")]
+    DisputeServiceError(#[from] DisputeServiceError),
+}
+
+impl DisputeError {
+    /// Stable machine-readable code identifying the error to API clients.
+    pub const fn error_code(&self) -> &'static str {
+        match self {
+            Self::TransactionNotDisputable(..) => "transaction_not_disputable",
+            Self::RejectedCommand(_) => "rejected_command",
+            Self::DisputeServiceError(DisputeServiceError::Account(error)) => {
+                account_error_code(error)
+            },
+            Self::DisputeServiceError(_) => "dispute_service_failed",
+        }
+    }
+}
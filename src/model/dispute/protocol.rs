@@ -0,0 +1,108 @@
+use crate::model::{AccountId, AtmId, BankAccountEvent, CheckNumber, DisputeId};
+use cqrs_es::DomainEvent;
+use money2::Money;
+use serde::{Deserialize, Serialize};
+use strum::Display;
+use utoipa::ToSchema;
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum DisputeCommand {
+    OpenDispute {
+        dispute_id: DisputeId,
+        account_id: AccountId,
+        sequence: usize,
+        reason: String,
+    },
+    IssueProvisionalCredit,
+    ResolveForCustomer {
+        note: String,
+    },
+    ResolveForBank {
+        note: String,
+    },
+}
+
+#[derive(Debug, Display, Clone, PartialEq, Eq, ToSchema, Serialize, Deserialize)]
+#[strum(serialize_all = "snake_case")]
+pub enum DisputeEvent {
+    /// The customer disputes the withdrawal posted by the event with the `sequence` number on the
+    /// account.
+    DisputeOpened {
+        dispute_id: DisputeId,
+        account_id: AccountId,
+        sequence: usize,
+        transaction: DisputedTransaction,
+        reason: String,
+    },
+    ProvisionalCreditIssued {
+        #[schema(value_type = ApiMoney)]
+        amount: Money,
+    },
+    ResolvedForCustomer {
+        note: String,
+    },
+    ResolvedForBank {
+        note: String,
+    },
+}
+
+#[derive(Debug, Display, Default, Copy, Clone, PartialEq, Eq, ToSchema, Serialize, Deserialize)]
+#[strum(serialize_all = "snake_case")]
+#[serde(rename_all = "snake_case")]
+pub enum DisputeStatus {
+    #[default]
+    Opened,
+    ProvisionalCreditIssued,
+    ResolvedForCustomer,
+    ResolvedForBank,
+}
+
+/// The withdrawal under dispute, as it was posted to the account.
+#[derive(Debug, Clone, PartialEq, Eq, ToSchema, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DisputedTransaction {
+    CashWithdrawal {
+        #[schema(value_type = ApiMoney)]
+        amount: Money,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        atm_id: Option<AtmId>,
+    },
+    CheckWithdrawal {
+        check_nr: CheckNumber,
+        #[schema(value_type = ApiMoney)]
+        amount: Money,
+    },
+}
+
+impl DisputedTransaction {
+    /// The disputed transaction posted by the event, if it may be disputed.
+    pub fn from_event(event: BankAccountEvent) -> Option<Self> {
+        match event {
+            BankAccountEvent::CashWithdrawal { amount, atm_id, .. } => {
+                Some(Self::CashWithdrawal { amount, atm_id })
+            },
+            BankAccountEvent::CheckWithdrawal { check_nr, amount, .. } => {
+                Some(Self::CheckWithdrawal { check_nr, amount })
+            },
+            _ => None,
+        }
+    }
+
+    pub const fn amount(&self) -> Money {
+        match self {
+            Self::CashWithdrawal { amount, .. } | Self::CheckWithdrawal { amount, .. } => *amount,
+        }
+    }
+}
+
+const VERSION: &str = "1.0";
+
+impl DomainEvent for DisputeEvent {
+    fn event_type(&self) -> String {
+        self.to_string()
+    }
+
+    fn event_version(&self) -> String {
+        VERSION.to_string()
+    }
+}
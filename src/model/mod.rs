@@ -9,18 +9,50 @@ use utoipa::{IntoParams, ToSchema};
 use validator::{Validate, ValidationError, ValidationErrors};
 
 pub mod bank_account;
+mod command_executor;
+pub mod customer;
+pub mod dispute;
+pub mod standing_order;
 
 pub use bank_account::{
-    BankAccount, BankAccountAggregate, BankAccountCommand, BankAccountError, BankAccountEvent,
+    account_error_code, AccountHolder, AccountType, BankAccount, BankAccountAggregate,
+    BankAccountCommand, BankAccountError, BankAccountEvent, FeeKind, FlaggedTransaction,
+    FlaggedTransactionEntry, FundsHold, HolderChange, HolderRole, PendingHolderChange,
+};
+pub use command_executor::{
+    CommandExecutor, COMMAND_CONFLICTS_EXHAUSTED, COMMAND_CONFLICT_RETRIES,
+};
+pub use customer::{
+    Customer, CustomerAggregate, CustomerCommand, CustomerError, CustomerEvent, DocumentKind,
+    DocumentReference, KycStatus,
+};
+pub use dispute::{
+    Dispute, DisputeAggregate, DisputeCommand, DisputeError, DisputeEvent, DisputeStatus,
+    DisputedTransaction,
+};
+pub use standing_order::{
+    Beneficiary, Schedule, StandingOrder, StandingOrderAggregate, StandingOrderCommand,
+    StandingOrderError, StandingOrderEvent, StandingOrderStatus,
 };
 
 pub static ZERO_MONEY: Lazy<Money> = Lazy::new(|| Money::new(0, 2, Currency::Usd));
 
-static EXCHANGE_RATES: Lazy<ExchangeRates> = Lazy::new(|| {
-    let rates = std::fs::read_to_string("./resources/eurofxref.csv")
-        .expect("failed to load exchange rate file");
-    ExchangeRates::from_str(&rates).expect("failed to parse exchange rate file")
-});
+const EXCHANGE_RATES_PATH: &str = "./resources/eurofxref.csv";
+
+static EXCHANGE_RATES: Lazy<ExchangeRates> =
+    Lazy::new(|| load_exchange_rates().expect("failed to load exchange rates"));
+
+fn load_exchange_rates() -> Result<ExchangeRates, anyhow::Error> {
+    let rates = std::fs::read_to_string(EXCHANGE_RATES_PATH)
+        .map_err(|err| anyhow::anyhow!("failed to read exchange rate file: {err}"))?;
+    ExchangeRates::from_str(&rates)
+        .map_err(|err| anyhow::anyhow!("failed to parse exchange rate file: {err}"))
+}
+
+/// Verifies the exchange rates used to convert amounts between currencies can be loaded.
+pub fn verify_exchange_rates() -> Result<(), anyhow::Error> {
+    load_exchange_rates().map(|_| ())
+}
 
 pub fn convert_amount(currency: Currency, amount: Money) -> Money {
     if currency == amount.currency {
@@ -30,6 +62,11 @@ pub fn convert_amount(currency: Currency, amount: Money) -> Money {
     }
 }
 
+/// The amount with its sign flipped, e.g., to turn a credit into the matching debit.
+pub fn negate(amount: Money) -> Money {
+    Money::new(-1, 0, amount.currency) * amount
+}
+
 #[derive(
     Debug,
     Copy,
@@ -93,7 +130,7 @@ impl MailingAddress {
         Self(id.into())
     }
 
-    pub fn as_str(&self) -> &str {
+    pub const fn as_str(&self) -> &str {
         self.0.as_str()
     }
 }
@@ -116,7 +153,7 @@ impl EmailAddress {
         email_address.validate().map(|_| email_address)
     }
 
-    pub fn as_str(&self) -> &str {
+    pub const fn as_str(&self) -> &str {
         self.0.as_str()
     }
 }
@@ -159,9 +196,15 @@ impl AtmId {
         Self(id.into())
     }
 
-    pub fn as_str(&self) -> &str {
+    pub const fn as_str(&self) -> &str {
         self.0.as_str()
     }
+
+    /// The network operating the ATM, given by the id's prefix before its first underscore; e.g.,
+    /// `abc` for `abc_123`.
+    pub fn network(&self) -> &str {
+        self.0.split_once('_').map_or(self.0.as_str(), |(network, _)| network)
+    }
 }
 
 impl fmt::Display for AtmId {
@@ -206,6 +249,316 @@ impl fmt::Display for CheckNumber {
     }
 }
 
+/// Identifies a transaction held for fraud review, unique within the account.
+#[derive(
+    Debug,
+    Copy,
+    Clone,
+    PartialEq,
+    Eq,
+    Hash,
+    PartialOrd,
+    Ord,
+    ToSchema,
+    IntoParams,
+    Serialize,
+    Deserialize,
+)]
+#[schema(example = json!(3))]
+#[into_params(names("flag_id"))]
+#[serde(transparent)]
+#[repr(transparent)]
+pub struct FlagId(u32);
+
+impl FlagId {
+    pub const fn new(flag_id: u32) -> Self {
+        Self(flag_id)
+    }
+
+    pub const fn as_u32(&self) -> u32 {
+        self.0
+    }
+
+    pub const fn next(&self) -> Self {
+        Self(self.0 + 1)
+    }
+}
+
+impl fmt::Display for FlagId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+/// Identifies a pending change to an account's holders, unique within the account.
+#[derive(
+    Debug,
+    Copy,
+    Clone,
+    PartialEq,
+    Eq,
+    Hash,
+    PartialOrd,
+    Ord,
+    ToSchema,
+    IntoParams,
+    Serialize,
+    Deserialize,
+)]
+#[schema(example = json!(1))]
+#[into_params(names("change_id"))]
+#[serde(transparent)]
+#[repr(transparent)]
+pub struct HolderChangeId(u32);
+
+impl HolderChangeId {
+    pub const fn new(change_id: u32) -> Self {
+        Self(change_id)
+    }
+
+    pub const fn as_u32(&self) -> u32 {
+        self.0
+    }
+
+    pub const fn next(&self) -> Self {
+        Self(self.0 + 1)
+    }
+}
+
+impl fmt::Display for HolderChangeId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+/// Identifies a hold reserving funds for a pending authorization, e.g., a card network
+/// authorization code. Assigned by the caller and unique within the account.
+#[derive(
+    Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord, ToSchema, IntoParams, Serialize, Deserialize,
+)]
+#[schema(example = json!("auth_0042"))]
+#[into_params(names("hold_id"))]
+#[serde(transparent)]
+#[repr(transparent)]
+pub struct HoldId(String);
+
+impl HoldId {
+    pub fn new(id: impl Into<String>) -> Self {
+        Self(id.into())
+    }
+
+    pub const fn as_str(&self) -> &str {
+        self.0.as_str()
+    }
+}
+
+impl fmt::Display for HoldId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+/// Identifies a customer dispute case.
+#[derive(
+    Debug,
+    Copy,
+    Clone,
+    PartialEq,
+    Eq,
+    Hash,
+    PartialOrd,
+    Ord,
+    ToSchema,
+    IntoParams,
+    Serialize,
+    Deserialize,
+)]
+#[schema(example = json!(7019374521480093696_u64))]
+#[into_params(names("dispute_id"))]
+#[serde(transparent)]
+#[repr(transparent)]
+pub struct DisputeId(i64);
+
+impl DisputeId {
+    pub fn new(id: impl Into<i64>) -> Self {
+        Self(id.into())
+    }
+
+    pub const fn as_num(&self) -> i64 {
+        self.0
+    }
+}
+
+impl fmt::Display for DisputeId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+impl From<Id<Dispute>> for DisputeId {
+    fn from(id: Id<Dispute>) -> Self {
+        Self::new(id.num())
+    }
+}
+
+impl From<DisputeId> for Id<Dispute> {
+    fn from(dispute_id: DisputeId) -> Self {
+        Self::new(
+            <Dispute as Label>::labeler().label(),
+            dispute_id.as_num(),
+            &pretty_snowflake::generator::prettifier(),
+        )
+    }
+}
+
+/// Identifies a standing order of scheduled or recurring payments.
+#[derive(
+    Debug,
+    Copy,
+    Clone,
+    PartialEq,
+    Eq,
+    Hash,
+    PartialOrd,
+    Ord,
+    ToSchema,
+    IntoParams,
+    Serialize,
+    Deserialize,
+)]
+#[schema(example = json!(7021537436124676096_u64))]
+#[into_params(names("order_id"))]
+#[serde(transparent)]
+#[repr(transparent)]
+pub struct StandingOrderId(i64);
+
+impl StandingOrderId {
+    pub fn new(id: impl Into<i64>) -> Self {
+        Self(id.into())
+    }
+
+    pub const fn as_num(&self) -> i64 {
+        self.0
+    }
+}
+
+impl fmt::Display for StandingOrderId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+impl From<Id<StandingOrder>> for StandingOrderId {
+    fn from(id: Id<StandingOrder>) -> Self {
+        Self::new(id.num())
+    }
+}
+
+impl From<StandingOrderId> for Id<StandingOrder> {
+    fn from(order_id: StandingOrderId) -> Self {
+        Self::new(
+            <StandingOrder as Label>::labeler().label(),
+            order_id.as_num(),
+            &pretty_snowflake::generator::prettifier(),
+        )
+    }
+}
+
+/// Identifies a bank account command accepted for asynchronous execution. Drawn from the account
+/// id generator, so ids are unique across the cluster and ordered by acceptance.
+#[derive(
+    Debug,
+    Copy,
+    Clone,
+    PartialEq,
+    Eq,
+    Hash,
+    PartialOrd,
+    Ord,
+    ToSchema,
+    IntoParams,
+    Serialize,
+    Deserialize,
+)]
+#[schema(example = json!(7022915432371814400_u64))]
+#[into_params(names("command_id"))]
+#[serde(transparent)]
+#[repr(transparent)]
+pub struct CommandId(i64);
+
+impl CommandId {
+    pub fn new(id: impl Into<i64>) -> Self {
+        Self(id.into())
+    }
+
+    pub fn generate() -> Self {
+        Self::new(bank_account::generate_id().num())
+    }
+
+    pub const fn as_num(&self) -> i64 {
+        self.0
+    }
+}
+
+impl fmt::Display for CommandId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+/// Identifies a customer of the bank, whose identity is verified before they may open accounts.
+#[derive(
+    Debug,
+    Copy,
+    Clone,
+    PartialEq,
+    Eq,
+    Hash,
+    PartialOrd,
+    Ord,
+    ToSchema,
+    IntoParams,
+    Serialize,
+    Deserialize,
+)]
+#[schema(example = json!(7021806137352503296_u64))]
+#[into_params(names("customer_id"))]
+#[serde(transparent)]
+#[repr(transparent)]
+pub struct CustomerId(i64);
+
+impl CustomerId {
+    pub fn new(id: impl Into<i64>) -> Self {
+        Self(id.into())
+    }
+
+    pub const fn as_num(&self) -> i64 {
+        self.0
+    }
+}
+
+impl fmt::Display for CustomerId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+impl From<Id<Customer>> for CustomerId {
+    fn from(id: Id<Customer>) -> Self {
+        Self::new(id.num())
+    }
+}
+
+impl From<CustomerId> for Id<Customer> {
+    fn from(customer_id: CustomerId) -> Self {
+        Self::new(
+            <Customer as Label>::labeler().label(),
+            customer_id.as_num(),
+            &pretty_snowflake::generator::prettifier(),
+        )
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -0,0 +1,366 @@
+use crate::model::{AccountId, BankAccountCommand, BankAccountError, StandingOrderId};
+use crate::services::{AccountPaymentApi, StandingOrderServices};
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use cqrs_es::{Aggregate, AggregateError};
+use money2::Money;
+use postgres_es::PostgresCqrs;
+use pretty_snowflake::{Id, Label};
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+
+mod errors;
+mod protocol;
+
+pub use errors::StandingOrderError;
+pub use protocol::{
+    Beneficiary, Schedule, StandingOrderCommand, StandingOrderEvent, StandingOrderStatus,
+};
+
+pub type StandingOrderAggregate = Arc<PostgresCqrs<StandingOrder>>;
+
+pub const AGGREGATE_TYPE: &str = "standing_order";
+
+#[inline]
+pub fn generate_id() -> Id<StandingOrder> {
+    pretty_snowflake::generator::next_id()
+}
+
+/// A customer's instruction to pay a beneficiary from their account on a schedule. Each occurrence
+/// is paid once it comes due; a payment declined by the account is retried until the retry window
+/// after its due time closes, at which point the occurrence fails.
+#[derive(Debug, Default, Clone, Label, PartialEq, Serialize, Deserialize)]
+pub struct StandingOrder {
+    order: Option<OrderState>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+struct OrderState {
+    order_id: StandingOrderId,
+    account_id: AccountId,
+    amount: Money,
+    beneficiary: Beneficiary,
+    schedule: Schedule,
+    status: StandingOrderStatus,
+
+    /// When the occurrence currently being paid came due.
+    due_at: DateTime<Utc>,
+
+    /// When payment of the current occurrence is next attempted.
+    next_attempt_at: DateTime<Utc>,
+
+    /// Number of declined attempts to pay the current occurrence.
+    attempts: u32,
+}
+
+#[async_trait]
+impl Aggregate for StandingOrder {
+    type Command = StandingOrderCommand;
+    type Error = StandingOrderError;
+    type Event = StandingOrderEvent;
+    type Services = StandingOrderServices;
+
+    fn aggregate_type() -> String {
+        AGGREGATE_TYPE.to_string()
+    }
+
+    #[tracing::instrument(level = "trace", skip(services))]
+    async fn handle(
+        &self, command: Self::Command, services: &Self::Services,
+    ) -> Result<Vec<Self::Event>, Self::Error> {
+        match (&self.order, command) {
+            (
+                None,
+                StandingOrderCommand::CreateStandingOrder {
+                    order_id,
+                    account_id,
+                    amount,
+                    beneficiary,
+                    schedule,
+                },
+            ) => {
+                if beneficiary == (Beneficiary::Account { account_id }) {
+                    return Err(StandingOrderError::RejectedCommand(format!(
+                        "Standing order cannot transfer from account {account_id} to itself."
+                    )));
+                }
+
+                schedule.validate().map_err(StandingOrderError::InvalidSchedule)?;
+                let first_due_at = schedule.next_after(Utc::now()).ok_or_else(|| {
+                    StandingOrderError::InvalidSchedule(format!(
+                        "{schedule:?} has no future occurrence"
+                    ))
+                })?;
+
+                Ok(vec![StandingOrderEvent::StandingOrderCreated {
+                    order_id,
+                    account_id,
+                    amount,
+                    beneficiary,
+                    schedule,
+                    first_due_at,
+                }])
+            },
+
+            (Some(order), StandingOrderCommand::CreateStandingOrder { .. }) => {
+                Err(StandingOrderError::RejectedCommand(format!(
+                    "Standing order {} is already created.",
+                    order.order_id
+                )))
+            },
+
+            (None, cmd) => Err(StandingOrderError::RejectedCommand(format!(
+                "No standing order is created to accept command: {cmd:?}"
+            ))),
+
+            (Some(order), cmd) => order.handle(cmd, services).await,
+        }
+    }
+
+    fn apply(&mut self, event: Self::Event) {
+        match event {
+            StandingOrderEvent::StandingOrderCreated {
+                order_id,
+                account_id,
+                amount,
+                beneficiary,
+                schedule,
+                first_due_at,
+            } => {
+                self.order = Some(OrderState {
+                    order_id,
+                    account_id,
+                    amount,
+                    beneficiary,
+                    schedule,
+                    status: StandingOrderStatus::Scheduled,
+                    due_at: first_due_at,
+                    next_attempt_at: first_due_at,
+                    attempts: 0,
+                });
+            },
+
+            StandingOrderEvent::StandingOrderPaid { next_due_at, .. } => {
+                self.advance(next_due_at, StandingOrderStatus::Completed)
+            },
+
+            StandingOrderEvent::PaymentRetryScheduled { attempt, retry_at, .. } => {
+                match self.order.as_mut() {
+                    Some(order) => {
+                        order.attempts = attempt;
+                        order.next_attempt_at = retry_at;
+                    },
+                    None => tracing::warn!("no standing order created to retry -- ignored"),
+                }
+            },
+
+            StandingOrderEvent::StandingOrderFailed { next_due_at, .. } => {
+                self.advance(next_due_at, StandingOrderStatus::Failed)
+            },
+
+            StandingOrderEvent::StandingOrderCancelled => match self.order.as_mut() {
+                Some(order) => order.status = StandingOrderStatus::Cancelled,
+                None => tracing::warn!("no standing order created to cancel -- ignored"),
+            },
+        }
+    }
+}
+
+impl StandingOrder {
+    /// Moves the order on to its next occurrence, or ends it with the `final_status` if none.
+    fn advance(&mut self, next_due_at: Option<DateTime<Utc>>, final_status: StandingOrderStatus) {
+        match (self.order.as_mut(), next_due_at) {
+            (Some(order), Some(next_due_at)) => {
+                order.due_at = next_due_at;
+                order.next_attempt_at = next_due_at;
+                order.attempts = 0;
+            },
+            (Some(order), None) => order.status = final_status,
+            (None, _) => {
+                tracing::warn!(%final_status, "no standing order created to advance -- ignored")
+            },
+        }
+    }
+}
+
+impl OrderState {
+    #[tracing::instrument(level = "trace", skip(services))]
+    async fn handle(
+        &self, command: StandingOrderCommand, services: &StandingOrderServices,
+    ) -> Result<Vec<StandingOrderEvent>, StandingOrderError> {
+        match (self.status, command) {
+            (StandingOrderStatus::Scheduled, StandingOrderCommand::PayDueOccurrence) => {
+                self.pay_due_occurrence(services).await
+            },
+
+            (StandingOrderStatus::Scheduled, StandingOrderCommand::CancelStandingOrder) => {
+                Ok(vec![StandingOrderEvent::StandingOrderCancelled])
+            },
+
+            (status, cmd) => Err(StandingOrderError::RejectedCommand(format!(
+                "Standing order {} is {status} and will not accept command: {cmd:?}",
+                self.order_id
+            ))),
+        }
+    }
+
+    async fn pay_due_occurrence(
+        &self, services: &StandingOrderServices,
+    ) -> Result<Vec<StandingOrderEvent>, StandingOrderError> {
+        let now = Utc::now();
+        if now < self.next_attempt_at {
+            return Err(StandingOrderError::NotDue(
+                self.order_id,
+                self.next_attempt_at,
+            ));
+        }
+
+        let due_at = self.due_at;
+        match self.pay(services).await {
+            Ok(()) => Ok(vec![StandingOrderEvent::StandingOrderPaid {
+                due_at,
+                next_due_at: self.schedule.next_after(due_at),
+            }]),
+
+            Err(AggregateError::UserError(declined)) => {
+                let reason = declined.to_string();
+                let retry_deadline = due_at.checked_add_signed(services.retry_window());
+                let retry_at = now
+                    .checked_add_signed(services.retry_interval())
+                    .filter(|retry_at| retry_deadline.is_none_or(|deadline| *retry_at <= deadline));
+
+                match retry_at {
+                    Some(retry_at) => Ok(vec![StandingOrderEvent::PaymentRetryScheduled {
+                        due_at,
+                        attempt: self.attempts + 1,
+                        retry_at,
+                        reason,
+                    }]),
+                    None => Ok(vec![StandingOrderEvent::StandingOrderFailed {
+                        due_at,
+                        reason,
+                        next_due_at: self.schedule.next_after(due_at),
+                    }]),
+                }
+            },
+
+            Err(error) => Err(error.into()),
+        }
+    }
+
+    /// Debits the order's account and, for a transfer, credits the beneficiary account. Either leg
+    /// already applied by an earlier, interrupted attempt is not applied again. A transfer the
+    /// beneficiary account declines is reversed out of the order's account, so the occurrence is
+    /// paid in full or not at all.
+    async fn pay(
+        &self, payments: &impl AccountPaymentApi,
+    ) -> Result<(), AggregateError<BankAccountError>> {
+        let debit = BankAccountCommand::DebitStandingOrder {
+            order_id: self.order_id,
+            due_at: self.due_at,
+            amount: self.amount,
+            beneficiary: self.beneficiary.clone(),
+        };
+        skip_applied(payments.execute(self.account_id, debit).await)?;
+
+        if let Beneficiary::Account { account_id } = self.beneficiary {
+            let credit = BankAccountCommand::CreditStandingOrder {
+                order_id: self.order_id,
+                due_at: self.due_at,
+                amount: self.amount,
+                from_account: self.account_id,
+            };
+
+            if let Err(declined) = skip_applied(payments.execute(account_id, credit).await) {
+                let reversal = BankAccountCommand::ReverseStandingOrderDebit {
+                    order_id: self.order_id,
+                    due_at: self.due_at,
+                    reason: format!(
+                        "Standing order {} to account {account_id} declined: {declined}",
+                        self.order_id
+                    ),
+                };
+                payments.execute(self.account_id, reversal).await?;
+                return Err(declined);
+            }
+        }
+
+        Ok(())
+    }
+}
+
+fn skip_applied(
+    result: Result<(), AggregateError<BankAccountError>>,
+) -> Result<(), AggregateError<BankAccountError>> {
+    match result {
+        Err(AggregateError::UserError(BankAccountError::StandingOrderAlreadyApplied(..))) => Ok(()),
+        result => result,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::bank_account;
+    use claim::assert_err;
+    use money2::Currency;
+    use pretty_assertions::assert_eq;
+    use std::sync::Mutex;
+
+    /// Records the payment commands executed, declining every command on the closed account.
+    struct ClosedBeneficiary {
+        closed: AccountId,
+        executed: Mutex<Vec<(AccountId, BankAccountCommand)>>,
+    }
+
+    #[async_trait]
+    impl AccountPaymentApi for ClosedBeneficiary {
+        async fn execute(
+            &self, account_id: AccountId, command: BankAccountCommand,
+        ) -> Result<(), AggregateError<BankAccountError>> {
+            let declined = BankAccountError::AccountClosed(account_id, format!("{command:?}"));
+            if let Ok(mut executed) = self.executed.lock() {
+                executed.push((account_id, command));
+            }
+
+            if account_id == self.closed {
+                Err(AggregateError::UserError(declined))
+            } else {
+                Ok(())
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn test_declined_transfer_reverses_debit() {
+        let from_account: AccountId = bank_account::generate_id().into();
+        let to_account: AccountId = bank_account::generate_id().into();
+        let order = OrderState {
+            order_id: generate_id().into(),
+            account_id: from_account,
+            amount: Money::new(7500, 2, Currency::Usd),
+            beneficiary: Beneficiary::Account { account_id: to_account },
+            schedule: Schedule::Once { at: Utc::now() },
+            status: StandingOrderStatus::Scheduled,
+            due_at: Utc::now(),
+            next_attempt_at: Utc::now(),
+            attempts: 0,
+        };
+        let payments = ClosedBeneficiary { closed: to_account, executed: Mutex::default() };
+
+        let error = assert_err!(order.pay(&payments).await);
+        assert!(matches!(
+            error,
+            AggregateError::UserError(BankAccountError::AccountClosed(account_id, _)) if account_id == to_account
+        ));
+
+        let executed = payments.executed.into_inner().unwrap_or_default();
+        let accounts: Vec<_> = executed.iter().map(|(account_id, _)| *account_id).collect();
+        assert_eq!(accounts, vec![from_account, to_account, from_account]);
+        assert!(matches!(
+            &executed[2].1,
+            BankAccountCommand::ReverseStandingOrderDebit { order_id, due_at, .. }
+                if *order_id == order.order_id && *due_at == order.due_at
+        ));
+    }
+}
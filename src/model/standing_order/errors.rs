@@ -0,0 +1,31 @@
+use crate::model::{account_error_code, BankAccountError, StandingOrderId};
+use chrono::{DateTime, Utc};
+use cqrs_es::AggregateError;
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+pub enum StandingOrderError {
+    #[error("invalid schedule: {0}")]
+    InvalidSchedule(String),
+
+    #[error("Rejected command: {0}")]
+    RejectedCommand(String),
+
+    #[error("standing order {0} is not due until {1}")]
+    NotDue(StandingOrderId, DateTime<Utc>),
+
+    #[error("{0}")]
+    Account(#[from] AggregateError<BankAccountError>),
+}
+
+impl StandingOrderError {
+    /// Stable machine-readable code identifying the error to API clients.
+    pub const fn error_code(&self) -> &'static str {
+        match self {
+            Self::InvalidSchedule(_) => "invalid_schedule",
+            Self::RejectedCommand(_) => "rejected_command",
+            Self::NotDue(..) => "standing_order_not_due",
+            Self::Account(error) => account_error_code(error),
+        }
+    }
+}
@@ -0,0 +1,217 @@
+use crate::model::{AccountId, StandingOrderId};
+use chrono::{DateTime, Datelike, Duration, Months, Utc};
+use cqrs_es::DomainEvent;
+use money2::Money;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::str::FromStr;
+use strum::Display;
+use utoipa::ToSchema;
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum StandingOrderCommand {
+    CreateStandingOrder {
+        order_id: StandingOrderId,
+        account_id: AccountId,
+        amount: Money,
+        beneficiary: Beneficiary,
+        schedule: Schedule,
+    },
+    PayDueOccurrence,
+    CancelStandingOrder,
+}
+
+#[derive(Debug, Display, Clone, PartialEq, Eq, ToSchema, Serialize, Deserialize)]
+#[strum(serialize_all = "snake_case")]
+pub enum StandingOrderEvent {
+    StandingOrderCreated {
+        order_id: StandingOrderId,
+        account_id: AccountId,
+        #[schema(value_type = ApiMoney)]
+        amount: Money,
+        beneficiary: Beneficiary,
+        schedule: Schedule,
+        first_due_at: DateTime<Utc>,
+    },
+    /// The occurrence due at `due_at` is paid; the order next comes due at `next_due_at`, if any.
+    StandingOrderPaid {
+        due_at: DateTime<Utc>,
+        next_due_at: Option<DateTime<Utc>>,
+    },
+    /// Payment of the occurrence due at `due_at` was declined and is retried at `retry_at`.
+    PaymentRetryScheduled {
+        due_at: DateTime<Utc>,
+        attempt: u32,
+        retry_at: DateTime<Utc>,
+        reason: String,
+    },
+    /// Payment of the occurrence due at `due_at` was declined through the retry window, and the
+    /// occurrence is abandoned. A recurring order next comes due at `next_due_at`, if any.
+    StandingOrderFailed {
+        due_at: DateTime<Utc>,
+        reason: String,
+        next_due_at: Option<DateTime<Utc>>,
+    },
+    StandingOrderCancelled,
+}
+
+#[derive(Debug, Display, Default, Copy, Clone, PartialEq, Eq, ToSchema, Serialize, Deserialize)]
+#[strum(serialize_all = "snake_case")]
+#[serde(rename_all = "snake_case")]
+pub enum StandingOrderStatus {
+    #[default]
+    Scheduled,
+    Completed,
+    Failed,
+    Cancelled,
+}
+
+/// Recipient of standing order payments.
+#[derive(Debug, Clone, PartialEq, Eq, ToSchema, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Beneficiary {
+    /// Transfer to another account at the bank.
+    Account { account_id: AccountId },
+    /// Debit paid out to an external payee.
+    Payee { name: String },
+}
+
+impl fmt::Display for Beneficiary {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Account { account_id } => write!(f, "account {account_id}"),
+            Self::Payee { name } => write!(f, "{name}"),
+        }
+    }
+}
+
+/// When a standing order comes due.
+#[derive(Debug, Clone, PartialEq, Eq, ToSchema, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Schedule {
+    /// A single future-dated payment.
+    Once { at: DateTime<Utc> },
+    /// Every week, starting at `starting`.
+    Weekly { starting: DateTime<Utc> },
+    /// Every month on the day of month of `starting`, or the last day of shorter months.
+    Monthly { starting: DateTime<Utc> },
+    /// Occurrences of a cron expression, including seconds; e.g., `0 0 9 * * Mon-Fri`.
+    Cron { expression: String },
+}
+
+impl Schedule {
+    /// Checks that the schedule can be interpreted.
+    pub fn validate(&self) -> Result<(), String> {
+        match self {
+            Self::Cron { expression } => cron::Schedule::from_str(expression)
+                .map(|_| ())
+                .map_err(|err| format!("invalid cron expression {expression:?}: {err}")),
+            _ => Ok(()),
+        }
+    }
+
+    /// The first occurrence of the schedule strictly after `after`, if any.
+    pub fn next_after(&self, after: DateTime<Utc>) -> Option<DateTime<Utc>> {
+        match self {
+            Self::Once { at } => Some(*at).filter(|at| after < *at),
+
+            Self::Weekly { starting } if after < *starting => Some(*starting),
+            Self::Weekly { starting } => {
+                let nr_weeks = (after - *starting).num_weeks() + 1;
+                Some(*starting + Duration::weeks(nr_weeks))
+            },
+
+            Self::Monthly { starting } if after < *starting => Some(*starting),
+            Self::Monthly { starting } => {
+                let elapsed_months = (after.year() - starting.year()) * 12 + after.month() as i32
+                    - starting.month() as i32;
+                (elapsed_months.max(0) as u32..)
+                    .map_while(|nr_months| starting.checked_add_months(Months::new(nr_months)))
+                    .find(|occurrence| after < *occurrence)
+            },
+
+            Self::Cron { expression } => cron::Schedule::from_str(expression)
+                .ok()
+                .and_then(|schedule| schedule.after(&after).next()),
+        }
+    }
+}
+
+const VERSION: &str = "1.0";
+
+impl DomainEvent for StandingOrderEvent {
+    fn event_type(&self) -> String {
+        self.to_string()
+    }
+
+    fn event_version(&self) -> String {
+        VERSION.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use claim::{assert_err, assert_none, assert_ok, assert_some};
+    use pretty_assertions::assert_eq;
+
+    fn ts(rep: &str) -> DateTime<Utc> {
+        assert_ok!(rep.parse::<DateTime<Utc>>())
+    }
+
+    #[test]
+    fn test_once_schedule() {
+        let schedule = Schedule::Once { at: ts("2023-02-01T09:00:00Z") };
+        assert_eq!(
+            assert_some!(schedule.next_after(ts("2023-01-20T12:00:00Z"))),
+            ts("2023-02-01T09:00:00Z")
+        );
+        assert_none!(schedule.next_after(ts("2023-02-01T09:00:00Z")));
+    }
+
+    #[test]
+    fn test_weekly_schedule() {
+        let schedule = Schedule::Weekly { starting: ts("2023-01-02T09:00:00Z") };
+        assert_eq!(
+            assert_some!(schedule.next_after(ts("2022-12-25T00:00:00Z"))),
+            ts("2023-01-02T09:00:00Z")
+        );
+        assert_eq!(
+            assert_some!(schedule.next_after(ts("2023-01-02T09:00:00Z"))),
+            ts("2023-01-09T09:00:00Z")
+        );
+        assert_eq!(
+            assert_some!(schedule.next_after(ts("2023-01-20T12:00:00Z"))),
+            ts("2023-01-23T09:00:00Z")
+        );
+    }
+
+    #[test]
+    fn test_monthly_schedule() {
+        let schedule = Schedule::Monthly { starting: ts("2023-01-31T09:00:00Z") };
+        assert_eq!(
+            assert_some!(schedule.next_after(ts("2023-01-31T09:00:00Z"))),
+            ts("2023-02-28T09:00:00Z")
+        );
+        assert_eq!(
+            assert_some!(schedule.next_after(ts("2023-02-28T09:00:00Z"))),
+            ts("2023-03-31T09:00:00Z")
+        );
+        assert_eq!(
+            assert_some!(schedule.next_after(ts("2024-02-01T00:00:00Z"))),
+            ts("2024-02-29T09:00:00Z")
+        );
+    }
+
+    #[test]
+    fn test_cron_schedule() {
+        let schedule = Schedule::Cron { expression: "0 0 9 * * Mon".to_string() };
+        assert_ok!(schedule.validate());
+        assert_eq!(
+            assert_some!(schedule.next_after(ts("2023-01-20T12:00:00Z"))),
+            ts("2023-01-23T09:00:00Z")
+        );
+
+        assert_err!(Schedule::Cron { expression: "every monday".to_string() }.validate());
+    }
+}
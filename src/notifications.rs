@@ -0,0 +1,51 @@
+//! Customer notifications sent in reaction to account events.
+//!
+//! The notification worker dispatches each account event queued in the transactional event
+//! outbox to the [NotificationDispatcher], which tracks each account's contact details and
+//! notification preferences and renders a message for notable events (account opened, large
+//! withdrawal, email changed, account dormant). Rendered messages are queued in the same
+//! transaction, and the worker hands them to the configured [Mailer], retrying failed sends with
+//! backoff until a message exhausts its attempts and is marked failed.
+
+mod dispatch;
+mod mailer;
+mod recipients;
+mod templates;
+mod worker;
+
+use thiserror::Error;
+
+pub use dispatch::NotificationDispatcher;
+pub use mailer::{InMemoryMailer, Mailer, NotificationMailer};
+pub use recipients::{
+    load_preferences, save_preferences, NotificationPreferences, RecipientContactQuery,
+};
+pub use templates::{EmailMessage, Notification};
+pub use worker::spawn_notification_worker;
+
+pub const RECIPIENTS_TABLE: &str = "notification_recipients";
+pub const MESSAGES_TABLE: &str = "notification_messages";
+
+#[derive(Debug, Error)]
+pub enum NotificationError {
+    #[error("failed to send notification over SMTP: {0}")]
+    Smtp(#[from] lettre::transport::smtp::Error),
+
+    #[error("invalid notification mailbox: {0}")]
+    Address(#[from] lettre::address::AddressError),
+
+    #[error("failed to build notification message: {0}")]
+    Message(#[from] lettre::error::Error),
+
+    #[error("{0}")]
+    IO(#[from] std::io::Error),
+
+    #[error("failed to serialize notification: {0}")]
+    Serialization(#[from] serde_json::Error),
+
+    #[error("failed notification database operation: {0}")]
+    Sql(#[from] sqlx::Error),
+
+    #[error("notification was not sent within {0:?}")]
+    Timeout(std::time::Duration),
+}
@@ -0,0 +1,119 @@
+use super::recipients::{self, Recipient};
+use super::{EmailMessage, Notification, NotificationError};
+use crate::model::{self, BankAccountEvent, CheckNumber};
+use crate::settings::NotificationSettings;
+use money2::Money;
+use sqlx::PgConnection;
+
+/// Renders the notifications for an account event, keeping each account's contact details
+/// current as its events are dispatched.
+#[derive(Debug, Clone)]
+pub struct NotificationDispatcher {
+    sender: String,
+    large_withdrawal_threshold: Money,
+}
+
+impl NotificationDispatcher {
+    pub fn new(settings: &NotificationSettings) -> Self {
+        Self {
+            sender: settings.sender.clone(),
+            large_withdrawal_threshold: settings.large_withdrawal_threshold,
+        }
+    }
+
+    fn is_large_withdrawal(&self, amount: Money) -> bool {
+        let threshold = self.large_withdrawal_threshold;
+        threshold <= model::convert_amount(threshold.currency, amount)
+    }
+
+    #[tracing::instrument(level = "debug", skip(self, conn))]
+    pub(super) async fn prepare(
+        &self, conn: &mut PgConnection, aggregate_id: &str, event: &BankAccountEvent,
+    ) -> Result<Vec<EmailMessage>, NotificationError> {
+        let messages = match event {
+            BankAccountEvent::AccountOpened { user_name, email, .. } => {
+                recipients::save_contact(&mut *conn, aggregate_id, Some(user_name.as_str()), email)
+                    .await?;
+                let recipient = recipients::find_recipient(&mut *conn, aggregate_id).await?;
+                let notification = Notification::AccountOpened { user_name: user_name.clone() };
+                self.render_for(recipient.as_ref(), &notification)
+            },
+
+            BankAccountEvent::CashWithdrawal { amount, .. } => {
+                self.prepare_withdrawal(conn, aggregate_id, *amount, None).await?
+            },
+
+            BankAccountEvent::CheckWithdrawal { check_nr, amount, .. } => {
+                self.prepare_withdrawal(conn, aggregate_id, *amount, Some(*check_nr))
+                    .await?
+            },
+
+            BankAccountEvent::EmailUpdated { new_email } => {
+                let recipient = recipients::find_recipient(&mut *conn, aggregate_id).await?;
+                recipients::save_contact(&mut *conn, aggregate_id, None, new_email).await?;
+
+                match recipient {
+                    Some(Recipient { user_name, email: Some(old_email), preferences }) => {
+                        let notification = Notification::EmailChanged {
+                            user_name,
+                            old_email: old_email.clone(),
+                            new_email: new_email.clone(),
+                        };
+
+                        if !preferences.allows(&notification) {
+                            Vec::new()
+                        } else if &old_email == new_email {
+                            vec![notification.render(&self.sender, new_email)]
+                        } else {
+                            vec![
+                                notification.render(&self.sender, &old_email),
+                                notification.render(&self.sender, new_email),
+                            ]
+                        }
+                    },
+                    _ => Vec::new(),
+                }
+            },
+
+            BankAccountEvent::AccountMarkedDormant { last_activity_at } => {
+                let recipient = recipients::find_recipient(&mut *conn, aggregate_id).await?;
+                let notification = Notification::AccountDormant {
+                    user_name: recipient.as_ref().map(|r| r.user_name.clone()).unwrap_or_default(),
+                    last_activity_at: *last_activity_at,
+                };
+                self.render_for(recipient.as_ref(), &notification)
+            },
+
+            _ => Vec::new(),
+        };
+
+        Ok(messages)
+    }
+
+    async fn prepare_withdrawal(
+        &self, conn: &mut PgConnection, aggregate_id: &str, amount: Money,
+        check_nr: Option<CheckNumber>,
+    ) -> Result<Vec<EmailMessage>, NotificationError> {
+        if !self.is_large_withdrawal(amount) {
+            return Ok(Vec::new());
+        }
+
+        let recipient = recipients::find_recipient(&mut *conn, aggregate_id).await?;
+        let notification = Notification::LargeWithdrawal {
+            user_name: recipient.as_ref().map(|r| r.user_name.clone()).unwrap_or_default(),
+            amount,
+            check_nr,
+        };
+        Ok(self.render_for(recipient.as_ref(), &notification))
+    }
+
+    fn render_for(
+        &self, recipient: Option<&Recipient>, notification: &Notification,
+    ) -> Vec<EmailMessage> {
+        recipient
+            .filter(|r| r.preferences.allows(notification))
+            .and_then(|r| r.email.as_ref())
+            .map(|email| vec![notification.render(&self.sender, email)])
+            .unwrap_or_default()
+    }
+}
@@ -0,0 +1,158 @@
+use super::{EmailMessage, NotificationError};
+use crate::settings::{MailerSettings, SmtpSettings};
+use async_trait::async_trait;
+use lettre::message::header::ContentType;
+use lettre::message::Mailbox;
+use lettre::transport::smtp::authentication::Credentials;
+use lettre::{AsyncSmtpTransport, AsyncTransport, Message, Tokio1Executor};
+use secrecy::ExposeSecret;
+use std::fmt;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+use tokio::io::AsyncWriteExt;
+
+#[async_trait]
+pub trait Mailer: Sync + Send {
+    async fn send(&self, message: &EmailMessage) -> Result<(), NotificationError>;
+}
+
+#[derive(Debug, Clone)]
+pub enum NotificationMailer {
+    Smtp(SmtpMailer),
+    File(FileMailer),
+    InMemory(InMemoryMailer),
+}
+
+impl NotificationMailer {
+    pub fn from_settings(settings: &MailerSettings) -> Result<Self, NotificationError> {
+        let mailer = match settings {
+            MailerSettings::Smtp(smtp) => SmtpMailer::new(smtp)?.into(),
+            MailerSettings::File { path } => FileMailer::new(path.clone()).into(),
+            MailerSettings::InMemory => InMemoryMailer::default().into(),
+        };
+
+        Ok(mailer)
+    }
+}
+
+#[async_trait]
+impl Mailer for NotificationMailer {
+    async fn send(&self, message: &EmailMessage) -> Result<(), NotificationError> {
+        match self {
+            Self::Smtp(mailer) => mailer.send(message).await,
+            Self::File(mailer) => mailer.send(message).await,
+            Self::InMemory(mailer) => mailer.send(message).await,
+        }
+    }
+}
+
+impl From<SmtpMailer> for NotificationMailer {
+    fn from(mailer: SmtpMailer) -> Self {
+        Self::Smtp(mailer)
+    }
+}
+
+impl From<FileMailer> for NotificationMailer {
+    fn from(mailer: FileMailer) -> Self {
+        Self::File(mailer)
+    }
+}
+
+impl From<InMemoryMailer> for NotificationMailer {
+    fn from(mailer: InMemoryMailer) -> Self {
+        Self::InMemory(mailer)
+    }
+}
+
+#[derive(Clone)]
+pub struct SmtpMailer {
+    transport: AsyncSmtpTransport<Tokio1Executor>,
+}
+
+impl fmt::Debug for SmtpMailer {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SmtpMailer").finish()
+    }
+}
+
+impl SmtpMailer {
+    pub fn new(settings: &SmtpSettings) -> Result<Self, NotificationError> {
+        let builder = if settings.starttls {
+            AsyncSmtpTransport::<Tokio1Executor>::starttls_relay(&settings.host)?
+        } else {
+            AsyncSmtpTransport::<Tokio1Executor>::builder_dangerous(&settings.host)
+        };
+
+        let mut builder = builder.port(settings.port);
+        if let (Some(username), Some(password)) = (&settings.username, &settings.password) {
+            builder = builder.credentials(Credentials::new(
+                username.clone(),
+                password.expose_secret().clone(),
+            ));
+        }
+
+        Ok(Self { transport: builder.build() })
+    }
+}
+
+#[async_trait]
+impl Mailer for SmtpMailer {
+    async fn send(&self, message: &EmailMessage) -> Result<(), NotificationError> {
+        let email = Message::builder()
+            .from(message.from.parse::<Mailbox>()?)
+            .to(message.to.as_str().parse::<Mailbox>()?)
+            .subject(message.subject.clone())
+            .header(ContentType::TEXT_PLAIN)
+            .body(message.body.clone())?;
+        self.transport.send(email).await?;
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct FileMailer {
+    path: PathBuf,
+}
+
+impl FileMailer {
+    pub const fn new(path: PathBuf) -> Self {
+        Self { path }
+    }
+}
+
+#[async_trait]
+impl Mailer for FileMailer {
+    async fn send(&self, message: &EmailMessage) -> Result<(), NotificationError> {
+        let line = serde_json::to_string(message)?;
+        let mut file = tokio::fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)
+            .await?;
+        file.write_all(format!("{line}\n").as_bytes()).await?;
+        file.sync_data().await?;
+        Ok(())
+    }
+}
+
+#[derive(Debug, Default, Clone)]
+pub struct InMemoryMailer {
+    sent: Arc<Mutex<Vec<EmailMessage>>>,
+}
+
+impl InMemoryMailer {
+    /// Messages sent so far, in the order they were sent.
+    pub fn sent(&self) -> Vec<EmailMessage> {
+        self.sent.lock().map(|sent| sent.clone()).unwrap_or_default()
+    }
+}
+
+#[async_trait]
+impl Mailer for InMemoryMailer {
+    async fn send(&self, message: &EmailMessage) -> Result<(), NotificationError> {
+        if let Ok(mut sent) = self.sent.lock() {
+            sent.push(message.clone());
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,180 @@
+use super::{Notification, RECIPIENTS_TABLE};
+use crate::model::{BankAccount, BankAccountEvent, EmailAddress};
+use async_trait::async_trait;
+use cqrs_es::{EventEnvelope, Query};
+use serde::{Deserialize, Serialize};
+use sqlx::postgres::{PgExecutor, PgRow};
+use sqlx::{PgPool, Row};
+use utoipa::ToSchema;
+
+/// Notifications the account holder chooses to receive. All are sent by default.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, ToSchema, Serialize, Deserialize)]
+#[serde(default)]
+pub struct NotificationPreferences {
+    pub account_opened: bool,
+    pub large_withdrawal: bool,
+    pub email_changed: bool,
+}
+
+impl Default for NotificationPreferences {
+    fn default() -> Self {
+        Self {
+            account_opened: true,
+            large_withdrawal: true,
+            email_changed: true,
+        }
+    }
+}
+
+impl NotificationPreferences {
+    pub const fn allows(&self, notification: &Notification) -> bool {
+        match notification {
+            Notification::AccountOpened { .. } => self.account_opened,
+            Notification::LargeWithdrawal { .. } => self.large_withdrawal,
+            Notification::EmailChanged { .. } => self.email_changed,
+            // required notice before unclaimed property is escheated, so it cannot be opted out
+            Notification::AccountDormant { .. } => true,
+        }
+    }
+
+    fn from_row(row: &PgRow) -> Result<Self, sqlx::Error> {
+        Ok(Self {
+            account_opened: row.try_get("account_opened")?,
+            large_withdrawal: row.try_get("large_withdrawal")?,
+            email_changed: row.try_get("email_changed")?,
+        })
+    }
+}
+
+/// Contact details and preferences of the account holder, as of the latest dispatched event.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(super) struct Recipient {
+    pub user_name: String,
+    pub email: Option<EmailAddress>,
+    pub preferences: NotificationPreferences,
+}
+
+impl Recipient {
+    fn from_row(row: &PgRow) -> Result<Self, sqlx::Error> {
+        let user_name: Option<String> = row.try_get("user_name")?;
+        let email: Option<String> = row.try_get("email")?;
+        let email = email.and_then(|email| match EmailAddress::parse(email) {
+            Ok(email) => Some(email),
+            Err(error) => {
+                tracing::warn!(?error, "ignoring invalid recipient email");
+                None
+            },
+        });
+
+        Ok(Self {
+            user_name: user_name.unwrap_or_default(),
+            email,
+            preferences: NotificationPreferences::from_row(row)?,
+        })
+    }
+}
+
+const RECIPIENT_COLUMNS: &str = "user_name, email, account_opened, large_withdrawal, email_changed";
+
+pub(super) async fn find_recipient(
+    executor: impl PgExecutor<'_>, aggregate_id: &str,
+) -> Result<Option<Recipient>, sqlx::Error> {
+    let select_sql =
+        format!("SELECT {RECIPIENT_COLUMNS} FROM {RECIPIENTS_TABLE} WHERE aggregate_id = $1");
+    let row = sqlx::query(&select_sql)
+        .bind(aggregate_id)
+        .fetch_optional(executor)
+        .await?;
+    row.as_ref().map(Recipient::from_row).transpose()
+}
+
+/// Records the account holder's current contact details, keeping any saved preferences.
+pub(super) async fn save_contact(
+    executor: impl PgExecutor<'_>, aggregate_id: &str, user_name: Option<&str>,
+    email: &EmailAddress,
+) -> Result<(), sqlx::Error> {
+    let upsert_sql = format!(
+        r##"INSERT INTO {RECIPIENTS_TABLE} (aggregate_id, user_name, email) VALUES ($1, $2, $3)
+        ON CONFLICT (aggregate_id) DO UPDATE
+        SET user_name = COALESCE(EXCLUDED.user_name, {RECIPIENTS_TABLE}.user_name),
+            email = EXCLUDED.email,
+            updated_at = now()"##
+    );
+    sqlx::query(&upsert_sql)
+        .bind(aggregate_id)
+        .bind(user_name)
+        .bind(email.as_str())
+        .execute(executor)
+        .await?;
+    Ok(())
+}
+
+/// Records the account holders' contact details from committed account events. The notification
+/// dispatcher keeps them current as it renders messages, so the query is only used to rebuild
+/// them by replay; it keeps the holders' saved preferences.
+#[derive(Debug, Clone)]
+pub struct RecipientContactQuery {
+    pool: PgPool,
+}
+
+impl RecipientContactQuery {
+    pub const fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+}
+
+#[async_trait]
+impl Query<BankAccount> for RecipientContactQuery {
+    #[tracing::instrument(level = "debug", skip(self, events))]
+    async fn dispatch(&self, aggregate_id: &str, events: &[EventEnvelope<BankAccount>]) {
+        for event in events {
+            let result = match &event.payload {
+                BankAccountEvent::AccountOpened { user_name, email, .. } => {
+                    save_contact(&self.pool, aggregate_id, Some(user_name.as_str()), email).await
+                },
+                BankAccountEvent::EmailUpdated { new_email } => {
+                    save_contact(&self.pool, aggregate_id, None, new_email).await
+                },
+                _ => Ok(()),
+            };
+
+            if let Err(error) = result {
+                tracing::error!(
+                    ?error, sequence=%event.sequence,
+                    "failed to record notification recipient contact"
+                );
+            }
+        }
+    }
+}
+
+pub async fn load_preferences(
+    pool: &PgPool, aggregate_id: &str,
+) -> Result<Option<NotificationPreferences>, sqlx::Error> {
+    find_recipient(pool, aggregate_id)
+        .await
+        .map(|recipient| recipient.map(|r| r.preferences))
+}
+
+pub async fn save_preferences(
+    pool: &PgPool, aggregate_id: &str, preferences: NotificationPreferences,
+) -> Result<NotificationPreferences, sqlx::Error> {
+    let upsert_sql = format!(
+        r##"INSERT INTO {RECIPIENTS_TABLE} (aggregate_id, account_opened, large_withdrawal, email_changed)
+        VALUES ($1, $2, $3, $4)
+        ON CONFLICT (aggregate_id) DO UPDATE
+        SET account_opened = EXCLUDED.account_opened,
+            large_withdrawal = EXCLUDED.large_withdrawal,
+            email_changed = EXCLUDED.email_changed,
+            updated_at = now()
+        RETURNING account_opened, large_withdrawal, email_changed"##
+    );
+    let row = sqlx::query(&upsert_sql)
+        .bind(aggregate_id)
+        .bind(preferences.account_opened)
+        .bind(preferences.large_withdrawal)
+        .bind(preferences.email_changed)
+        .fetch_one(pool)
+        .await?;
+    NotificationPreferences::from_row(&row)
+}
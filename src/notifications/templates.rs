@@ -0,0 +1,140 @@
+use crate::model::{CheckNumber, EmailAddress};
+use chrono::{DateTime, Utc};
+use money2::Money;
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct EmailMessage {
+    pub from: String,
+    pub to: EmailAddress,
+    pub subject: String,
+    pub body: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Notification {
+    AccountOpened {
+        user_name: String,
+    },
+    LargeWithdrawal {
+        user_name: String,
+        amount: Money,
+        check_nr: Option<CheckNumber>,
+    },
+    EmailChanged {
+        user_name: String,
+        old_email: EmailAddress,
+        new_email: EmailAddress,
+    },
+    AccountDormant {
+        user_name: String,
+        last_activity_at: DateTime<Utc>,
+    },
+}
+
+impl Notification {
+    pub fn render(&self, from: &str, to: &EmailAddress) -> EmailMessage {
+        let (subject, body) = match self {
+            Self::AccountOpened { user_name } => (
+                "Welcome to the bank".to_string(),
+                format!(
+                    "Hi {user_name},\n\nYour new bank account is open and ready to use.\n\n\
+                     Thank you for banking with us."
+                ),
+            ),
+
+            Self::LargeWithdrawal { user_name, amount, check_nr } => {
+                let channel = check_nr
+                    .map(|check_nr| format!("by check {check_nr}"))
+                    .unwrap_or_else(|| "at an ATM".to_string());
+                (
+                    format!("Withdrawal of {amount} from your account"),
+                    format!(
+                        "Hi {user_name},\n\n{amount} was withdrawn from your account {channel}.\n\n\
+                         If you did not make this withdrawal, please contact us immediately."
+                    ),
+                )
+            },
+
+            Self::EmailChanged { user_name, old_email, new_email } => (
+                "The email address on your account changed".to_string(),
+                format!(
+                    "Hi {user_name},\n\nThe email address on your account was changed from \
+                     {old_email} to {new_email}.\n\n\
+                     If you did not make this change, please contact us immediately."
+                ),
+            ),
+
+            Self::AccountDormant { user_name, last_activity_at } => (
+                "Your account is now dormant".to_string(),
+                format!(
+                    "Hi {user_name},\n\nThere has been no activity on your account since {}, so \
+                     it is now dormant and withdrawals are blocked. Please contact us to \
+                     reactivate your account.\n\n\
+                     Balances of accounts left dormant may be transferred to the state as \
+                     unclaimed property.",
+                    last_activity_at.format("%B %-d, %Y")
+                ),
+            ),
+        };
+
+        EmailMessage {
+            from: from.to_string(),
+            to: to.clone(),
+            subject,
+            body,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use claim::assert_ok;
+    use money2::Currency;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn test_render_large_check_withdrawal() {
+        let to = assert_ok!(EmailAddress::parse("neo@example.com"));
+        let notification = Notification::LargeWithdrawal {
+            user_name: "neo".to_string(),
+            amount: Money::new(2_500, 0, Currency::Usd),
+            check_nr: Some(CheckNumber::new(1087_u32)),
+        };
+
+        let actual = notification.render("Bank <no-reply@bank.example.com>", &to);
+        assert_eq!(actual.to, to);
+        assert_eq!(actual.from, "Bank <no-reply@bank.example.com>");
+        assert!(actual.subject.starts_with("Withdrawal of "));
+        assert!(actual.body.contains("by check 1087"));
+    }
+
+    #[test]
+    fn test_render_email_changed_names_both_addresses() {
+        let old_email = assert_ok!(EmailAddress::parse("neo@example.com"));
+        let new_email = assert_ok!(EmailAddress::parse("the.one@example.com"));
+        let notification = Notification::EmailChanged {
+            user_name: "neo".to_string(),
+            old_email: old_email.clone(),
+            new_email,
+        };
+
+        let actual = notification.render("no-reply@bank.example.com", &old_email);
+        assert_eq!(actual.subject, "The email address on your account changed");
+        assert!(actual.body.contains("changed from neo@example.com to the.one@example.com"));
+    }
+
+    #[test]
+    fn test_render_account_dormant_names_last_activity() {
+        let to = assert_ok!(EmailAddress::parse("neo@example.com"));
+        let notification = Notification::AccountDormant {
+            user_name: "neo".to_string(),
+            last_activity_at: assert_ok!("2022-01-27T09:30:00Z".parse()),
+        };
+
+        let actual = notification.render("no-reply@bank.example.com", &to);
+        assert_eq!(actual.subject, "Your account is now dormant");
+        assert!(actual.body.contains("no activity on your account since January 27, 2022"));
+    }
+}
@@ -0,0 +1,283 @@
+use super::{
+    EmailMessage, Mailer, NotificationDispatcher, NotificationError, NotificationMailer,
+    MESSAGES_TABLE,
+};
+use crate::model::{bank_account, BankAccountEvent};
+use crate::outbox::OUTBOX_TABLE;
+use crate::settings::NotificationSettings;
+use chrono::{DateTime, Utc};
+use sqlx::{PgPool, Postgres, Row, Transaction};
+use std::time::Duration;
+use strum_macros::Display;
+use tokio::task::JoinHandle;
+use tokio::time::MissedTickBehavior;
+
+/// Advisory lock key held while rendering, so only one instance dispatches outbox events at a time
+/// and each account's events update its recipient details in sequence.
+const NOTIFICATION_DISPATCH_LOCK: i64 = 0x6e6f_7469_6679; // "notify"
+
+/// Where a queued message stands; only pending messages are sent.
+#[derive(Debug, Display, Copy, Clone, PartialEq, Eq)]
+#[strum(serialize_all = "snake_case")]
+enum MessageStatus {
+    Pending,
+    Sent,
+    Failed,
+}
+
+pub fn spawn_notification_worker(
+    pool: PgPool, settings: NotificationSettings, mailer: NotificationMailer,
+) -> JoinHandle<()> {
+    tokio::spawn(async move {
+        tracing::info!("starting customer notification worker...");
+        let dispatcher = NotificationDispatcher::new(&settings);
+        let mut interval = tokio::time::interval(settings.poll_interval);
+        interval.set_missed_tick_behavior(MissedTickBehavior::Delay);
+
+        loop {
+            interval.tick().await;
+            match dispatch_batch(&pool, &dispatcher, settings.batch_size).await {
+                Ok(0) => {},
+                Ok(nr_dispatched) => {
+                    tracing::debug!(%nr_dispatched, "rendered notifications for outbox events")
+                },
+                Err(error) => tracing::error!(?error, "notification dispatch pass failed"),
+            }
+
+            match send_batch(&pool, &mailer, &settings).await {
+                Ok(0) => {},
+                Ok(nr_attempted) => tracing::debug!(%nr_attempted, "attempted notifications"),
+                Err(error) => tracing::error!(?error, "notification send pass failed"),
+            }
+        }
+    })
+}
+
+/// Renders the notifications for the next batch of undispatched account events in the event
+/// outbox, returning the number of events dispatched. The rendered messages are queued, and the
+/// events marked dispatched, in one transaction, so each event is notified exactly once.
+#[tracing::instrument(level = "debug", skip(pool, dispatcher))]
+async fn dispatch_batch(
+    pool: &PgPool, dispatcher: &NotificationDispatcher, batch_size: i64,
+) -> Result<usize, NotificationError> {
+    let mut tx = pool.begin().await?;
+    let is_dispatch_leader: bool = sqlx::query_scalar("SELECT pg_try_advisory_xact_lock($1)")
+        .bind(NOTIFICATION_DISPATCH_LOCK)
+        .fetch_one(&mut tx)
+        .await?;
+
+    if !is_dispatch_leader {
+        tracing::debug!("another instance is dispatching notifications");
+        tx.commit().await?;
+        return Ok(0);
+    }
+
+    let select_sql = format!(
+        r##"SELECT id, aggregate_type, aggregate_id, sequence, payload FROM {OUTBOX_TABLE}
+        WHERE notifications_dispatched_at IS NULL
+        ORDER BY id
+        LIMIT $1"##
+    );
+    let rows = sqlx::query(&select_sql).bind(batch_size).fetch_all(&mut tx).await?;
+
+    let mut outbox_ids = Vec::with_capacity(rows.len());
+    for row in rows {
+        let outbox_id: i64 = row.try_get("id")?;
+        outbox_ids.push(outbox_id);
+
+        let aggregate_type: String = row.try_get("aggregate_type")?;
+        if aggregate_type != bank_account::AGGREGATE_TYPE {
+            continue;
+        }
+
+        let aggregate_id: String = row.try_get("aggregate_id")?;
+        let sequence: i64 = row.try_get("sequence")?;
+        let payload: serde_json::Value = row.try_get("payload")?;
+        let event: BankAccountEvent = match serde_json::from_value(payload) {
+            Ok(event) => event,
+            Err(error) => {
+                tracing::error!(
+                    ?error, %aggregate_id, %sequence,
+                    "skipping notifications for undecodable account event"
+                );
+                continue;
+            },
+        };
+
+        for message in dispatcher.prepare(&mut tx, &aggregate_id, &event).await? {
+            enqueue(&mut tx, &aggregate_id, sequence, &message).await?;
+        }
+    }
+
+    let update_sql =
+        format!("UPDATE {OUTBOX_TABLE} SET notifications_dispatched_at = now() WHERE id = ANY($1)");
+    sqlx::query(&update_sql).bind(&outbox_ids).execute(&mut tx).await?;
+
+    tx.commit().await?;
+    Ok(outbox_ids.len())
+}
+
+async fn enqueue(
+    tx: &mut Transaction<'_, Postgres>, aggregate_id: &str, sequence: i64, message: &EmailMessage,
+) -> Result<(), NotificationError> {
+    let insert_sql = format!(
+        "INSERT INTO {MESSAGES_TABLE} (aggregate_id, sequence, message) VALUES ($1, $2, $3)"
+    );
+    sqlx::query(&insert_sql)
+        .bind(aggregate_id)
+        .bind(sequence)
+        .bind(serde_json::to_value(message)?)
+        .execute(&mut *tx)
+        .await?;
+    Ok(())
+}
+
+/// Time beyond the send timeout for which a claimed message is leased, to record its outcome after
+/// it is sent.
+const LEASE_MARGIN: Duration = Duration::from_secs(30);
+
+#[derive(Debug)]
+struct PendingMessage {
+    id: i64,
+    attempts: i32,
+    message: serde_json::Value,
+}
+
+/// Sends the due pending messages, returning the number attempted. Each message is claimed by
+/// leasing it until its next attempt before it is sent, so several instances can send concurrently
+/// without sending a message twice, and its outcome is recorded on its own so a failure does not
+/// undo the messages already sent. Failed sends are retried with backoff, until the message
+/// exhausts its attempts and is marked failed.
+#[tracing::instrument(level = "debug", skip(pool, mailer, settings))]
+async fn send_batch(
+    pool: &PgPool, mailer: &NotificationMailer, settings: &NotificationSettings,
+) -> Result<usize, NotificationError> {
+    let lease = settings.send_timeout + LEASE_MARGIN;
+    let batch_size = usize::try_from(settings.batch_size).unwrap_or_default();
+    let mut nr_attempted = 0;
+
+    while nr_attempted < batch_size {
+        let (pending, leased_until) = match claim_due_message(pool, lease).await? {
+            Some(claimed) => claimed,
+            None => break,
+        };
+
+        let outcome = send(mailer, &pending.message, settings.send_timeout).await;
+        let is_recorded = record_outcome(pool, &pending, leased_until, outcome, settings).await?;
+        if !is_recorded {
+            tracing::warn!(
+                message_id=%pending.id, %leased_until,
+                "notification lease expired before its outcome was recorded -- left to its new lease"
+            );
+        }
+        nr_attempted += 1;
+    }
+
+    Ok(nr_attempted)
+}
+
+/// Claims the next due pending message by leasing it, deferring its next attempt until the lease
+/// expires in case its outcome is never recorded.
+async fn claim_due_message(
+    pool: &PgPool, lease: Duration,
+) -> Result<Option<(PendingMessage, DateTime<Utc>)>, sqlx::Error> {
+    let update_sql = format!(
+        r##"UPDATE {MESSAGES_TABLE}
+        SET next_attempt_at = now() + make_interval(secs => $2)
+        WHERE id = (
+            SELECT id FROM {MESSAGES_TABLE}
+            WHERE status = $1 AND next_attempt_at <= now()
+            ORDER BY id
+            LIMIT 1
+            FOR UPDATE SKIP LOCKED
+        )
+        RETURNING id, message, attempts, next_attempt_at"##
+    );
+
+    let claimed = sqlx::query(&update_sql)
+        .bind(MessageStatus::Pending.to_string())
+        .bind(lease.as_secs_f64())
+        .fetch_optional(pool)
+        .await?;
+
+    claimed
+        .map(|row| {
+            let pending = PendingMessage {
+                id: row.try_get("id")?,
+                attempts: row.try_get("attempts")?,
+                message: row.try_get("message")?,
+            };
+            Ok((pending, row.try_get("next_attempt_at")?))
+        })
+        .transpose()
+}
+
+async fn send(
+    mailer: &NotificationMailer, message: &serde_json::Value, timeout: Duration,
+) -> Result<(), NotificationError> {
+    let message: EmailMessage = serde_json::from_value(message.clone())?;
+    tokio::time::timeout(timeout, mailer.send(&message))
+        .await
+        .unwrap_or_else(|_elapsed| Err(NotificationError::Timeout(timeout)))
+}
+
+/// Records the outcome of sending the message under the worker's lease, returning whether it was
+/// recorded. An outcome is not recorded once the lease expired and the message was claimed again,
+/// so a stalled worker does not overwrite the outcome recorded under the newer lease.
+async fn record_outcome(
+    pool: &PgPool, pending: &PendingMessage, leased_until: DateTime<Utc>,
+    outcome: Result<(), NotificationError>, settings: &NotificationSettings,
+) -> Result<bool, sqlx::Error> {
+    let result = match outcome {
+        Ok(()) => {
+            let update_sql = format!(
+                r##"UPDATE {MESSAGES_TABLE}
+                SET status = $3, attempts = attempts + 1, last_error = NULL, sent_at = now()
+                WHERE id = $1 AND status = $4 AND next_attempt_at = $2"##
+            );
+            sqlx::query(&update_sql)
+                .bind(pending.id)
+                .bind(leased_until)
+                .bind(MessageStatus::Sent.to_string())
+                .bind(MessageStatus::Pending.to_string())
+                .execute(pool)
+                .await?
+        },
+        Err(error) => {
+            let attempts = pending.attempts.saturating_add(1);
+            let retry_delay = settings.backoff.delay_for(attempts.unsigned_abs());
+            let status = if settings.max_attempts <= attempts.unsigned_abs() {
+                tracing::error!(
+                    message_id=%pending.id, %attempts, ?error,
+                    "notification exhausted its attempts -- marked failed"
+                );
+                MessageStatus::Failed
+            } else {
+                tracing::warn!(
+                    message_id=%pending.id, %attempts, ?retry_delay, ?error,
+                    "failed to send notification -- will retry"
+                );
+                MessageStatus::Pending
+            };
+
+            let update_sql = format!(
+                r##"UPDATE {MESSAGES_TABLE}
+                SET status = $3, attempts = $4, last_error = $5,
+                    next_attempt_at = now() + make_interval(secs => $6)
+                WHERE id = $1 AND status = $7 AND next_attempt_at = $2"##
+            );
+            sqlx::query(&update_sql)
+                .bind(pending.id)
+                .bind(leased_until)
+                .bind(status.to_string())
+                .bind(attempts)
+                .bind(error.to_string())
+                .bind(retry_delay.as_secs_f64())
+                .bind(MessageStatus::Pending.to_string())
+                .execute(pool)
+                .await?
+        },
+    };
+
+    Ok(0 < result.rows_affected())
+}
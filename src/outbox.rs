@@ -0,0 +1,79 @@
+//! Transactional outbox of account events for external consumers.
+//!
+//! Every insert of an account event into the `events` table also writes an `event_outbox` row in
+//! the same transaction (see the `events_enqueue_outbox` trigger), so an account event is committed
+//! if and only if it is queued for publication. Events of the other aggregates sharing the table,
+//! such as customers and disputes, are not queued. The relay task publishes queued rows, in order
+//! per aggregate, to the configured [EventPublisher] and retries failures with backoff, which
+//! provides at-least-once delivery. Consumers should deduplicate on `(aggregate_id, sequence)`.
+//! Account events carry the holder's name, email and mailing address, so the publisher endpoint
+//! receives that personal data.
+//!
+//! The pruner task deletes rows past the configured retention period once every enabled outbox
+//! consumer, i.e., the relay, webhook dispatch and notifications, has handled them.
+
+mod publishers;
+mod relay;
+mod retention;
+
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use thiserror::Error;
+
+pub use publishers::{EventPublisher, OutboxPublisher};
+pub use relay::spawn_relay;
+pub use retention::{spawn_pruner, OutboxConsumer};
+
+pub const OUTBOX_TABLE: &str = "event_outbox";
+
+/// An event queued for publication, as delivered to external consumers.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct OutboxMessage {
+    #[serde(skip)]
+    pub outbox_id: i64,
+    pub aggregate_type: String,
+    pub aggregate_id: String,
+    pub sequence: i64,
+    pub event_type: String,
+    pub event_version: String,
+    pub payload: serde_json::Value,
+    pub metadata: serde_json::Value,
+    pub recorded_at: DateTime<Utc>,
+}
+
+impl OutboxMessage {
+    /// Stable identifier consumers may use to deduplicate redelivered events.
+    pub fn event_id(&self) -> String {
+        format!("{}-{}", self.aggregate_id, self.sequence)
+    }
+}
+
+#[derive(Debug, Error)]
+pub enum PublishError {
+    #[error("failed to send event to publisher endpoint: {0}")]
+    Http(#[from] reqwest::Error),
+
+    #[error("publisher endpoint rejected event with status {status}: {body}")]
+    Rejected {
+        status: reqwest::StatusCode,
+        body: String,
+    },
+
+    #[error("invalid publisher endpoint: {0}")]
+    Endpoint(#[from] url::ParseError),
+
+    #[error("failed to write event: {0}")]
+    IO(#[from] std::io::Error),
+
+    #[error("failed to serialize event: {0}")]
+    Serialization(#[from] serde_json::Error),
+}
+
+#[derive(Debug, Error)]
+pub enum OutboxError {
+    #[error("{0}")]
+    Publish(#[from] PublishError),
+
+    #[error("failed outbox database operation: {0}")]
+    Sql(#[from] sqlx::Error),
+}
@@ -0,0 +1,287 @@
+use super::{OutboxMessage, PublishError};
+use crate::settings::{OutboxSettings, PublisherSettings};
+use async_trait::async_trait;
+use serde_json::json;
+use std::path::PathBuf;
+use tokio::io::AsyncWriteExt;
+use url::Url;
+
+#[async_trait]
+pub trait EventPublisher: Sync + Send {
+    /// Publishes the event, returning only once the consumer has accepted it.
+    async fn publish(&self, message: &OutboxMessage) -> Result<(), PublishError>;
+}
+
+#[derive(Debug, Clone)]
+pub enum OutboxPublisher {
+    Stdout(StdoutPublisher),
+    File(FilePublisher),
+    Webhook(WebhookPublisher),
+    KafkaRest(KafkaRestPublisher),
+}
+
+impl OutboxPublisher {
+    pub fn from_settings(settings: &OutboxSettings) -> Result<Self, PublishError> {
+        let make_client = || reqwest::Client::builder().timeout(settings.request_timeout).build();
+
+        let publisher = match &settings.publisher {
+            PublisherSettings::Stdout => StdoutPublisher.into(),
+            PublisherSettings::File { path } => FilePublisher::new(path.clone()).into(),
+            PublisherSettings::Webhook { url } => {
+                WebhookPublisher::new(make_client()?, url.clone()).into()
+            },
+            PublisherSettings::KafkaRest { base_url, topic } => {
+                KafkaRestPublisher::new(make_client()?, base_url, topic)?.into()
+            },
+        };
+
+        Ok(publisher)
+    }
+}
+
+#[async_trait]
+impl EventPublisher for OutboxPublisher {
+    async fn publish(&self, message: &OutboxMessage) -> Result<(), PublishError> {
+        match self {
+            Self::Stdout(publisher) => publisher.publish(message).await,
+            Self::File(publisher) => publisher.publish(message).await,
+            Self::Webhook(publisher) => publisher.publish(message).await,
+            Self::KafkaRest(publisher) => publisher.publish(message).await,
+        }
+    }
+}
+
+#[derive(Debug, Copy, Clone)]
+pub struct StdoutPublisher;
+
+#[async_trait]
+impl EventPublisher for StdoutPublisher {
+    async fn publish(&self, message: &OutboxMessage) -> Result<(), PublishError> {
+        let line = serde_json::to_string(message)?;
+        let mut stdout = tokio::io::stdout();
+        stdout.write_all(format!("{line}\n").as_bytes()).await?;
+        stdout.flush().await?;
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct FilePublisher {
+    path: PathBuf,
+}
+
+impl FilePublisher {
+    pub const fn new(path: PathBuf) -> Self {
+        Self { path }
+    }
+}
+
+#[async_trait]
+impl EventPublisher for FilePublisher {
+    async fn publish(&self, message: &OutboxMessage) -> Result<(), PublishError> {
+        let line = serde_json::to_string(message)?;
+        let mut file = tokio::fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)
+            .await?;
+        file.write_all(format!("{line}\n").as_bytes()).await?;
+        file.sync_data().await?;
+        Ok(())
+    }
+}
+
+pub const EVENT_ID_HEADER: &str = "x-event-id";
+pub const EVENT_TYPE_HEADER: &str = "x-event-type";
+
+#[derive(Debug, Clone)]
+pub struct WebhookPublisher {
+    client: reqwest::Client,
+    url: Url,
+}
+
+impl WebhookPublisher {
+    pub const fn new(client: reqwest::Client, url: Url) -> Self {
+        Self { client, url }
+    }
+}
+
+#[async_trait]
+impl EventPublisher for WebhookPublisher {
+    async fn publish(&self, message: &OutboxMessage) -> Result<(), PublishError> {
+        let response = self
+            .client
+            .post(self.url.clone())
+            .header(EVENT_ID_HEADER, message.event_id())
+            .header(EVENT_TYPE_HEADER, message.event_type.as_str())
+            .json(message)
+            .send()
+            .await?;
+
+        check_response(response).await.map(|_| ())
+    }
+}
+
+const KAFKA_JSON_CONTENT_TYPE: &str = "application/vnd.kafka.json.v2+json";
+
+/// Produces events to a Kafka topic through the Confluent REST proxy v2 API. Records are keyed by
+/// aggregate id so Kafka's per-partition ordering preserves the per-aggregate event order.
+#[derive(Debug, Clone)]
+pub struct KafkaRestPublisher {
+    client: reqwest::Client,
+    topic_url: Url,
+}
+
+impl KafkaRestPublisher {
+    pub fn new(client: reqwest::Client, base_url: &Url, topic: &str) -> Result<Self, PublishError> {
+        let topic_url = base_url.join(&format!("topics/{topic}"))?;
+        Ok(Self { client, topic_url })
+    }
+}
+
+#[async_trait]
+impl EventPublisher for KafkaRestPublisher {
+    async fn publish(&self, message: &OutboxMessage) -> Result<(), PublishError> {
+        let body = serde_json::to_vec(&json!({
+            "records": [{ "key": message.aggregate_id, "value": message }]
+        }))?;
+
+        let response = self
+            .client
+            .post(self.topic_url.clone())
+            .header(reqwest::header::CONTENT_TYPE, KAFKA_JSON_CONTENT_TYPE)
+            .header(reqwest::header::ACCEPT, "application/vnd.kafka.v2+json")
+            .body(body)
+            .send()
+            .await?;
+
+        let status = response.status();
+        let produced: serde_json::Value = check_response(response).await?.json().await?;
+        let record_error = produced["offsets"]
+            .as_array()
+            .and_then(|offsets| offsets.iter().find_map(|offset| offset["error"].as_str()));
+
+        record_error.map_or_else(
+            || Ok(()),
+            |error| Err(PublishError::Rejected { status, body: error.to_string() }),
+        )
+    }
+}
+
+async fn check_response(response: reqwest::Response) -> Result<reqwest::Response, PublishError> {
+    let status = response.status();
+    if status.is_success() {
+        Ok(response)
+    } else {
+        let body = response.text().await.unwrap_or_default();
+        Err(PublishError::Rejected { status, body })
+    }
+}
+
+impl From<StdoutPublisher> for OutboxPublisher {
+    fn from(publisher: StdoutPublisher) -> Self {
+        Self::Stdout(publisher)
+    }
+}
+
+impl From<FilePublisher> for OutboxPublisher {
+    fn from(publisher: FilePublisher) -> Self {
+        Self::File(publisher)
+    }
+}
+
+impl From<WebhookPublisher> for OutboxPublisher {
+    fn from(publisher: WebhookPublisher) -> Self {
+        Self::Webhook(publisher)
+    }
+}
+
+impl From<KafkaRestPublisher> for OutboxPublisher {
+    fn from(publisher: KafkaRestPublisher) -> Self {
+        Self::KafkaRest(publisher)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Utc;
+    use claim::{assert_err, assert_ok};
+    use pretty_assertions::assert_eq;
+    use wiremock::matchers::{body_partial_json, header, method, path};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    fn message() -> OutboxMessage {
+        OutboxMessage {
+            outbox_id: 1,
+            aggregate_type: "account".to_string(),
+            aggregate_id: "account::AAAA".to_string(),
+            sequence: 2,
+            event_type: "balance_deposited".to_string(),
+            event_version: "1.0".to_string(),
+            payload: json!({ "BalanceDeposited": { "amount": { "amount": "10.00", "currency": "USD" } } }),
+            metadata: json!({}),
+            recorded_at: Utc::now(),
+        }
+    }
+
+    fn kafka_publisher(server: &MockServer) -> KafkaRestPublisher {
+        let base_url = assert_ok!(Url::parse(&format!("{}/", server.uri())));
+        assert_ok!(KafkaRestPublisher::new(
+            reqwest::Client::new(),
+            &base_url,
+            "bank-account-events"
+        ))
+    }
+
+    #[tokio::test]
+    async fn test_kafka_rest_publisher_produces_keyed_record() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/topics/bank-account-events"))
+            .and(header("content-type", KAFKA_JSON_CONTENT_TYPE))
+            .and(body_partial_json(json!({
+                "records": [{ "key": "account::AAAA", "value": { "sequence": 2 } }]
+            })))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "offsets": [{ "partition": 0, "offset": 17, "error_code": null, "error": null }]
+            })))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        assert_ok!(kafka_publisher(&server).publish(&message()).await);
+    }
+
+    #[tokio::test]
+    async fn test_kafka_rest_publisher_fails_on_record_error() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "offsets": [{ "partition": null, "offset": null, "error_code": 50002, "error": "broker unavailable" }]
+            })))
+            .mount(&server)
+            .await;
+
+        let error = assert_err!(kafka_publisher(&server).publish(&message()).await);
+        assert_eq!(
+            error.to_string(),
+            "publisher endpoint rejected event with status 200 OK: broker unavailable"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_webhook_publisher_fails_on_error_status() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(header(EVENT_ID_HEADER, "account::AAAA-2"))
+            .respond_with(ResponseTemplate::new(503))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let url = assert_ok!(Url::parse(&server.uri()));
+        let publisher = WebhookPublisher::new(reqwest::Client::new(), url);
+        assert_err!(publisher.publish(&message()).await);
+    }
+}
@@ -0,0 +1,176 @@
+use super::{EventPublisher, OutboxError, OutboxMessage, OutboxPublisher, OUTBOX_TABLE};
+use crate::settings::OutboxSettings;
+use sqlx::{PgConnection, PgPool, Row};
+use std::collections::HashSet;
+use tokio::task::JoinHandle;
+use tokio::time::MissedTickBehavior;
+
+/// Advisory lock key held while relaying, so only one instance publishes at a time and the
+/// per-aggregate ordering of events is preserved across replicas.
+const OUTBOX_RELAY_LOCK: i64 = 0x6f75_7462_6f78; // "outbox"
+
+pub fn spawn_relay(
+    pool: PgPool, settings: OutboxSettings, publisher: OutboxPublisher,
+) -> JoinHandle<()> {
+    tokio::spawn(async move {
+        tracing::info!(publisher=?settings.publisher, "starting event outbox relay...");
+        let mut interval = tokio::time::interval(settings.poll_interval);
+        interval.set_missed_tick_behavior(MissedTickBehavior::Delay);
+
+        loop {
+            interval.tick().await;
+            match relay_batch(&pool, &publisher, &settings).await {
+                Ok(0) => {},
+                Ok(nr_published) => tracing::debug!(%nr_published, "relayed outbox events"),
+                Err(error) => tracing::error!(?error, "event outbox relay pass failed"),
+            }
+        }
+    })
+}
+
+/// Publishes one batch of pending outbox rows, returning the number successfully published.
+///
+/// Only aggregates whose earliest unpublished row is due are selected, so an aggregate waiting to
+/// retry does not take up the batch of the others. Once an aggregate's row fails, its later rows
+/// are held back until the next pass so that consumers see each aggregate's events in sequence.
+///
+/// The relay lock is a session lock on a dedicated connection rather than a transaction lock, and
+/// each row is marked as soon as it is published, so no transaction is open across publishes.
+#[tracing::instrument(level = "debug", skip(pool, publisher, settings))]
+pub async fn relay_batch<P: EventPublisher>(
+    pool: &PgPool, publisher: &P, settings: &OutboxSettings,
+) -> Result<usize, OutboxError> {
+    let mut conn = pool.acquire().await?;
+    let is_relay_leader: bool = sqlx::query_scalar("SELECT pg_try_advisory_lock($1)")
+        .bind(OUTBOX_RELAY_LOCK)
+        .fetch_one(&mut *conn)
+        .await?;
+
+    if !is_relay_leader {
+        tracing::debug!("another instance is relaying the event outbox");
+        return Ok(0);
+    }
+
+    let result = relay_pending(&mut conn, publisher, settings).await;
+
+    let unlocked = sqlx::query("SELECT pg_advisory_unlock($1)")
+        .bind(OUTBOX_RELAY_LOCK)
+        .execute(&mut *conn)
+        .await;
+    if let Err(error) = &unlocked {
+        // closing the connection releases its session lock instead of returning it to the pool
+        tracing::warn!(
+            ?error,
+            "failed to release event outbox relay lock -- closing its connection"
+        );
+        drop(conn.detach());
+    }
+    unlocked?;
+
+    result
+}
+
+async fn relay_pending<P: EventPublisher>(
+    conn: &mut PgConnection, publisher: &P, settings: &OutboxSettings,
+) -> Result<usize, OutboxError> {
+    let pending = load_pending(conn, settings.batch_size).await?;
+    let mut held_aggregates = HashSet::new();
+    let mut nr_published = 0;
+
+    for (message, attempts) in pending {
+        let aggregate = (message.aggregate_type.clone(), message.aggregate_id.clone());
+        if held_aggregates.contains(&aggregate) {
+            continue;
+        }
+
+        match publisher.publish(&message).await {
+            Ok(()) => {
+                mark_published(conn, message.outbox_id).await?;
+                nr_published += 1;
+            },
+            Err(error) => {
+                let attempts = attempts.saturating_add(1);
+                let retry_delay = settings.backoff.delay_for(attempts.unsigned_abs());
+                tracing::warn!(
+                    event_id=%message.event_id(), %attempts, ?retry_delay, ?error,
+                    "failed to publish outbox event -- will retry"
+                );
+                mark_failed(conn, message.outbox_id, &error.to_string(), retry_delay).await?;
+                held_aggregates.insert(aggregate);
+            },
+        }
+    }
+
+    Ok(nr_published)
+}
+
+/// Loads the unpublished rows, in insertion order, of the aggregates whose earliest unpublished
+/// row is due for an attempt.
+async fn load_pending(
+    conn: &mut PgConnection, batch_size: i64,
+) -> Result<Vec<(OutboxMessage, i32)>, sqlx::Error> {
+    let select_sql = format!(
+        r##"WITH due_aggregates AS (
+            SELECT aggregate_type, aggregate_id
+            FROM (
+                SELECT DISTINCT ON (aggregate_type, aggregate_id)
+                    aggregate_type, aggregate_id, next_attempt_at
+                FROM {OUTBOX_TABLE}
+                WHERE published_at IS NULL
+                ORDER BY aggregate_type, aggregate_id, id
+            ) AS heads
+            WHERE next_attempt_at <= now()
+        )
+        SELECT o.id, o.aggregate_type, o.aggregate_id, o.sequence, o.event_type, o.event_version,
+            o.payload, o.metadata, o.created_at, o.attempts
+        FROM {OUTBOX_TABLE} o
+        JOIN due_aggregates d ON d.aggregate_type = o.aggregate_type AND d.aggregate_id = o.aggregate_id
+        WHERE o.published_at IS NULL
+        ORDER BY o.id
+        LIMIT $1"##
+    );
+
+    let rows = sqlx::query(&select_sql).bind(batch_size).fetch_all(&mut *conn).await?;
+    rows.into_iter()
+        .map(|row| {
+            let message = OutboxMessage {
+                outbox_id: row.try_get("id")?,
+                aggregate_type: row.try_get("aggregate_type")?,
+                aggregate_id: row.try_get("aggregate_id")?,
+                sequence: row.try_get("sequence")?,
+                event_type: row.try_get("event_type")?,
+                event_version: row.try_get("event_version")?,
+                payload: row.try_get("payload")?,
+                metadata: row.try_get("metadata")?,
+                recorded_at: row.try_get("created_at")?,
+            };
+            Ok((message, row.try_get("attempts")?))
+        })
+        .collect()
+}
+
+async fn mark_published(conn: &mut PgConnection, outbox_id: i64) -> Result<(), sqlx::Error> {
+    let update_sql =
+        format!("UPDATE {OUTBOX_TABLE} SET published_at = now(), last_error = NULL WHERE id = $1");
+    sqlx::query(&update_sql).bind(outbox_id).execute(&mut *conn).await?;
+    Ok(())
+}
+
+async fn mark_failed(
+    conn: &mut PgConnection, outbox_id: i64, error: &str, retry_delay: std::time::Duration,
+) -> Result<(), sqlx::Error> {
+    let update_sql = format!(
+        r##"UPDATE {OUTBOX_TABLE}
+        SET attempts = attempts + 1,
+            last_error = $2,
+            next_attempt_at = now() + make_interval(secs => $3)
+        WHERE id = $1"##
+    );
+    sqlx::query(&update_sql)
+        .bind(outbox_id)
+        .bind(error)
+        .bind(retry_delay.as_secs_f64())
+        .execute(&mut *conn)
+        .await?;
+    Ok(())
+}
@@ -0,0 +1,85 @@
+use super::OUTBOX_TABLE;
+use crate::settings::OutboxSettings;
+use sqlx::PgPool;
+use std::time::Duration;
+use tokio::task::JoinHandle;
+use tokio::time::MissedTickBehavior;
+
+/// Maximum number of outbox rows deleted in each statement, so a pass does not hold a long lock.
+const PRUNE_BATCH_SIZE: i64 = 1_000;
+
+/// A task reading the event outbox, which marks the rows it has handled in its own column.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OutboxConsumer {
+    Relay,
+    Webhooks,
+    Notifications,
+}
+
+impl OutboxConsumer {
+    const fn handled_column(&self) -> &'static str {
+        match self {
+            Self::Relay => "published_at",
+            Self::Webhooks => "webhooks_dispatched_at",
+            Self::Notifications => "notifications_dispatched_at",
+        }
+    }
+}
+
+pub fn spawn_pruner(
+    pool: PgPool, settings: OutboxSettings, consumers: Vec<OutboxConsumer>,
+) -> JoinHandle<()> {
+    tokio::spawn(async move {
+        tracing::info!(
+            retention=?settings.retention, ?consumers,
+            "starting event outbox pruner..."
+        );
+        let mut interval = tokio::time::interval(settings.prune_interval);
+        interval.set_missed_tick_behavior(MissedTickBehavior::Delay);
+
+        loop {
+            interval.tick().await;
+            match prune(&pool, settings.retention, &consumers).await {
+                Ok(0) => {},
+                Ok(nr_pruned) => tracing::debug!(%nr_pruned, "pruned event outbox rows"),
+                Err(error) => tracing::error!(?error, "event outbox prune pass failed"),
+            }
+        }
+    })
+}
+
+/// Deletes the outbox rows older than the `retention` period that each of the `consumers` has
+/// handled, returning the number deleted. Rows still pending for a consumer are kept however old
+/// they are, so pruning never drops an event an enabled consumer has yet to see.
+#[tracing::instrument(level = "debug", skip(pool))]
+pub async fn prune(
+    pool: &PgPool, retention: Duration, consumers: &[OutboxConsumer],
+) -> Result<u64, sqlx::Error> {
+    let handled: String = consumers
+        .iter()
+        .map(|consumer| format!(" AND {} IS NOT NULL", consumer.handled_column()))
+        .collect();
+    let delete_sql = format!(
+        r##"DELETE FROM {OUTBOX_TABLE}
+        WHERE id IN (
+            SELECT id FROM {OUTBOX_TABLE}
+            WHERE created_at < now() - make_interval(secs => $1){handled}
+            ORDER BY id
+            LIMIT $2
+            FOR UPDATE SKIP LOCKED
+        )"##
+    );
+
+    let mut nr_pruned = 0;
+    loop {
+        let result = sqlx::query(&delete_sql)
+            .bind(retention.as_secs_f64())
+            .bind(PRUNE_BATCH_SIZE)
+            .execute(pool)
+            .await?;
+        nr_pruned += result.rows_affected();
+        if result.rows_affected() < PRUNE_BATCH_SIZE.unsigned_abs() {
+            return Ok(nr_pruned);
+        }
+    }
+}
@@ -0,0 +1,169 @@
+use crate::model::{
+    Customer, CustomerEvent, CustomerId, DocumentReference, EmailAddress, KycStatus, MailingAddress,
+};
+use crate::queries::RECV_TIMESTAMP;
+use chrono::{DateTime, Utc};
+use cqrs_es::persist::GenericQuery;
+use cqrs_es::{EventEnvelope, View};
+use postgres_es::PostgresViewRepository;
+use pretty_snowflake::Id;
+use serde::{Deserialize, Serialize};
+use sqlx::{PgPool, Row};
+use std::sync::Arc;
+use utoipa::{IntoParams, ToSchema};
+
+pub const CUSTOMER_QUERY_VIEW: &str = "customer_query";
+
+pub type CustomerViewRepository = PostgresViewRepository<CustomerView, Customer>;
+pub type CustomerViewProjection = Arc<CustomerViewRepository>;
+
+/// Serialize and persist the customer view after it is updated.
+pub type CustomerQuery = GenericQuery<CustomerViewRepository, CustomerView, Customer>;
+
+#[derive(Debug, Default, Clone, PartialEq, Eq, ToSchema, Serialize, Deserialize)]
+pub struct CustomerView {
+    pub customer_id: Option<CustomerId>,
+
+    /// Authenticated subject the customer signs in as.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub subject: Option<String>,
+
+    pub name: String,
+    pub email: Option<EmailAddress>,
+    pub mailing_address: Option<MailingAddress>,
+    pub kyc_status: KycStatus,
+
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub documents: Vec<DocumentReference>,
+
+    /// Provider that verified or rejected the customer's identity.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub verifier: Option<String>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rejection_reason: Option<String>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub registered_at: Option<DateTime<Utc>>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub updated_at: Option<DateTime<Utc>>,
+}
+
+impl View<Customer> for CustomerView {
+    fn update(&mut self, event: &EventEnvelope<Customer>) {
+        let recorded_at = event.metadata.get(RECV_TIMESTAMP).and_then(|ts| ts.parse().ok());
+
+        match &event.payload {
+            CustomerEvent::CustomerRegistered {
+                customer_id,
+                subject,
+                name,
+                email,
+                mailing_address,
+            } => {
+                self.customer_id = Some(*customer_id);
+                self.subject = subject.clone();
+                self.name = name.clone();
+                self.email = Some(email.clone());
+                self.mailing_address = Some(mailing_address.clone());
+                self.kyc_status = KycStatus::Pending;
+                self.registered_at = recorded_at;
+            },
+
+            CustomerEvent::DocumentSubmitted { document } => {
+                self.documents.push(document.clone());
+                self.kyc_status = KycStatus::Pending;
+                self.rejection_reason = None;
+            },
+
+            CustomerEvent::IdentityVerified { verifier } => {
+                self.kyc_status = KycStatus::Verified;
+                self.verifier = Some(verifier.clone());
+            },
+
+            CustomerEvent::IdentityRejected { verifier, reason } => {
+                self.kyc_status = KycStatus::Rejected;
+                self.verifier = Some(verifier.clone());
+                self.rejection_reason = Some(reason.clone());
+            },
+        }
+
+        self.updated_at = recorded_at;
+    }
+}
+
+/// Optional criteria used to narrow down the listed customers.
+#[derive(Debug, Default, Clone, PartialEq, Eq, IntoParams, Deserialize)]
+#[into_params(parameter_in = Query)]
+pub struct CustomerFilter {
+    /// Only include customers in this KYC standing, e.g., `pending` for the review queue.
+    #[param(value_type = Option<String>)]
+    pub kyc_status: Option<KycStatus>,
+}
+
+/// Lists the customers matching the filter, most recently registered first.
+#[tracing::instrument(level = "debug", skip(pool))]
+pub async fn list_customers(
+    pool: &PgPool, filter: &CustomerFilter, offset: i64, limit: i64,
+) -> Result<Vec<CustomerView>, sqlx::Error> {
+    let select_sql = format!(
+        r##"SELECT payload FROM {CUSTOMER_QUERY_VIEW}
+        WHERE $1::text IS NULL OR payload->>'kyc_status' = $1
+        ORDER BY (payload->>'customer_id')::bigint DESC
+        LIMIT $2 OFFSET $3"##
+    );
+
+    let rows = sqlx::query(&select_sql)
+        .bind(filter.kyc_status.map(|status| status.to_string()))
+        .bind(limit)
+        .bind(offset)
+        .fetch_all(pool)
+        .await?;
+
+    Ok(rows
+        .into_iter()
+        .filter_map(|row| match serde_json::from_value(row.get("payload")) {
+            Ok(view) => Some(view),
+            Err(err) => {
+                tracing::warn!(error=?err, "failed to read customer view payload");
+                None
+            },
+        })
+        .collect())
+}
+
+/// The customer's view, or `None` if no such customer is registered.
+#[tracing::instrument(level = "debug", skip(pool))]
+pub async fn load_customer(
+    pool: &PgPool, customer_id: CustomerId,
+) -> Result<Option<CustomerView>, sqlx::Error> {
+    let aggregate_id: Id<Customer> = customer_id.into();
+    let select_sql = format!("SELECT payload FROM {CUSTOMER_QUERY_VIEW} WHERE view_id = $1");
+    let row = sqlx::query(&select_sql)
+        .bind(aggregate_id.pretty())
+        .fetch_optional(pool)
+        .await?;
+
+    row.map(|row| {
+        serde_json::from_value::<CustomerView>(row.get("payload"))
+            .map_err(|err| sqlx::Error::Decode(err.into()))
+    })
+    .transpose()
+}
+
+/// The customer who signs in as the authenticated `subject`, if any is registered.
+#[tracing::instrument(level = "debug", skip(pool))]
+pub async fn find_customer_id(
+    pool: &PgPool, subject: &str,
+) -> Result<Option<CustomerId>, sqlx::Error> {
+    let select_sql = format!(
+        r##"SELECT (payload->>'customer_id')::bigint AS customer_id FROM {CUSTOMER_QUERY_VIEW}
+        WHERE payload->>'subject' = $1
+        ORDER BY (payload->>'customer_id')::bigint
+        LIMIT 1"##
+    );
+    let customer_id: Option<i64> =
+        sqlx::query_scalar(&select_sql).bind(subject).fetch_optional(pool).await?;
+    Ok(customer_id.map(CustomerId::new))
+}
@@ -0,0 +1,129 @@
+use crate::model::{
+    AccountId, Dispute, DisputeEvent, DisputeId, DisputeStatus, DisputedTransaction,
+};
+use crate::queries::RECV_TIMESTAMP;
+use chrono::{DateTime, Utc};
+use cqrs_es::persist::GenericQuery;
+use cqrs_es::{EventEnvelope, View};
+use money2::Money;
+use postgres_es::PostgresViewRepository;
+use serde::{Deserialize, Serialize};
+use sqlx::{PgPool, Row};
+use std::sync::Arc;
+use utoipa::{IntoParams, ToSchema};
+
+pub const DISPUTE_QUERY_VIEW: &str = "dispute_query";
+
+pub type DisputeViewRepository = PostgresViewRepository<DisputeView, Dispute>;
+pub type DisputeViewProjection = Arc<DisputeViewRepository>;
+
+/// Serialize and persist the dispute view after it is updated.
+pub type DisputeQuery = GenericQuery<DisputeViewRepository, DisputeView, Dispute>;
+
+#[derive(Debug, Default, Clone, PartialEq, Eq, ToSchema, Serialize, Deserialize)]
+pub struct DisputeView {
+    pub dispute_id: Option<DisputeId>,
+    pub account_id: Option<AccountId>,
+
+    /// Sequence number of the account event that posted the disputed transaction.
+    pub sequence: usize,
+
+    pub transaction: Option<DisputedTransaction>,
+    pub reason: String,
+    pub status: DisputeStatus,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[schema(value_type = Option<ApiMoney>)]
+    pub provisional_credit: Option<Money>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub resolution_note: Option<String>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub opened_at: Option<DateTime<Utc>>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub updated_at: Option<DateTime<Utc>>,
+}
+
+impl View<Dispute> for DisputeView {
+    fn update(&mut self, event: &EventEnvelope<Dispute>) {
+        let recorded_at = event.metadata.get(RECV_TIMESTAMP).and_then(|ts| ts.parse().ok());
+
+        match &event.payload {
+            DisputeEvent::DisputeOpened {
+                dispute_id,
+                account_id,
+                sequence,
+                transaction,
+                reason,
+            } => {
+                self.dispute_id = Some(*dispute_id);
+                self.account_id = Some(*account_id);
+                self.sequence = *sequence;
+                self.transaction = Some(transaction.clone());
+                self.reason = reason.clone();
+                self.status = DisputeStatus::Opened;
+                self.opened_at = recorded_at;
+            },
+
+            DisputeEvent::ProvisionalCreditIssued { amount } => {
+                self.status = DisputeStatus::ProvisionalCreditIssued;
+                self.provisional_credit = Some(*amount);
+            },
+
+            DisputeEvent::ResolvedForCustomer { note } => {
+                self.status = DisputeStatus::ResolvedForCustomer;
+                self.resolution_note = Some(note.clone());
+            },
+
+            DisputeEvent::ResolvedForBank { note } => {
+                self.status = DisputeStatus::ResolvedForBank;
+                self.provisional_credit = None;
+                self.resolution_note = Some(note.clone());
+            },
+        }
+
+        self.updated_at = recorded_at;
+    }
+}
+
+/// Optional criteria used to narrow down the listed disputes.
+#[derive(Debug, Default, Clone, PartialEq, Eq, IntoParams, Deserialize)]
+#[into_params(parameter_in = Query)]
+pub struct DisputeFilter {
+    /// Only include disputes of this account.
+    #[param(value_type = Option<i64>)]
+    pub account_id: Option<AccountId>,
+}
+
+/// Lists the disputes matching the filter, most recently opened first.
+#[tracing::instrument(level = "debug", skip(pool))]
+pub async fn list_disputes(
+    pool: &PgPool, filter: &DisputeFilter, offset: i64, limit: i64,
+) -> Result<Vec<DisputeView>, sqlx::Error> {
+    let select_sql = format!(
+        r##"SELECT payload FROM {DISPUTE_QUERY_VIEW}
+        WHERE $1::bigint IS NULL OR (payload->>'account_id')::bigint = $1
+        ORDER BY (payload->>'dispute_id')::bigint DESC
+        LIMIT $2 OFFSET $3"##
+    );
+
+    let rows = sqlx::query(&select_sql)
+        .bind(filter.account_id.map(|account_id| account_id.as_num()))
+        .bind(limit)
+        .bind(offset)
+        .fetch_all(pool)
+        .await?;
+
+    Ok(rows
+        .into_iter()
+        .filter_map(|row| match serde_json::from_value(row.get("payload")) {
+            Ok(view) => Some(view),
+            Err(err) => {
+                tracing::warn!(error=?err, "failed to read dispute view payload");
+                None
+            },
+        })
+        .collect())
+}
@@ -0,0 +1,378 @@
+use crate::errors::BankError;
+use crate::model::{bank_account, BankAccount};
+use crate::queries::BankAccountView;
+use anyhow::anyhow;
+use chrono::{DateTime, Utc};
+use cqrs_es::{Aggregate, EventEnvelope, View};
+use serde::Deserialize;
+use serde_with::{serde_as, DisplayFromStr};
+use sqlx::postgres::PgRow;
+use sqlx::{PgPool, Row};
+use std::collections::HashMap;
+use std::fmt;
+use std::str::FromStr;
+use utoipa::IntoParams;
+
+/// Table used by the postgres event store to persist aggregate events.
+pub const EVENTS_TABLE: &str = "events";
+
+/// Optional criteria used to narrow down an account's event stream.
+#[derive(Debug, Default, Clone, PartialEq, Eq, IntoParams, Deserialize)]
+#[into_params(parameter_in = Query)]
+pub struct EventFilter {
+    /// Only include events of this type; e.g., `balance_deposited`.
+    pub event_type: Option<String>,
+}
+
+/// A bank account event as it is persisted in the event store.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PersistedEvent {
+    pub sequence: i64,
+    pub event_type: String,
+    pub event_version: String,
+    pub payload: serde_json::Value,
+    pub metadata: serde_json::Value,
+}
+
+impl PersistedEvent {
+    /// The time the event store received the command that produced this event, if recorded in the
+    /// event metadata.
+    pub fn recorded_at(&self) -> Option<DateTime<Utc>> {
+        self.metadata
+            .get(RECV_TIMESTAMP)
+            .and_then(|ts| ts.as_str())
+            .and_then(|ts| ts.parse::<DateTime<Utc>>().ok())
+    }
+
+    fn from_row(row: &PgRow) -> Result<Self, sqlx::Error> {
+        Ok(Self {
+            sequence: row.try_get("sequence")?,
+            event_type: row.try_get("event_type")?,
+            event_version: row.try_get("event_version")?,
+            payload: row.try_get("payload")?,
+            metadata: row.try_get("metadata")?,
+        })
+    }
+
+    /// Converts the persisted row into the envelope form consumed by aggregates and views. A row
+    /// with a negative sequence is corrupt, and fails the conversion rather than being renumbered.
+    pub fn to_envelope(&self, aggregate_id: &str) -> Result<EventEnvelope<BankAccount>, BankError> {
+        let sequence = usize::try_from(self.sequence).map_err(|_| BankError::Deserialization {
+            source: anyhow!(
+                "event {} of {aggregate_id} has a negative sequence",
+                self.sequence
+            ),
+        })?;
+
+        let metadata: HashMap<String, String> = self
+            .metadata
+            .as_object()
+            .map(|meta| {
+                meta.iter()
+                    .map(|(key, value)| {
+                        let value = value
+                            .as_str()
+                            .map(|v| v.to_string())
+                            .unwrap_or_else(|| value.to_string());
+                        (key.clone(), value)
+                    })
+                    .collect()
+            })
+            .unwrap_or_default();
+
+        Ok(EventEnvelope {
+            aggregate_id: aggregate_id.to_string(),
+            sequence,
+            payload: serde_json::from_value(self.payload.clone())?,
+            metadata,
+        })
+    }
+}
+
+/// Event metadata key under which the time the command was received is recorded.
+pub const RECV_TIMESTAMP: &str = "recv_timestamp";
+
+/// Event metadata key under which the id of the queued command that produced the event is
+/// recorded.
+pub const COMMAND_ID: &str = "command_id";
+
+/// Point in an account's history, identified either by event sequence number or by the time the
+/// event was recorded.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum AsOf {
+    Sequence(i64),
+    Timestamp(DateTime<Utc>),
+}
+
+impl AsOf {
+    /// Whether the event precedes the point. An event without a valid recorded time cannot be
+    /// placed relative to a timestamp, so it fails the replay rather than being guessed at.
+    fn includes(&self, event: &PersistedEvent) -> Result<bool, BankError> {
+        match self {
+            Self::Sequence(sequence) => Ok(event.sequence <= *sequence),
+            Self::Timestamp(as_of) => {
+                let recorded_at =
+                    event.recorded_at().ok_or_else(|| BankError::Deserialization {
+                        source: anyhow!(
+                        "event {} has no valid {RECV_TIMESTAMP} to place it relative to {as_of}",
+                        event.sequence
+                    ),
+                    })?;
+                Ok(recorded_at <= *as_of)
+            },
+        }
+    }
+}
+
+impl fmt::Display for AsOf {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Sequence(sequence) => write!(f, "{sequence}"),
+            Self::Timestamp(timestamp) => write!(f, "{}", timestamp.to_rfc3339()),
+        }
+    }
+}
+
+impl FromStr for AsOf {
+    type Err = String;
+
+    fn from_str(rep: &str) -> Result<Self, Self::Err> {
+        if let Ok(sequence) = rep.parse::<i64>() {
+            return Ok(Self::Sequence(sequence));
+        }
+
+        DateTime::parse_from_rfc3339(rep)
+            .map(|ts| Self::Timestamp(ts.with_timezone(&Utc)))
+            .map_err(|err| {
+                format!(
+                    "as_of must be an event sequence or an RFC 3339 timestamp, not {rep}: {err}"
+                )
+            })
+    }
+}
+
+#[serde_as]
+#[derive(Debug, Default, Clone, PartialEq, Eq, IntoParams, Deserialize)]
+#[into_params(parameter_in = Query)]
+pub struct AsOfQuery {
+    /// Reconstruct the account as of this event sequence number or RFC 3339 timestamp rather than
+    /// serving the current projection.
+    #[param(value_type = Option<String>, example = "2023-01-05T17:30:00Z")]
+    #[serde_as(as = "Option<DisplayFromStr>")]
+    pub as_of: Option<AsOf>,
+}
+
+/// Replays the account's events up to the `as_of` point through the aggregate and view, without
+/// touching the persisted projection. Returns `None` if no events precede the point.
+pub fn replay_as_of(
+    aggregate_id: &str, events: &[PersistedEvent], as_of: &AsOf,
+) -> Result<Option<(BankAccount, BankAccountView)>, BankError> {
+    let mut replayed = None;
+
+    for event in events {
+        if !as_of.includes(event)? {
+            break;
+        }
+
+        let envelope = event.to_envelope(aggregate_id)?;
+        let (account, view): &mut (BankAccount, BankAccountView) =
+            replayed.get_or_insert_with(Default::default);
+        view.update(&envelope);
+        account.apply(envelope.payload);
+    }
+
+    Ok(replayed)
+}
+
+#[tracing::instrument(level = "debug", skip(pool))]
+pub async fn load_account_events(
+    pool: &PgPool, aggregate_id: &str, filter: &EventFilter, offset: i64, limit: Option<i64>,
+) -> Result<Vec<PersistedEvent>, sqlx::Error> {
+    let select_sql = format!(
+        r##"SELECT sequence, event_type, event_version, payload, metadata
+        FROM {EVENTS_TABLE}
+        WHERE aggregate_type = $1 AND aggregate_id = $2 AND ($3::text IS NULL OR event_type = $3)
+        ORDER BY sequence
+        LIMIT $4 OFFSET $5"##
+    );
+
+    let rows = sqlx::query(&select_sql)
+        .bind(bank_account::AGGREGATE_TYPE)
+        .bind(aggregate_id)
+        .bind(filter.event_type.as_deref())
+        .bind(limit)
+        .bind(offset)
+        .fetch_all(pool)
+        .await?;
+
+    rows.iter().map(PersistedEvent::from_row).collect()
+}
+
+#[tracing::instrument(level = "debug", skip(pool))]
+pub async fn load_account_event(
+    pool: &PgPool, aggregate_id: &str, sequence: i64,
+) -> Result<Option<PersistedEvent>, sqlx::Error> {
+    let select_sql = format!(
+        r##"SELECT sequence, event_type, event_version, payload, metadata
+        FROM {EVENTS_TABLE}
+        WHERE aggregate_type = $1 AND aggregate_id = $2 AND sequence = $3"##
+    );
+
+    let row = sqlx::query(&select_sql)
+        .bind(bank_account::AGGREGATE_TYPE)
+        .bind(aggregate_id)
+        .bind(sequence)
+        .fetch_optional(pool)
+        .await?;
+
+    row.as_ref().map(PersistedEvent::from_row).transpose()
+}
+
+/// Loads the account's events produced by the queued command, in sequence order.
+#[tracing::instrument(level = "debug", skip(pool))]
+pub async fn load_command_events(
+    pool: &PgPool, aggregate_id: &str, command_id: &str,
+) -> Result<Vec<PersistedEvent>, sqlx::Error> {
+    let select_sql = format!(
+        r##"SELECT sequence, event_type, event_version, payload, metadata
+        FROM {EVENTS_TABLE}
+        WHERE aggregate_type = $1 AND aggregate_id = $2 AND metadata->>'{COMMAND_ID}' = $3
+        ORDER BY sequence"##
+    );
+
+    let rows = sqlx::query(&select_sql)
+        .bind(bank_account::AGGREGATE_TYPE)
+        .bind(aggregate_id)
+        .bind(command_id)
+        .fetch_all(pool)
+        .await?;
+
+    rows.iter().map(PersistedEvent::from_row).collect()
+}
+
+#[tracing::instrument(level = "debug", skip(pool))]
+pub async fn account_has_events(pool: &PgPool, aggregate_id: &str) -> Result<bool, sqlx::Error> {
+    let select_sql = format!(
+        "SELECT EXISTS(SELECT 1 FROM {EVENTS_TABLE} WHERE aggregate_type = $1 AND aggregate_id = $2)"
+    );
+    sqlx::query_scalar(&select_sql)
+        .bind(bank_account::AGGREGATE_TYPE)
+        .bind(aggregate_id)
+        .fetch_one(pool)
+        .await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::AccountId;
+    use claim::{assert_err, assert_none, assert_ok, assert_some};
+    use money2::{Currency, Money};
+    use pretty_assertions::assert_eq;
+    use serde_json::json;
+
+    fn event(sequence: i64, payload: serde_json::Value, recorded_at: &str) -> PersistedEvent {
+        PersistedEvent {
+            sequence,
+            event_type: "test".to_string(),
+            event_version: "1.0".to_string(),
+            payload,
+            metadata: json!({ RECV_TIMESTAMP: recorded_at }),
+        }
+    }
+
+    fn account_history() -> Vec<PersistedEvent> {
+        vec![
+            event(
+                1,
+                json!({ "AccountOpened": {
+                    "account_id": 17,
+                    "user_name": "otis",
+                    "mailing_address": "12 Seahawks Way, Renton, WA 98056",
+                    "email": "otis@example.com"
+                }}),
+                "2023-01-05T10:00:00Z",
+            ),
+            event(
+                2,
+                json!({ "BalanceDeposited": { "amount": { "amount": "100.00", "currency": "USD" } } }),
+                "2023-01-05T11:00:00Z",
+            ),
+            event(
+                3,
+                json!({ "CashWithdrawal": { "amount": { "amount": "40.00", "currency": "USD" } } }),
+                "2023-01-05T12:00:00Z",
+            ),
+        ]
+    }
+
+    #[test]
+    fn test_as_of_parsing() {
+        assert_eq!(assert_ok!(AsOf::from_str("2")), AsOf::Sequence(2));
+        assert_eq!(
+            assert_ok!(AsOf::from_str("2023-01-05T11:30:00Z")),
+            AsOf::Timestamp(assert_ok!("2023-01-05T11:30:00Z".parse::<DateTime<Utc>>()))
+        );
+        assert_err!(AsOf::from_str("yesterday"));
+    }
+
+    #[test]
+    fn test_replay_as_of_sequence() {
+        let (_, view) = assert_some!(assert_ok!(replay_as_of(
+            "account::17",
+            &account_history(),
+            &AsOf::Sequence(2)
+        )));
+        assert_eq!(view.account_id, Some(AccountId::new(17)));
+        assert_eq!(view.balance, Money::new(10_000, 2, Currency::Usd));
+        assert_eq!(view.ledger.len(), 1);
+    }
+
+    #[test]
+    fn test_replay_as_of_timestamp() {
+        let as_of = assert_ok!(AsOf::from_str("2023-01-05T12:30:00Z"));
+        let (_, view) = assert_some!(assert_ok!(replay_as_of(
+            "account::17",
+            &account_history(),
+            &as_of
+        )));
+        assert_eq!(view.balance, Money::new(60_00, 2, Currency::Usd));
+        assert_eq!(view.ledger.len(), 2);
+
+        let before_opened = assert_ok!(AsOf::from_str("2023-01-04T00:00:00Z"));
+        assert_none!(assert_ok!(replay_as_of(
+            "account::17",
+            &account_history(),
+            &before_opened
+        )));
+    }
+
+    #[test]
+    fn test_replay_as_of_timestamp_requires_recorded_times() {
+        let mut history = account_history();
+        history[1].metadata = json!({});
+        let as_of = assert_ok!(AsOf::from_str("2023-01-05T12:30:00Z"));
+        assert_err!(replay_as_of("account::17", &history, &as_of));
+
+        history[1].metadata = json!({ RECV_TIMESTAMP: "last tuesday" });
+        assert_err!(replay_as_of("account::17", &history, &as_of));
+
+        assert_some!(assert_ok!(replay_as_of(
+            "account::17",
+            &history,
+            &AsOf::Sequence(2)
+        )));
+    }
+
+    #[test]
+    fn test_to_envelope_rejects_negative_sequence() {
+        let mut history = account_history();
+        assert_eq!(
+            assert_ok!(history[1].to_envelope("account::17")).sequence,
+            2
+        );
+
+        history[1].sequence = -2;
+        assert_err!(history[1].to_envelope("account::17"));
+    }
+}
@@ -0,0 +1,145 @@
+use crate::model::{
+    AccountHolder, AccountId, BankAccount, BankAccountEvent, CustomerId, HolderRole,
+};
+use async_trait::async_trait;
+use cqrs_es::{EventEnvelope, Query};
+use serde::{Deserialize, Serialize};
+use sqlx::{PgPool, Row};
+use std::str::FromStr;
+use utoipa::ToSchema;
+
+pub const ACCOUNT_HOLDERS_TABLE: &str = "account_holders";
+
+/// A customer's holding of an account.
+#[derive(Debug, Clone, PartialEq, Eq, ToSchema, Serialize, Deserialize)]
+pub struct AccountHolding {
+    pub account_id: AccountId,
+    pub customer_id: CustomerId,
+    pub role: HolderRole,
+}
+
+/// Maintains the lookup of account holders, by account and by customer, from committed account
+/// events.
+#[derive(Debug, Clone)]
+pub struct AccountHoldersQuery {
+    pool: PgPool,
+}
+
+impl AccountHoldersQuery {
+    pub const fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+
+    async fn record(
+        &self, aggregate_id: &str, event: &EventEnvelope<BankAccount>,
+    ) -> Result<(), sqlx::Error> {
+        match &event.payload {
+            BankAccountEvent::AccountOpened { account_id, customer_id, joint_holders, .. } => {
+                let insert_sql = format!(
+                    r##"INSERT INTO {ACCOUNT_HOLDERS_TABLE}
+                    (aggregate_id, account_id, customer_id, role)
+                    VALUES ($1, $2, $3, $4)
+                    ON CONFLICT DO NOTHING"##
+                );
+                let owner = customer_id.map(|id| AccountHolder::new(id, HolderRole::Owner));
+                for holder in owner.iter().chain(joint_holders.iter()) {
+                    sqlx::query(&insert_sql)
+                        .bind(aggregate_id)
+                        .bind(account_id.as_num())
+                        .bind(holder.customer_id.as_num())
+                        .bind(holder.role.to_string())
+                        .execute(&self.pool)
+                        .await?;
+                }
+            },
+
+            BankAccountEvent::AccountHolderAdded { holder, .. } => {
+                let insert_sql = format!(
+                    r##"INSERT INTO {ACCOUNT_HOLDERS_TABLE}
+                    (aggregate_id, account_id, customer_id, role)
+                    SELECT aggregate_id, account_id, $2, $3 FROM {ACCOUNT_HOLDERS_TABLE}
+                    WHERE aggregate_id = $1
+                    LIMIT 1
+                    ON CONFLICT (account_id, customer_id) DO UPDATE SET role = EXCLUDED.role"##
+                );
+                sqlx::query(&insert_sql)
+                    .bind(aggregate_id)
+                    .bind(holder.customer_id.as_num())
+                    .bind(holder.role.to_string())
+                    .execute(&self.pool)
+                    .await?;
+            },
+
+            BankAccountEvent::AccountHolderRemoved { customer_id, .. } => {
+                let delete_sql = format!(
+                    r##"DELETE FROM {ACCOUNT_HOLDERS_TABLE}
+                    WHERE aggregate_id = $1 AND customer_id = $2"##
+                );
+                sqlx::query(&delete_sql)
+                    .bind(aggregate_id)
+                    .bind(customer_id.as_num())
+                    .execute(&self.pool)
+                    .await?;
+            },
+
+            _ => {},
+        }
+
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl Query<BankAccount> for AccountHoldersQuery {
+    #[tracing::instrument(level = "debug", skip(self, events))]
+    async fn dispatch(&self, aggregate_id: &str, events: &[EventEnvelope<BankAccount>]) {
+        for event in events {
+            if let Err(error) = self.record(aggregate_id, event).await {
+                tracing::error!(
+                    ?error, sequence=%event.sequence,
+                    "failed to record account holders"
+                );
+            }
+        }
+    }
+}
+
+/// Lists the holders of the account, owner first.
+#[tracing::instrument(level = "debug", skip(pool))]
+pub async fn list_account_holders(
+    pool: &PgPool, account_id: AccountId,
+) -> Result<Vec<AccountHolding>, sqlx::Error> {
+    let select_sql = format!(
+        r##"SELECT account_id, customer_id, role FROM {ACCOUNT_HOLDERS_TABLE}
+        WHERE account_id = $1
+        ORDER BY role = 'owner' DESC, customer_id"##
+    );
+    let rows = sqlx::query(&select_sql).bind(account_id.as_num()).fetch_all(pool).await?;
+    rows.iter().map(holding_from_row).collect()
+}
+
+/// Lists the accounts the customer holds, in any role.
+#[tracing::instrument(level = "debug", skip(pool))]
+pub async fn list_customer_accounts(
+    pool: &PgPool, customer_id: CustomerId,
+) -> Result<Vec<AccountHolding>, sqlx::Error> {
+    let select_sql = format!(
+        r##"SELECT account_id, customer_id, role FROM {ACCOUNT_HOLDERS_TABLE}
+        WHERE customer_id = $1
+        ORDER BY account_id"##
+    );
+    let rows = sqlx::query(&select_sql).bind(customer_id.as_num()).fetch_all(pool).await?;
+    rows.iter().map(holding_from_row).collect()
+}
+
+fn holding_from_row(row: &sqlx::postgres::PgRow) -> Result<AccountHolding, sqlx::Error> {
+    let role: String = row.try_get("role")?;
+    Ok(AccountHolding {
+        account_id: AccountId::new(row.try_get::<i64, _>("account_id")?),
+        customer_id: CustomerId::new(row.try_get::<i64, _>("customer_id")?),
+        role: HolderRole::from_str(&role).map_err(|err| sqlx::Error::ColumnDecode {
+            index: "role".to_string(),
+            source: err.into(),
+        })?,
+    })
+}
@@ -1,7 +1,10 @@
 use crate::model;
-use crate::model::{AccountId, BankAccount};
-use crate::model::{BankAccountEvent, CheckNumber};
+use crate::model::{AccountId, AccountType, BankAccount};
+use crate::model::{
+    BankAccountEvent, CheckNumber, FeeKind, FlaggedTransactionEntry, FundsHold, PendingHolderChange,
+};
 use async_trait::async_trait;
+use chrono::{DateTime, Utc};
 use cqrs_es::persist::GenericQuery;
 use cqrs_es::{EventEnvelope, Query, View};
 use money2::{Currency, Money};
@@ -10,6 +13,33 @@ use serde::{Deserialize, Serialize};
 use std::sync::Arc;
 use utoipa::ToSchema;
 
+mod customer;
+mod dispute;
+mod history;
+mod holders;
+mod standing_order;
+
+pub use customer::{
+    find_customer_id, list_customers, load_customer, CustomerFilter, CustomerQuery, CustomerView,
+    CustomerViewProjection, CUSTOMER_QUERY_VIEW,
+};
+pub use dispute::{
+    list_disputes, DisputeFilter, DisputeQuery, DisputeView, DisputeViewProjection,
+    DISPUTE_QUERY_VIEW,
+};
+pub use history::{
+    account_has_events, load_account_event, load_account_events, load_command_events, replay_as_of,
+    AsOfQuery, EventFilter, PersistedEvent, COMMAND_ID, EVENTS_TABLE, RECV_TIMESTAMP,
+};
+pub use holders::{
+    list_account_holders, list_customer_accounts, AccountHoldersQuery, AccountHolding,
+    ACCOUNT_HOLDERS_TABLE,
+};
+pub use standing_order::{
+    list_standing_orders, StandingOrderFilter, StandingOrderQuery, StandingOrderView,
+    StandingOrderViewProjection, STANDING_ORDER_QUERY_VIEW,
+};
+
 pub type BankAccountViewRepository = PostgresViewRepository<BankAccountView, BankAccount>;
 pub type BankAccountViewProjection = Arc<BankAccountViewRepository>;
 
@@ -22,69 +52,389 @@ pub type AccountQuery = GenericQuery<BankAccountViewRepository, BankAccountView,
 #[derive(Debug, PartialEq, Eq, ToSchema, Serialize, Deserialize)]
 pub struct BankAccountView {
     pub account_id: Option<AccountId>,
+
+    /// The type whose policy governs the account's transactions.
+    #[serde(default)]
+    pub account_type: AccountType,
+
+    /// The settled (ledger) balance.
+    #[schema(value_type = ApiMoney)]
     pub balance: Money,
+
+    /// The ledger balance less funds reserved by pending holds.
+    #[serde(default)]
+    #[schema(value_type = ApiMoney)]
+    pub available_balance: Money,
+
     pub written_checks: Vec<CheckNumber>,
     pub ledger: Vec<LedgerEntry>,
+
+    /// Pending authorizations reserving funds.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub holds: Vec<FundsHold>,
+
+    /// Transactions held for fraud review.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub flagged_transactions: Vec<FlaggedTransactionEntry>,
+
+    /// Present while the account is frozen by compliance.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub freeze: Option<AccountFreeze>,
+
+    /// Audit trail of who froze and unfroze the account.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub freeze_history: Vec<FreezeAuditEntry>,
+
+    /// Holder changes requested by joint owners, awaiting the owner's approval.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub pending_holder_changes: Vec<PendingHolderChange>,
+
+    /// Present while the account is dormant and withdrawals are blocked.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub dormancy: Option<AccountDormancy>,
+
+    /// Fees waived for the account.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub fee_waivers: Vec<FeeKind>,
 }
 
 impl Default for BankAccountView {
     fn default() -> Self {
         Self {
             account_id: None,
+            account_type: AccountType::default(),
             balance: Money { currency: Currency::Usd, ..Default::default() },
+            available_balance: Money { currency: Currency::Usd, ..Default::default() },
             written_checks: Vec::default(),
             ledger: Vec::default(),
+            holds: Vec::default(),
+            flagged_transactions: Vec::default(),
+            freeze: None,
+            freeze_history: Vec::default(),
+            pending_holder_changes: Vec::default(),
+            dormancy: None,
+            fee_waivers: Vec::default(),
         }
     }
 }
 
-#[derive(Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[derive(Debug, PartialEq, Eq, ToSchema, Serialize, Deserialize)]
 pub struct LedgerEntry {
     pub description: String,
+    #[schema(value_type = ApiMoney)]
     pub amount: Money,
+
+    /// Sequence number of the event that posted the entry.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sequence: Option<usize>,
+
+    /// For a reversal, the sequence number of the entry it reverses.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reverses: Option<usize>,
+
+    /// For a reversed entry, the sequence number of its reversal.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reversed_by: Option<usize>,
 }
 
 impl LedgerEntry {
     pub fn new(description: impl Into<String>, amount: Money) -> Self {
-        Self { description: description.into(), amount }
+        Self {
+            description: description.into(),
+            amount,
+            sequence: None,
+            reverses: None,
+            reversed_by: None,
+        }
+    }
+
+    pub fn with_sequence(self, sequence: usize) -> Self {
+        Self { sequence: Some(sequence), ..self }
     }
 }
 
-fn make_neg_factor(currency: Currency) -> Money {
-    Money::new(-1, 0, currency)
+#[derive(Debug, Clone, PartialEq, Eq, ToSchema, Serialize, Deserialize)]
+pub struct AccountFreeze {
+    pub reason: String,
+    pub authority: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, ToSchema, Serialize, Deserialize)]
+pub struct AccountDormancy {
+    /// The latest customer-initiated activity on the account.
+    pub last_activity_at: DateTime<Utc>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub marked_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, ToSchema, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FreezeAction {
+    Frozen,
+    Unfrozen,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, ToSchema, Serialize, Deserialize)]
+pub struct FreezeAuditEntry {
+    pub sequence: usize,
+    pub action: FreezeAction,
+    pub authority: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reason: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub recorded_at: Option<DateTime<Utc>>,
+}
+
+impl FreezeAuditEntry {
+    fn from_envelope(
+        event: &EventEnvelope<BankAccount>, action: FreezeAction, authority: &str,
+        reason: Option<&str>,
+    ) -> Self {
+        Self {
+            sequence: event.sequence,
+            action,
+            authority: authority.to_string(),
+            reason: reason.map(|r| r.to_string()),
+            recorded_at: event.metadata.get(RECV_TIMESTAMP).and_then(|ts| ts.parse().ok()),
+        }
+    }
+}
+
+const fn fee_description(kind: FeeKind) -> &'static str {
+    match kind {
+        FeeKind::MonthlyMaintenance => "Monthly maintenance fee",
+        FeeKind::BelowMinimumBalance => "Below minimum balance fee",
+        FeeKind::Check => "Check fee",
+        FeeKind::ForeignAtm => "Foreign ATM fee",
+    }
+}
+
+impl BankAccountView {
+    fn find_entry_mut(&mut self, sequence: usize) -> Option<&mut LedgerEntry> {
+        self.ledger.iter_mut().find(|entry| entry.sequence == Some(sequence))
+    }
+
+    fn refresh_available_balance(&mut self) {
+        let currency = self.balance.currency;
+        self.available_balance = self.holds.iter().fold(self.balance, |available, hold| {
+            available - model::convert_amount(currency, hold.amount)
+        });
+    }
 }
 
 /// Updates the CQRS view with events as they are committed.
 impl View<BankAccount> for BankAccountView {
     fn update(&mut self, event: &EventEnvelope<BankAccount>) {
         match &event.payload {
-            BankAccountEvent::AccountOpened { account_id, .. } => {
+            BankAccountEvent::AccountOpened { account_id, account_type, .. } => {
                 self.account_id = Some(*account_id);
+                self.account_type = *account_type;
             },
 
+            BankAccountEvent::AccountTypeChanged { to, .. } => self.account_type = *to,
+
             BankAccountEvent::BalanceDeposited { amount } => {
-                self.ledger.push(LedgerEntry::new("deposit", *amount));
+                self.ledger
+                    .push(LedgerEntry::new("deposit", *amount).with_sequence(event.sequence));
                 let converted = model::convert_amount(self.balance.currency, *amount);
                 self.balance += converted;
             },
 
-            BankAccountEvent::CashWithdrawal { amount } => {
-                let debit = make_neg_factor(amount.currency) * *amount;
-                self.ledger.push(LedgerEntry::new("ATM withdrawal", debit));
+            BankAccountEvent::CashWithdrawal { amount, .. } => {
+                let debit = model::negate(*amount);
+                self.ledger
+                    .push(LedgerEntry::new("ATM withdrawal", debit).with_sequence(event.sequence));
                 let converted = model::convert_amount(self.balance.currency, *amount);
                 self.balance -= converted;
             },
 
-            BankAccountEvent::CheckWithdrawal { check_nr, amount } => {
-                let debit = make_neg_factor(amount.currency) * *amount;
-                self.ledger.push(LedgerEntry::new(format!("Check {check_nr}"), debit));
+            BankAccountEvent::CheckWithdrawal { check_nr, amount, .. } => {
+                let debit = model::negate(*amount);
+                self.ledger.push(
+                    LedgerEntry::new(format!("Check {check_nr}"), debit)
+                        .with_sequence(event.sequence),
+                );
                 self.written_checks.push(*check_nr);
                 let converted = model::convert_amount(self.balance.currency, *amount);
                 self.balance -= converted;
             },
 
+            BankAccountEvent::HoldPlaced { hold_id, amount, expires_at } => {
+                self.holds.push(FundsHold {
+                    hold_id: hold_id.clone(),
+                    amount: *amount,
+                    expires_at: *expires_at,
+                });
+            },
+
+            BankAccountEvent::HoldCaptured { hold_id, amount } => {
+                self.holds.retain(|hold| &hold.hold_id != hold_id);
+                let debit = model::negate(*amount);
+                self.ledger.push(
+                    LedgerEntry::new(format!("Hold {hold_id} captured"), debit)
+                        .with_sequence(event.sequence),
+                );
+                let converted = model::convert_amount(self.balance.currency, *amount);
+                self.balance -= converted;
+            },
+
+            BankAccountEvent::HoldReleased { hold_id }
+            | BankAccountEvent::HoldExpired { hold_id } => {
+                self.holds.retain(|hold| &hold.hold_id != hold_id);
+            },
+
+            BankAccountEvent::TransactionReversed { sequence, amount, reason } => {
+                let description = match self.find_entry_mut(*sequence) {
+                    Some(original) => {
+                        original.reversed_by = Some(event.sequence);
+                        format!("Reversal of {}: {reason}", original.description)
+                    },
+                    None => format!("Reversal of transaction {sequence}: {reason}"),
+                };
+
+                self.ledger.push(LedgerEntry {
+                    reverses: Some(*sequence),
+                    ..LedgerEntry::new(description, *amount).with_sequence(event.sequence)
+                });
+                let converted = model::convert_amount(self.balance.currency, *amount);
+                self.balance += converted;
+            },
+
+            BankAccountEvent::ProvisionalCreditIssued { dispute_id, sequence, amount } => {
+                if let Some(original) = self.find_entry_mut(*sequence) {
+                    original.reversed_by = Some(event.sequence);
+                }
+                self.ledger.push(LedgerEntry {
+                    reverses: Some(*sequence),
+                    ..LedgerEntry::new(
+                        format!("Provisional credit for dispute {dispute_id}"),
+                        *amount,
+                    )
+                    .with_sequence(event.sequence)
+                });
+                let converted = model::convert_amount(self.balance.currency, *amount);
+                self.balance += converted;
+            },
+
+            BankAccountEvent::ProvisionalCreditReversed {
+                dispute_id,
+                sequence,
+                credit_sequence,
+                amount,
+            } => {
+                if let Some(original) = self.find_entry_mut(*sequence) {
+                    original.reversed_by = None;
+                }
+                if let Some(credit) = self.find_entry_mut(*credit_sequence) {
+                    credit.reversed_by = Some(event.sequence);
+                }
+                self.ledger.push(LedgerEntry {
+                    reverses: Some(*credit_sequence),
+                    ..LedgerEntry::new(
+                        format!("Provisional credit for dispute {dispute_id} reversed"),
+                        *amount,
+                    )
+                    .with_sequence(event.sequence)
+                });
+                let converted = model::convert_amount(self.balance.currency, *amount);
+                self.balance += converted;
+            },
+
+            BankAccountEvent::StandingOrderDebited { order_id, amount, beneficiary, .. } => {
+                let debit = model::negate(*amount);
+                self.ledger.push(
+                    LedgerEntry::new(format!("Standing order {order_id} to {beneficiary}"), debit)
+                        .with_sequence(event.sequence),
+                );
+                let converted = model::convert_amount(self.balance.currency, *amount);
+                self.balance -= converted;
+            },
+
+            BankAccountEvent::StandingOrderCredited { order_id, amount, from_account, .. } => {
+                self.ledger.push(
+                    LedgerEntry::new(
+                        format!("Standing order {order_id} from account {from_account}"),
+                        *amount,
+                    )
+                    .with_sequence(event.sequence),
+                );
+                let converted = model::convert_amount(self.balance.currency, *amount);
+                self.balance += converted;
+            },
+
+            BankAccountEvent::TransactionFlagged { flag_id, transaction, reasons } => {
+                self.flagged_transactions.push(FlaggedTransactionEntry {
+                    flag_id: *flag_id,
+                    transaction: transaction.clone(),
+                    reasons: reasons.clone(),
+                });
+            },
+
+            BankAccountEvent::FlaggedTransactionReleased { flag_id }
+            | BankAccountEvent::FlaggedTransactionRejected { flag_id, .. } => {
+                self.flagged_transactions.retain(|entry| entry.flag_id != *flag_id);
+            },
+
+            BankAccountEvent::AccountFrozen { reason, authority } => {
+                self.freeze = Some(AccountFreeze {
+                    reason: reason.clone(),
+                    authority: authority.clone(),
+                });
+                self.freeze_history.push(FreezeAuditEntry::from_envelope(
+                    event,
+                    FreezeAction::Frozen,
+                    authority,
+                    Some(reason),
+                ));
+            },
+
+            BankAccountEvent::AccountUnfrozen { authority } => {
+                self.freeze = None;
+                self.freeze_history.push(FreezeAuditEntry::from_envelope(
+                    event,
+                    FreezeAction::Unfrozen,
+                    authority,
+                    None,
+                ));
+            },
+
+            BankAccountEvent::HolderChangeRequested { change_id, change, requested_by } => {
+                self.pending_holder_changes.push(PendingHolderChange {
+                    change_id: *change_id,
+                    change: change.clone(),
+                    requested_by: *requested_by,
+                });
+            },
+
+            BankAccountEvent::HolderChangeApproved { change_id, .. } => {
+                self.pending_holder_changes
+                    .retain(|pending| pending.change_id != *change_id);
+            },
+
+            BankAccountEvent::AccountMarkedDormant { last_activity_at } => {
+                self.dormancy = Some(AccountDormancy {
+                    last_activity_at: *last_activity_at,
+                    marked_at: event.metadata.get(RECV_TIMESTAMP).and_then(|ts| ts.parse().ok()),
+                });
+            },
+
+            BankAccountEvent::AccountReactivated { .. } => self.dormancy = None,
+
+            BankAccountEvent::FeeCharged { kind, amount } => {
+                let debit = model::negate(*amount);
+                self.ledger.push(
+                    LedgerEntry::new(fee_description(*kind), debit).with_sequence(event.sequence),
+                );
+                let converted = model::convert_amount(self.balance.currency, *amount);
+                self.balance -= converted;
+            },
+
+            BankAccountEvent::FeeWaiversSet { waivers } => self.fee_waivers = waivers.clone(),
+
             event => tracing::debug!(?event, "ignoring non-transactional event"),
         }
+
+        self.refresh_available_balance();
     }
 }
 
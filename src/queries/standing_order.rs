@@ -0,0 +1,159 @@
+use crate::model::{
+    AccountId, Beneficiary, Schedule, StandingOrder, StandingOrderEvent, StandingOrderId,
+    StandingOrderStatus,
+};
+use crate::queries::RECV_TIMESTAMP;
+use chrono::{DateTime, Utc};
+use cqrs_es::persist::GenericQuery;
+use cqrs_es::{EventEnvelope, View};
+use money2::Money;
+use postgres_es::PostgresViewRepository;
+use serde::{Deserialize, Serialize};
+use sqlx::{PgPool, Row};
+use std::sync::Arc;
+use utoipa::{IntoParams, ToSchema};
+
+pub const STANDING_ORDER_QUERY_VIEW: &str = "standing_order_query";
+
+pub type StandingOrderViewRepository = PostgresViewRepository<StandingOrderView, StandingOrder>;
+pub type StandingOrderViewProjection = Arc<StandingOrderViewRepository>;
+
+/// Serialize and persist the standing order view after it is updated.
+pub type StandingOrderQuery =
+    GenericQuery<StandingOrderViewRepository, StandingOrderView, StandingOrder>;
+
+#[derive(Debug, Default, Clone, PartialEq, Eq, ToSchema, Serialize, Deserialize)]
+pub struct StandingOrderView {
+    pub order_id: Option<StandingOrderId>,
+    pub account_id: Option<AccountId>,
+
+    #[schema(value_type = Option<ApiMoney>)]
+    pub amount: Option<Money>,
+
+    pub beneficiary: Option<Beneficiary>,
+    pub schedule: Option<Schedule>,
+    pub status: StandingOrderStatus,
+
+    /// When the next occurrence of a scheduled order comes due.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub next_due_at: Option<DateTime<Utc>>,
+
+    /// When payment of a declined occurrence is next attempted.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub retry_at: Option<DateTime<Utc>>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub last_paid_at: Option<DateTime<Utc>>,
+
+    /// Reason the latest payment attempt was declined, if it was.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub last_failure: Option<String>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub created_at: Option<DateTime<Utc>>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub updated_at: Option<DateTime<Utc>>,
+}
+
+impl StandingOrderView {
+    const fn advance(
+        &mut self, next_due_at: Option<DateTime<Utc>>, final_status: StandingOrderStatus,
+    ) {
+        self.next_due_at = next_due_at;
+        self.retry_at = None;
+        if next_due_at.is_none() {
+            self.status = final_status;
+        }
+    }
+}
+
+impl View<StandingOrder> for StandingOrderView {
+    fn update(&mut self, event: &EventEnvelope<StandingOrder>) {
+        let recorded_at = event.metadata.get(RECV_TIMESTAMP).and_then(|ts| ts.parse().ok());
+
+        match &event.payload {
+            StandingOrderEvent::StandingOrderCreated {
+                order_id,
+                account_id,
+                amount,
+                beneficiary,
+                schedule,
+                first_due_at,
+            } => {
+                self.order_id = Some(*order_id);
+                self.account_id = Some(*account_id);
+                self.amount = Some(*amount);
+                self.beneficiary = Some(beneficiary.clone());
+                self.schedule = Some(schedule.clone());
+                self.status = StandingOrderStatus::Scheduled;
+                self.next_due_at = Some(*first_due_at);
+                self.created_at = recorded_at;
+            },
+
+            StandingOrderEvent::StandingOrderPaid { next_due_at, .. } => {
+                self.advance(*next_due_at, StandingOrderStatus::Completed);
+                self.last_paid_at = recorded_at;
+                self.last_failure = None;
+            },
+
+            StandingOrderEvent::PaymentRetryScheduled { retry_at, reason, .. } => {
+                self.retry_at = Some(*retry_at);
+                self.last_failure = Some(reason.clone());
+            },
+
+            StandingOrderEvent::StandingOrderFailed { reason, next_due_at, .. } => {
+                self.advance(*next_due_at, StandingOrderStatus::Failed);
+                self.last_failure = Some(reason.clone());
+            },
+
+            StandingOrderEvent::StandingOrderCancelled => {
+                self.status = StandingOrderStatus::Cancelled;
+                self.next_due_at = None;
+                self.retry_at = None;
+            },
+        }
+
+        self.updated_at = recorded_at;
+    }
+}
+
+/// Optional criteria used to narrow down the listed standing orders.
+#[derive(Debug, Default, Clone, PartialEq, Eq, IntoParams, Deserialize)]
+#[into_params(parameter_in = Query)]
+pub struct StandingOrderFilter {
+    /// Only include standing orders paid from this account.
+    #[param(value_type = Option<i64>)]
+    pub account_id: Option<AccountId>,
+}
+
+/// Lists the standing orders matching the filter, most recently created first.
+#[tracing::instrument(level = "debug", skip(pool))]
+pub async fn list_standing_orders(
+    pool: &PgPool, filter: &StandingOrderFilter, offset: i64, limit: i64,
+) -> Result<Vec<StandingOrderView>, sqlx::Error> {
+    let select_sql = format!(
+        r##"SELECT payload FROM {STANDING_ORDER_QUERY_VIEW}
+        WHERE $1::bigint IS NULL OR (payload->>'account_id')::bigint = $1
+        ORDER BY (payload->>'order_id')::bigint DESC
+        LIMIT $2 OFFSET $3"##
+    );
+
+    let rows = sqlx::query(&select_sql)
+        .bind(filter.account_id.map(|account_id| account_id.as_num()))
+        .bind(limit)
+        .bind(offset)
+        .fetch_all(pool)
+        .await?;
+
+    Ok(rows
+        .into_iter()
+        .filter_map(|row| match serde_json::from_value(row.get("payload")) {
+            Ok(view) => Some(view),
+            Err(err) => {
+                tracing::warn!(error=?err, "failed to read standing order view payload");
+                None
+            },
+        })
+        .collect())
+}
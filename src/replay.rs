@@ -0,0 +1,122 @@
+//! Rebuilding of the view projections and worker indexes from the event store.
+//!
+//! Each view and index table is cleared and every persisted event of its aggregate type is replayed
+//! through the query maintaining it, in order. The indexes are dated by the time each event was
+//! recorded rather than by the replay, so the fraud history, dormancy clocks and billing periods
+//! are restored as they were. The notification recipients' contact details are replayed without
+//! clearing their table, which also holds the preferences the account holders saved. Only the
+//! outbox relay and the notification and webhook senders it feeds are left out, so replaying never
+//! publishes events or notifies customers and subscribers twice.
+
+use crate::application::ACCOUNT_QUERY_VIEW;
+use crate::dormancy::{self, AccountActivityQuery};
+use crate::fees::{FeeAssessmentQuery, ASSESSMENT_TABLE};
+use crate::holds::{HoldExpiryQuery, HOLDS_TABLE};
+use crate::model::{BankAccount, Customer, Dispute, StandingOrder};
+use crate::notifications::{RecipientContactQuery, RECIPIENTS_TABLE};
+use crate::queries::{
+    AccountHoldersQuery, AccountQuery, CustomerQuery, DisputeQuery, StandingOrderQuery,
+    ACCOUNT_HOLDERS_TABLE, CUSTOMER_QUERY_VIEW, DISPUTE_QUERY_VIEW, STANDING_ORDER_QUERY_VIEW,
+};
+use crate::services::{FraudActivityQuery, FRAUD_ACTIVITY_TABLE};
+use crate::settings::FeeSettings;
+use crate::standing_orders::{StandingOrderScheduleQuery, SCHEDULE_TABLE};
+use cqrs_es::persist::QueryReplay;
+use cqrs_es::{Aggregate, Query};
+use postgres_es::{PostgresEventRepository, PostgresViewRepository};
+use sqlx::PgPool;
+use std::sync::Arc;
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+pub enum ReplayError {
+    #[error("failed to clear view {view}: {source}")]
+    Clear {
+        view: &'static str,
+        source: sqlx::Error,
+    },
+
+    #[error("failed to replay events into view {view}: {reason}")]
+    Replay { view: &'static str, reason: String },
+}
+
+/// Rebuilds every view projection and worker index from the event store.
+///
+/// The service should be stopped while the projections are rebuilt, since views are cleared before
+/// their events are replayed.
+#[tracing::instrument(level = "info", skip(pool, fees))]
+pub async fn replay_projections(pool: &PgPool, fees: &FeeSettings) -> Result<(), ReplayError> {
+    let account_query = AccountQuery::new(Arc::new(PostgresViewRepository::new(
+        ACCOUNT_QUERY_VIEW,
+        pool.clone(),
+    )));
+    replay_view::<BankAccount, _>(pool, ACCOUNT_QUERY_VIEW, account_query).await?;
+
+    let account_holders_query = AccountHoldersQuery::new(pool.clone());
+    replay_view::<BankAccount, _>(pool, ACCOUNT_HOLDERS_TABLE, account_holders_query).await?;
+
+    let hold_expiry_query = HoldExpiryQuery::new(pool.clone());
+    replay_view::<BankAccount, _>(pool, HOLDS_TABLE, hold_expiry_query).await?;
+
+    let fee_assessment_query = FeeAssessmentQuery::new(pool.clone(), fees.billing_period);
+    replay_view::<BankAccount, _>(pool, ASSESSMENT_TABLE, fee_assessment_query).await?;
+
+    let account_activity_query = AccountActivityQuery::new(pool.clone());
+    replay_view::<BankAccount, _>(pool, dormancy::ACTIVITY_TABLE, account_activity_query).await?;
+
+    let fraud_activity_query = FraudActivityQuery::new(pool.clone());
+    replay_view::<BankAccount, _>(pool, FRAUD_ACTIVITY_TABLE, fraud_activity_query).await?;
+
+    let recipient_contact_query = RecipientContactQuery::new(pool.clone());
+    replay_events::<BankAccount, _>(pool, RECIPIENTS_TABLE, recipient_contact_query).await?;
+
+    let customer_query = CustomerQuery::new(Arc::new(PostgresViewRepository::new(
+        CUSTOMER_QUERY_VIEW,
+        pool.clone(),
+    )));
+    replay_view::<Customer, _>(pool, CUSTOMER_QUERY_VIEW, customer_query).await?;
+
+    let dispute_query = DisputeQuery::new(Arc::new(PostgresViewRepository::new(
+        DISPUTE_QUERY_VIEW,
+        pool.clone(),
+    )));
+    replay_view::<Dispute, _>(pool, DISPUTE_QUERY_VIEW, dispute_query).await?;
+
+    let standing_order_query = StandingOrderQuery::new(Arc::new(PostgresViewRepository::new(
+        STANDING_ORDER_QUERY_VIEW,
+        pool.clone(),
+    )));
+    replay_view::<StandingOrder, _>(pool, STANDING_ORDER_QUERY_VIEW, standing_order_query).await?;
+
+    let schedule_query = StandingOrderScheduleQuery::new(pool.clone());
+    replay_view::<StandingOrder, _>(pool, SCHEDULE_TABLE, schedule_query).await
+}
+
+async fn replay_view<A, Q>(pool: &PgPool, view: &'static str, query: Q) -> Result<(), ReplayError>
+where
+    A: Aggregate,
+    Q: Query<A>,
+{
+    let delete_sql = format!("DELETE FROM {view}");
+    let cleared = sqlx::query(&delete_sql)
+        .execute(pool)
+        .await
+        .map_err(|source| ReplayError::Clear { view, source })?;
+    tracing::info!(%view, nr_cleared=%cleared.rows_affected(), "cleared view for replay");
+
+    replay_events::<A, Q>(pool, view, query).await
+}
+
+/// Replays every event of the aggregate type through the query, without clearing its table.
+async fn replay_events<A, Q>(pool: &PgPool, view: &'static str, query: Q) -> Result<(), ReplayError>
+where
+    A: Aggregate,
+    Q: Query<A>,
+{
+    QueryReplay::new(PostgresEventRepository::new(pool.clone()), query)
+        .replay_all()
+        .await
+        .map_err(|err| ReplayError::Replay { view, reason: err.to_string() })?;
+    tracing::info!(%view, "replayed events into view");
+    Ok(())
+}
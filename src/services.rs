@@ -1,8 +1,31 @@
-use crate::model::{AccountId, AtmId, CheckNumber};
+use crate::model::{AccountId, AccountType, AtmId, CheckNumber, CommandId, CustomerId};
+use crate::queries::CustomerView;
+use crate::settings::{AccountTypePolicy, FeeSchedule};
 use async_trait::async_trait;
+use chrono::{DateTime, Utc};
 use money2::Money;
 use thiserror::Error;
 
+mod account_types;
+mod command_claims;
+mod customers;
+mod disputes;
+mod fraud;
+mod standing_orders;
+
+pub use account_types::AccountPolicies;
+pub use command_claims::CommandClaims;
+pub use customers::{
+    CustomerDirectory, CustomerServices, IdentityVerification, IdentityVerifier,
+    IdentityVerifierError, IdentityVerifiers,
+};
+pub use disputes::{AccountLedgerApi, DisputeServiceError, DisputeServices};
+pub use fraud::{
+    FraudActivityQuery, FraudOutcome, FraudRulesEngine, ScreenedActivity, Screening,
+    FRAUD_ACTIVITY_TABLE,
+};
+pub use standing_orders::{AccountPaymentApi, StandingOrderServices};
+
 #[async_trait]
 pub trait BankAccountApi: Sync + Send {
     async fn validate_atm_withdrawal(
@@ -12,15 +35,134 @@ pub trait BankAccountApi: Sync + Send {
     async fn validate_check(
         &self, account_id: &AccountId, check: CheckNumber,
     ) -> Result<(), BankServiceError>;
+
+    /// Verifies the ATM and check services can be reached.
+    async fn check_availability(&self) -> Result<(), BankServiceError>;
+}
+
+#[async_trait]
+pub trait FraudScreeningApi: Sync + Send {
+    /// Screens the activity against the fraud rules before it is applied to the account.
+    async fn screen(
+        &self, account_id: &AccountId, activity: &ScreenedActivity,
+    ) -> Result<Screening, BankServiceError>;
+}
+
+#[async_trait]
+pub trait CustomerKycApi: Sync + Send {
+    /// The customer's registered name and KYC standing, or `None` if no such customer is
+    /// registered.
+    async fn load_customer(
+        &self, customer_id: CustomerId,
+    ) -> Result<Option<CustomerView>, BankServiceError>;
 }
 
+#[async_trait]
+pub trait CommandClaimApi: Sync + Send {
+    /// Whether the worker's claim on the queued command, lasting until `claimed_until`, is still
+    /// current: the command is pending and was not claimed again since, so its outcome may be
+    /// recorded under the claim.
+    async fn holds_claim(
+        &self, command_id: CommandId, claimed_until: DateTime<Utc>,
+    ) -> Result<bool, BankServiceError>;
+}
+
+pub trait AccountPolicyApi: Sync + Send {
+    /// The policy governing transactions on accounts of the type.
+    fn policy(&self, account_type: AccountType) -> &AccountTypePolicy;
+
+    /// The fees charged to accounts.
+    fn fee_schedule(&self) -> &FeeSchedule;
+}
+
+/// External services consulted by the bank account aggregate while handling commands.
 #[derive(Debug, Clone)]
-pub enum BankAccountServices {
-    HappyPath(HappyPathBankAccountServices),
+pub struct BankAccountServices {
+    validation: ValidationServices,
+    fraud: FraudRulesEngine,
+    customers: CustomerDirectory,
+    claims: CommandClaims,
+    policies: AccountPolicies,
+}
+
+impl BankAccountServices {
+    pub fn new(
+        validation: impl Into<ValidationServices>, fraud: FraudRulesEngine,
+        customers: CustomerDirectory, claims: CommandClaims, policies: AccountPolicies,
+    ) -> Self {
+        Self {
+            validation: validation.into(),
+            fraud,
+            customers,
+            claims,
+            policies,
+        }
+    }
 }
 
 #[async_trait]
 impl BankAccountApi for BankAccountServices {
+    async fn validate_atm_withdrawal(
+        &self, atm_id: &AtmId, amount: Money,
+    ) -> Result<(), BankServiceError> {
+        self.validation.validate_atm_withdrawal(atm_id, amount).await
+    }
+
+    async fn validate_check(
+        &self, account_id: &AccountId, check: CheckNumber,
+    ) -> Result<(), BankServiceError> {
+        self.validation.validate_check(account_id, check).await
+    }
+
+    async fn check_availability(&self) -> Result<(), BankServiceError> {
+        self.validation.check_availability().await
+    }
+}
+
+#[async_trait]
+impl FraudScreeningApi for BankAccountServices {
+    async fn screen(
+        &self, account_id: &AccountId, activity: &ScreenedActivity,
+    ) -> Result<Screening, BankServiceError> {
+        self.fraud.screen(account_id, activity).await
+    }
+}
+
+#[async_trait]
+impl CustomerKycApi for BankAccountServices {
+    async fn load_customer(
+        &self, customer_id: CustomerId,
+    ) -> Result<Option<CustomerView>, BankServiceError> {
+        self.customers.load_customer(customer_id).await
+    }
+}
+
+#[async_trait]
+impl CommandClaimApi for BankAccountServices {
+    async fn holds_claim(
+        &self, command_id: CommandId, claimed_until: DateTime<Utc>,
+    ) -> Result<bool, BankServiceError> {
+        self.claims.holds_claim(command_id, claimed_until).await
+    }
+}
+
+impl AccountPolicyApi for BankAccountServices {
+    fn policy(&self, account_type: AccountType) -> &AccountTypePolicy {
+        self.policies.policy(account_type)
+    }
+
+    fn fee_schedule(&self) -> &FeeSchedule {
+        self.policies.fee_schedule()
+    }
+}
+
+#[derive(Debug, Clone)]
+pub enum ValidationServices {
+    HappyPath(HappyPathBankAccountServices),
+}
+
+#[async_trait]
+impl BankAccountApi for ValidationServices {
     async fn validate_atm_withdrawal(
         &self, atm_id: &AtmId, amount: Money,
     ) -> Result<(), BankServiceError> {
@@ -36,6 +178,12 @@ impl BankAccountApi for BankAccountServices {
             Self::HappyPath(svc) => svc.validate_check(account_id, check).await,
         }
     }
+
+    async fn check_availability(&self) -> Result<(), BankServiceError> {
+        match self {
+            Self::HappyPath(svc) => svc.check_availability().await,
+        }
+    }
 }
 
 #[derive(Debug, Error)]
@@ -43,8 +191,34 @@ pub enum BankServiceError {
     #[error("ATM rule violation: {0}")]
     Atm(String),
 
+    #[error("ATM or check validation service is unavailable: {0}")]
+    Unavailable(String),
+
     #[error("Invalid check {1} for account {0}")]
     InvalidCheck(AccountId, CheckNumber),
+
+    #[error("fraud screening failed: {0}")]
+    FraudScreening(sqlx::Error),
+
+    #[error("customer lookup failed: {0}")]
+    CustomerLookup(sqlx::Error),
+
+    #[error("command claim lookup failed: {0}")]
+    CommandClaimLookup(sqlx::Error),
+}
+
+impl BankServiceError {
+    /// Stable machine-readable code identifying the error to API clients.
+    pub const fn error_code(&self) -> &'static str {
+        match self {
+            Self::Atm(_) => "atm_rule_violation",
+            Self::Unavailable(_) => "validation_service_unavailable",
+            Self::InvalidCheck(..) => "invalid_check",
+            Self::FraudScreening(_) => "fraud_screening_unavailable",
+            Self::CustomerLookup(_) => "customer_lookup_failed",
+            Self::CommandClaimLookup(_) => "command_queue_unavailable",
+        }
+    }
 }
 
 #[derive(Debug, Copy, Clone)]
@@ -63,9 +237,13 @@ impl BankAccountApi for HappyPathBankAccountServices {
     ) -> Result<(), BankServiceError> {
         Ok(())
     }
+
+    async fn check_availability(&self) -> Result<(), BankServiceError> {
+        Ok(())
+    }
 }
 
-impl From<HappyPathBankAccountServices> for BankAccountServices {
+impl From<HappyPathBankAccountServices> for ValidationServices {
     fn from(svc: HappyPathBankAccountServices) -> Self {
         Self::HappyPath(svc)
     }
@@ -0,0 +1,40 @@
+use crate::model::AccountType;
+use crate::services::AccountPolicyApi;
+use crate::settings::{AccountTypePolicy, AccountTypeSettings, FeeSchedule};
+use std::fmt;
+use std::sync::Arc;
+
+/// Provides the configured policy for each account type and the fee schedule.
+#[derive(Clone)]
+pub struct AccountPolicies {
+    settings: Arc<AccountTypeSettings>,
+    fees: Arc<FeeSchedule>,
+}
+
+impl fmt::Debug for AccountPolicies {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("AccountPolicies")
+            .field("settings", &self.settings)
+            .field("fees", &self.fees)
+            .finish()
+    }
+}
+
+impl AccountPolicies {
+    pub fn new(settings: &AccountTypeSettings, fees: &FeeSchedule) -> Self {
+        Self {
+            settings: Arc::new(settings.clone()),
+            fees: Arc::new(fees.clone()),
+        }
+    }
+}
+
+impl AccountPolicyApi for AccountPolicies {
+    fn policy(&self, account_type: AccountType) -> &AccountTypePolicy {
+        self.settings.policy(account_type)
+    }
+
+    fn fee_schedule(&self) -> &FeeSchedule {
+        &self.fees
+    }
+}
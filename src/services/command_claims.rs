@@ -0,0 +1,47 @@
+use crate::commands::COMMAND_QUEUE_TABLE;
+use crate::model::CommandId;
+use crate::services::{BankServiceError, CommandClaimApi};
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use sqlx::PgPool;
+use std::fmt;
+
+/// Looks up the claims command queue workers hold on queued commands for the bank account
+/// aggregate.
+#[derive(Clone)]
+pub struct CommandClaims {
+    pool: PgPool,
+}
+
+impl fmt::Debug for CommandClaims {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("CommandClaims").finish()
+    }
+}
+
+impl CommandClaims {
+    pub const fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+}
+
+#[async_trait]
+impl CommandClaimApi for CommandClaims {
+    #[tracing::instrument(level = "debug", skip(self))]
+    async fn holds_claim(
+        &self, command_id: CommandId, claimed_until: DateTime<Utc>,
+    ) -> Result<bool, BankServiceError> {
+        let select_sql = format!(
+            r##"SELECT EXISTS (
+                SELECT 1 FROM {COMMAND_QUEUE_TABLE}
+                WHERE command_id = $1 AND status = 'pending' AND claimed_until = $2
+            )"##
+        );
+        sqlx::query_scalar(&select_sql)
+            .bind(command_id.as_num())
+            .bind(claimed_until)
+            .fetch_one(&self.pool)
+            .await
+            .map_err(BankServiceError::CommandClaimLookup)
+    }
+}
@@ -0,0 +1,147 @@
+use crate::model::{CustomerId, DocumentReference};
+use crate::queries::{self, CustomerView};
+use crate::services::{BankServiceError, CustomerKycApi};
+use crate::settings::IdentityVerifierSettings;
+use async_trait::async_trait;
+use sqlx::PgPool;
+use std::fmt;
+use thiserror::Error;
+
+/// Outcome of checking a customer's identity against their submitted documents.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum IdentityVerification {
+    Verified,
+    Rejected { reason: String },
+}
+
+#[async_trait]
+pub trait IdentityVerifier: Sync + Send {
+    /// Name of the verification provider, recorded with the verification outcome.
+    fn name(&self) -> &str;
+
+    /// Checks the customer's identity against the submitted documents.
+    async fn verify(
+        &self, customer_id: CustomerId, name: &str, documents: &[DocumentReference],
+    ) -> Result<IdentityVerification, IdentityVerifierError>;
+}
+
+/// Services consulted by the customer aggregate to verify customer identities.
+#[derive(Debug, Clone)]
+pub struct CustomerServices {
+    verifier: IdentityVerifiers,
+}
+
+impl CustomerServices {
+    pub fn new(verifier: impl Into<IdentityVerifiers>) -> Self {
+        Self { verifier: verifier.into() }
+    }
+}
+
+#[async_trait]
+impl IdentityVerifier for CustomerServices {
+    fn name(&self) -> &str {
+        self.verifier.name()
+    }
+
+    async fn verify(
+        &self, customer_id: CustomerId, name: &str, documents: &[DocumentReference],
+    ) -> Result<IdentityVerification, IdentityVerifierError> {
+        self.verifier.verify(customer_id, name, documents).await
+    }
+}
+
+#[derive(Debug, Clone)]
+pub enum IdentityVerifiers {
+    Local(LocalIdentityVerifier),
+}
+
+impl IdentityVerifiers {
+    pub fn from_settings(settings: &IdentityVerifierSettings) -> Self {
+        match settings {
+            IdentityVerifierSettings::Local => LocalIdentityVerifier.into(),
+        }
+    }
+}
+
+#[async_trait]
+impl IdentityVerifier for IdentityVerifiers {
+    fn name(&self) -> &str {
+        match self {
+            Self::Local(svc) => svc.name(),
+        }
+    }
+
+    async fn verify(
+        &self, customer_id: CustomerId, name: &str, documents: &[DocumentReference],
+    ) -> Result<IdentityVerification, IdentityVerifierError> {
+        match self {
+            Self::Local(svc) => svc.verify(customer_id, name, documents).await,
+        }
+    }
+}
+
+#[derive(Debug, Error)]
+pub enum IdentityVerifierError {
+    #[error("identity verification provider is unavailable: {0}")]
+    Unavailable(String),
+}
+
+/// Stand-in for an identity verification provider: verifies any customer who submitted
+/// government-issued photo identification.
+#[derive(Debug, Copy, Clone)]
+pub struct LocalIdentityVerifier;
+
+#[async_trait]
+impl IdentityVerifier for LocalIdentityVerifier {
+    fn name(&self) -> &str {
+        "local"
+    }
+
+    async fn verify(
+        &self, _customer_id: CustomerId, _name: &str, documents: &[DocumentReference],
+    ) -> Result<IdentityVerification, IdentityVerifierError> {
+        if documents.iter().any(|document| document.kind.is_photo_id()) {
+            Ok(IdentityVerification::Verified)
+        } else {
+            Ok(IdentityVerification::Rejected {
+                reason: "no government-issued photo identification submitted".to_string(),
+            })
+        }
+    }
+}
+
+impl From<LocalIdentityVerifier> for IdentityVerifiers {
+    fn from(svc: LocalIdentityVerifier) -> Self {
+        Self::Local(svc)
+    }
+}
+
+/// Looks up customers' names and KYC standing for the bank account aggregate.
+#[derive(Clone)]
+pub struct CustomerDirectory {
+    pool: PgPool,
+}
+
+impl fmt::Debug for CustomerDirectory {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("CustomerDirectory").finish()
+    }
+}
+
+impl CustomerDirectory {
+    pub const fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+}
+
+#[async_trait]
+impl CustomerKycApi for CustomerDirectory {
+    #[tracing::instrument(level = "debug", skip(self))]
+    async fn load_customer(
+        &self, customer_id: CustomerId,
+    ) -> Result<Option<CustomerView>, BankServiceError> {
+        queries::load_customer(&self.pool, customer_id)
+            .await
+            .map_err(BankServiceError::CustomerLookup)
+    }
+}
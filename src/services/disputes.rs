@@ -0,0 +1,88 @@
+use crate::model::{
+    AccountId, BankAccount, BankAccountAggregate, BankAccountCommand, BankAccountError,
+    BankAccountEvent,
+};
+use crate::queries;
+use async_trait::async_trait;
+use cqrs_es::AggregateError;
+use pretty_snowflake::envelope::MetaData;
+use pretty_snowflake::Id;
+use sqlx::PgPool;
+use std::fmt;
+use thiserror::Error;
+
+#[async_trait]
+pub trait AccountLedgerApi: Sync + Send {
+    /// Looks up the event with the `sequence` number in the account's event stream.
+    async fn find_transaction(
+        &self, account_id: AccountId, sequence: usize,
+    ) -> Result<Option<BankAccountEvent>, DisputeServiceError>;
+
+    /// Executes the command against the account, e.g., to credit or reverse a disputed
+    /// transaction.
+    async fn execute(
+        &self, account_id: AccountId, command: BankAccountCommand,
+    ) -> Result<(), DisputeServiceError>;
+}
+
+/// Services consulted by the dispute aggregate to inspect and adjust the disputed account.
+#[derive(Clone)]
+pub struct DisputeServices {
+    pool: PgPool,
+    accounts: BankAccountAggregate,
+}
+
+impl fmt::Debug for DisputeServices {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("DisputeServices").finish()
+    }
+}
+
+impl DisputeServices {
+    pub const fn new(pool: PgPool, accounts: BankAccountAggregate) -> Self {
+        Self { pool, accounts }
+    }
+}
+
+#[async_trait]
+impl AccountLedgerApi for DisputeServices {
+    #[tracing::instrument(level = "debug", skip(self))]
+    async fn find_transaction(
+        &self, account_id: AccountId, sequence: usize,
+    ) -> Result<Option<BankAccountEvent>, DisputeServiceError> {
+        let aggregate_id: Id<BankAccount> = account_id.into();
+        let event =
+            queries::load_account_event(&self.pool, aggregate_id.pretty(), sequence as i64).await?;
+        event
+            .map(|event| serde_json::from_value(event.payload))
+            .transpose()
+            .map_err(|err| err.into())
+    }
+
+    #[tracing::instrument(level = "debug", skip(self))]
+    async fn execute(
+        &self, account_id: AccountId, command: BankAccountCommand,
+    ) -> Result<(), DisputeServiceError> {
+        let aggregate_id: Id<BankAccount> = account_id.into();
+        self.accounts
+            .execute_with_metadata(
+                aggregate_id.pretty(),
+                command,
+                MetaData::<BankAccount>::default().into(),
+            )
+            .await?;
+        Ok(())
+    }
+}
+
+#[derive(Debug, Error)]
+pub enum DisputeServiceError {
+    #[error("failed to load disputed transaction: {0}")]
+    Load(#[from] sqlx::Error),
+
+    #[error("failed to read disputed transaction: {0}")]
+    Deserialization(#[from] serde_json::Error),
+
+    #[error("{0}")]
+    Account(#[from] AggregateError<BankAccountError>),
+}
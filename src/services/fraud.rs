@@ -0,0 +1,331 @@
+use super::BankServiceError;
+use crate::model::{self, AccountId, AtmId, BankAccount, CheckNumber};
+use crate::settings::{FraudRule, FraudSettings};
+use chrono::{DateTime, Utc};
+use money2::Money;
+use pretty_snowflake::Id;
+use serde::{Deserialize, Serialize};
+use sqlx::PgPool;
+use std::sync::Arc;
+use std::time::Duration;
+use strum_macros::Display;
+use utoipa::ToSchema;
+
+mod activity;
+
+pub use activity::{FraudActivityQuery, ACTIVITY_TABLE as FRAUD_ACTIVITY_TABLE};
+
+/// Outcome of fraud screening, ordered by severity.
+#[derive(
+    Debug,
+    Display,
+    Copy,
+    Clone,
+    PartialEq,
+    Eq,
+    PartialOrd,
+    Ord,
+    Hash,
+    ToSchema,
+    Serialize,
+    Deserialize,
+)]
+#[strum(serialize_all = "snake_case")]
+#[serde(rename_all = "snake_case")]
+pub enum FraudOutcome {
+    Allow,
+    Hold,
+    Deny,
+}
+
+/// Account activity submitted for fraud screening.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ScreenedActivity {
+    CashWithdrawal {
+        amount: Money,
+        atm_id: AtmId,
+    },
+    CheckWithdrawal {
+        check_nr: CheckNumber,
+        amount: Money,
+    },
+    ContactChange,
+}
+
+impl ScreenedActivity {
+    const fn withdrawal_amount(&self) -> Option<Money> {
+        match self {
+            Self::CashWithdrawal { amount, .. } | Self::CheckWithdrawal { amount, .. } => {
+                Some(*amount)
+            },
+            Self::ContactChange => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Screening {
+    pub outcome: FraudOutcome,
+
+    /// Descriptions of the rules that matched the activity.
+    pub reasons: Vec<String>,
+}
+
+impl Screening {
+    pub const fn allow() -> Self {
+        Self { outcome: FraudOutcome::Allow, reasons: Vec::new() }
+    }
+}
+
+/// Recent account activity the rules are evaluated against.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct ActivityHistory {
+    pub withdrawals: Vec<DateTime<Utc>>,
+    pub contact_changes: Vec<DateTime<Utc>>,
+
+    /// Whether the account has previously withdrawn cash from the ATM being screened.
+    pub known_atm: bool,
+}
+
+impl ActivityHistory {
+    fn count_since(timestamps: &[DateTime<Utc>], now: DateTime<Utc>, window: Duration) -> usize {
+        let since = chrono::Duration::from_std(window)
+            .ok()
+            .and_then(|window| now.checked_sub_signed(window));
+        timestamps
+            .iter()
+            .filter(|ts| since.is_none_or(|since| since <= **ts))
+            .count()
+    }
+}
+
+/// Evaluates the rules against the activity, deciding on the most severe matching outcome.
+pub fn screen_activity(
+    rules: &[FraudRule], activity: &ScreenedActivity, history: &ActivityHistory, now: DateTime<Utc>,
+) -> Screening {
+    rules
+        .iter()
+        .filter_map(|rule| evaluate(rule, activity, history, now))
+        .fold(Screening::allow(), |mut screening, (outcome, reason)| {
+            screening.outcome = screening.outcome.max(outcome);
+            screening.reasons.push(reason);
+            screening
+        })
+}
+
+fn evaluate(
+    rule: &FraudRule, activity: &ScreenedActivity, history: &ActivityHistory, now: DateTime<Utc>,
+) -> Option<(FraudOutcome, String)> {
+    match (rule, activity) {
+        (FraudRule::AmountThreshold { threshold, outcome }, activity) => {
+            let amount = activity.withdrawal_amount()?;
+            (*threshold <= model::convert_amount(threshold.currency, amount)).then(|| {
+                (
+                    *outcome,
+                    format!("withdrawal of {amount} meets the {threshold} threshold"),
+                )
+            })
+        },
+
+        (FraudRule::RapidWithdrawals { max_withdrawals, window, outcome }, activity) => {
+            activity.withdrawal_amount()?;
+            let recent = ActivityHistory::count_since(&history.withdrawals, now, *window);
+            (*max_withdrawals <= recent).then(|| {
+                (
+                    *outcome,
+                    format!("{recent} withdrawals already made within {window:?}"),
+                )
+            })
+        },
+
+        (FraudRule::WithdrawalAfterContactChange { window, outcome }, activity) => {
+            activity.withdrawal_amount()?;
+            let recent = ActivityHistory::count_since(&history.contact_changes, now, *window);
+            (0 < recent).then(|| {
+                (
+                    *outcome,
+                    format!("withdrawal within {window:?} of a contact information change"),
+                )
+            })
+        },
+
+        (FraudRule::NewAtm { outcome }, ScreenedActivity::CashWithdrawal { atm_id, .. }) => {
+            (!history.known_atm).then(|| (*outcome, format!("first withdrawal from ATM {atm_id}")))
+        },
+
+        (
+            FraudRule::RapidContactChanges { max_changes, window, outcome },
+            ScreenedActivity::ContactChange,
+        ) => {
+            let recent = ActivityHistory::count_since(&history.contact_changes, now, *window);
+            (*max_changes <= recent).then(|| {
+                (
+                    *outcome,
+                    format!("{recent} contact information changes already made within {window:?}"),
+                )
+            })
+        },
+
+        _ => None,
+    }
+}
+
+/// Screens account activity against the configured rules, using the account's recent activity
+/// recorded by the [FraudActivityQuery].
+#[derive(Debug, Clone)]
+pub struct FraudRulesEngine {
+    pool: PgPool,
+    rules: Arc<Vec<FraudRule>>,
+}
+
+impl FraudRulesEngine {
+    pub fn new(pool: PgPool, settings: &FraudSettings) -> Self {
+        Self { pool, rules: Arc::new(settings.rules.clone()) }
+    }
+
+    /// The longest window any rule looks back over.
+    fn lookback(&self) -> Duration {
+        self.rules
+            .iter()
+            .filter_map(|rule| match rule {
+                FraudRule::RapidWithdrawals { window, .. }
+                | FraudRule::WithdrawalAfterContactChange { window, .. }
+                | FraudRule::RapidContactChanges { window, .. } => Some(*window),
+                FraudRule::AmountThreshold { .. } | FraudRule::NewAtm { .. } => None,
+            })
+            .max()
+            .unwrap_or_default()
+    }
+
+    #[tracing::instrument(level = "debug", skip(self))]
+    pub async fn screen(
+        &self, account_id: &AccountId, activity: &ScreenedActivity,
+    ) -> Result<Screening, BankServiceError> {
+        if self.rules.is_empty() {
+            return Ok(Screening::allow());
+        }
+
+        let aggregate_id: Id<BankAccount> = (*account_id).into();
+        let now = Utc::now();
+        let history = activity::load_history(
+            &self.pool,
+            aggregate_id.pretty(),
+            activity,
+            now,
+            self.lookback(),
+        )
+        .await
+        .map_err(BankServiceError::FraudScreening)?;
+
+        let screening = screen_activity(&self.rules, activity, &history, now);
+        if screening.outcome != FraudOutcome::Allow {
+            tracing::info!(?screening, %account_id, ?activity, "fraud rules matched account activity");
+        }
+        Ok(screening)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use money2::Currency;
+    use pretty_assertions::assert_eq;
+
+    fn cash(amount: i64, atm_id: &str) -> ScreenedActivity {
+        ScreenedActivity::CashWithdrawal {
+            amount: Money::new(amount, 0, Currency::Usd),
+            atm_id: AtmId::new(atm_id),
+        }
+    }
+
+    #[test]
+    fn test_no_rules_allows_activity() {
+        let actual = screen_activity(
+            &[],
+            &cash(10_000, "abc_123"),
+            &ActivityHistory::default(),
+            Utc::now(),
+        );
+        assert_eq!(actual, Screening::allow());
+    }
+
+    #[test]
+    fn test_most_severe_outcome_wins() {
+        let rules = vec![
+            FraudRule::AmountThreshold {
+                threshold: Money::new(500, 0, Currency::Usd),
+                outcome: FraudOutcome::Hold,
+            },
+            FraudRule::AmountThreshold {
+                threshold: Money::new(5_000, 0, Currency::Usd),
+                outcome: FraudOutcome::Deny,
+            },
+            FraudRule::NewAtm { outcome: FraudOutcome::Hold },
+        ];
+        let history = ActivityHistory { known_atm: true, ..ActivityHistory::default() };
+
+        let actual = screen_activity(&rules, &cash(100, "abc_123"), &history, Utc::now());
+        assert_eq!(actual.outcome, FraudOutcome::Allow);
+
+        let actual = screen_activity(&rules, &cash(1_000, "abc_123"), &history, Utc::now());
+        assert_eq!(actual.outcome, FraudOutcome::Hold);
+        assert_eq!(actual.reasons.len(), 1);
+
+        let actual = screen_activity(
+            &rules,
+            &cash(6_000, "xyz_789"),
+            &ActivityHistory::default(),
+            Utc::now(),
+        );
+        assert_eq!(actual.outcome, FraudOutcome::Deny);
+        assert_eq!(actual.reasons.len(), 3);
+    }
+
+    #[test]
+    fn test_rapid_withdrawals_only_count_the_window() {
+        let now = Utc::now();
+        let rules = vec![FraudRule::RapidWithdrawals {
+            max_withdrawals: 2,
+            window: Duration::from_secs(600),
+            outcome: FraudOutcome::Hold,
+        }];
+        let mut history = ActivityHistory {
+            withdrawals: vec![
+                now - chrono::Duration::hours(2),
+                now - chrono::Duration::minutes(5),
+            ],
+            known_atm: true,
+            ..ActivityHistory::default()
+        };
+
+        let actual = screen_activity(&rules, &cash(10, "abc_123"), &history, now);
+        assert_eq!(actual.outcome, FraudOutcome::Allow);
+
+        history.withdrawals.push(now - chrono::Duration::minutes(1));
+        let actual = screen_activity(&rules, &cash(10, "abc_123"), &history, now);
+        assert_eq!(actual.outcome, FraudOutcome::Hold);
+    }
+
+    #[test]
+    fn test_withdrawal_after_contact_change() {
+        let now = Utc::now();
+        let rules = vec![FraudRule::WithdrawalAfterContactChange {
+            window: Duration::from_secs(24 * 60 * 60),
+            outcome: FraudOutcome::Hold,
+        }];
+        let history = ActivityHistory {
+            contact_changes: vec![now - chrono::Duration::hours(3)],
+            ..ActivityHistory::default()
+        };
+
+        let check = ScreenedActivity::CheckWithdrawal {
+            check_nr: CheckNumber::new(1087_u32),
+            amount: Money::new(20, 0, Currency::Usd),
+        };
+        let actual = screen_activity(&rules, &check, &history, now);
+        assert_eq!(actual.outcome, FraudOutcome::Hold);
+
+        let actual = screen_activity(&rules, &ScreenedActivity::ContactChange, &history, now);
+        assert_eq!(actual, Screening::allow());
+    }
+}
@@ -0,0 +1,115 @@
+use super::{ActivityHistory, ScreenedActivity};
+use crate::model::{BankAccount, BankAccountEvent};
+use crate::queries::RECV_TIMESTAMP;
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use cqrs_es::{EventEnvelope, Query};
+use sqlx::{PgPool, Row};
+use std::time::Duration;
+
+pub const ACTIVITY_TABLE: &str = "fraud_activity";
+
+const WITHDRAWAL: &str = "withdrawal";
+const CONTACT_CHANGE: &str = "contact_change";
+
+/// Records committed withdrawals and contact changes so the fraud rules can consider the
+/// account's recent activity.
+#[derive(Debug, Clone)]
+pub struct FraudActivityQuery {
+    pool: PgPool,
+}
+
+impl FraudActivityQuery {
+    pub const fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+
+    async fn record(
+        &self, aggregate_id: &str, event: &EventEnvelope<BankAccount>,
+    ) -> Result<(), sqlx::Error> {
+        let (kind, atm_id) = match &event.payload {
+            BankAccountEvent::CashWithdrawal { atm_id, .. } => {
+                (WITHDRAWAL, atm_id.as_ref().map(|id| id.as_str()))
+            },
+            BankAccountEvent::CheckWithdrawal { .. } => (WITHDRAWAL, None),
+            BankAccountEvent::MailingAddressUpdated { .. }
+            | BankAccountEvent::EmailUpdated { .. } => (CONTACT_CHANGE, None),
+            _ => return Ok(()),
+        };
+
+        // dated when the event was recorded, so a replay of the event store keeps the history
+        let recorded_at: Option<DateTime<Utc>> =
+            event.metadata.get(RECV_TIMESTAMP).and_then(|ts| ts.parse().ok());
+        let insert_sql = format!(
+            r##"INSERT INTO {ACTIVITY_TABLE} (aggregate_id, sequence, kind, atm_id, occurred_at)
+            VALUES ($1, $2, $3, $4, COALESCE($5, now()))
+            ON CONFLICT DO NOTHING"##
+        );
+        sqlx::query(&insert_sql)
+            .bind(aggregate_id)
+            .bind(event.sequence as i64)
+            .bind(kind)
+            .bind(atm_id)
+            .bind(recorded_at)
+            .execute(&self.pool)
+            .await?;
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl Query<BankAccount> for FraudActivityQuery {
+    #[tracing::instrument(level = "debug", skip(self, events))]
+    async fn dispatch(&self, aggregate_id: &str, events: &[EventEnvelope<BankAccount>]) {
+        for event in events {
+            if let Err(error) = self.record(aggregate_id, event).await {
+                tracing::error!(
+                    ?error, sequence=%event.sequence,
+                    "failed to record account activity for fraud screening"
+                );
+            }
+        }
+    }
+}
+
+pub(super) async fn load_history(
+    pool: &PgPool, aggregate_id: &str, activity: &ScreenedActivity, now: DateTime<Utc>,
+    lookback: Duration,
+) -> Result<ActivityHistory, sqlx::Error> {
+    let since = chrono::Duration::from_std(lookback)
+        .ok()
+        .and_then(|lookback| now.checked_sub_signed(lookback))
+        .unwrap_or_else(|| DateTime::<Utc>::from(std::time::UNIX_EPOCH));
+    let select_sql = format!(
+        "SELECT kind, occurred_at FROM {ACTIVITY_TABLE} WHERE aggregate_id = $1 AND $2 <= occurred_at"
+    );
+    let rows = sqlx::query(&select_sql)
+        .bind(aggregate_id)
+        .bind(since)
+        .fetch_all(pool)
+        .await?;
+
+    let mut history = ActivityHistory::default();
+    for row in rows {
+        let kind: String = row.try_get("kind")?;
+        let occurred_at: DateTime<Utc> = row.try_get("occurred_at")?;
+        match kind.as_str() {
+            WITHDRAWAL => history.withdrawals.push(occurred_at),
+            CONTACT_CHANGE => history.contact_changes.push(occurred_at),
+            kind => tracing::warn!(%kind, "ignoring unrecognized fraud activity"),
+        }
+    }
+
+    if let ScreenedActivity::CashWithdrawal { atm_id, .. } = activity {
+        let exists_sql = format!(
+            "SELECT EXISTS(SELECT 1 FROM {ACTIVITY_TABLE} WHERE aggregate_id = $1 AND atm_id = $2)"
+        );
+        history.known_atm = sqlx::query_scalar(&exists_sql)
+            .bind(aggregate_id)
+            .bind(atm_id.as_str())
+            .fetch_one(pool)
+            .await?;
+    }
+
+    Ok(history)
+}
@@ -0,0 +1,74 @@
+use crate::model::{
+    AccountId, BankAccount, BankAccountAggregate, BankAccountCommand, BankAccountError,
+};
+use crate::settings::StandingOrderSettings;
+use async_trait::async_trait;
+use chrono::Duration;
+use cqrs_es::AggregateError;
+use pretty_snowflake::envelope::MetaData;
+use pretty_snowflake::Id;
+use std::fmt;
+
+#[async_trait]
+pub trait AccountPaymentApi: Sync + Send {
+    /// Executes the payment command against the account.
+    async fn execute(
+        &self, account_id: AccountId, command: BankAccountCommand,
+    ) -> Result<(), AggregateError<BankAccountError>>;
+}
+
+/// Services consulted by the standing order aggregate to pay its occurrences.
+#[derive(Clone)]
+pub struct StandingOrderServices {
+    accounts: BankAccountAggregate,
+    retry_interval: Duration,
+    retry_window: Duration,
+}
+
+impl fmt::Debug for StandingOrderServices {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("StandingOrderServices")
+            .field("retry_interval", &self.retry_interval)
+            .field("retry_window", &self.retry_window)
+            .finish()
+    }
+}
+
+impl StandingOrderServices {
+    pub fn new(accounts: BankAccountAggregate, settings: &StandingOrderSettings) -> Self {
+        Self {
+            accounts,
+            retry_interval: Duration::from_std(settings.retry_interval)
+                .unwrap_or_else(|_| Duration::max_value()),
+            retry_window: Duration::from_std(settings.retry_window)
+                .unwrap_or_else(|_| Duration::max_value()),
+        }
+    }
+
+    /// Delay before a declined payment is attempted again.
+    pub const fn retry_interval(&self) -> Duration {
+        self.retry_interval
+    }
+
+    /// How long after its due time a declined payment is retried.
+    pub const fn retry_window(&self) -> Duration {
+        self.retry_window
+    }
+}
+
+#[async_trait]
+impl AccountPaymentApi for StandingOrderServices {
+    #[tracing::instrument(level = "debug", skip(self))]
+    async fn execute(
+        &self, account_id: AccountId, command: BankAccountCommand,
+    ) -> Result<(), AggregateError<BankAccountError>> {
+        let aggregate_id: Id<BankAccount> = account_id.into();
+        self.accounts
+            .execute_with_metadata(
+                aggregate_id.pretty(),
+                command,
+                MetaData::<BankAccount>::default().into(),
+            )
+            .await
+    }
+}
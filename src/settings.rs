@@ -1,22 +1,98 @@
+use pretty_snowflake::{
+    AlphabetCodec, IdPrettifier, Label, LabeledRealtimeIdGenerator, MachineNode,
+};
 use serde::Deserialize;
 use settings_loader::common::database::DatabaseSettings;
 use settings_loader::SettingsLoader;
+use thiserror::Error;
 
+mod account_type_settings;
+mod auth_settings;
+mod backoff_settings;
 mod cli_options;
+mod command_queue_settings;
+mod command_retry_settings;
+mod dormancy_settings;
+mod fee_settings;
+mod fraud_settings;
+mod hold_settings;
 mod http_api_settings;
+mod identity_verifier_settings;
+mod migration_settings;
+mod notification_settings;
+mod outbox_settings;
+mod standing_order_settings;
 #[cfg(test)]
 mod tests;
+mod webhook_settings;
 
-pub use cli_options::CliOptions;
-pub use http_api_settings::HttpApiSettings;
+pub use account_type_settings::{AccountTypePolicy, AccountTypeSettings};
+pub use auth_settings::{ApiTokenSettings, AuthSettings};
+pub use backoff_settings::BackoffSettings;
+pub use cli_options::{CliCommand, CliOptions};
+pub use command_queue_settings::CommandQueueSettings;
+pub use command_retry_settings::CommandRetrySettings;
+pub use dormancy_settings::DormancySettings;
+pub use fee_settings::{FeeSchedule, FeeSettings};
+pub use fraud_settings::{FraudRule, FraudSettings};
+pub use hold_settings::HoldSettings;
+pub use http_api_settings::{HttpApiSettings, RateLimitQuota, RateLimitSettings};
+pub use identity_verifier_settings::IdentityVerifierSettings;
+pub use migration_settings::MigrationSettings;
+pub use notification_settings::{MailerSettings, NotificationSettings, SmtpSettings};
+pub use outbox_settings::{OutboxSettings, PublisherSettings};
+pub use standing_order_settings::StandingOrderSettings;
+pub use webhook_settings::WebhookSettings;
 
 #[derive(Debug, Clone, PartialEq, Deserialize)]
 pub struct Settings {
     pub http_api: HttpApiSettings,
     pub database: DatabaseSettings,
 
+    #[serde(default)]
+    pub migrations: MigrationSettings,
+
     #[serde(flatten)]
     pub correlation: CorrelationSettings,
+
+    #[serde(default)]
+    pub outbox: OutboxSettings,
+
+    #[serde(default)]
+    pub webhooks: WebhookSettings,
+
+    #[serde(default)]
+    pub notifications: NotificationSettings,
+
+    #[serde(default)]
+    pub fraud: FraudSettings,
+
+    #[serde(default)]
+    pub auth: AuthSettings,
+
+    #[serde(default)]
+    pub holds: HoldSettings,
+
+    #[serde(default)]
+    pub standing_orders: StandingOrderSettings,
+
+    #[serde(default)]
+    pub dormancy: DormancySettings,
+
+    #[serde(default)]
+    pub account_types: AccountTypeSettings,
+
+    #[serde(default)]
+    pub fees: FeeSettings,
+
+    #[serde(default)]
+    pub command_retry: CommandRetrySettings,
+
+    #[serde(default)]
+    pub command_queue: CommandQueueSettings,
+
+    #[serde(default)]
+    pub identity_verifier: IdentityVerifierSettings,
 }
 
 impl SettingsLoader for Settings {
@@ -44,6 +120,49 @@ impl Default for CorrelationSettings {
     }
 }
 
+impl CorrelationSettings {
+    /// Exclusive upper bound of the machine and node ids.
+    pub const ID_BOUND: i32 = 31;
+
+    /// The snowflake machine and node, once both ids are verified to be in [0, 31).
+    pub fn machine_node(&self) -> Result<MachineNode, CorrelationIdOutOfRange> {
+        for (field, value) in [("machine_id", self.machine_id), ("node_id", self.node_id)] {
+            if !(0..Self::ID_BOUND).contains(&value) {
+                return Err(CorrelationIdOutOfRange { field, value });
+            }
+        }
+
+        Ok(MachineNode::new(self.machine_id, self.node_id))
+    }
+
+    /// Creates a generator minting ids for this machine and node.
+    pub fn id_generator<T: Label>(
+        &self,
+    ) -> Result<LabeledRealtimeIdGenerator<T>, CorrelationIdOutOfRange> {
+        Ok(LabeledRealtimeIdGenerator::new(
+            self.machine_node()?,
+            IdPrettifier::<AlphabetCodec>::default(),
+        ))
+    }
+
+    /// Configures the process-wide generator behind the aggregates' `generate_id` functions, so
+    /// replicas with distinct machine and node ids never mint the same id.
+    pub fn install_id_generator(&self) -> Result<(), CorrelationIdOutOfRange> {
+        pretty_snowflake::generator::set_id_generator(
+            self.machine_node()?,
+            IdPrettifier::<AlphabetCodec>::default(),
+        );
+        Ok(())
+    }
+}
+
+#[derive(Debug, Error, PartialEq, Eq)]
+#[error("{field} must be in [0, 31) for snowflake id generation, but is {value}")]
+pub struct CorrelationIdOutOfRange {
+    pub field: &'static str,
+    pub value: i32,
+}
+
 fn deser_string_or_i32<'de, D>(deserializer: D) -> Result<i32, D::Error>
 where
     D: serde::de::Deserializer<'de>,
@@ -0,0 +1,67 @@
+use crate::model::AccountType;
+use money2::{Currency, Money};
+use serde::Deserialize;
+
+/// The policy governing transactions for each type of account.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(default)]
+pub struct AccountTypeSettings {
+    pub checking: AccountTypePolicy,
+    pub savings: AccountTypePolicy,
+    pub business: AccountTypePolicy,
+}
+
+impl AccountTypeSettings {
+    pub const fn policy(&self, account_type: AccountType) -> &AccountTypePolicy {
+        match account_type {
+            AccountType::Checking => &self.checking,
+            AccountType::Savings => &self.savings,
+            AccountType::Business => &self.business,
+        }
+    }
+}
+
+impl Default for AccountTypeSettings {
+    fn default() -> Self {
+        Self {
+            checking: AccountTypePolicy::default(),
+            savings: AccountTypePolicy {
+                checks_allowed: false,
+                monthly_withdrawal_cap: Some(6),
+                minimum_balance: Some(Money::new(100, 0, Currency::Usd)),
+                ..AccountTypePolicy::default()
+            },
+            business: AccountTypePolicy {
+                minimum_balance: Some(Money::new(1_000, 0, Currency::Usd)),
+                ..AccountTypePolicy::default()
+            },
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(default)]
+pub struct AccountTypePolicy {
+    /// Largest amount that may be withdrawn in a single transaction.
+    pub max_withdrawal: Option<Money>,
+
+    /// Whether checks may be disbursed from the account.
+    pub checks_allowed: bool,
+
+    /// Maximum number of cash and check withdrawals in a calendar month.
+    pub monthly_withdrawal_cap: Option<usize>,
+
+    /// Balance that withdrawals may not take the account's available balance below.
+    pub minimum_balance: Option<Money>,
+}
+
+impl Default for AccountTypePolicy {
+    fn default() -> Self {
+        Self {
+            max_withdrawal: None,
+            checks_allowed: true,
+            monthly_withdrawal_cap: None,
+            minimum_balance: None,
+        }
+    }
+}
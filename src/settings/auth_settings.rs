@@ -0,0 +1,29 @@
+use secrecy::{ExposeSecret, Secret};
+use serde::Deserialize;
+
+#[derive(Debug, Default, Clone, PartialEq, Deserialize)]
+#[serde(default)]
+pub struct AuthSettings {
+    /// Bearer tokens accepted by the API, and the principal each authenticates.
+    pub tokens: Vec<ApiTokenSettings>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct ApiTokenSettings {
+    /// Identifies the principal in audit records, e.g., the compliance officer's user id.
+    pub subject: String,
+
+    pub token: Secret<String>,
+
+    /// Scopes granted to the principal; e.g., `admin:account`.
+    #[serde(default)]
+    pub scopes: Vec<String>,
+}
+
+impl PartialEq for ApiTokenSettings {
+    fn eq(&self, other: &Self) -> bool {
+        self.subject == other.subject
+            && self.token.expose_secret() == other.token.expose_secret()
+            && self.scopes == other.scopes
+    }
+}
@@ -0,0 +1,42 @@
+use serde::Deserialize;
+use serde_with::serde_as;
+use std::time::Duration;
+
+/// Exponential backoff applied between retries of a failed operation.
+#[serde_as]
+#[derive(Debug, Copy, Clone, PartialEq, Deserialize)]
+#[serde(default)]
+pub struct BackoffSettings {
+    #[serde(alias = "initial_delay_millis")]
+    #[serde_as(as = "serde_with::DurationMilliSeconds<u64>")]
+    pub initial_delay: Duration,
+
+    #[serde(alias = "max_delay_secs")]
+    #[serde_as(as = "serde_with::DurationSecondsWithFrac<f64>")]
+    pub max_delay: Duration,
+
+    pub multiplier: f64,
+}
+
+impl Default for BackoffSettings {
+    fn default() -> Self {
+        Self {
+            initial_delay: Duration::from_millis(500),
+            max_delay: Duration::from_secs(5 * 60),
+            multiplier: 2.0,
+        }
+    }
+}
+
+impl BackoffSettings {
+    /// Delay to wait before the next try following the given number of failed attempts.
+    pub fn delay_for(&self, failed_attempts: u32) -> Duration {
+        let exponent = i32::try_from(failed_attempts.saturating_sub(1)).unwrap_or(i32::MAX);
+        let delay_secs = self.initial_delay.as_secs_f64() * self.multiplier.powi(exponent);
+        if delay_secs.is_finite() && delay_secs < self.max_delay.as_secs_f64() {
+            Duration::from_secs_f64(delay_secs)
+        } else {
+            self.max_delay
+        }
+    }
+}
@@ -1,4 +1,4 @@
-use clap::Parser;
+use clap::{Parser, Subcommand};
 use config::builder::DefaultState;
 use config::ConfigBuilder;
 use settings_loader::{Environment, LoadingOptions, SettingsError};
@@ -42,6 +42,27 @@ pub struct CliOptions {
     /// Optionally override the engine.node_id setting.
     #[clap(short, long, value_name = "[0, 31)")]
     pub node_id: Option<i8>,
+
+    /// Lifecycle task to run; defaults to serving the HTTP API.
+    #[clap(subcommand)]
+    pub command: Option<CliCommand>,
+}
+
+#[derive(Debug, Default, Copy, Clone, Subcommand, PartialEq, Eq)]
+pub enum CliCommand {
+    /// Serve the HTTP API along with the background workers.
+    #[default]
+    Serve,
+
+    /// Apply the database migrations embedded in the binary.
+    Migrate,
+
+    /// Rebuild the view projections from the event store. The service should be stopped while
+    /// projections are rebuilt.
+    Replay,
+
+    /// Load and print the resolved settings, with secrets redacted.
+    CheckConfig,
 }
 
 const DEFAULT_SEARCH_PATH: &str = "./resources";
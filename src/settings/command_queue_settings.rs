@@ -0,0 +1,41 @@
+use serde::Deserialize;
+use serde_with::serde_as;
+use std::time::Duration;
+
+#[serde_as]
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(default)]
+pub struct CommandQueueSettings {
+    /// Accept deposits and withdrawals for asynchronous execution, responding `202 Accepted` with
+    /// the command's id, and run the workers that execute them.
+    pub enabled: bool,
+
+    /// Number of workers executing queued commands concurrently.
+    pub nr_workers: usize,
+
+    #[serde(alias = "poll_interval_millis")]
+    #[serde_as(as = "serde_with::DurationMilliSeconds<u64>")]
+    pub poll_interval: Duration,
+
+    /// How long a worker's claim on a command lasts before another worker may execute it, e.g.,
+    /// after the claiming instance stopped.
+    #[serde(alias = "claim_timeout_secs")]
+    #[serde_as(as = "serde_with::DurationSeconds<u64>")]
+    pub claim_timeout: Duration,
+
+    /// Number of claims after which a command whose outcome was never recorded is no longer
+    /// executed, and is failed instead.
+    pub max_attempts: u32,
+}
+
+impl Default for CommandQueueSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            nr_workers: 4,
+            poll_interval: Duration::from_millis(100),
+            claim_timeout: Duration::from_secs(60),
+            max_attempts: 5,
+        }
+    }
+}
@@ -0,0 +1,29 @@
+use super::BackoffSettings;
+use serde::Deserialize;
+use std::time::Duration;
+
+/// Retries of bank account commands rejected on a conflict with a concurrent command on the same
+/// account, e.g., two deposits racing to append the account's next event.
+#[derive(Debug, Copy, Clone, PartialEq, Deserialize)]
+#[serde(default)]
+pub struct CommandRetrySettings {
+    /// Retries after the first attempt before the conflict is returned to the caller.
+    pub max_retries: u32,
+
+    /// Delay before each retry, of which a random part is waived so retries of commands that
+    /// conflicted together do not collide again.
+    pub backoff: BackoffSettings,
+}
+
+impl Default for CommandRetrySettings {
+    fn default() -> Self {
+        Self {
+            max_retries: 5,
+            backoff: BackoffSettings {
+                initial_delay: Duration::from_millis(10),
+                max_delay: Duration::from_millis(500),
+                multiplier: 2.0,
+            },
+        }
+    }
+}
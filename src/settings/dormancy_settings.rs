@@ -0,0 +1,43 @@
+use serde::Deserialize;
+use serde_with::serde_as;
+use std::time::Duration;
+
+const DAY: u64 = 24 * 60 * 60;
+
+#[serde_as]
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(default)]
+pub struct DormancySettings {
+    /// Run the task that marks inactive accounts dormant and flags them for escheatment.
+    pub worker_enabled: bool,
+
+    #[serde(alias = "poll_interval_secs")]
+    #[serde_as(as = "serde_with::DurationSeconds<u64>")]
+    pub poll_interval: Duration,
+
+    /// Maximum number of inactive accounts marked dormant in each pass.
+    pub batch_size: i64,
+
+    /// How long an account may go without customer-initiated activity before it is dormant.
+    #[serde(alias = "dormant_after_secs")]
+    #[serde_as(as = "serde_with::DurationSeconds<u64>")]
+    pub dormant_after: Duration,
+
+    /// How long a dormant account may go without customer-initiated activity before its balance
+    /// is reported for transfer to the state as unclaimed property.
+    #[serde(alias = "escheat_after_secs")]
+    #[serde_as(as = "serde_with::DurationSeconds<u64>")]
+    pub escheat_after: Duration,
+}
+
+impl Default for DormancySettings {
+    fn default() -> Self {
+        Self {
+            worker_enabled: true,
+            poll_interval: Duration::from_secs(60 * 60),
+            batch_size: 100,
+            dormant_after: Duration::from_secs(365 * DAY),
+            escheat_after: Duration::from_secs(3 * 365 * DAY),
+        }
+    }
+}
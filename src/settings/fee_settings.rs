@@ -0,0 +1,89 @@
+use crate::model::AtmId;
+use money2::{Currency, Money};
+use serde::Deserialize;
+use serde_with::serde_as;
+use std::time::Duration;
+
+const DAY: u64 = 24 * 60 * 60;
+
+#[serde_as]
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(default)]
+pub struct FeeSettings {
+    /// Run the task that assesses periodic fees at the end of each account's billing period.
+    pub worker_enabled: bool,
+
+    #[serde(alias = "poll_interval_secs")]
+    #[serde_as(as = "serde_with::DurationSeconds<u64>")]
+    pub poll_interval: Duration,
+
+    /// Maximum number of accounts assessed in each pass.
+    pub batch_size: i64,
+
+    /// Length of the billing period over which periodic fees are assessed.
+    #[serde(alias = "billing_period_secs")]
+    #[serde_as(as = "serde_with::DurationSeconds<u64>")]
+    pub billing_period: Duration,
+
+    /// Delay before retrying the assessment of an account that could not be assessed.
+    #[serde(alias = "retry_interval_secs")]
+    #[serde_as(as = "serde_with::DurationSeconds<u64>")]
+    pub retry_interval: Duration,
+
+    pub schedule: FeeSchedule,
+}
+
+impl Default for FeeSettings {
+    fn default() -> Self {
+        Self {
+            worker_enabled: true,
+            poll_interval: Duration::from_secs(60 * 60),
+            batch_size: 100,
+            billing_period: Duration::from_secs(30 * DAY),
+            retry_interval: Duration::from_secs(60 * 60),
+            schedule: FeeSchedule::default(),
+        }
+    }
+}
+
+/// The fees charged to accounts. A fee is only charged once its amount is configured.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(default)]
+pub struct FeeSchedule {
+    /// Charged at the end of each billing period.
+    pub monthly_maintenance: Option<Money>,
+
+    /// Charged at the end of a billing period if the account's balance is below `minimum_balance`.
+    pub below_minimum_balance: Option<Money>,
+
+    /// Balance below which the below-minimum-balance fee is charged.
+    pub minimum_balance: Money,
+
+    /// Charged for each check disbursed from the account.
+    pub per_check: Option<Money>,
+
+    /// Charged for each cash withdrawal from an ATM outside the home networks.
+    pub foreign_atm: Option<Money>,
+
+    /// Networks of the bank's own ATMs, identified by the `AtmId` network prefix.
+    pub home_atm_networks: Vec<String>,
+}
+
+impl FeeSchedule {
+    pub fn is_home_atm(&self, atm_id: &AtmId) -> bool {
+        self.home_atm_networks.iter().any(|network| network == atm_id.network())
+    }
+}
+
+impl Default for FeeSchedule {
+    fn default() -> Self {
+        Self {
+            monthly_maintenance: None,
+            below_minimum_balance: None,
+            minimum_balance: Money::new(0, 2, Currency::Usd),
+            per_check: None,
+            foreign_atm: None,
+            home_atm_networks: Vec::new(),
+        }
+    }
+}
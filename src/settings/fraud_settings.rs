@@ -0,0 +1,53 @@
+use crate::services::FraudOutcome;
+use money2::Money;
+use serde::Deserialize;
+use serde_with::serde_as;
+use std::time::Duration;
+
+#[derive(Debug, Default, Clone, PartialEq, Eq, Deserialize)]
+#[serde(default)]
+pub struct FraudSettings {
+    /// Rules consulted before withdrawals and contact changes. The most severe outcome of the
+    /// rules that match decides whether the transaction is allowed, held for review or denied.
+    pub rules: Vec<FraudRule>,
+}
+
+#[serde_as]
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(tag = "rule", rename_all = "snake_case")]
+pub enum FraudRule {
+    /// Matches withdrawals of at least the threshold amount.
+    AmountThreshold {
+        threshold: Money,
+        outcome: FraudOutcome,
+    },
+
+    /// Matches a withdrawal when the account already made `max_withdrawals` within the window.
+    RapidWithdrawals {
+        max_withdrawals: usize,
+        #[serde(alias = "window_secs")]
+        #[serde_as(as = "serde_with::DurationSeconds<u64>")]
+        window: Duration,
+        outcome: FraudOutcome,
+    },
+
+    /// Matches a withdrawal made within the window after the mailing address or email changed.
+    WithdrawalAfterContactChange {
+        #[serde(alias = "window_secs")]
+        #[serde_as(as = "serde_with::DurationSeconds<u64>")]
+        window: Duration,
+        outcome: FraudOutcome,
+    },
+
+    /// Matches a cash withdrawal from an ATM the account has never used before.
+    NewAtm { outcome: FraudOutcome },
+
+    /// Matches a contact change when the account already made `max_changes` within the window.
+    RapidContactChanges {
+        max_changes: usize,
+        #[serde(alias = "window_secs")]
+        #[serde_as(as = "serde_with::DurationSeconds<u64>")]
+        window: Duration,
+        outcome: FraudOutcome,
+    },
+}
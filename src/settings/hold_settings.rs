@@ -0,0 +1,28 @@
+use serde::Deserialize;
+use serde_with::serde_as;
+use std::time::Duration;
+
+#[serde_as]
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(default)]
+pub struct HoldSettings {
+    /// Run the task that expires holds once their authorization window has passed.
+    pub expiry_enabled: bool,
+
+    #[serde(alias = "poll_interval_secs")]
+    #[serde_as(as = "serde_with::DurationSeconds<u64>")]
+    pub poll_interval: Duration,
+
+    /// Maximum number of expired holds released in each pass.
+    pub batch_size: i64,
+}
+
+impl Default for HoldSettings {
+    fn default() -> Self {
+        Self {
+            expiry_enabled: true,
+            poll_interval: Duration::from_secs(30),
+            batch_size: 100,
+        }
+    }
+}
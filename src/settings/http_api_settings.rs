@@ -1,10 +1,12 @@
-use serde::Deserialize;
+use serde::de::{Error, Unexpected};
+use serde::{Deserialize, Deserializer};
 use serde_with::serde_as;
 use settings_loader::common::http::HttpServerSettings;
+use std::net::IpAddr;
 use std::time::Duration;
 
 #[serde_as]
-#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
 pub struct HttpApiSettings {
     #[serde(flatten)]
     pub server: HttpServerSettings,
@@ -14,13 +16,58 @@ pub struct HttpApiSettings {
     pub timeout: Duration,
 
     pub rate_limit: RateLimitSettings,
+
+    /// Addresses of the reverse proxies in front of the server. The `X-Forwarded-For` and
+    /// `X-Real-IP` headers identify a client only on requests these proxies pass on; any other
+    /// client could set them to spread its requests over addresses it does not own.
+    #[serde(default)]
+    pub trusted_proxies: Vec<IpAddr>,
 }
 
-#[serde_as]
+/// Request quotas of each client, identified by its authenticated subject or else its IP address.
+///
+/// Reads and writes are counted in separate buckets, so polling an account does not use up the
+/// quota for moving money.
 #[derive(Debug, Copy, Clone, PartialEq, Eq, Deserialize)]
 pub struct RateLimitSettings {
+    /// Quota for reading requests, and for writes unless `writes` is set.
+    #[serde(flatten)]
+    pub reads: RateLimitQuota,
+
+    /// Quota for requests that change state, such as deposits, withdrawals and transfers.
+    #[serde(default)]
+    pub writes: Option<RateLimitQuota>,
+}
+
+impl RateLimitSettings {
+    pub fn writes_quota(&self) -> RateLimitQuota {
+        self.writes.unwrap_or(self.reads)
+    }
+}
+
+#[serde_as]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Deserialize)]
+pub struct RateLimitQuota {
+    /// Number of requests allowed in a burst, replenished evenly over `per_duration`. A quota
+    /// allowing no requests is rejected, since its bucket would never refill.
+    #[serde(deserialize_with = "deser_nonzero_requests")]
     pub nr_requests: u64,
+
     #[serde(alias = "per_secs")]
     #[serde_as(as = "serde_with::DurationSecondsWithFrac<f64>")]
     pub per_duration: Duration,
 }
+
+fn deser_nonzero_requests<'de, D>(deserializer: D) -> Result<u64, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let nr_requests = u64::deserialize(deserializer)?;
+    if nr_requests == 0 {
+        return Err(D::Error::invalid_value(
+            Unexpected::Unsigned(0),
+            &"a positive number of requests",
+        ));
+    }
+    Ok(nr_requests)
+}
@@ -0,0 +1,11 @@
+use serde::Deserialize;
+
+/// The provider verifying customers' identities against their submitted documents.
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum IdentityVerifierSettings {
+    /// Verifies any customer who submitted government-issued photo identification, standing in
+    /// for a verification provider.
+    #[default]
+    Local,
+}
@@ -0,0 +1,9 @@
+use serde::Deserialize;
+
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq, Deserialize)]
+#[serde(default)]
+pub struct MigrationSettings {
+    /// Apply pending database migrations when the application starts. Replicas starting together
+    /// take turns under an advisory lock, so each migration is applied once.
+    pub run_on_startup: bool,
+}
@@ -0,0 +1,111 @@
+use super::BackoffSettings;
+use money2::{Currency, Money};
+use secrecy::{ExposeSecret, Secret};
+use serde::Deserialize;
+use serde_with::serde_as;
+use std::path::PathBuf;
+use std::time::Duration;
+
+#[serde_as]
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(default)]
+pub struct NotificationSettings {
+    /// Send customer notifications in reaction to account events. Notifications are rendered from
+    /// the event outbox, so events recorded while disabled are notified once enabled, as long as
+    /// they are still within the outbox retention period.
+    pub enabled: bool,
+
+    /// Mailbox used as the sender of notifications, e.g., `Bank <no-reply@bank.example.com>`.
+    pub sender: String,
+
+    /// Withdrawals of at least this amount trigger a large withdrawal notification.
+    pub large_withdrawal_threshold: Money,
+
+    pub mailer: MailerSettings,
+
+    #[serde(alias = "poll_interval_millis")]
+    #[serde_as(as = "serde_with::DurationMilliSeconds<u64>")]
+    pub poll_interval: Duration,
+
+    /// Maximum number of outbox events rendered, and of messages sent, in each pass.
+    pub batch_size: i64,
+
+    /// Number of failed attempts after which a message is no longer retried.
+    pub max_attempts: u32,
+
+    /// Time allowed for sending a message, after which the attempt counts as failed.
+    #[serde(alias = "send_timeout_secs")]
+    #[serde_as(as = "serde_with::DurationSeconds<u64>")]
+    pub send_timeout: Duration,
+
+    pub backoff: BackoffSettings,
+}
+
+impl Default for NotificationSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            sender: "Bank <no-reply@bank.example.com>".to_string(),
+            large_withdrawal_threshold: Money::new(1_000, 0, Currency::Usd),
+            mailer: MailerSettings::default(),
+            poll_interval: Duration::from_secs(1),
+            batch_size: 100,
+            max_attempts: 8,
+            send_timeout: Duration::from_secs(30),
+            backoff: BackoffSettings::default(),
+        }
+    }
+}
+
+#[derive(Debug, Default, Clone, PartialEq, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum MailerSettings {
+    /// Delivers notifications through an SMTP relay.
+    Smtp(SmtpSettings),
+
+    /// Appends each notification as a JSON line to the file, which is useful for testing.
+    File { path: PathBuf },
+
+    /// Retains notifications in memory, which is useful for testing.
+    #[default]
+    InMemory,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct SmtpSettings {
+    pub host: String,
+
+    #[serde(default = "SmtpSettings::default_port")]
+    pub port: u16,
+
+    #[serde(default)]
+    pub username: Option<String>,
+
+    #[serde(default)]
+    pub password: Option<Secret<String>>,
+
+    /// Upgrade the connection to TLS via STARTTLS; disable only for local relays.
+    #[serde(default = "SmtpSettings::default_starttls")]
+    pub starttls: bool,
+}
+
+impl SmtpSettings {
+    const fn default_port() -> u16 {
+        587
+    }
+
+    const fn default_starttls() -> bool {
+        true
+    }
+}
+
+impl PartialEq for SmtpSettings {
+    fn eq(&self, other: &Self) -> bool {
+        self.host == other.host
+            && self.port == other.port
+            && self.username == other.username
+            && self.password.as_ref().map(|p| p.expose_secret())
+                == other.password.as_ref().map(|p| p.expose_secret())
+            && self.starttls == other.starttls
+    }
+}
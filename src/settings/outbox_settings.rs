@@ -0,0 +1,108 @@
+use super::BackoffSettings;
+use serde::Deserialize;
+use serde_with::serde_as;
+use std::fmt;
+use std::path::PathBuf;
+use std::time::Duration;
+use url::Url;
+
+#[serde_as]
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(default)]
+pub struct OutboxSettings {
+    /// Run the relay task that publishes outbox rows to the configured publisher.
+    pub enabled: bool,
+
+    #[serde(alias = "poll_interval_millis")]
+    #[serde_as(as = "serde_with::DurationMilliSeconds<u64>")]
+    pub poll_interval: Duration,
+
+    /// Maximum number of outbox rows considered in each relay pass.
+    pub batch_size: i64,
+
+    #[serde(alias = "request_timeout_secs")]
+    #[serde_as(as = "serde_with::DurationSeconds<u64>")]
+    pub request_timeout: Duration,
+
+    pub backoff: BackoffSettings,
+
+    pub publisher: PublisherSettings,
+
+    /// Run the task that deletes outbox rows past the retention period.
+    pub prune_enabled: bool,
+
+    /// Age after which outbox rows are deleted, once every enabled outbox consumer (the relay,
+    /// webhooks and notifications) has handled them. A consumer enabled later only sees the rows
+    /// recorded within this period.
+    #[serde(alias = "retention_secs")]
+    #[serde_as(as = "serde_with::DurationSeconds<u64>")]
+    pub retention: Duration,
+
+    #[serde(alias = "prune_interval_secs")]
+    #[serde_as(as = "serde_with::DurationSeconds<u64>")]
+    pub prune_interval: Duration,
+}
+
+impl Default for OutboxSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            poll_interval: Duration::from_secs(1),
+            batch_size: 100,
+            request_timeout: Duration::from_secs(10),
+            backoff: BackoffSettings::default(),
+            publisher: PublisherSettings::Stdout,
+            prune_enabled: true,
+            retention: Duration::from_secs(7 * 24 * 60 * 60),
+            prune_interval: Duration::from_secs(60 * 60),
+        }
+    }
+}
+
+#[derive(Clone, PartialEq, Eq, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum PublisherSettings {
+    /// Write each event as a JSON line to standard out.
+    Stdout,
+
+    /// Append each event as a JSON line to the file.
+    File { path: PathBuf },
+
+    /// POST each event as JSON to the url.
+    Webhook { url: Url },
+
+    /// Produce each event, keyed by aggregate id, to the topic via a Kafka REST proxy (v2 API).
+    KafkaRest { base_url: Url, topic: String },
+}
+
+/// Shows the publisher urls with their userinfo redacted, since it may carry credentials and the
+/// settings are printed by `check-config`.
+impl fmt::Debug for PublisherSettings {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Stdout => f.write_str("Stdout"),
+            Self::File { path } => f.debug_struct("File").field("path", path).finish(),
+            Self::Webhook { url } => {
+                f.debug_struct("Webhook").field("url", &redacted(url)).finish()
+            },
+            Self::KafkaRest { base_url, topic } => f
+                .debug_struct("KafkaRest")
+                .field("base_url", &redacted(base_url))
+                .field("topic", topic)
+                .finish(),
+        }
+    }
+}
+
+/// The url with any username and password replaced.
+fn redacted(url: &Url) -> String {
+    if url.username().is_empty() && url.password().is_none() {
+        return url.to_string();
+    }
+
+    let mut redacted = url.clone();
+    // a url with userinfo has a host, so its userinfo can be set
+    let _ = redacted.set_username("REDACTED");
+    let _ = redacted.set_password(None);
+    redacted.to_string()
+}
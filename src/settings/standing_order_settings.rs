@@ -0,0 +1,40 @@
+use serde::Deserialize;
+use serde_with::serde_as;
+use std::time::Duration;
+
+#[serde_as]
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(default)]
+pub struct StandingOrderSettings {
+    /// Run the scheduler that pays standing orders as they come due.
+    pub scheduler_enabled: bool,
+
+    #[serde(alias = "poll_interval_secs")]
+    #[serde_as(as = "serde_with::DurationSeconds<u64>")]
+    pub poll_interval: Duration,
+
+    /// Maximum number of due standing orders paid in each pass.
+    pub batch_size: i64,
+
+    /// Delay before retrying a payment declined for insufficient funds.
+    #[serde(alias = "retry_interval_secs")]
+    #[serde_as(as = "serde_with::DurationSeconds<u64>")]
+    pub retry_interval: Duration,
+
+    /// How long after its due time a declined payment is retried before it is failed.
+    #[serde(alias = "retry_window_secs")]
+    #[serde_as(as = "serde_with::DurationSeconds<u64>")]
+    pub retry_window: Duration,
+}
+
+impl Default for StandingOrderSettings {
+    fn default() -> Self {
+        Self {
+            scheduler_enabled: true,
+            poll_interval: Duration::from_secs(30),
+            batch_size: 100,
+            retry_interval: Duration::from_secs(60 * 60),
+            retry_window: Duration::from_secs(3 * 24 * 60 * 60),
+        }
+    }
+}
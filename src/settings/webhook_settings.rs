@@ -0,0 +1,42 @@
+use super::BackoffSettings;
+use serde::Deserialize;
+use serde_with::serde_as;
+use std::time::Duration;
+
+#[serde_as]
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(default)]
+pub struct WebhookSettings {
+    /// Run the task that dispatches outbox events to subscriptions and delivers them to
+    /// subscribers. Disabled by default.
+    pub enabled: bool,
+
+    #[serde(alias = "poll_interval_millis")]
+    #[serde_as(as = "serde_with::DurationMilliSeconds<u64>")]
+    pub poll_interval: Duration,
+
+    /// Maximum number of deliveries attempted in each pass.
+    pub batch_size: i64,
+
+    /// Number of failed attempts after which a delivery is moved to the dead-letter list.
+    pub max_attempts: u32,
+
+    #[serde(alias = "request_timeout_secs")]
+    #[serde_as(as = "serde_with::DurationSeconds<u64>")]
+    pub request_timeout: Duration,
+
+    pub backoff: BackoffSettings,
+}
+
+impl Default for WebhookSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            poll_interval: Duration::from_secs(1),
+            batch_size: 50,
+            max_attempts: 8,
+            request_timeout: Duration::from_secs(10),
+            backoff: BackoffSettings::default(),
+        }
+    }
+}
@@ -0,0 +1,213 @@
+//! Scheduled payment of standing orders.
+//!
+//! The [StandingOrderScheduleQuery] indexes each scheduled standing order by the time its payment
+//! is next attempted, and the scheduler periodically issues a `PayDueOccurrence` command for the
+//! orders that are due. Each pass holds a Postgres advisory lock for its duration, so only one
+//! application instance pays due orders at a time; the order and the accounts it pays from and to
+//! each reject an occurrence already paid, so an occurrence interrupted by a restart is completed
+//! rather than paid twice. An index entry that has fallen behind its order is moved to the time
+//! the order is next due, and one for an order that can no longer be paid is removed.
+
+use crate::model::{StandingOrder, StandingOrderAggregate, StandingOrderCommand};
+use crate::model::{StandingOrderError, StandingOrderEvent};
+use crate::settings::StandingOrderSettings;
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use cqrs_es::{AggregateError, EventEnvelope, Query};
+use pretty_snowflake::envelope::MetaData;
+use sqlx::{PgConnection, PgExecutor, PgPool, Row};
+use tokio::task::JoinHandle;
+use tokio::time::MissedTickBehavior;
+
+pub const SCHEDULE_TABLE: &str = "standing_order_schedule";
+
+/// Key of the advisory lock held by the scheduler pass paying due standing orders.
+const SCHEDULER_LOCK_KEY: i64 = 0x5354_4f52_4445_5253; // "STORDERS"
+
+/// Maintains the index of scheduled standing orders from committed standing order events.
+#[derive(Debug, Clone)]
+pub struct StandingOrderScheduleQuery {
+    pool: PgPool,
+}
+
+impl StandingOrderScheduleQuery {
+    pub const fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+
+    async fn record(
+        &self, aggregate_id: &str, event: &EventEnvelope<StandingOrder>,
+    ) -> Result<(), sqlx::Error> {
+        match &event.payload {
+            StandingOrderEvent::StandingOrderCreated { first_due_at: next_attempt_at, .. }
+            | StandingOrderEvent::StandingOrderPaid {
+                next_due_at: Some(next_attempt_at), ..
+            }
+            | StandingOrderEvent::StandingOrderFailed {
+                next_due_at: Some(next_attempt_at), ..
+            }
+            | StandingOrderEvent::PaymentRetryScheduled { retry_at: next_attempt_at, .. } => {
+                self.schedule(aggregate_id, next_attempt_at).await
+            },
+
+            StandingOrderEvent::StandingOrderPaid { next_due_at: None, .. }
+            | StandingOrderEvent::StandingOrderFailed { next_due_at: None, .. }
+            | StandingOrderEvent::StandingOrderCancelled => {
+                unschedule(&self.pool, aggregate_id).await
+            },
+        }
+    }
+
+    async fn schedule(
+        &self, aggregate_id: &str, next_attempt_at: &DateTime<Utc>,
+    ) -> Result<(), sqlx::Error> {
+        let upsert_sql = format!(
+            r##"INSERT INTO {SCHEDULE_TABLE} (aggregate_id, next_attempt_at)
+            VALUES ($1, $2)
+            ON CONFLICT (aggregate_id) DO UPDATE SET next_attempt_at = EXCLUDED.next_attempt_at"##
+        );
+        sqlx::query(&upsert_sql)
+            .bind(aggregate_id)
+            .bind(next_attempt_at)
+            .execute(&self.pool)
+            .await?;
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl Query<StandingOrder> for StandingOrderScheduleQuery {
+    #[tracing::instrument(level = "debug", skip(self, events))]
+    async fn dispatch(&self, aggregate_id: &str, events: &[EventEnvelope<StandingOrder>]) {
+        for event in events {
+            if let Err(error) = self.record(aggregate_id, event).await {
+                tracing::error!(
+                    ?error, sequence=%event.sequence,
+                    "failed to index standing order schedule"
+                );
+            }
+        }
+    }
+}
+
+async fn unschedule<'e>(
+    executor: impl PgExecutor<'e>, aggregate_id: &str,
+) -> Result<(), sqlx::Error> {
+    let delete_sql = format!("DELETE FROM {SCHEDULE_TABLE} WHERE aggregate_id = $1");
+    sqlx::query(&delete_sql).bind(aggregate_id).execute(executor).await?;
+    Ok(())
+}
+
+/// Moves a scheduled order's next attempt to the time the order itself is next due, so a stale
+/// schedule entry is not attempted again every pass.
+async fn defer<'e>(
+    executor: impl PgExecutor<'e>, aggregate_id: &str, next_attempt_at: DateTime<Utc>,
+) -> Result<(), sqlx::Error> {
+    let update_sql =
+        format!("UPDATE {SCHEDULE_TABLE} SET next_attempt_at = $2 WHERE aggregate_id = $1");
+    sqlx::query(&update_sql)
+        .bind(aggregate_id)
+        .bind(next_attempt_at)
+        .execute(executor)
+        .await?;
+    Ok(())
+}
+
+pub fn spawn_scheduler(
+    pool: PgPool, agg: StandingOrderAggregate, settings: StandingOrderSettings,
+) -> JoinHandle<()> {
+    tokio::spawn(async move {
+        tracing::info!("starting standing order scheduler...");
+        let mut interval = tokio::time::interval(settings.poll_interval);
+        interval.set_missed_tick_behavior(MissedTickBehavior::Delay);
+
+        loop {
+            interval.tick().await;
+            match pay_due_batch(&pool, &agg, settings.batch_size).await {
+                Ok(0) => {},
+                Ok(nr_attempted) => tracing::debug!(%nr_attempted, "paid due standing orders"),
+                Err(error) => tracing::error!(?error, "standing order scheduler pass failed"),
+            }
+        }
+    })
+}
+
+/// Attempts payment of one batch of due standing orders, returning the number attempted. The pass
+/// is skipped if another instance holds the scheduler lock.
+#[tracing::instrument(level = "debug", skip(pool, agg))]
+pub async fn pay_due_batch(
+    pool: &PgPool, agg: &StandingOrderAggregate, batch_size: i64,
+) -> Result<usize, sqlx::Error> {
+    let mut conn = pool.acquire().await?;
+    let locked: bool = sqlx::query_scalar("SELECT pg_try_advisory_lock($1)")
+        .bind(SCHEDULER_LOCK_KEY)
+        .fetch_one(&mut *conn)
+        .await?;
+    if !locked {
+        tracing::debug!("standing order scheduler is locked by another instance -- skipping pass");
+        return Ok(0);
+    }
+
+    let result = pay_due(&mut conn, agg, batch_size).await;
+
+    let unlocked = sqlx::query("SELECT pg_advisory_unlock($1)")
+        .bind(SCHEDULER_LOCK_KEY)
+        .execute(&mut *conn)
+        .await;
+    if let Err(error) = &unlocked {
+        // closing the connection releases its session lock instead of returning it to the pool
+        tracing::warn!(
+            ?error,
+            "failed to release standing order scheduler lock -- closing its connection"
+        );
+        drop(conn.detach());
+    }
+    unlocked?;
+
+    result
+}
+
+async fn pay_due(
+    conn: &mut PgConnection, agg: &StandingOrderAggregate, batch_size: i64,
+) -> Result<usize, sqlx::Error> {
+    let select_sql = format!(
+        r##"SELECT aggregate_id FROM {SCHEDULE_TABLE}
+        WHERE next_attempt_at <= now()
+        ORDER BY next_attempt_at
+        LIMIT $1"##
+    );
+    let rows = sqlx::query(&select_sql).bind(batch_size).fetch_all(&mut *conn).await?;
+
+    let mut nr_attempted = 0;
+    for row in rows {
+        let aggregate_id: String = row.try_get("aggregate_id")?;
+
+        let result = agg
+            .execute_with_metadata(
+                &aggregate_id,
+                StandingOrderCommand::PayDueOccurrence,
+                MetaData::<StandingOrder>::default().into(),
+            )
+            .await;
+
+        match result {
+            Ok(()) => nr_attempted += 1,
+            Err(AggregateError::UserError(StandingOrderError::NotDue(_, next_attempt_at))) => {
+                tracing::debug!(
+                    %aggregate_id, %next_attempt_at,
+                    "standing order not due yet -- deferred"
+                );
+                defer(&mut *conn, &aggregate_id, next_attempt_at).await?;
+            },
+            Err(AggregateError::UserError(StandingOrderError::RejectedCommand(reason))) => {
+                tracing::info!(%aggregate_id, %reason, "standing order not payable -- unscheduled");
+                unschedule(&mut *conn, &aggregate_id).await?;
+            },
+            Err(error) => {
+                tracing::warn!(?error, %aggregate_id, "failed to pay standing order");
+            },
+        }
+    }
+
+    Ok(nr_attempted)
+}
@@ -0,0 +1,36 @@
+//! Partner webhook subscriptions to bank account events.
+//!
+//! The delivery worker dispatches each event queued in the transactional event outbox, enqueueing
+//! a delivery for every subscription matching its event type and account filters. It then posts
+//! each queued delivery to its subscriber, signed with an HMAC-SHA256 of the subscription's shared
+//! secret, and retries failures with exponential backoff until the attempt budget is exhausted, at
+//! which point the delivery is moved to the dead-letter list.
+
+mod delivery;
+mod dispatch;
+mod subscriptions;
+
+use thiserror::Error;
+
+pub use delivery::{sign_payload, spawn_delivery_worker, SIGNATURE_HEADER, TIMESTAMP_HEADER};
+pub use dispatch::dispatch_batch;
+pub use subscriptions::{
+    create_subscription, delete_subscription, find_subscription, list_dead_letters,
+    list_deliveries, list_subscriptions, requeue_delivery, Delivery, DeliveryFilter, DeliveryId,
+    DeliveryStatus, Subscription, SubscriptionId, SubscriptionRequest,
+};
+
+pub const SUBSCRIPTIONS_TABLE: &str = "webhook_subscriptions";
+pub const DELIVERIES_TABLE: &str = "webhook_deliveries";
+
+#[derive(Debug, Error)]
+pub enum WebhookError {
+    #[error("failed to build webhook client: {0}")]
+    Http(#[from] reqwest::Error),
+
+    #[error("failed webhook database operation: {0}")]
+    Sql(#[from] sqlx::Error),
+
+    #[error("invalid webhook delivery data: {0}")]
+    Serialization(#[from] serde_json::Error),
+}
@@ -0,0 +1,305 @@
+use super::{dispatch_batch, DeliveryStatus, WebhookError, DELIVERIES_TABLE, SUBSCRIPTIONS_TABLE};
+use crate::settings::WebhookSettings;
+use chrono::{DateTime, Utc};
+use hmac::{Hmac, Mac};
+use serde_json::json;
+use sha2::Sha256;
+use sqlx::{PgPool, Row};
+use std::time::Duration;
+use tokio::task::JoinHandle;
+use tokio::time::MissedTickBehavior;
+
+/// Header carrying `sha256=<hex digest>` of the HMAC-SHA256 over `"{timestamp}.{body}"`.
+pub const SIGNATURE_HEADER: &str = "x-bank-signature";
+
+/// Header carrying the unix timestamp (seconds) included in the signature.
+pub const TIMESTAMP_HEADER: &str = "x-bank-timestamp";
+
+pub const DELIVERY_ID_HEADER: &str = "x-bank-delivery";
+pub const EVENT_TYPE_HEADER: &str = "x-bank-event";
+
+type HmacSha256 = Hmac<Sha256>;
+
+/// Signs the delivery body so subscribers can verify it was sent by the bank and not altered.
+pub fn sign_payload(secret: &str, timestamp: i64, body: &[u8]) -> String {
+    let mut mac =
+        HmacSha256::new_from_slice(secret.as_bytes()).expect("HMAC accepts keys of any size");
+    mac.update(timestamp.to_string().as_bytes());
+    mac.update(b".");
+    mac.update(body);
+    format!("sha256={}", hex::encode(mac.finalize().into_bytes()))
+}
+
+pub fn spawn_delivery_worker(
+    pool: PgPool, settings: WebhookSettings, client: reqwest::Client,
+) -> JoinHandle<()> {
+    tokio::spawn(async move {
+        tracing::info!("starting webhook delivery worker...");
+        let mut interval = tokio::time::interval(settings.poll_interval);
+        interval.set_missed_tick_behavior(MissedTickBehavior::Delay);
+
+        loop {
+            interval.tick().await;
+            match dispatch_batch(&pool, settings.batch_size).await {
+                Ok(0) => {},
+                Ok(nr_dispatched) => {
+                    tracing::debug!(
+                        %nr_dispatched,
+                        "dispatched outbox events to webhook subscriptions"
+                    )
+                },
+                Err(error) => tracing::error!(?error, "webhook dispatch pass failed"),
+            }
+
+            match deliver_batch(&pool, &client, &settings).await {
+                Ok(0) => {},
+                Ok(nr_attempted) => tracing::debug!(%nr_attempted, "attempted webhook deliveries"),
+                Err(error) => tracing::error!(?error, "webhook delivery pass failed"),
+            }
+        }
+    })
+}
+
+#[derive(Debug)]
+struct PendingDelivery {
+    id: i64,
+    subscription_id: i64,
+    aggregate_type: String,
+    aggregate_id: String,
+    sequence: i64,
+    event_type: String,
+    payload: serde_json::Value,
+    attempts: i32,
+    target_url: String,
+    secret: String,
+}
+
+/// Time beyond the request timeout for which a claimed delivery is leased, to record its outcome
+/// after it is sent.
+const LEASE_MARGIN: Duration = Duration::from_secs(30);
+
+/// Longest subscriber response body recorded as a failed delivery's error.
+const MAX_ERROR_BODY_LEN: usize = 1024;
+
+/// Attempts the due pending deliveries, returning the number attempted. Each delivery is claimed
+/// by leasing it until its next attempt before it is sent, so several instances can deliver
+/// concurrently without sending a delivery twice, and its outcome is recorded on its own so a
+/// failure does not undo the deliveries already made.
+#[tracing::instrument(level = "debug", skip(pool, client, settings))]
+async fn deliver_batch(
+    pool: &PgPool, client: &reqwest::Client, settings: &WebhookSettings,
+) -> Result<usize, WebhookError> {
+    let lease = settings.request_timeout + LEASE_MARGIN;
+    let batch_size = usize::try_from(settings.batch_size).unwrap_or_default();
+    let mut nr_attempted = 0;
+
+    while nr_attempted < batch_size {
+        let (delivery, leased_until) = match claim_due_delivery(pool, lease).await? {
+            Some(claimed) => claimed,
+            None => break,
+        };
+
+        let outcome = send(client, &delivery).await;
+        let is_recorded = record_outcome(pool, &delivery, leased_until, outcome, settings).await?;
+        if !is_recorded {
+            tracing::warn!(
+                delivery_id=%delivery.id, %leased_until,
+                "webhook delivery lease expired before its outcome was recorded -- left to its new lease"
+            );
+        }
+        nr_attempted += 1;
+    }
+
+    Ok(nr_attempted)
+}
+
+/// Claims the next due pending delivery by leasing it, deferring its next attempt until the lease
+/// expires in case its outcome is never recorded.
+async fn claim_due_delivery(
+    pool: &PgPool, lease: Duration,
+) -> Result<Option<(PendingDelivery, DateTime<Utc>)>, sqlx::Error> {
+    let update_sql = format!(
+        r##"UPDATE {DELIVERIES_TABLE} d
+        SET next_attempt_at = now() + make_interval(secs => $2)
+        FROM {SUBSCRIPTIONS_TABLE} s
+        WHERE s.id = d.subscription_id AND d.id = (
+            SELECT id FROM {DELIVERIES_TABLE}
+            WHERE status = $1 AND next_attempt_at <= now()
+            ORDER BY id
+            LIMIT 1
+            FOR UPDATE SKIP LOCKED
+        )
+        RETURNING d.id, d.subscription_id, d.aggregate_type, d.aggregate_id, d.sequence,
+            d.event_type, d.payload, d.attempts, d.next_attempt_at, s.target_url, s.secret"##
+    );
+
+    let claimed = sqlx::query(&update_sql)
+        .bind(DeliveryStatus::Pending.to_string())
+        .bind(lease.as_secs_f64())
+        .fetch_optional(pool)
+        .await?;
+
+    claimed
+        .map(|row| {
+            let delivery = PendingDelivery {
+                id: row.try_get("id")?,
+                subscription_id: row.try_get("subscription_id")?,
+                aggregate_type: row.try_get("aggregate_type")?,
+                aggregate_id: row.try_get("aggregate_id")?,
+                sequence: row.try_get("sequence")?,
+                event_type: row.try_get("event_type")?,
+                payload: row.try_get("payload")?,
+                attempts: row.try_get("attempts")?,
+                target_url: row.try_get("target_url")?,
+                secret: row.try_get("secret")?,
+            };
+            Ok((delivery, row.try_get("next_attempt_at")?))
+        })
+        .transpose()
+}
+
+/// Records the outcome of the delivery under the worker's lease, returning whether it was
+/// recorded. An outcome is not recorded once the lease expired and the delivery was claimed again,
+/// so a stalled worker does not overwrite the outcome recorded under the newer lease.
+async fn record_outcome(
+    pool: &PgPool, delivery: &PendingDelivery, leased_until: DateTime<Utc>,
+    outcome: Result<i32, (Option<i32>, String)>, settings: &WebhookSettings,
+) -> Result<bool, sqlx::Error> {
+    let result = match outcome {
+        Ok(status_code) => {
+            let update_sql = format!(
+                r##"UPDATE {DELIVERIES_TABLE}
+                SET status = $3, attempts = attempts + 1, last_status_code = $4,
+                    last_error = NULL, delivered_at = now()
+                WHERE id = $1 AND status = $5 AND next_attempt_at = $2"##
+            );
+            sqlx::query(&update_sql)
+                .bind(delivery.id)
+                .bind(leased_until)
+                .bind(DeliveryStatus::Delivered.to_string())
+                .bind(status_code)
+                .bind(DeliveryStatus::Pending.to_string())
+                .execute(pool)
+                .await?
+        },
+        Err((status_code, error)) => {
+            let attempts = delivery.attempts.saturating_add(1);
+            let status = if settings.max_attempts <= attempts.unsigned_abs() {
+                tracing::warn!(
+                    delivery_id=%delivery.id, %attempts, %error,
+                    "webhook delivery exhausted its attempts -- moved to dead letters"
+                );
+                DeliveryStatus::Dead
+            } else {
+                tracing::info!(
+                    delivery_id=%delivery.id, %attempts, %error,
+                    "webhook delivery failed -- will retry"
+                );
+                DeliveryStatus::Pending
+            };
+            let retry_delay = settings.backoff.delay_for(attempts.unsigned_abs());
+
+            let update_sql = format!(
+                r##"UPDATE {DELIVERIES_TABLE}
+                SET status = $3, attempts = $4, last_status_code = $5, last_error = $6,
+                    next_attempt_at = now() + make_interval(secs => $7)
+                WHERE id = $1 AND status = $8 AND next_attempt_at = $2"##
+            );
+            sqlx::query(&update_sql)
+                .bind(delivery.id)
+                .bind(leased_until)
+                .bind(status.to_string())
+                .bind(attempts)
+                .bind(status_code)
+                .bind(error)
+                .bind(retry_delay.as_secs_f64())
+                .bind(DeliveryStatus::Pending.to_string())
+                .execute(pool)
+                .await?
+        },
+    };
+
+    Ok(0 < result.rows_affected())
+}
+
+/// Posts the delivery, returning the response status code or the failure status (if a response
+/// was received) and error description.
+async fn send(
+    client: &reqwest::Client, delivery: &PendingDelivery,
+) -> Result<i32, (Option<i32>, String)> {
+    let body = json!({
+        "delivery_id": delivery.id,
+        "subscription_id": delivery.subscription_id,
+        "aggregate_type": delivery.aggregate_type,
+        "aggregate_id": delivery.aggregate_id,
+        "sequence": delivery.sequence,
+        "event_type": delivery.event_type,
+        "event": delivery.payload,
+    });
+    let body = serde_json::to_vec(&body).map_err(|err| (None, err.to_string()))?;
+    let timestamp = Utc::now().timestamp();
+
+    let response = client
+        .post(delivery.target_url.as_str())
+        .header(reqwest::header::CONTENT_TYPE, "application/json")
+        .header(DELIVERY_ID_HEADER, delivery.id)
+        .header(EVENT_TYPE_HEADER, delivery.event_type.as_str())
+        .header(TIMESTAMP_HEADER, timestamp)
+        .header(
+            SIGNATURE_HEADER,
+            sign_payload(&delivery.secret, timestamp, &body),
+        )
+        .body(body)
+        .send()
+        .await
+        .map_err(|err| (err.status().map(|s| i32::from(s.as_u16())), err.to_string()))?;
+
+    let status = response.status();
+    let status_code = i32::from(status.as_u16());
+    if status.is_success() {
+        Ok(status_code)
+    } else {
+        let error = response.text().await.unwrap_or_else(|_| status.to_string());
+        let error = truncate(error, MAX_ERROR_BODY_LEN);
+        Err((
+            Some(status_code),
+            format!("subscriber responded {status}: {error}"),
+        ))
+    }
+}
+
+/// Truncates the text to at most `max_len` bytes, on a character boundary.
+fn truncate(mut text: String, max_len: usize) -> String {
+    if max_len < text.len() {
+        let end = (0..=max_len).rev().find(|&i| text.is_char_boundary(i)).unwrap_or_default();
+        text.truncate(end);
+    }
+    text
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn test_sign_payload() {
+        let actual = sign_payload(
+            "super-secret-shared-key",
+            1_673_290_800,
+            br##"{"sequence":2}"##,
+        );
+        assert_eq!(
+            actual,
+            "sha256=f92b08d7d9e1e6a7a02e6822a91684c7abe69031e5f500e4e16950b751625b3e"
+        );
+    }
+
+    #[test]
+    fn test_truncate() {
+        assert_eq!(truncate("short".to_string(), 10), "short");
+        assert_eq!(truncate("x".repeat(20), 10), "x".repeat(10));
+        // "é" takes two bytes, so it is dropped rather than split
+        assert_eq!(truncate("abcdefghié".to_string(), 10), "abcdefghi");
+    }
+}
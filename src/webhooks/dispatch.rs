@@ -0,0 +1,42 @@
+use super::{WebhookError, DELIVERIES_TABLE, SUBSCRIPTIONS_TABLE};
+use crate::model::bank_account;
+use crate::outbox::OUTBOX_TABLE;
+use sqlx::PgPool;
+
+/// Fans out the next batch of undispatched outbox events, enqueueing a webhook delivery for every
+/// subscription matching each account event, and returns the number of events dispatched. Events
+/// of other aggregates, such as customers and disputes, are marked dispatched without deliveries.
+/// The outbox rows are written in the same transaction as their events, and they are marked
+/// dispatched in the same statement that enqueues their deliveries, so no committed event is
+/// missed or enqueued twice. Rows are locked with `SKIP LOCKED`, so several instances can
+/// dispatch concurrently.
+#[tracing::instrument(level = "debug", skip(pool))]
+pub async fn dispatch_batch(pool: &PgPool, batch_size: i64) -> Result<u64, WebhookError> {
+    let dispatch_sql = format!(
+        r##"WITH pending AS (
+            SELECT id, aggregate_type, aggregate_id, sequence, event_type, payload FROM {OUTBOX_TABLE}
+            WHERE webhooks_dispatched_at IS NULL
+            ORDER BY id
+            LIMIT $1
+            FOR UPDATE SKIP LOCKED
+        ), enqueued AS (
+            INSERT INTO {DELIVERIES_TABLE}
+                (subscription_id, aggregate_type, aggregate_id, sequence, event_type, payload)
+            SELECT s.id, p.aggregate_type, p.aggregate_id, p.sequence, p.event_type, p.payload
+            FROM pending p JOIN {SUBSCRIPTIONS_TABLE} s
+                ON p.aggregate_type = $2
+                AND (cardinality(s.event_types) = 0 OR p.event_type = ANY(s.event_types))
+                AND (cardinality(s.aggregate_ids) = 0 OR p.aggregate_id = ANY(s.aggregate_ids))
+            ON CONFLICT (subscription_id, aggregate_id, sequence) DO NOTHING
+        )
+        UPDATE {OUTBOX_TABLE} SET webhooks_dispatched_at = now()
+        WHERE id IN (SELECT id FROM pending)"##
+    );
+
+    let result = sqlx::query(&dispatch_sql)
+        .bind(batch_size)
+        .bind(bank_account::AGGREGATE_TYPE)
+        .execute(pool)
+        .await?;
+    Ok(result.rows_affected())
+}
@@ -0,0 +1,332 @@
+use super::{DELIVERIES_TABLE, SUBSCRIPTIONS_TABLE};
+use crate::model::{AccountId, BankAccount};
+use chrono::{DateTime, Utc};
+use pretty_snowflake::Id;
+use serde::{Deserialize, Serialize};
+use sqlx::postgres::PgRow;
+use sqlx::{PgPool, Row};
+use std::fmt;
+use std::str::FromStr;
+use strum_macros::{Display, EnumString};
+use url::Url;
+use utoipa::{IntoParams, ToSchema};
+use validator::{Validate, ValidationError};
+
+#[derive(
+    Debug,
+    Copy,
+    Clone,
+    PartialEq,
+    Eq,
+    Hash,
+    PartialOrd,
+    Ord,
+    ToSchema,
+    IntoParams,
+    Serialize,
+    Deserialize,
+)]
+#[schema(example = json!(17_i64))]
+#[into_params(names("subscription_id"))]
+#[serde(transparent)]
+#[repr(transparent)]
+pub struct SubscriptionId(i64);
+
+impl SubscriptionId {
+    pub const fn as_num(&self) -> i64 {
+        self.0
+    }
+}
+
+impl fmt::Display for SubscriptionId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+#[derive(
+    Debug,
+    Copy,
+    Clone,
+    PartialEq,
+    Eq,
+    Hash,
+    PartialOrd,
+    Ord,
+    ToSchema,
+    IntoParams,
+    Serialize,
+    Deserialize,
+)]
+#[schema(example = json!(1087_i64))]
+#[into_params(names("delivery_id"))]
+#[serde(transparent)]
+#[repr(transparent)]
+pub struct DeliveryId(i64);
+
+impl DeliveryId {
+    pub const fn as_num(&self) -> i64 {
+        self.0
+    }
+}
+
+impl fmt::Display for DeliveryId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+/// Request to be notified of account events. Empty `event_types` or `account_ids` match all.
+#[derive(Debug, Clone, PartialEq, Eq, ToSchema, Validate, Deserialize)]
+#[schema(example = json!({
+    "target_url": "https://partner.example.com/bank/events",
+    "event_types": ["balance_deposited", "cash_withdrawal", "check_withdrawal"],
+    "account_ids": [7006077196242653184_u64],
+    "secret": "8f2d0b6c4e1a9f73d5b2",
+}))]
+pub struct SubscriptionRequest {
+    #[schema(value_type = String)]
+    #[validate(custom = "validate_target_url")]
+    pub target_url: Url,
+
+    #[serde(default)]
+    pub event_types: Vec<String>,
+
+    #[serde(default)]
+    pub account_ids: Vec<AccountId>,
+
+    /// Shared secret used to sign deliveries; it is never returned by the API.
+    #[validate(length(min = 16))]
+    pub secret: String,
+}
+
+fn validate_target_url(url: &Url) -> Result<(), ValidationError> {
+    match url.scheme() {
+        "http" | "https" => Ok(()),
+        _ => Err(ValidationError::new(
+            "target_url must be an http or https url",
+        )),
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, ToSchema, Serialize)]
+pub struct Subscription {
+    pub id: SubscriptionId,
+    #[schema(value_type = String)]
+    pub target_url: Url,
+    pub event_types: Vec<String>,
+    pub account_ids: Vec<AccountId>,
+    pub created_at: DateTime<Utc>,
+}
+
+impl Subscription {
+    fn from_row(row: &PgRow) -> Result<Self, sqlx::Error> {
+        let target_url: String = row.try_get("target_url")?;
+        let target_url = Url::parse(&target_url).map_err(|err| sqlx::Error::ColumnDecode {
+            index: "target_url".to_string(),
+            source: err.into(),
+        })?;
+        let account_ids: Vec<i64> = row.try_get("account_ids")?;
+
+        Ok(Self {
+            id: SubscriptionId(row.try_get("id")?),
+            target_url,
+            event_types: row.try_get("event_types")?,
+            account_ids: account_ids.into_iter().map(AccountId::new).collect(),
+            created_at: row.try_get("created_at")?,
+        })
+    }
+}
+
+#[derive(
+    Debug, Display, Copy, Clone, PartialEq, Eq, EnumString, ToSchema, Serialize, Deserialize,
+)]
+#[strum(serialize_all = "snake_case")]
+#[serde(rename_all = "snake_case")]
+pub enum DeliveryStatus {
+    Pending,
+    Delivered,
+    Dead,
+}
+
+#[derive(Debug, Default, Clone, PartialEq, Eq, IntoParams, Deserialize)]
+#[into_params(parameter_in = Query)]
+pub struct DeliveryFilter {
+    /// Only include deliveries in this status.
+    pub status: Option<DeliveryStatus>,
+}
+
+/// Log entry for the delivery of one account event to one subscription.
+#[derive(Debug, Clone, PartialEq, Eq, ToSchema, Serialize)]
+pub struct Delivery {
+    pub id: DeliveryId,
+    pub subscription_id: SubscriptionId,
+    pub aggregate_type: String,
+    pub aggregate_id: String,
+    pub sequence: i64,
+    pub event_type: String,
+    pub status: DeliveryStatus,
+    pub attempts: i32,
+    pub last_status_code: Option<i32>,
+    pub last_error: Option<String>,
+    pub next_attempt_at: DateTime<Utc>,
+    pub created_at: DateTime<Utc>,
+    pub delivered_at: Option<DateTime<Utc>>,
+}
+
+impl Delivery {
+    fn from_row(row: &PgRow) -> Result<Self, sqlx::Error> {
+        let status: String = row.try_get("status")?;
+        let status = DeliveryStatus::from_str(&status).map_err(|err| {
+            sqlx::Error::ColumnDecode { index: "status".to_string(), source: err.into() }
+        })?;
+
+        Ok(Self {
+            id: DeliveryId(row.try_get("id")?),
+            subscription_id: SubscriptionId(row.try_get("subscription_id")?),
+            aggregate_type: row.try_get("aggregate_type")?,
+            aggregate_id: row.try_get("aggregate_id")?,
+            sequence: row.try_get("sequence")?,
+            event_type: row.try_get("event_type")?,
+            status,
+            attempts: row.try_get("attempts")?,
+            last_status_code: row.try_get("last_status_code")?,
+            last_error: row.try_get("last_error")?,
+            next_attempt_at: row.try_get("next_attempt_at")?,
+            created_at: row.try_get("created_at")?,
+            delivered_at: row.try_get("delivered_at")?,
+        })
+    }
+}
+
+const SUBSCRIPTION_COLUMNS: &str = "id, target_url, event_types, account_ids, created_at";
+
+const DELIVERY_COLUMNS: &str = "id, subscription_id, aggregate_type, aggregate_id, sequence, \
+                                event_type, status, attempts, last_status_code, last_error, \
+                                next_attempt_at, created_at, delivered_at";
+
+#[tracing::instrument(level = "debug", skip(pool, request), fields(target_url=%request.target_url))]
+pub async fn create_subscription(
+    pool: &PgPool, request: SubscriptionRequest,
+) -> Result<Subscription, sqlx::Error> {
+    let account_ids: Vec<i64> = request.account_ids.iter().map(AccountId::as_num).collect();
+    let aggregate_ids: Vec<String> = request
+        .account_ids
+        .iter()
+        .map(|account_id| {
+            let aggregate_id: Id<BankAccount> = (*account_id).into();
+            aggregate_id.pretty().to_string()
+        })
+        .collect();
+
+    let insert_sql = format!(
+        r##"INSERT INTO {SUBSCRIPTIONS_TABLE} (target_url, event_types, account_ids, aggregate_ids, secret)
+        VALUES ($1, $2, $3, $4, $5)
+        RETURNING {SUBSCRIPTION_COLUMNS}"##
+    );
+
+    let row = sqlx::query(&insert_sql)
+        .bind(request.target_url.as_str())
+        .bind(&request.event_types)
+        .bind(&account_ids)
+        .bind(&aggregate_ids)
+        .bind(&request.secret)
+        .fetch_one(pool)
+        .await?;
+
+    Subscription::from_row(&row)
+}
+
+#[tracing::instrument(level = "debug", skip(pool))]
+pub async fn list_subscriptions(pool: &PgPool) -> Result<Vec<Subscription>, sqlx::Error> {
+    let select_sql =
+        format!("SELECT {SUBSCRIPTION_COLUMNS} FROM {SUBSCRIPTIONS_TABLE} ORDER BY id");
+    let rows = sqlx::query(&select_sql).fetch_all(pool).await?;
+    rows.iter().map(Subscription::from_row).collect()
+}
+
+#[tracing::instrument(level = "debug", skip(pool))]
+pub async fn find_subscription(
+    pool: &PgPool, subscription_id: SubscriptionId,
+) -> Result<Option<Subscription>, sqlx::Error> {
+    let select_sql =
+        format!("SELECT {SUBSCRIPTION_COLUMNS} FROM {SUBSCRIPTIONS_TABLE} WHERE id = $1");
+    let row = sqlx::query(&select_sql)
+        .bind(subscription_id.as_num())
+        .fetch_optional(pool)
+        .await?;
+    row.as_ref().map(Subscription::from_row).transpose()
+}
+
+/// Deletes the subscription along with its delivery log, returning whether it existed.
+#[tracing::instrument(level = "debug", skip(pool))]
+pub async fn delete_subscription(
+    pool: &PgPool, subscription_id: SubscriptionId,
+) -> Result<bool, sqlx::Error> {
+    let delete_sql = format!("DELETE FROM {SUBSCRIPTIONS_TABLE} WHERE id = $1");
+    let result = sqlx::query(&delete_sql)
+        .bind(subscription_id.as_num())
+        .execute(pool)
+        .await?;
+    Ok(0 < result.rows_affected())
+}
+
+#[tracing::instrument(level = "debug", skip(pool))]
+pub async fn list_deliveries(
+    pool: &PgPool, subscription_id: SubscriptionId, filter: &DeliveryFilter, offset: i64,
+    limit: i64,
+) -> Result<Vec<Delivery>, sqlx::Error> {
+    let select_sql = format!(
+        r##"SELECT {DELIVERY_COLUMNS} FROM {DELIVERIES_TABLE}
+        WHERE subscription_id = $1 AND ($2::text IS NULL OR status = $2)
+        ORDER BY id DESC
+        LIMIT $3 OFFSET $4"##
+    );
+    let rows = sqlx::query(&select_sql)
+        .bind(subscription_id.as_num())
+        .bind(filter.status.map(|status| status.to_string()))
+        .bind(limit)
+        .bind(offset)
+        .fetch_all(pool)
+        .await?;
+    rows.iter().map(Delivery::from_row).collect()
+}
+
+#[tracing::instrument(level = "debug", skip(pool))]
+pub async fn list_dead_letters(
+    pool: &PgPool, offset: i64, limit: i64,
+) -> Result<Vec<Delivery>, sqlx::Error> {
+    let select_sql = format!(
+        r##"SELECT {DELIVERY_COLUMNS} FROM {DELIVERIES_TABLE}
+        WHERE status = $1
+        ORDER BY id DESC
+        LIMIT $2 OFFSET $3"##
+    );
+    let rows = sqlx::query(&select_sql)
+        .bind(DeliveryStatus::Dead.to_string())
+        .bind(limit)
+        .bind(offset)
+        .fetch_all(pool)
+        .await?;
+    rows.iter().map(Delivery::from_row).collect()
+}
+
+/// Returns a dead-lettered delivery to the pending queue with a fresh attempt budget.
+#[tracing::instrument(level = "debug", skip(pool))]
+pub async fn requeue_delivery(
+    pool: &PgPool, delivery_id: DeliveryId,
+) -> Result<Option<Delivery>, sqlx::Error> {
+    let update_sql = format!(
+        r##"UPDATE {DELIVERIES_TABLE}
+        SET status = $2, attempts = 0, next_attempt_at = now()
+        WHERE id = $1 AND status = $3
+        RETURNING {DELIVERY_COLUMNS}"##
+    );
+    let row = sqlx::query(&update_sql)
+        .bind(delivery_id.as_num())
+        .bind(DeliveryStatus::Pending.to_string())
+        .bind(DeliveryStatus::Dead.to_string())
+        .fetch_optional(pool)
+        .await?;
+    row.as_ref().map(Delivery::from_row).transpose()
+}
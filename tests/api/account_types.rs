@@ -0,0 +1,141 @@
+use crate::helpers::{spawn_app_with, TestApp, TELLER_TOKEN, X_REAL_IP};
+use axum::http::StatusCode;
+use bankaccount::application::Version;
+use bankaccount::{AccountId, AccountType};
+use claim::assert_ok;
+use money2::{Currency, Money};
+use pretty_assertions::assert_eq;
+use serde_json::json;
+
+async fn withdraw(app: &TestApp, account_id: AccountId, amount: &str) -> reqwest::Response {
+    app.post_atm_withdrawal(
+        account_id,
+        json!({ "atm_id": "abc_123", "amount": { "amount": amount, "currency": "USD" } }),
+    )
+    .await
+}
+
+async fn disburse_check(app: &TestApp, account_id: AccountId, amount: &str) -> reqwest::Response {
+    app.post_check_withdrawal(
+        account_id,
+        json!({ "check_nr": 1001, "amount": { "amount": amount, "currency": "USD" } }),
+    )
+    .await
+}
+
+async fn change_account_type(
+    app: &TestApp, account_id: AccountId, account_type: &str,
+) -> reqwest::Response {
+    assert_ok!(
+        app.api_client
+            .post(format!("{}/{}/account_type", app.bank_url(), account_id))
+            .header(X_REAL_IP, "127.0.0.1")
+            .bearer_auth(TELLER_TOKEN)
+            .json(&json!({ "account_type": account_type }))
+            .send()
+            .await
+    )
+}
+
+#[tokio::test]
+async fn savings_account_rejects_checks_and_keeps_minimum_balance() {
+    let app = spawn_app_with(Version::latest(), |_| {}).await;
+    let account_id = app
+        .open_account_with(json!({ "account_type": "savings" }), Some("500.00"))
+        .await;
+    assert_eq!(
+        app.account_view(account_id).await.account_type,
+        AccountType::Savings
+    );
+
+    let response = disburse_check(&app, account_id, "20.00").await;
+    assert_eq!(response.status(), StatusCode::UNPROCESSABLE_ENTITY);
+
+    let response = withdraw(&app, account_id, "450.00").await;
+    assert_eq!(response.status(), StatusCode::UNPROCESSABLE_ENTITY);
+
+    let response = withdraw(&app, account_id, "400.00").await;
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(
+        app.account_view(account_id).await.balance,
+        Money::new(100, 0, Currency::Usd)
+    );
+}
+
+#[tokio::test]
+async fn monthly_withdrawal_cap_and_limit_are_loaded_from_settings() {
+    let app = spawn_app_with(Version::latest(), |settings| {
+        settings.account_types.checking.monthly_withdrawal_cap = Some(2);
+        settings.account_types.checking.max_withdrawal = Some(Money::new(50, 0, Currency::Usd));
+    })
+    .await;
+    let account_id = app
+        .open_account_with(json!({ "account_type": "checking" }), Some("500.00"))
+        .await;
+
+    let response = withdraw(&app, account_id, "75.00").await;
+    assert_eq!(response.status(), StatusCode::UNPROCESSABLE_ENTITY);
+
+    let response = withdraw(&app, account_id, "20.00").await;
+    assert_eq!(response.status(), StatusCode::OK);
+    let response = disburse_check(&app, account_id, "20.00").await;
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let response = withdraw(&app, account_id, "20.00").await;
+    assert_eq!(response.status(), StatusCode::UNPROCESSABLE_ENTITY);
+    assert_eq!(
+        app.account_view(account_id).await.balance,
+        Money::new(460, 0, Currency::Usd)
+    );
+}
+
+#[tokio::test]
+async fn concurrent_withdrawals_do_not_exceed_the_monthly_cap() {
+    let app = spawn_app_with(Version::latest(), |settings| {
+        settings.account_types.checking.monthly_withdrawal_cap = Some(1);
+    })
+    .await;
+    let account_id = app
+        .open_account_with(json!({ "account_type": "checking" }), Some("500.00"))
+        .await;
+
+    let withdrawals = (0..5).map(|_| withdraw(&app, account_id, "20.00"));
+    let mut nr_withdrawn = 0;
+    for response in futures::future::join_all(withdrawals).await {
+        match response.status() {
+            StatusCode::OK => nr_withdrawn += 1,
+            StatusCode::CONFLICT | StatusCode::UNPROCESSABLE_ENTITY => {},
+            status => panic!("unexpected withdrawal status: {status}"),
+        }
+    }
+
+    assert_eq!(nr_withdrawn, 1);
+    assert_eq!(
+        app.account_view(account_id).await.balance,
+        Money::new(480, 0, Currency::Usd)
+    );
+}
+
+#[tokio::test]
+async fn account_type_changes_only_when_eligible() {
+    let app = spawn_app_with(Version::latest(), |_| {}).await;
+    let account_id = app
+        .open_account_with(json!({ "account_type": "checking" }), Some("500.00"))
+        .await;
+
+    let response = change_account_type(&app, account_id, "checking").await;
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+
+    let response = change_account_type(&app, account_id, "business").await;
+    assert_eq!(response.status(), StatusCode::UNPROCESSABLE_ENTITY);
+
+    let response = change_account_type(&app, account_id, "savings").await;
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(
+        app.account_view(account_id).await.account_type,
+        AccountType::Savings
+    );
+
+    let response = disburse_check(&app, account_id, "20.00").await;
+    assert_eq!(response.status(), StatusCode::UNPROCESSABLE_ENTITY);
+}
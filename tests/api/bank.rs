@@ -1,11 +1,15 @@
-use crate::helpers::{spawn_latest_app, TestApp};
+use crate::helpers::{spawn_app_with, spawn_latest_app, TestApp, TELLER_TOKEN, X_REAL_IP};
 use axum::http::StatusCode;
-use bankaccount::{AccountId, AtmId, BankAccount, BankAccountView, CheckNumber, LedgerEntry};
+use bankaccount::application::Version;
+use bankaccount::{
+    AccountId, ApiTokenSettings, AtmId, BankAccount, BankAccountView, CheckNumber, LedgerEntry,
+};
 use claim::{assert_ok, assert_some};
 use money2::{Currency, Money};
 use pretty_assertions::{assert_eq, assert_ne};
 use pretty_snowflake::Id;
 use reqwest::Response;
+use secrecy::Secret;
 use serde_json::json;
 
 fn create_account_body(
@@ -61,11 +65,13 @@ async fn create_bank_account_returns_a_200_and_account_id() {
 #[tokio::test]
 async fn create_bank_account_persists_event_and_view() {
     let app = spawn_latest_app().await;
-    let body = create_account_body(
+    let customer_id = app.register_verified_customer("otis").await;
+    let mut body = create_account_body(
         Some("otis"),
         Some("123 Main St., Springfield, IL, 61890"),
         None,
     );
+    body["customer_id"] = json!(customer_id);
 
     let response = app.post_create_bank_account(body).await;
     let account_id: AccountId = assert_ok!(response.json().await);
@@ -90,6 +96,8 @@ async fn create_bank_account_persists_event_and_view() {
         json!({
             "AccountOpened": {
                 "account_id": account_id,
+                "account_type": "checking",
+                "customer_id": customer_id,
                 "email": "otis@example.com",
                 "mailing_address": "123 Main St., Springfield, IL, 61890",
                 "user_name": "otis"
@@ -123,20 +131,51 @@ async fn create_bank_account_persists_event_and_view() {
         saved_view.payload,
         json!({
             "account_id": account_id,
+            "account_type": "checking",
             "balance": {
                 "amount": "0",
                 "currency": "USD"
             },
+            "available_balance": {
+                "amount": "0",
+                "currency": "USD"
+            },
             "ledger": [],
             "written_checks": []
         })
     );
 }
 
+#[tokio::test]
+async fn create_bank_account_opens_in_the_customers_registered_name() {
+    let app = spawn_latest_app().await;
+    let customer_id = app.register_verified_customer("otis").await;
+    let mut body = create_account_body(Some("mallory"), None, None);
+    body["customer_id"] = json!(customer_id);
+
+    let response = app.post_create_bank_account(body).await;
+    assert_eq!(response.status(), StatusCode::OK);
+    let account_id: AccountId = assert_ok!(response.json().await);
+    let aggregate_id: Id<BankAccount> = account_id.into();
+
+    let owner: String = assert_ok!(
+        sqlx::query_scalar(
+            "SELECT payload -> 'AccountOpened' ->> 'user_name' FROM events WHERE aggregate_id = \
+             $1 AND sequence = 1"
+        )
+        .bind(aggregate_id.pretty())
+        .fetch_one(&app.db_pool)
+        .await
+    );
+    assert_eq!(owner, "otis");
+}
+
 #[tokio::test]
 async fn create_account_fails_if_there_is_a_fatal_database_error() {
     let app = spawn_latest_app().await;
-    let body = create_account_body(None, None, None);
+    let customer_id = app.register_verified_customer("neo").await;
+    let mut body = create_account_body(None, None, None);
+    body["customer_id"] = json!(customer_id);
 
     assert_ok!(
         sqlx::query!("ALTER TABLE events DROP COLUMN aggregate_type;")
@@ -176,10 +215,10 @@ async fn deposit_amount_returns_a_200() {
         BankAccountView {
             account_id: Some(account_id),
             balance: Money::new(123456, 2, Currency::Usd),
-            ledger: vec![LedgerEntry::new(
-                "deposit",
-                Money::new(123456, 2, Currency::Usd),
-            )],
+            available_balance: Money::new(123456, 2, Currency::Usd),
+            ledger: vec![
+                LedgerEntry::new("deposit", Money::new(123456, 2, Currency::Usd)).with_sequence(2),
+            ],
             ..Default::default()
         },
     )
@@ -210,9 +249,11 @@ async fn atm_withdrawal_returns_a_200() {
         BankAccountView {
             account_id: Some(account_id),
             balance: Money::new(77, 2, Currency::Usd),
+            available_balance: Money::new(77, 2, Currency::Usd),
             ledger: vec![
-                LedgerEntry::new("deposit", Money::new(1000, 2, Currency::Usd)),
-                LedgerEntry::new("ATM withdrawal", Money::new(-923, 2, Currency::Usd)),
+                LedgerEntry::new("deposit", Money::new(1000, 2, Currency::Usd)).with_sequence(2),
+                LedgerEntry::new("ATM withdrawal", Money::new(-923, 2, Currency::Usd))
+                    .with_sequence(3),
             ],
             ..Default::default()
         },
@@ -245,12 +286,14 @@ async fn check_withdrawal_returns_a_200() {
         BankAccountView {
             account_id: Some(account_id),
             balance: Money::new(77, 2, Currency::Usd),
+            available_balance: Money::new(77, 2, Currency::Usd),
             ledger: vec![
-                LedgerEntry::new("deposit", Money::new(1000, 2, Currency::Usd)),
+                LedgerEntry::new("deposit", Money::new(1000, 2, Currency::Usd)).with_sequence(2),
                 LedgerEntry::new(
                     format!("Check {check_nr}"),
                     Money::new(-923, 2, Currency::Usd),
-                ),
+                )
+                .with_sequence(3),
             ],
             written_checks: vec![CheckNumber::new(873487_u32)],
             ..Default::default()
@@ -259,6 +302,252 @@ async fn check_withdrawal_returns_a_200() {
     .await;
 }
 
+#[tokio::test]
+async fn account_events_returns_persisted_event_stream() {
+    let app = spawn_latest_app().await;
+    let response = app.post_create_bank_account(create_account_body(None, None, None)).await;
+    assert_eq!(response.status(), StatusCode::OK);
+    let account_id: AccountId = assert_ok!(response.json().await);
+    for amount in [1000, 250] {
+        let response = app
+            .post_deposit_amount(
+                account_id,
+                create_money_body(Money::new(amount, 2, Currency::Usd)),
+            )
+            .await;
+        assert_eq!(response.status(), StatusCode::OK);
+    }
+
+    let response = app.get_account_events(account_id, &[]).await;
+    assert_eq!(response.status(), StatusCode::OK);
+    let events: Vec<serde_json::Value> = assert_ok!(response.json().await);
+    let event_types: Vec<_> = events.iter().map(|e| e["event_type"].clone()).collect();
+    assert_eq!(
+        event_types,
+        vec![
+            json!("account_opened"),
+            json!("balance_deposited"),
+            json!("balance_deposited")
+        ]
+    );
+    assert_eq!(events[1]["sequence"], json!(2));
+    assert_eq!(events[1]["event_version"], json!("1.0"));
+    assert_eq!(
+        events[1]["payload"],
+        json!({ "BalanceDeposited": { "amount": { "amount": "10.00", "currency": "USD" } } })
+    );
+    assert_ne!(
+        events[1]["metadata"]["recv_timestamp"],
+        serde_json::Value::Null
+    );
+
+    let response = app
+        .get_account_events(
+            account_id,
+            &[
+                ("event_type", "balance_deposited"),
+                ("page", "2"),
+                ("per_page", "1"),
+            ],
+        )
+        .await;
+    assert_eq!(response.status(), StatusCode::OK);
+    let events: Vec<serde_json::Value> = assert_ok!(response.json().await);
+    assert_eq!(events.len(), 1);
+    assert_eq!(events[0]["sequence"], json!(3));
+}
+
+#[tokio::test]
+async fn account_events_are_served_only_to_holders_and_admins() {
+    let app = spawn_app_with(Version::latest(), |settings| {
+        settings.auth.tokens.push(ApiTokenSettings {
+            subject: "smith".to_string(),
+            token: Secret::new("smith-token".to_string()),
+            scopes: vec![],
+        });
+    })
+    .await;
+    let account_id = app.open_account().await;
+    let events_url = format!("{}/{}/events", app.bank_url(), account_id);
+
+    let response =
+        assert_ok!(app.api_client.get(&events_url).header(X_REAL_IP, "127.0.0.1").send().await);
+    assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+
+    let response = assert_ok!(
+        app.api_client
+            .get(&events_url)
+            .header(X_REAL_IP, "127.0.0.1")
+            .bearer_auth("smith-token")
+            .send()
+            .await
+    );
+    assert_eq!(response.status(), StatusCode::FORBIDDEN);
+}
+
+#[tokio::test]
+async fn account_view_is_withheld_from_non_holders_and_as_of_views_from_anonymous_callers() {
+    let app = spawn_app_with(Version::latest(), |settings| {
+        settings.auth.tokens.push(ApiTokenSettings {
+            subject: "smith".to_string(),
+            token: Secret::new("smith-token".to_string()),
+            scopes: vec![],
+        });
+    })
+    .await;
+    let account_id = app.open_account().await;
+    let account_url = format!("{}/{}", app.bank_url(), account_id);
+
+    let response = app.get_serve_bank_account(account_id).await;
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let response = assert_ok!(
+        app.api_client
+            .get(&account_url)
+            .query(&[("as_of", "1")])
+            .header(X_REAL_IP, "127.0.0.1")
+            .send()
+            .await
+    );
+    assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+
+    let queries: [&[(&str, &str)]; 2] = [&[], &[("as_of", "1")]];
+    for query in queries {
+        let response = assert_ok!(
+            app.api_client
+                .get(&account_url)
+                .query(query)
+                .header(X_REAL_IP, "127.0.0.1")
+                .bearer_auth("smith-token")
+                .send()
+                .await
+        );
+        assert_eq!(response.status(), StatusCode::FORBIDDEN, "{query:?}");
+    }
+
+    let response = app.get_serve_bank_account_as_of(account_id, "1").await;
+    assert_eq!(response.status(), StatusCode::OK);
+}
+
+#[tokio::test]
+async fn accounts_by_balance_are_served_only_to_admins() {
+    let app = spawn_app_with(Version::latest(), |settings| {
+        settings.auth.tokens.push(ApiTokenSettings {
+            subject: "smith".to_string(),
+            token: Secret::new("smith-token".to_string()),
+            scopes: vec![],
+        });
+    })
+    .await;
+    let account_id = app.open_funded_account("10.00").await;
+    let balance_url = format!("{}/balance", app.bank_url());
+
+    let response = assert_ok!(
+        app.api_client
+            .get(&balance_url)
+            .header(X_REAL_IP, "127.0.0.1")
+            .send()
+            .await
+    );
+    assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+
+    for (token, expected) in [
+        ("smith-token", StatusCode::FORBIDDEN),
+        (TELLER_TOKEN, StatusCode::OK),
+    ] {
+        let response = assert_ok!(
+            app.api_client
+                .get(&balance_url)
+                .header(X_REAL_IP, "127.0.0.1")
+                .bearer_auth(token)
+                .send()
+                .await
+        );
+        assert_eq!(response.status(), expected, "{token}");
+        if expected == StatusCode::OK {
+            let views: Vec<BankAccountView> = assert_ok!(response.json().await);
+            assert_eq!(views.len(), 1);
+            assert_eq!(views[0].account_id, Some(account_id));
+        }
+    }
+}
+
+#[tokio::test]
+async fn account_events_page_out_of_range_is_a_bad_request() {
+    let app = spawn_latest_app().await;
+    let account_id = app.open_account().await;
+    let response = app
+        .get_account_events(account_id, &[("page", "9223372036854775807")])
+        .await;
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+}
+
+#[tokio::test]
+async fn account_events_for_unknown_account_returns_a_404() {
+    let app = spawn_latest_app().await;
+    let response = app.get_account_events(AccountId::new(42), &[]).await;
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn bank_account_as_of_sequence_replays_history() {
+    let app = spawn_latest_app().await;
+    let response = app.post_create_bank_account(create_account_body(None, None, None)).await;
+    assert_eq!(response.status(), StatusCode::OK);
+    let account_id: AccountId = assert_ok!(response.json().await);
+    let response = app
+        .post_deposit_amount(
+            account_id,
+            create_money_body(Money::new(1000, 2, Currency::Usd)),
+        )
+        .await;
+    assert_eq!(response.status(), StatusCode::OK);
+    let response = app
+        .post_atm_withdrawal(
+            account_id,
+            create_atm_withdrawal_body("abc_123", Money::new(400, 2, Currency::Usd)),
+        )
+        .await;
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let response = app.get_serve_bank_account_as_of(account_id, "2").await;
+    assert_eq!(response.status(), StatusCode::OK);
+    let actual: BankAccountView = assert_ok!(response.json().await);
+    assert_eq!(
+        actual,
+        BankAccountView {
+            account_id: Some(account_id),
+            balance: Money::new(1000, 2, Currency::Usd),
+            available_balance: Money::new(1000, 2, Currency::Usd),
+            ledger: vec![
+                LedgerEntry::new("deposit", Money::new(1000, 2, Currency::Usd)).with_sequence(2)
+            ],
+            ..Default::default()
+        }
+    );
+
+    // the persisted projection is untouched by the replay
+    let _ = assert_bank_account_detail(
+        &app,
+        account_id,
+        BankAccountView {
+            account_id: Some(account_id),
+            balance: Money::new(600, 2, Currency::Usd),
+            available_balance: Money::new(600, 2, Currency::Usd),
+            ledger: vec![
+                LedgerEntry::new("deposit", Money::new(1000, 2, Currency::Usd)).with_sequence(2),
+                LedgerEntry::new("ATM withdrawal", Money::new(-400, 2, Currency::Usd))
+                    .with_sequence(3),
+            ],
+            ..Default::default()
+        },
+    )
+    .await;
+
+    let response = app.get_serve_bank_account_as_of(account_id, "2000-01-01T00:00:00Z").await;
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+}
+
 // redundant given other tests in this module
 #[tokio::test]
 async fn account_view_updates_with_commands() {
@@ -281,7 +570,8 @@ async fn account_view_updates_with_commands() {
         account_id,
         BankAccountView {
             balance: deposit,
-            ledger: vec![LedgerEntry::new("deposit", deposit)],
+            available_balance: deposit,
+            ledger: vec![LedgerEntry::new("deposit", deposit).with_sequence(2)],
             ..e_created
         },
     )
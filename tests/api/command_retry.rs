@@ -0,0 +1,78 @@
+use crate::helpers::{spawn_app_with, spawn_latest_app, TestApp};
+use axum::http::StatusCode;
+use bankaccount::application::Version;
+use bankaccount::{AccountId, COMMAND_CONFLICTS_EXHAUSTED, COMMAND_CONFLICT_RETRIES};
+use claim::assert_ok;
+use pretty_assertions::assert_eq;
+use serde_json::json;
+
+const NR_DEPOSITS: usize = 16;
+
+/// The account commands retried and those returned still conflicting so far. The counters are
+/// shared by the tests running concurrently, so only their growth is compared.
+fn conflict_counts() -> (u64, u64) {
+    (
+        COMMAND_CONFLICT_RETRIES.with_label_values(&["account"]).get(),
+        COMMAND_CONFLICTS_EXHAUSTED.with_label_values(&["account"]).get(),
+    )
+}
+
+/// Deposits concurrently into the account, returning the number of deposits made and the number
+/// returned as conflicts.
+async fn deposit_concurrently(app: &TestApp, account_id: AccountId) -> (usize, usize) {
+    let deposits = (0..NR_DEPOSITS).map(|_| {
+        app.post_deposit_amount(account_id, json!({ "amount": "10.00", "currency": "USD" }))
+    });
+
+    let (mut nr_deposited, mut nr_conflicts) = (0, 0);
+    for response in futures::future::join_all(deposits).await {
+        match response.status() {
+            StatusCode::OK => nr_deposited += 1,
+            StatusCode::CONFLICT => nr_conflicts += 1,
+            status => panic!("unexpected deposit status: {status}"),
+        }
+    }
+
+    let response = app
+        .get_account_events(
+            account_id,
+            &[("event_type", "balance_deposited"), ("per_page", "100")],
+        )
+        .await;
+    assert_eq!(response.status(), StatusCode::OK);
+    let events: Vec<serde_json::Value> = assert_ok!(response.json().await);
+    assert_eq!(events.len(), nr_deposited);
+
+    (nr_deposited, nr_conflicts)
+}
+
+#[tokio::test]
+async fn concurrent_deposits_are_retried_after_conflicts() {
+    let app = spawn_latest_app().await;
+    let account_id = app.open_account().await;
+    let (retries_before, exhausted_before) = conflict_counts();
+
+    let (nr_deposited, nr_conflicts) = deposit_concurrently(&app, account_id).await;
+
+    let (retries_after, exhausted_after) = conflict_counts();
+    assert!(0 < nr_deposited);
+    assert!(retries_before < retries_after);
+    assert!(exhausted_before + nr_conflicts as u64 <= exhausted_after);
+}
+
+#[tokio::test]
+async fn conflicts_past_the_retry_budget_are_returned() {
+    let app = spawn_app_with(Version::latest(), |settings| {
+        settings.command_retry.max_retries = 0;
+    })
+    .await;
+    let account_id = app.open_account().await;
+    let (_, exhausted_before) = conflict_counts();
+
+    let (nr_deposited, nr_conflicts) = deposit_concurrently(&app, account_id).await;
+
+    let (_, exhausted_after) = conflict_counts();
+    assert!(0 < nr_deposited);
+    assert!(0 < nr_conflicts);
+    assert!(exhausted_before + nr_conflicts as u64 <= exhausted_after);
+}
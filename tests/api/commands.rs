@@ -0,0 +1,245 @@
+use crate::helpers::{spawn_app_with, TestApp, X_REAL_IP};
+use axum::http::{header, StatusCode};
+use bankaccount::application::Version;
+use bankaccount::ApiTokenSettings;
+use claim::{assert_ok, assert_some};
+use pretty_assertions::assert_eq;
+use secrecy::Secret;
+use serde_json::json;
+use std::time::Duration;
+
+const POLL_ATTEMPTS: usize = 100;
+const STRANGER_TOKEN: &str = "stranger-token";
+
+async fn spawn_async_app() -> TestApp {
+    spawn_app_with(Version::latest(), |settings| {
+        settings.command_queue.enabled = true;
+        settings.command_queue.poll_interval = Duration::from_millis(10);
+        settings.auth.tokens.extend([ApiTokenSettings {
+            subject: "stranger".to_string(),
+            token: Secret::new(STRANGER_TOKEN.to_string()),
+            scopes: vec![],
+        }]);
+    })
+    .await
+}
+
+async fn accepted_command_id(response: reqwest::Response) -> i64 {
+    assert_eq!(response.status(), StatusCode::ACCEPTED);
+    let location = assert_some!(response.headers().get(header::LOCATION));
+    let location = assert_ok!(location.to_str()).to_string();
+
+    let accepted: serde_json::Value = assert_ok!(response.json().await);
+    let command_id = assert_some!(accepted["command_id"].as_i64());
+    assert_eq!(location, format!("/api/v1/commands/{command_id}"));
+    command_id
+}
+
+/// Polls the command's report until a worker has recorded its outcome.
+async fn await_outcome(app: &TestApp, command_id: i64) -> serde_json::Value {
+    for _ in 0..POLL_ATTEMPTS {
+        let response = app.get_command(command_id).await;
+        assert_eq!(response.status(), StatusCode::OK);
+        let report: serde_json::Value = assert_ok!(response.json().await);
+        if report["status"] != "pending" {
+            return report;
+        }
+        tokio::time::sleep(Duration::from_millis(50)).await;
+    }
+    panic!("command {command_id} still pending after {POLL_ATTEMPTS} polls");
+}
+
+#[tokio::test]
+async fn accepted_deposit_reports_its_events_once_executed() {
+    let app = spawn_async_app().await;
+    let account_id = app.open_account().await;
+
+    let response = app
+        .post_deposit_amount(account_id, json!({ "amount": "25.00", "currency": "USD" }))
+        .await;
+    let command_id = accepted_command_id(response).await;
+
+    let report = await_outcome(&app, command_id).await;
+    assert_eq!(report["status"], "succeeded");
+    assert_eq!(report["account_id"], json!(account_id));
+    assert!(report.get("error").is_none());
+    let events = assert_some!(report["events"].as_array());
+    assert_eq!(events.len(), 2);
+    assert_eq!(events[0]["event_type"], "balance_deposited");
+    assert_eq!(events[0]["metadata"]["command_id"], command_id.to_string());
+    assert_eq!(events[1]["event_type"], "queued_command_applied");
+}
+
+#[tokio::test]
+async fn command_claimed_again_while_executing_is_applied_once() {
+    let app = spawn_app_with(Version::latest(), |settings| {
+        settings.command_queue.enabled = true;
+        settings.command_queue.poll_interval = Duration::from_millis(10);
+        settings.command_queue.claim_timeout = Duration::from_secs(1);
+    })
+    .await;
+    let account_id = app.open_account().await;
+
+    // Stall every worker's commit of the deposit until the first claim has expired and the
+    // command was claimed again.
+    let mut stall = assert_ok!(app.db_pool.begin().await);
+    assert_ok!(
+        sqlx::query("LOCK TABLE events IN EXCLUSIVE MODE")
+            .execute(&mut stall)
+            .await
+    );
+    let response = app
+        .post_deposit_amount(account_id, json!({ "amount": "25.00", "currency": "USD" }))
+        .await;
+    let command_id = accepted_command_id(response).await;
+    tokio::time::sleep(Duration::from_millis(2_500)).await;
+    let attempts: i32 = assert_ok!(
+        sqlx::query_scalar("SELECT attempts FROM command_queue WHERE command_id = $1")
+            .bind(command_id)
+            .fetch_one(&app.db_pool)
+            .await
+    );
+    assert!(1 < attempts, "command claimed {attempts} times");
+    assert_ok!(stall.rollback().await);
+
+    let report = await_outcome(&app, command_id).await;
+    assert_eq!(report["status"], "succeeded");
+    let response = app
+        .get_account_events(account_id, &[("event_type", "balance_deposited")])
+        .await;
+    assert_eq!(response.status(), StatusCode::OK);
+    let deposits: Vec<serde_json::Value> = assert_ok!(response.json().await);
+    assert_eq!(deposits.len(), 1);
+}
+
+#[tokio::test]
+async fn command_whose_attempts_are_exhausted_is_failed_instead_of_claimed_again() {
+    let app = spawn_app_with(Version::latest(), |settings| {
+        settings.command_queue.enabled = true;
+        settings.command_queue.poll_interval = Duration::from_millis(10);
+        settings.command_queue.max_attempts = 3;
+    })
+    .await;
+    let account_id = app.open_account().await;
+
+    let response = app
+        .post_deposit_amount(account_id, json!({ "amount": "25.00", "currency": "USD" }))
+        .await;
+    let command_id = accepted_command_id(response).await;
+    assert_eq!(await_outcome(&app, command_id).await["status"], "succeeded");
+
+    // As if each of its workers stopped before recording its outcome.
+    assert_ok!(
+        sqlx::query(
+            "UPDATE command_queue
+            SET status = 'pending', error = NULL, attempts = 3, completed_at = NULL,
+                claimed_until = now() - interval '1 second'
+            WHERE command_id = $1"
+        )
+        .bind(command_id)
+        .execute(&app.db_pool)
+        .await
+    );
+
+    let report = await_outcome(&app, command_id).await;
+    assert_eq!(report["status"], "failed");
+    assert_eq!(report["error"]["code"], "command_attempts_exhausted");
+    assert_eq!(
+        report["error"]["status"],
+        StatusCode::INTERNAL_SERVER_ERROR.as_u16()
+    );
+    let attempts: i32 = assert_ok!(
+        sqlx::query_scalar("SELECT attempts FROM command_queue WHERE command_id = $1")
+            .bind(command_id)
+            .fetch_one(&app.db_pool)
+            .await
+    );
+    assert_eq!(attempts, 3);
+}
+
+#[tokio::test]
+async fn rejected_withdrawal_reports_its_problem() {
+    let app = spawn_async_app().await;
+    let account_id = app.open_account().await;
+
+    let response = app
+        .post_atm_withdrawal(
+            account_id,
+            json!({ "atm_id": "abc_123", "amount": { "amount": "20.00", "currency": "USD" } }),
+        )
+        .await;
+    let command_id = accepted_command_id(response).await;
+
+    let report = await_outcome(&app, command_id).await;
+    assert_eq!(report["status"], "failed");
+    assert_eq!(report["events"], json!([]));
+    assert_eq!(report["error"]["code"], "insufficient_funds");
+    assert_eq!(
+        report["error"]["status"],
+        StatusCode::UNPROCESSABLE_ENTITY.as_u16()
+    );
+}
+
+#[tokio::test]
+async fn commands_on_an_account_execute_in_acceptance_order() {
+    let app = spawn_async_app().await;
+    let account_id = app.open_account().await;
+
+    let deposit = app
+        .post_deposit_amount(account_id, json!({ "amount": "30.00", "currency": "USD" }))
+        .await;
+    let deposit_id = accepted_command_id(deposit).await;
+    let withdrawal = app
+        .post_check_withdrawal(
+            account_id,
+            json!({ "check_nr": 1170, "amount": { "amount": "20.00", "currency": "USD" } }),
+        )
+        .await;
+    let withdrawal_id = accepted_command_id(withdrawal).await;
+
+    assert_eq!(await_outcome(&app, deposit_id).await["status"], "succeeded");
+    assert_eq!(
+        await_outcome(&app, withdrawal_id).await["status"],
+        "succeeded"
+    );
+}
+
+#[tokio::test]
+async fn unknown_command_is_not_found() {
+    let app = spawn_async_app().await;
+    let response = app.get_command(7022915432371814400).await;
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn command_report_is_restricted_to_account_holders() {
+    let app = spawn_async_app().await;
+    let account_id = app.open_account().await;
+
+    let response = app
+        .post_deposit_amount(account_id, json!({ "amount": "25.00", "currency": "USD" }))
+        .await;
+    let command_id = accepted_command_id(response).await;
+
+    let command_url = format!("{}/{}", app.commands_url(), command_id);
+    let response = assert_ok!(
+        app.api_client
+            .get(&command_url)
+            .header(X_REAL_IP, "127.0.0.1")
+            .send()
+            .await
+    );
+    assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+
+    let response = assert_ok!(
+        app.api_client
+            .get(&command_url)
+            .header(X_REAL_IP, "127.0.0.1")
+            .bearer_auth(STRANGER_TOKEN)
+            .send()
+            .await
+    );
+    assert_eq!(response.status(), StatusCode::FORBIDDEN);
+
+    assert_eq!(await_outcome(&app, command_id).await["status"], "succeeded");
+}
@@ -0,0 +1,333 @@
+use crate::helpers::{
+    account_application, spawn_app_with, spawn_latest_app, TestApp, TELLER_TOKEN, X_REAL_IP,
+};
+use axum::http::{header, StatusCode};
+use bankaccount::application::Version;
+use bankaccount::{
+    AccountId, ApiTokenSettings, CustomerId, CustomerView, DocumentKind, DocumentReference,
+    KycStatus,
+};
+use claim::{assert_ok, assert_some};
+use pretty_assertions::assert_eq;
+use secrecy::Secret;
+use serde_json::json;
+
+const NEO_TOKEN: &str = "neo-token";
+
+async fn spawn_customer_app() -> TestApp {
+    spawn_app_with(Version::latest(), |settings| {
+        settings.auth.tokens.extend([ApiTokenSettings {
+            subject: "neo".to_string(),
+            token: Secret::new(NEO_TOKEN.to_string()),
+            scopes: vec![],
+        }]);
+    })
+    .await
+}
+
+/// Registers the customer named `name`, who signs in as the same subject, on the teller's
+/// authority.
+async fn register_customer(app: &TestApp, name: &str) -> CustomerId {
+    let response = post_registration(app, Some(name), name, Some(TELLER_TOKEN)).await;
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_ok!(response.json().await)
+}
+
+async fn post_registration(
+    app: &TestApp, subject: Option<&str>, name: &str, token: Option<&str>,
+) -> reqwest::Response {
+    let mut registration = json!({
+        "name": name,
+        "email": format!("{name}@example.com"),
+        "mailing_address": "12 Seahawks Way, Renton, WA 98056, USA",
+    });
+    if let Some(subject) = subject {
+        registration["subject"] = json!(subject);
+    }
+
+    let mut request = app
+        .api_client
+        .post(app.customers_url())
+        .header(X_REAL_IP, "127.0.0.1")
+        .json(&registration);
+    if let Some(token) = token {
+        request = request.bearer_auth(token);
+    }
+    assert_ok!(request.send().await)
+}
+
+async fn post_customer_action(
+    app: &TestApp, customer_id: CustomerId, action: &str, body: serde_json::Value,
+    token: Option<&str>,
+) -> reqwest::Response {
+    let mut request = app
+        .api_client
+        .post(format!(
+            "{}/{}/{}",
+            app.customers_url(),
+            customer_id,
+            action
+        ))
+        .header(X_REAL_IP, "127.0.0.1")
+        .json(&body);
+    if let Some(token) = token {
+        request = request.bearer_auth(token);
+    }
+    assert_ok!(request.send().await)
+}
+
+async fn get_customer(
+    app: &TestApp, customer_id: CustomerId, token: Option<&str>,
+) -> reqwest::Response {
+    let mut request = app
+        .api_client
+        .get(format!("{}/{}", app.customers_url(), customer_id))
+        .header(X_REAL_IP, "127.0.0.1");
+    if let Some(token) = token {
+        request = request.bearer_auth(token);
+    }
+    assert_ok!(request.send().await)
+}
+
+async fn customer_view(app: &TestApp, customer_id: CustomerId) -> CustomerView {
+    let response = get_customer(app, customer_id, Some(TELLER_TOKEN)).await;
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_ok!(response.json().await)
+}
+
+async fn apply_for_account(app: &TestApp, customer_id: CustomerId) -> reqwest::Response {
+    let mut application = account_application();
+    application["customer_id"] = json!(customer_id);
+    app.post_create_bank_account(application).await
+}
+
+#[tokio::test]
+async fn registered_customer_is_pending_verification() {
+    let app = spawn_latest_app().await;
+    let customer_id = register_customer(&app, "neo").await;
+
+    let view = customer_view(&app, customer_id).await;
+    assert_eq!(view.customer_id, Some(customer_id));
+    assert_eq!(view.name, "neo");
+    assert_eq!(view.kyc_status, KycStatus::Pending);
+
+    let response = assert_ok!(
+        app.api_client
+            .get(app.customers_url())
+            .query(&[("kyc_status", "pending")])
+            .header(X_REAL_IP, "127.0.0.1")
+            .bearer_auth(TELLER_TOKEN)
+            .send()
+            .await
+    );
+    assert_eq!(response.status(), StatusCode::OK);
+    let pending: Vec<CustomerView> = assert_ok!(response.json().await);
+    assert!(pending.iter().any(|view| view.customer_id == Some(customer_id)));
+
+    let response =
+        post_customer_action(&app, customer_id, "verify", json!({}), Some(TELLER_TOKEN)).await;
+    assert_eq!(response.status(), StatusCode::UNPROCESSABLE_ENTITY);
+}
+
+#[tokio::test]
+async fn account_opening_requires_verified_customer() {
+    let app = spawn_latest_app().await;
+
+    let response = apply_for_account(&app, CustomerId::new(42)).await;
+    assert_eq!(response.status(), StatusCode::UNPROCESSABLE_ENTITY);
+
+    let customer_id = register_customer(&app, "neo").await;
+    let response = apply_for_account(&app, customer_id).await;
+    assert_eq!(response.status(), StatusCode::UNPROCESSABLE_ENTITY);
+
+    let response = post_customer_action(
+        &app,
+        customer_id,
+        "documents",
+        json!({ "kind": "utility_bill", "reference": "docs/neo/electric.pdf" }),
+        Some(TELLER_TOKEN),
+    )
+    .await;
+    assert_eq!(response.status(), StatusCode::OK);
+    let response =
+        post_customer_action(&app, customer_id, "verify", json!({}), Some(TELLER_TOKEN)).await;
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let view = customer_view(&app, customer_id).await;
+    assert_eq!(view.kyc_status, KycStatus::Rejected);
+    assert_eq!(view.verifier.as_deref(), Some("local"));
+    assert_some!(view.rejection_reason);
+    let response = apply_for_account(&app, customer_id).await;
+    assert_eq!(response.status(), StatusCode::UNPROCESSABLE_ENTITY);
+
+    let response = post_customer_action(
+        &app,
+        customer_id,
+        "documents",
+        json!({ "kind": "passport", "reference": "docs/neo/passport.pdf" }),
+        Some(TELLER_TOKEN),
+    )
+    .await;
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(
+        customer_view(&app, customer_id).await.kyc_status,
+        KycStatus::Pending
+    );
+    let response =
+        post_customer_action(&app, customer_id, "verify", json!({}), Some(TELLER_TOKEN)).await;
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let view = customer_view(&app, customer_id).await;
+    assert_eq!(view.kyc_status, KycStatus::Verified);
+    assert_eq!(
+        view.documents,
+        vec![
+            DocumentReference::new(DocumentKind::UtilityBill, "docs/neo/electric.pdf"),
+            DocumentReference::new(DocumentKind::Passport, "docs/neo/passport.pdf"),
+        ]
+    );
+
+    let response = apply_for_account(&app, customer_id).await;
+    assert_eq!(response.status(), StatusCode::OK);
+    let account_id: AccountId = assert_ok!(response.json().await);
+
+    let response = app.get_account_events(account_id, &[]).await;
+    assert_eq!(response.status(), StatusCode::OK);
+    let events: Vec<serde_json::Value> = assert_ok!(response.json().await);
+    assert_eq!(
+        events[0]["payload"]["AccountOpened"]["customer_id"],
+        json!(customer_id)
+    );
+}
+
+#[tokio::test]
+async fn verified_customer_does_not_accept_further_documents() {
+    let app = spawn_latest_app().await;
+    let customer_id = app.register_verified_customer("neo").await;
+
+    let response = post_customer_action(
+        &app,
+        customer_id,
+        "documents",
+        json!({ "kind": "national_id", "reference": "docs/neo/id.pdf" }),
+        Some(TELLER_TOKEN),
+    )
+    .await;
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    assert_eq!(
+        customer_view(&app, customer_id).await.kyc_status,
+        KycStatus::Verified
+    );
+}
+
+#[tokio::test]
+async fn identity_review_requires_an_admin_token() {
+    let app = spawn_latest_app().await;
+    let customer_id = register_customer(&app, "neo").await;
+
+    let response = post_customer_action(
+        &app,
+        customer_id,
+        "documents",
+        json!({ "kind": "passport", "reference": "docs/neo/passport.pdf" }),
+        None,
+    )
+    .await;
+    assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+
+    let response = post_customer_action(&app, customer_id, "verify", json!({}), None).await;
+    assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+
+    let response = assert_ok!(
+        app.api_client
+            .get(app.customers_url())
+            .header(X_REAL_IP, "127.0.0.1")
+            .send()
+            .await
+    );
+    assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+    assert_eq!(
+        customer_view(&app, customer_id).await.kyc_status,
+        KycStatus::Pending
+    );
+}
+
+#[tokio::test]
+async fn customer_registration_is_bound_to_the_callers_subject() {
+    let app = spawn_customer_app().await;
+
+    let response = post_registration(&app, None, "neo", None).await;
+    assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+
+    let response = post_registration(&app, Some("trinity"), "neo", Some(NEO_TOKEN)).await;
+    assert_eq!(response.status(), StatusCode::FORBIDDEN);
+
+    let response = post_registration(&app, None, "neo", Some(NEO_TOKEN)).await;
+    assert_eq!(response.status(), StatusCode::OK);
+    let customer_id: CustomerId = assert_ok!(response.json().await);
+    let view = customer_view(&app, customer_id).await;
+    assert_eq!(view.subject.as_deref(), Some("neo"));
+
+    let response = post_registration(&app, None, "thomas", Some(NEO_TOKEN)).await;
+    assert_eq!(response.status(), StatusCode::CONFLICT);
+    let response = post_registration(&app, Some("neo"), "neo", Some(TELLER_TOKEN)).await;
+    assert_eq!(response.status(), StatusCode::CONFLICT);
+
+    let response = post_registration(&app, None, "morpheus", Some(TELLER_TOKEN)).await;
+    assert_eq!(response.status(), StatusCode::OK);
+    let customer_id: CustomerId = assert_ok!(response.json().await);
+    assert_eq!(customer_view(&app, customer_id).await.subject, None);
+}
+
+#[tokio::test]
+async fn customer_is_served_only_to_the_customer_or_an_admin() {
+    let app = spawn_customer_app().await;
+    let neo = register_customer(&app, "neo").await;
+    let trinity = register_customer(&app, "trinity").await;
+    let impostor = assert_ok!(
+        post_registration(&app, Some("smith"), "neo", Some(TELLER_TOKEN))
+            .await
+            .json::<CustomerId>()
+            .await
+    );
+
+    let response = get_customer(&app, neo, None).await;
+    assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+
+    let response = get_customer(&app, neo, Some(NEO_TOKEN)).await;
+    assert_eq!(response.status(), StatusCode::OK);
+    let view: CustomerView = assert_ok!(response.json().await);
+    assert_eq!(view.name, "neo");
+
+    let response = get_customer(&app, trinity, Some(NEO_TOKEN)).await;
+    assert_eq!(response.status(), StatusCode::FORBIDDEN);
+
+    // sharing the customer's name does not grant access to their record
+    let response = get_customer(&app, impostor, Some(NEO_TOKEN)).await;
+    assert_eq!(response.status(), StatusCode::FORBIDDEN);
+
+    let response = get_customer(&app, trinity, Some(TELLER_TOKEN)).await;
+    assert_eq!(response.status(), StatusCode::OK);
+}
+
+#[tokio::test]
+async fn account_opening_for_a_verified_customer_needs_no_admin_token() {
+    let app = spawn_customer_app().await;
+    let customer_id = app.register_verified_customer("neo").await;
+
+    let mut application = account_application();
+    application["customer_id"] = json!(customer_id);
+    for token in [None, Some(NEO_TOKEN)] {
+        let mut request = app
+            .api_client
+            .post(app.bank_url())
+            .header(header::CONTENT_TYPE, "application/json")
+            .header(X_REAL_IP, "127.0.0.1")
+            .json(&application);
+        if let Some(token) = token {
+            request = request.bearer_auth(token);
+        }
+        let response = assert_ok!(request.send().await);
+        assert_eq!(response.status(), StatusCode::OK, "{token:?}");
+    }
+}
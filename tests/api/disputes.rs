@@ -0,0 +1,316 @@
+use crate::helpers::{api_token, spawn_auth_app, TestApp, X_REAL_IP};
+use axum::http::StatusCode;
+use bankaccount::{
+    AccountId, ApiTokenSettings, BankAccountView, DisputeId, DisputeStatus, DisputeView,
+};
+use claim::{assert_none, assert_ok, assert_some};
+use money2::{Currency, Money};
+use pretty_assertions::assert_eq;
+use serde_json::json;
+
+const ADMIN_TOKEN: &str = "disputes-desk-token";
+const HOLDER_TOKEN: &str = "neo-token";
+const STRANGER_TOKEN: &str = "stranger-token";
+
+fn auth_tokens() -> [ApiTokenSettings; 3] {
+    [
+        api_token("disputes-desk", ADMIN_TOKEN, &["admin:account"]),
+        api_token("neo", HOLDER_TOKEN, &[]),
+        api_token("stranger", STRANGER_TOKEN, &[]),
+    ]
+}
+
+/// Opens an account with a deposit (event 2) of 500.00 followed by an ATM withdrawal (event 3) of
+/// 120.00.
+async fn open_account_with_withdrawal(app: &TestApp) -> AccountId {
+    let account_id = app.open_funded_account("500.00").await;
+    let response = app
+        .post_atm_withdrawal(
+            account_id,
+            json!({ "atm_id": "abc_123", "amount": { "amount": "120.00", "currency": "USD" } }),
+        )
+        .await;
+    assert_eq!(response.status(), StatusCode::OK);
+    account_id
+}
+
+async fn open_dispute(app: &TestApp, account_id: AccountId, sequence: usize) -> reqwest::Response {
+    open_dispute_as(app, account_id, sequence, HOLDER_TOKEN).await
+}
+
+async fn open_dispute_as(
+    app: &TestApp, account_id: AccountId, sequence: usize, token: &str,
+) -> reqwest::Response {
+    assert_ok!(
+        app.api_client
+            .post(app.disputes_url())
+            .header(X_REAL_IP, "127.0.0.1")
+            .bearer_auth(token)
+            .json(&json!({
+                "account_id": account_id,
+                "sequence": sequence,
+                "reason": "ATM did not dispense cash",
+            }))
+            .send()
+            .await
+    )
+}
+
+async fn post_dispute_action(
+    app: &TestApp, dispute_id: DisputeId, action: &str, body: serde_json::Value,
+    token: Option<&str>,
+) -> reqwest::Response {
+    let mut request = app
+        .api_client
+        .post(format!("{}/{}/{}", app.disputes_url(), dispute_id, action))
+        .header(X_REAL_IP, "127.0.0.1")
+        .json(&body);
+    if let Some(token) = token {
+        request = request.bearer_auth(token);
+    }
+    assert_ok!(request.send().await)
+}
+
+async fn get_dispute(app: &TestApp, dispute_id: DisputeId, token: &str) -> reqwest::Response {
+    assert_ok!(
+        app.api_client
+            .get(format!("{}/{}", app.disputes_url(), dispute_id))
+            .header(X_REAL_IP, "127.0.0.1")
+            .bearer_auth(token)
+            .send()
+            .await
+    )
+}
+
+async fn dispute_view(app: &TestApp, dispute_id: DisputeId) -> DisputeView {
+    let response = get_dispute(app, dispute_id, HOLDER_TOKEN).await;
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_ok!(response.json().await)
+}
+
+async fn account_balance(app: &TestApp, account_id: AccountId) -> Money {
+    let response = app.get_serve_bank_account(account_id).await;
+    assert_eq!(response.status(), StatusCode::OK);
+    let view: BankAccountView = assert_ok!(response.json().await);
+    view.balance
+}
+
+#[tokio::test]
+async fn provisional_credit_is_taken_back_when_resolved_for_bank() {
+    let app = spawn_auth_app(auth_tokens()).await;
+    let account_id = open_account_with_withdrawal(&app).await;
+
+    let response = open_dispute(&app, account_id, 3).await;
+    assert_eq!(response.status(), StatusCode::OK);
+    let dispute_id: DisputeId = assert_ok!(response.json().await);
+
+    let response = post_dispute_action(
+        &app,
+        dispute_id,
+        "provisional_credit",
+        json!({}),
+        Some(ADMIN_TOKEN),
+    )
+    .await;
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(
+        account_balance(&app, account_id).await,
+        Money::new(500, 0, Currency::Usd)
+    );
+
+    let view = dispute_view(&app, dispute_id).await;
+    assert_eq!(view.status, DisputeStatus::ProvisionalCreditIssued);
+    assert_eq!(
+        assert_some!(view.provisional_credit),
+        Money::new(120, 0, Currency::Usd)
+    );
+
+    let response = post_dispute_action(
+        &app,
+        dispute_id,
+        "resolve",
+        json!({ "resolution": "bank", "note": "ATM journal shows cash dispensed" }),
+        Some(ADMIN_TOKEN),
+    )
+    .await;
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(
+        account_balance(&app, account_id).await,
+        Money::new(380, 0, Currency::Usd)
+    );
+
+    let view = dispute_view(&app, dispute_id).await;
+    assert_eq!(view.status, DisputeStatus::ResolvedForBank);
+    assert_none!(view.provisional_credit);
+}
+
+#[tokio::test]
+async fn withdrawal_is_reversed_when_resolved_for_customer() {
+    let app = spawn_auth_app(auth_tokens()).await;
+    let account_id = open_account_with_withdrawal(&app).await;
+
+    let response = open_dispute(&app, account_id, 3).await;
+    assert_eq!(response.status(), StatusCode::OK);
+    let dispute_id: DisputeId = assert_ok!(response.json().await);
+
+    let resolution = json!({ "resolution": "customer", "note": "ATM journal shows no dispense" });
+    let response = post_dispute_action(
+        &app,
+        dispute_id,
+        "resolve",
+        resolution.clone(),
+        Some(ADMIN_TOKEN),
+    )
+    .await;
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(
+        account_balance(&app, account_id).await,
+        Money::new(500, 0, Currency::Usd)
+    );
+    assert_eq!(
+        dispute_view(&app, dispute_id).await.status,
+        DisputeStatus::ResolvedForCustomer
+    );
+
+    let response =
+        post_dispute_action(&app, dispute_id, "resolve", resolution, Some(ADMIN_TOKEN)).await;
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+}
+
+#[tokio::test]
+async fn only_withdrawals_can_be_disputed() {
+    let app = spawn_auth_app(auth_tokens()).await;
+    let account_id = open_account_with_withdrawal(&app).await;
+
+    let response = open_dispute(&app, account_id, 2).await;
+    assert_eq!(response.status(), StatusCode::UNPROCESSABLE_ENTITY);
+
+    let response = open_dispute(&app, account_id, 42).await;
+    assert_eq!(response.status(), StatusCode::UNPROCESSABLE_ENTITY);
+}
+
+#[tokio::test]
+async fn disputes_are_listed_by_account() {
+    let app = spawn_auth_app(auth_tokens()).await;
+    let account_id = open_account_with_withdrawal(&app).await;
+    let other_account_id = open_account_with_withdrawal(&app).await;
+
+    let response = open_dispute(&app, account_id, 3).await;
+    assert_eq!(response.status(), StatusCode::OK);
+    let dispute_id: DisputeId = assert_ok!(response.json().await);
+    let response = open_dispute(&app, other_account_id, 3).await;
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let response = assert_ok!(
+        app.api_client
+            .get(app.disputes_url())
+            .query(&[("account_id", account_id.to_string())])
+            .header(X_REAL_IP, "127.0.0.1")
+            .bearer_auth(HOLDER_TOKEN)
+            .send()
+            .await
+    );
+    assert_eq!(response.status(), StatusCode::OK);
+    let disputes: Vec<DisputeView> = assert_ok!(response.json().await);
+    assert_eq!(disputes.len(), 1);
+    assert_eq!(disputes[0].dispute_id, Some(dispute_id));
+    assert_eq!(disputes[0].status, DisputeStatus::Opened);
+}
+
+#[tokio::test]
+async fn provisional_credit_requires_an_admin_token() {
+    let app = spawn_auth_app(auth_tokens()).await;
+    let account_id = open_account_with_withdrawal(&app).await;
+
+    let response = open_dispute(&app, account_id, 3).await;
+    let dispute_id: DisputeId = assert_ok!(response.json().await);
+
+    let response =
+        post_dispute_action(&app, dispute_id, "provisional_credit", json!({}), None).await;
+    assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+}
+
+#[tokio::test]
+async fn disputes_are_restricted_to_account_holders() {
+    let app = spawn_auth_app(auth_tokens()).await;
+    let account_id = open_account_with_withdrawal(&app).await;
+
+    let response = open_dispute_as(&app, account_id, 3, STRANGER_TOKEN).await;
+    assert_eq!(response.status(), StatusCode::FORBIDDEN);
+    let response = open_dispute(&app, account_id, 3).await;
+    assert_eq!(response.status(), StatusCode::OK);
+    let dispute_id: DisputeId = assert_ok!(response.json().await);
+
+    let response = get_dispute(&app, dispute_id, STRANGER_TOKEN).await;
+    assert_eq!(response.status(), StatusCode::FORBIDDEN);
+
+    for (token, expected) in [
+        (HOLDER_TOKEN, StatusCode::FORBIDDEN),
+        (ADMIN_TOKEN, StatusCode::OK),
+    ] {
+        let response = assert_ok!(
+            app.api_client
+                .get(app.disputes_url())
+                .header(X_REAL_IP, "127.0.0.1")
+                .bearer_auth(token)
+                .send()
+                .await
+        );
+        assert_eq!(response.status(), expected);
+    }
+}
+
+#[tokio::test]
+async fn dispute_command_is_retried_after_its_event_failed_to_commit() {
+    let app = spawn_auth_app(auth_tokens()).await;
+    let account_id = open_account_with_withdrawal(&app).await;
+    let response = open_dispute(&app, account_id, 3).await;
+    let dispute_id: DisputeId = assert_ok!(response.json().await);
+
+    let response = post_dispute_action(
+        &app,
+        dispute_id,
+        "provisional_credit",
+        json!({}),
+        Some(ADMIN_TOKEN),
+    )
+    .await;
+    assert_eq!(response.status(), StatusCode::OK);
+
+    // as if the dispute's event failed to commit after the account was credited
+    assert_ok!(
+        sqlx::query(
+            "DELETE FROM events WHERE aggregate_type = 'dispute' \
+             AND event_type = 'provisional_credit_issued'"
+        )
+        .execute(&app.db_pool)
+        .await
+    );
+
+    let response = post_dispute_action(
+        &app,
+        dispute_id,
+        "provisional_credit",
+        json!({}),
+        Some(ADMIN_TOKEN),
+    )
+    .await;
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(
+        account_balance(&app, account_id).await,
+        Money::new(500, 0, Currency::Usd)
+    );
+    let response = post_dispute_action(
+        &app,
+        dispute_id,
+        "resolve",
+        json!({ "resolution": "bank", "note": "ATM journal shows cash dispensed" }),
+        Some(ADMIN_TOKEN),
+    )
+    .await;
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(
+        account_balance(&app, account_id).await,
+        Money::new(380, 0, Currency::Usd)
+    );
+}
@@ -0,0 +1,263 @@
+use crate::helpers::{spawn_app_with, TestApp, TELLER_TOKEN, X_REAL_IP};
+use axum::http::StatusCode;
+use bankaccount::application::Version;
+use bankaccount::{
+    AccountId, ApiTokenSettings, BankAccount, BankAccountView, EmailMessage, EscheatmentEntry,
+    MailerSettings,
+};
+use claim::{assert_none, assert_ok, assert_some};
+use money2::{Currency, Money};
+use pretty_assertions::assert_eq;
+use pretty_snowflake::Id;
+use secrecy::Secret;
+use serde_json::json;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+const ADMIN_TOKEN: &str = "compliance-token";
+const HOLDER_TOKEN: &str = "neo-token";
+
+async fn spawn_dormancy_app(
+    dormant_after: Duration, escheat_after: Duration, mail_path: PathBuf,
+) -> TestApp {
+    spawn_app_with(Version::latest(), move |settings| {
+        settings.dormancy.poll_interval = Duration::from_millis(250);
+        settings.dormancy.dormant_after = dormant_after;
+        settings.dormancy.escheat_after = escheat_after;
+        settings.notifications.enabled = true;
+        settings.notifications.mailer = MailerSettings::File { path: mail_path };
+        settings.auth.tokens.extend([
+            ApiTokenSettings {
+                subject: "compliance-officer".to_string(),
+                token: Secret::new(ADMIN_TOKEN.to_string()),
+                scopes: vec!["admin:account".to_string()],
+            },
+            ApiTokenSettings {
+                subject: "neo".to_string(),
+                token: Secret::new(HOLDER_TOKEN.to_string()),
+                scopes: vec![],
+            },
+        ]);
+    })
+    .await
+}
+
+fn mail_path(test_name: &str) -> PathBuf {
+    let path = std::env::temp_dir().join(format!(
+        "{test_name}-{}.jsonl",
+        chrono::Utc::now().timestamp_nanos()
+    ));
+    let _ = std::fs::remove_file(&path);
+    path
+}
+
+async fn await_dormant(app: &TestApp, account_id: AccountId) -> BankAccountView {
+    let mut view = app.account_view(account_id).await;
+    for _ in 0..40 {
+        if view.dormancy.is_some() {
+            break;
+        }
+        tokio::time::sleep(Duration::from_millis(250)).await;
+        view = app.account_view(account_id).await;
+    }
+    view
+}
+
+async fn reactivate(app: &TestApp, account_id: AccountId) -> reqwest::Response {
+    reactivate_as(app, account_id, TELLER_TOKEN).await
+}
+
+async fn reactivate_as(app: &TestApp, account_id: AccountId, token: &str) -> reqwest::Response {
+    assert_ok!(
+        app.api_client
+            .post(format!("{}/{}/reactivate", app.bank_url(), account_id))
+            .header(X_REAL_IP, "127.0.0.1")
+            .bearer_auth(token)
+            .send()
+            .await
+    )
+}
+
+async fn escheatment_report(app: &TestApp, token: Option<&str>) -> reqwest::Response {
+    let mut request = app
+        .api_client
+        .get(format!("{}/escheatment", app.bank_url()))
+        .header(X_REAL_IP, "127.0.0.1");
+    if let Some(token) = token {
+        request = request.bearer_auth(token);
+    }
+    assert_ok!(request.send().await)
+}
+
+async fn await_mail(path: &Path, subject: &str) -> Vec<EmailMessage> {
+    let mut sent = Vec::new();
+    for _ in 0..40 {
+        sent = std::fs::read_to_string(path)
+            .unwrap_or_default()
+            .lines()
+            .map(|line| assert_ok!(serde_json::from_str::<EmailMessage>(line)))
+            .filter(|message| message.subject == subject)
+            .collect();
+        if !sent.is_empty() {
+            break;
+        }
+        tokio::time::sleep(Duration::from_millis(250)).await;
+    }
+    sent
+}
+
+async fn withdraw(app: &TestApp, account_id: AccountId) -> reqwest::Response {
+    app.post_atm_withdrawal(
+        account_id,
+        json!({ "atm_id": "abc_123", "amount": { "amount": "20.00", "currency": "USD" } }),
+    )
+    .await
+}
+
+#[tokio::test]
+async fn inactive_account_is_marked_dormant_until_reactivated() {
+    let path = mail_path("inactive_account_is_marked_dormant");
+    let app = spawn_dormancy_app(
+        Duration::from_secs(2),
+        Duration::from_secs(3_600),
+        path.clone(),
+    )
+    .await;
+    let account_id = app.open_funded_account("500.00").await;
+
+    let response = reactivate(&app, account_id).await;
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+
+    let view = await_dormant(&app, account_id).await;
+    let dormancy = assert_some!(view.dormancy);
+    assert_some!(dormancy.marked_at);
+
+    let sent = await_mail(&path, "Your account is now dormant").await;
+    assert_eq!(sent.len(), 1);
+    assert_eq!(sent[0].to.as_str(), "neo@example.com");
+
+    let response = withdraw(&app, account_id).await;
+    assert_eq!(response.status(), StatusCode::CONFLICT);
+
+    let response = app
+        .post_deposit_amount(account_id, json!({ "amount": "25.00", "currency": "USD" }))
+        .await;
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_some!(app.account_view(account_id).await.dormancy);
+
+    let response = reactivate(&app, account_id).await;
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_none!(app.account_view(account_id).await.dormancy);
+
+    let response = withdraw(&app, account_id).await;
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(
+        app.account_view(account_id).await.balance,
+        Money::new(505, 0, Currency::Usd)
+    );
+}
+
+#[tokio::test]
+async fn frozen_account_records_the_holder_reactivating_it() {
+    let path = mail_path("frozen_account_records_the_holder_reactivating_it");
+    let app = spawn_dormancy_app(Duration::from_secs(2), Duration::from_secs(3_600), path).await;
+    let account_id = app.open_funded_account("500.00").await;
+    assert_some!(await_dormant(&app, account_id).await.dormancy);
+
+    let response = assert_ok!(
+        app.api_client
+            .post(format!("{}/{}/freeze", app.bank_url(), account_id))
+            .header(X_REAL_IP, "127.0.0.1")
+            .bearer_auth(ADMIN_TOKEN)
+            .json(&json!({ "reason": "subpoena 2023-117" }))
+            .send()
+            .await
+    );
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let response = reactivate_as(&app, account_id, HOLDER_TOKEN).await;
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_none!(app.account_view(account_id).await.dormancy);
+
+    let response = app
+        .get_account_events(account_id, &[("event_type", "account_reactivated")])
+        .await;
+    assert_eq!(response.status(), StatusCode::OK);
+    let events: Vec<serde_json::Value> = assert_ok!(response.json().await);
+    assert_eq!(
+        events[0]["payload"],
+        json!({ "AccountReactivated": { "reactivated_by": "neo" } })
+    );
+}
+
+#[tokio::test]
+async fn escheatment_report_lists_balances_of_long_dormant_accounts() {
+    let path = mail_path("escheatment_report_lists_balances");
+    let app = spawn_dormancy_app(Duration::from_secs(1), Duration::from_secs(2), path).await;
+    let funded_id = app.open_funded_account("500.00").await;
+    let empty_id = app.open_account().await;
+
+    let response = escheatment_report(&app, None).await;
+    assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+
+    let mut report: Vec<EscheatmentEntry> = Vec::new();
+    for _ in 0..40 {
+        let response = escheatment_report(&app, Some(ADMIN_TOKEN)).await;
+        assert_eq!(response.status(), StatusCode::OK);
+        report = assert_ok!(response.json().await);
+        if !report.is_empty() {
+            break;
+        }
+        tokio::time::sleep(Duration::from_millis(250)).await;
+    }
+
+    assert_eq!(report.len(), 1);
+    let entry = &report[0];
+    assert_eq!(entry.account_id, funded_id);
+    assert_eq!(entry.owner, "neo");
+    assert_eq!(entry.balance, Money::new(500, 0, Currency::Usd));
+    assert!(entry.last_activity_at < entry.dormant_since);
+    assert!(entry.dormant_since <= entry.escheatable_since);
+
+    assert_some!(app.account_view(empty_id).await.dormancy);
+}
+
+#[tokio::test]
+async fn already_dormant_account_is_recorded_rather_than_reselected() {
+    let path = mail_path("already_dormant_account_is_recorded");
+    let app = spawn_dormancy_app(Duration::from_secs(1), Duration::from_secs(3_600), path).await;
+    let account_id = app.open_funded_account("500.00").await;
+    assert_some!(await_dormant(&app, account_id).await.dormancy);
+
+    assert_ok!(
+        sqlx::query("UPDATE account_activity SET dormant_since = NULL WHERE account_id = $1")
+            .bind(account_id.as_num())
+            .execute(&app.db_pool)
+            .await
+    );
+
+    let mut dormant_since: Option<chrono::DateTime<chrono::Utc>> = None;
+    for _ in 0..40 {
+        dormant_since = assert_ok!(
+            sqlx::query_scalar("SELECT dormant_since FROM account_activity WHERE account_id = $1")
+                .bind(account_id.as_num())
+                .fetch_one(&app.db_pool)
+                .await
+        );
+        if dormant_since.is_some() {
+            break;
+        }
+        tokio::time::sleep(Duration::from_millis(250)).await;
+    }
+    assert_some!(dormant_since);
+
+    let nr_marked: i64 = assert_ok!(
+        sqlx::query_scalar(
+            "SELECT count(*) FROM events WHERE aggregate_id = $1 AND event_type = 'account_marked_dormant'"
+        )
+        .bind(Id::<BankAccount>::from(account_id).pretty())
+        .fetch_one(&app.db_pool)
+        .await
+    );
+    assert_eq!(nr_marked, 1);
+}
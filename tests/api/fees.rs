@@ -0,0 +1,251 @@
+use crate::helpers::{spawn_app_with, TestApp, X_REAL_IP};
+use axum::http::StatusCode;
+use bankaccount::application::Version;
+use bankaccount::{
+    AccountId, ApiTokenSettings, BankAccount, BankAccountView, FeeKind, LedgerEntry,
+};
+use claim::assert_ok;
+use money2::{Currency, Money};
+use pretty_assertions::assert_eq;
+use pretty_snowflake::Id;
+use secrecy::Secret;
+use serde_json::json;
+use std::time::Duration;
+
+const ADMIN_TOKEN: &str = "compliance-token";
+
+async fn spawn_fee_app(billing_period: Duration) -> TestApp {
+    spawn_app_with(Version::latest(), move |settings| {
+        settings.fees.poll_interval = Duration::from_millis(250);
+        settings.fees.billing_period = billing_period;
+        settings.fees.schedule.monthly_maintenance = Some(Money::new(5, 0, Currency::Usd));
+        settings.fees.schedule.below_minimum_balance = Some(Money::new(10, 0, Currency::Usd));
+        settings.fees.schedule.minimum_balance = Money::new(500, 0, Currency::Usd);
+        settings.fees.schedule.per_check = Some(Money::new(2, 0, Currency::Usd));
+        settings.fees.schedule.foreign_atm = Some(Money::new(3, 0, Currency::Usd));
+        settings.fees.schedule.home_atm_networks = vec!["abc".to_string()];
+        settings.auth.tokens.extend([ApiTokenSettings {
+            subject: "compliance-officer".to_string(),
+            token: Secret::new(ADMIN_TOKEN.to_string()),
+            scopes: vec!["admin:account".to_string()],
+        }]);
+    })
+    .await
+}
+
+async fn set_fee_waivers(
+    app: &TestApp, account_id: AccountId, waivers: serde_json::Value, token: Option<&str>,
+) -> reqwest::Response {
+    let mut request = app
+        .api_client
+        .post(format!("{}/{}/fee_waivers", app.bank_url(), account_id))
+        .header(X_REAL_IP, "127.0.0.1")
+        .json(&json!({ "waivers": waivers }));
+    if let Some(token) = token {
+        request = request.bearer_auth(token);
+    }
+    assert_ok!(request.send().await)
+}
+
+async fn events_of_type(
+    app: &TestApp, account_id: AccountId, event_type: &str,
+) -> Vec<serde_json::Value> {
+    let response = app.get_account_events(account_id, &[("event_type", event_type)]).await;
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_ok!(response.json().await)
+}
+
+async fn await_assessment(app: &TestApp, account_id: AccountId) {
+    for _ in 0..40 {
+        if !events_of_type(app, account_id, "fees_assessed").await.is_empty() {
+            return;
+        }
+        tokio::time::sleep(Duration::from_millis(250)).await;
+    }
+    panic!("fees never assessed for account {account_id}");
+}
+
+fn fee_entries(view: &BankAccountView) -> Vec<&LedgerEntry> {
+    view.ledger
+        .iter()
+        .filter(|entry| entry.description.ends_with(" fee"))
+        .collect()
+}
+
+#[tokio::test]
+async fn transaction_fees_are_charged_unless_waived() {
+    let app = spawn_fee_app(Duration::from_secs(3_600)).await;
+    let account_id = app.open_funded_account("100.00").await;
+
+    let response = app
+        .post_atm_withdrawal(
+            account_id,
+            json!({ "atm_id": "abc_123", "amount": { "amount": "20.00", "currency": "USD" } }),
+        )
+        .await;
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let response = app
+        .post_atm_withdrawal(
+            account_id,
+            json!({ "atm_id": "xyz_987", "amount": { "amount": "20.00", "currency": "USD" } }),
+        )
+        .await;
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let response = app
+        .post_check_withdrawal(
+            account_id,
+            json!({ "check_nr": 1001, "amount": { "amount": "10.00", "currency": "USD" } }),
+        )
+        .await;
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let view = app.account_view(account_id).await;
+    assert_eq!(view.balance, Money::new(45, 0, Currency::Usd));
+    let fees: Vec<_> = fee_entries(&view)
+        .into_iter()
+        .map(|entry| (entry.description.as_str(), entry.amount))
+        .collect();
+    assert_eq!(
+        fees,
+        vec![
+            ("Foreign ATM fee", Money::new(-3, 0, Currency::Usd)),
+            ("Check fee", Money::new(-2, 0, Currency::Usd)),
+        ]
+    );
+
+    let response = set_fee_waivers(&app, account_id, json!(["check"]), None).await;
+    assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+    let response = set_fee_waivers(&app, account_id, json!(["check"]), Some(ADMIN_TOKEN)).await;
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let response = app
+        .post_check_withdrawal(
+            account_id,
+            json!({ "check_nr": 1002, "amount": { "amount": "10.00", "currency": "USD" } }),
+        )
+        .await;
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let view = app.account_view(account_id).await;
+    assert_eq!(view.fee_waivers, vec![FeeKind::Check]);
+    assert_eq!(view.balance, Money::new(35, 0, Currency::Usd));
+    assert_eq!(fee_entries(&view).len(), 2);
+}
+
+#[tokio::test]
+async fn minimum_balance_is_kept_after_the_transaction_fee() {
+    let app = spawn_fee_app(Duration::from_secs(3_600)).await;
+    let account_id = app
+        .open_account_with(json!({ "account_type": "savings" }), Some("500.00"))
+        .await;
+
+    let response = app
+        .post_atm_withdrawal(
+            account_id,
+            json!({ "atm_id": "xyz_987", "amount": { "amount": "400.00", "currency": "USD" } }),
+        )
+        .await;
+    assert_eq!(response.status(), StatusCode::UNPROCESSABLE_ENTITY);
+    let problem: serde_json::Value = assert_ok!(response.json().await);
+    assert_eq!(problem["code"], "minimum_balance_required");
+
+    let response = app
+        .post_atm_withdrawal(
+            account_id,
+            json!({ "atm_id": "xyz_987", "amount": { "amount": "397.00", "currency": "USD" } }),
+        )
+        .await;
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(
+        app.account_view(account_id).await.balance,
+        Money::new(100, 0, Currency::Usd)
+    );
+}
+
+#[tokio::test]
+async fn periodic_fees_are_assessed_at_end_of_billing_period() {
+    let app = spawn_fee_app(Duration::from_secs(2)).await;
+    let low_id = app.open_funded_account("100.00").await;
+    let waived_id = app.open_funded_account("1000.00").await;
+    let response = set_fee_waivers(
+        &app,
+        waived_id,
+        json!(["monthly_maintenance"]),
+        Some(ADMIN_TOKEN),
+    )
+    .await;
+    assert_eq!(response.status(), StatusCode::OK);
+
+    await_assessment(&app, low_id).await;
+    await_assessment(&app, waived_id).await;
+
+    let charged = events_of_type(&app, low_id, "fee_charged").await;
+    let kinds: Vec<_> = charged
+        .iter()
+        .take(2)
+        .map(|event| event["payload"]["FeeCharged"]["kind"].clone())
+        .collect();
+    assert_eq!(
+        kinds,
+        vec![json!("monthly_maintenance"), json!("below_minimum_balance")]
+    );
+    let view = app.account_view(low_id).await;
+    assert_eq!(
+        fee_entries(&view).first().map(|entry| entry.description.as_str()),
+        Some("Monthly maintenance fee")
+    );
+
+    assert!(events_of_type(&app, waived_id, "fee_charged").await.is_empty());
+    assert_eq!(
+        app.account_view(waived_id).await.balance,
+        Money::new(1000, 0, Currency::Usd)
+    );
+}
+
+#[tokio::test]
+async fn period_already_assessed_is_skipped() {
+    let app = spawn_fee_app(Duration::from_secs(2)).await;
+    let account_id = app.open_funded_account("1000.00").await;
+    await_assessment(&app, account_id).await;
+    let aggregate_id = Id::<BankAccount>::from(account_id).pretty().to_string();
+
+    let assessed = events_of_type(&app, account_id, "fees_assessed").await;
+    let period_ending: chrono::DateTime<chrono::Utc> = assert_ok!(serde_json::from_value(
+        assessed[0]["payload"]["FeesAssessed"]["period_ending"].clone()
+    ));
+    assert_ok!(
+        sqlx::query("UPDATE fee_assessment SET next_assessment_at = $2 WHERE aggregate_id = $1")
+            .bind(&aggregate_id)
+            .bind(period_ending)
+            .execute(&app.db_pool)
+            .await
+    );
+
+    let mut next_assessment_at = period_ending;
+    for _ in 0..40 {
+        next_assessment_at = assert_ok!(
+            sqlx::query_scalar(
+                "SELECT next_assessment_at FROM fee_assessment WHERE aggregate_id = $1"
+            )
+            .bind(&aggregate_id)
+            .fetch_one(&app.db_pool)
+            .await
+        );
+        if period_ending < next_assessment_at {
+            break;
+        }
+        tokio::time::sleep(Duration::from_millis(250)).await;
+    }
+    assert!(period_ending < next_assessment_at);
+
+    let periods: Vec<_> = events_of_type(&app, account_id, "fees_assessed")
+        .await
+        .iter()
+        .map(|event| event["payload"]["FeesAssessed"]["period_ending"].clone())
+        .collect();
+    let mut unique_periods = periods.clone();
+    unique_periods.dedup();
+    assert_eq!(periods, unique_periods);
+}
@@ -0,0 +1,185 @@
+use crate::helpers::{spawn_app_with, TestApp, X_REAL_IP};
+use axum::http::StatusCode;
+use bankaccount::application::Version;
+use bankaccount::{AccountId, ApiTokenSettings, FraudOutcome, FraudRule};
+use claim::assert_ok;
+use money2::{Currency, Money};
+use pretty_assertions::assert_eq;
+use secrecy::Secret;
+use serde_json::json;
+
+const ADMIN_TOKEN: &str = "fraud-desk-token";
+
+async fn spawn_screening_app() -> TestApp {
+    spawn_app_with(Version::latest(), |settings| {
+        settings.auth.tokens.extend([ApiTokenSettings {
+            subject: "fraud-analyst".to_string(),
+            token: Secret::new(ADMIN_TOKEN.to_string()),
+            scopes: vec!["admin:account".to_string()],
+        }]);
+        settings.fraud.rules = vec![
+            FraudRule::AmountThreshold {
+                threshold: Money::new(100, 0, Currency::Usd),
+                outcome: FraudOutcome::Hold,
+            },
+            FraudRule::AmountThreshold {
+                threshold: Money::new(1_000, 0, Currency::Usd),
+                outcome: FraudOutcome::Deny,
+            },
+        ];
+    })
+    .await
+}
+
+async fn post_flag_review(
+    app: &TestApp, account_id: AccountId, flag_id: u32, action: &str, body: serde_json::Value,
+    token: Option<&str>,
+) -> reqwest::Response {
+    let mut request = app
+        .api_client
+        .post(format!(
+            "{}/{}/flags/{}/{}",
+            app.bank_url(),
+            account_id,
+            flag_id,
+            action
+        ))
+        .header(X_REAL_IP, "127.0.0.1")
+        .json(&body);
+    if let Some(token) = token {
+        request = request.bearer_auth(token);
+    }
+    assert_ok!(request.send().await)
+}
+
+async fn account_view(app: &TestApp, account_id: AccountId) -> serde_json::Value {
+    let response = app.get_serve_bank_account(account_id).await;
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_ok!(response.json().await)
+}
+
+#[tokio::test]
+async fn held_withdrawal_is_applied_only_when_released() {
+    let app = spawn_screening_app().await;
+    let account_id = app.open_funded_account("5000.00").await;
+
+    let response = app
+        .post_atm_withdrawal(
+            account_id,
+            json!({ "atm_id": "abc_123", "amount": { "amount": "250.00", "currency": "USD" } }),
+        )
+        .await;
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let view = account_view(&app, account_id).await;
+    assert_eq!(view["balance"]["amount"], json!("5000.00"));
+    assert_eq!(view["flagged_transactions"][0]["flag_id"], json!(1));
+    assert_eq!(
+        view["flagged_transactions"][0]["transaction"],
+        json!({
+            "cash_withdrawal": {
+                "amount": { "amount": "250.00", "currency": "USD" },
+                "atm_id": "abc_123",
+            }
+        })
+    );
+
+    let response =
+        post_flag_review(&app, account_id, 1, "release", json!({}), Some(ADMIN_TOKEN)).await;
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let view = account_view(&app, account_id).await;
+    assert_eq!(view["balance"]["amount"], json!("4750.00"));
+    assert_eq!(view.get("flagged_transactions"), None);
+
+    let response =
+        post_flag_review(&app, account_id, 1, "release", json!({}), Some(ADMIN_TOKEN)).await;
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn rejected_withdrawal_is_never_applied() {
+    let app = spawn_screening_app().await;
+    let account_id = app.open_funded_account("5000.00").await;
+
+    let response = app
+        .post_check_withdrawal(
+            account_id,
+            json!({ "check_nr": 1087, "amount": { "amount": "300.00", "currency": "USD" } }),
+        )
+        .await;
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let rejection = json!({ "reason": "account holder did not write check" });
+    let response = post_flag_review(&app, account_id, 1, "reject", rejection.clone(), None).await;
+    assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+
+    let response =
+        post_flag_review(&app, account_id, 1, "reject", rejection, Some(ADMIN_TOKEN)).await;
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let view = account_view(&app, account_id).await;
+    assert_eq!(view["balance"]["amount"], json!("5000.00"));
+    assert_eq!(view["written_checks"], json!([]));
+    assert_eq!(view.get("flagged_transactions"), None);
+}
+
+#[tokio::test]
+async fn denied_withdrawal_returns_a_422() {
+    let app = spawn_screening_app().await;
+    let account_id = app.open_funded_account("5000.00").await;
+
+    let response = app
+        .post_atm_withdrawal(
+            account_id,
+            json!({ "atm_id": "abc_123", "amount": { "amount": "1500.00", "currency": "USD" } }),
+        )
+        .await;
+    assert_eq!(response.status(), StatusCode::UNPROCESSABLE_ENTITY);
+
+    let view = account_view(&app, account_id).await;
+    assert_eq!(view["balance"]["amount"], json!("5000.00"));
+}
+
+#[tokio::test]
+async fn released_withdrawal_is_held_to_the_account_policy() {
+    let app = spawn_app_with(Version::latest(), |settings| {
+        settings.auth.tokens.extend([ApiTokenSettings {
+            subject: "fraud-analyst".to_string(),
+            token: Secret::new(ADMIN_TOKEN.to_string()),
+            scopes: vec!["admin:account".to_string()],
+        }]);
+        settings.fraud.rules = vec![FraudRule::AmountThreshold {
+            threshold: Money::new(100, 0, Currency::Usd),
+            outcome: FraudOutcome::Hold,
+        }];
+        settings.account_types.checking.monthly_withdrawal_cap = Some(1);
+    })
+    .await;
+    let account_id = app
+        .open_account_with(json!({ "account_type": "checking" }), Some("5000.00"))
+        .await;
+
+    let response = app
+        .post_atm_withdrawal(
+            account_id,
+            json!({ "atm_id": "abc_123", "amount": { "amount": "250.00", "currency": "USD" } }),
+        )
+        .await;
+    assert_eq!(response.status(), StatusCode::OK);
+    let response = app
+        .post_atm_withdrawal(
+            account_id,
+            json!({ "atm_id": "abc_123", "amount": { "amount": "50.00", "currency": "USD" } }),
+        )
+        .await;
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let response =
+        post_flag_review(&app, account_id, 1, "release", json!({}), Some(ADMIN_TOKEN)).await;
+    assert_eq!(response.status(), StatusCode::UNPROCESSABLE_ENTITY);
+
+    let view = account_view(&app, account_id).await;
+    assert_eq!(view["balance"]["amount"], json!("4950.00"));
+    assert_eq!(view["flagged_transactions"][0]["flag_id"], json!(1));
+}
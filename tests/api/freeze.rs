@@ -0,0 +1,104 @@
+use crate::helpers::{api_token, spawn_auth_app, TestApp, X_REAL_IP};
+use axum::http::StatusCode;
+use bankaccount::{AccountId, ApiTokenSettings, BankAccountView, FreezeAction};
+use claim::{assert_none, assert_ok, assert_some};
+use pretty_assertions::assert_eq;
+use serde_json::json;
+
+const ADMIN_TOKEN: &str = "compliance-token";
+const CLERK_TOKEN: &str = "clerk-token";
+
+fn auth_tokens() -> [ApiTokenSettings; 2] {
+    [
+        api_token("compliance-officer", ADMIN_TOKEN, &["admin:account"]),
+        api_token("clerk", CLERK_TOKEN, &[]),
+    ]
+}
+
+async fn post_freeze_action(
+    app: &TestApp, account_id: AccountId, action: &str, token: Option<&str>,
+) -> reqwest::Response {
+    let mut request = app
+        .api_client
+        .post(format!("{}/{}/{}", app.bank_url(), account_id, action))
+        .header(X_REAL_IP, "127.0.0.1")
+        .json(&json!({ "reason": "subpoena 2023-117" }));
+    if let Some(token) = token {
+        request = request.bearer_auth(token);
+    }
+    assert_ok!(request.send().await)
+}
+
+async fn withdraw(app: &TestApp, account_id: AccountId) -> reqwest::Response {
+    app.post_atm_withdrawal(
+        account_id,
+        json!({ "atm_id": "abc_123", "amount": { "amount": "20.00", "currency": "USD" } }),
+    )
+    .await
+}
+
+#[tokio::test]
+async fn freeze_requires_admin_principal() {
+    let app = spawn_auth_app(auth_tokens()).await;
+    let account_id = app.open_funded_account("500.00").await;
+
+    let response = post_freeze_action(&app, account_id, "freeze", None).await;
+    assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+
+    let response = post_freeze_action(&app, account_id, "freeze", Some("bogus")).await;
+    assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+
+    let response = post_freeze_action(&app, account_id, "freeze", Some(CLERK_TOKEN)).await;
+    assert_eq!(response.status(), StatusCode::FORBIDDEN);
+
+    let response = withdraw(&app, account_id).await;
+    assert_eq!(response.status(), StatusCode::OK);
+}
+
+#[tokio::test]
+async fn frozen_account_accepts_deposits_but_rejects_withdrawals_until_unfrozen() {
+    let app = spawn_auth_app(auth_tokens()).await;
+    let account_id = app.open_funded_account("500.00").await;
+
+    let response = post_freeze_action(&app, account_id, "freeze", Some(ADMIN_TOKEN)).await;
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let response = withdraw(&app, account_id).await;
+    assert_eq!(response.status(), StatusCode::CONFLICT);
+
+    let response = app.post_update_email(account_id, "trinity@example.com").await;
+    assert_eq!(response.status(), StatusCode::CONFLICT);
+
+    let response = app
+        .post_deposit_amount(account_id, json!({ "amount": "10.00", "currency": "USD" }))
+        .await;
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let response = app.get_serve_bank_account(account_id).await;
+    let view: BankAccountView = assert_ok!(response.json().await);
+    let freeze = assert_some!(view.freeze);
+    assert_eq!(freeze.reason, "subpoena 2023-117");
+    assert_eq!(freeze.authority, "compliance-officer");
+
+    let response = post_freeze_action(&app, account_id, "unfreeze", Some(ADMIN_TOKEN)).await;
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let response = withdraw(&app, account_id).await;
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let response = app.get_serve_bank_account(account_id).await;
+    let view: BankAccountView = assert_ok!(response.json().await);
+    assert_none!(view.freeze);
+    let audit: Vec<_> = view
+        .freeze_history
+        .iter()
+        .map(|entry| (entry.action, entry.authority.as_str()))
+        .collect();
+    assert_eq!(
+        audit,
+        vec![
+            (FreezeAction::Frozen, "compliance-officer"),
+            (FreezeAction::Unfrozen, "compliance-officer"),
+        ]
+    );
+}
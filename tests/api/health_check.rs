@@ -1,23 +1,110 @@
-use crate::helpers::{spawn_latest_app, X_REAL_IP};
+use crate::helpers::{spawn_latest_app, TestApp, X_REAL_IP};
 use axum::http::StatusCode;
+use claim::assert_some;
 use pretty_assertions::assert_eq;
+use serde_json::{json, Value};
 use tokio_test::assert_ok;
 
+const COMPONENTS: [&str; 4] = [
+    "atm_and_check_services",
+    "event_store",
+    "exchange_rates",
+    "view_store",
+];
+
+async fn get_health(app: &TestApp, path: &str) -> (StatusCode, Value) {
+    let url = format!("{}/api/{}/health{path}", app.http_address, app.version);
+    let response = assert_ok!(app.api_client.get(url).header(X_REAL_IP, "127.0.0.1").send().await);
+    (response.status(), assert_ok!(response.json().await))
+}
+
 #[tokio::test]
 async fn health_check_works() {
     let app = spawn_latest_app().await;
-    let client = app.api_client;
-    let version = app.version;
-
-    let url = format!("{}/api/{version}/health", app.http_address);
+    let url = format!("{}/api/{}/health", app.http_address, app.version);
     assert_eq!(url, format!("http://0.0.0.0:{}/api/v1/health", app.port));
-    let response = assert_ok!(client.get(url).header(X_REAL_IP, "127.0.0.1").send().await);
 
-    assert_eq!(response.status(), StatusCode::OK);
+    let (status, report) = get_health(&app, "").await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(report["status"], json!("Up"));
+    let components = assert_some!(report["components"].as_object());
+    assert_eq!(components.keys().collect::<Vec<_>>(), COMPONENTS);
+    for (component, health) in components {
+        assert_eq!(health["status"], json!("Up"), "{component} is not up");
+        assert!(
+            health["latency_ms"].is_number(),
+            "{component} has no latency"
+        );
+        assert!(
+            health.get("last_error").is_none(),
+            "{component} reported an error"
+        );
+    }
+    assert!(report.get("pending_migrations").is_none());
+
+    let (status, _) = get_health(&app, "/ready").await;
+    assert_eq!(status, StatusCode::OK);
+}
+
+#[tokio::test]
+async fn health_report_keeps_last_error_of_recovered_component() {
+    let app = spawn_latest_app().await;
+
+    assert_ok!(
+        sqlx::query("ALTER TABLE events RENAME TO events_aside")
+            .execute(&app.db_pool)
+            .await
+    );
+    let (status, report) = get_health(&app, "").await;
+    assert_eq!(status, StatusCode::SERVICE_UNAVAILABLE);
+    assert_eq!(report["status"], json!("Down"));
     assert_eq!(
-        assert_ok!(response.text().await),
-        assert_ok!(serde_json::to_string(
-            &maplit::hashmap! { "status" => "Up", }
-        ))
+        report["components"]["event_store"]["status"],
+        json!("Error")
     );
+    assert_eq!(report["components"]["view_store"]["status"], json!("Up"));
+
+    let (status, report) = get_health(&app, "/live").await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(report, json!({ "status": "Up" }));
+
+    let (status, report) = get_health(&app, "/ready").await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(report, json!({ "status": "Up" }));
+
+    assert_ok!(
+        sqlx::query("ALTER TABLE events_aside RENAME TO events")
+            .execute(&app.db_pool)
+            .await
+    );
+    let (status, report) = get_health(&app, "").await;
+    assert_eq!(status, StatusCode::OK);
+    let event_store = &report["components"]["event_store"];
+    assert_eq!(event_store["status"], json!("Up"));
+    assert!(event_store["last_error"]["message"].is_string());
+    assert!(event_store["last_error"]["at"].is_string());
+}
+
+#[tokio::test]
+async fn readiness_fails_while_migrations_are_pending() {
+    let app = spawn_latest_app().await;
+    let latest: i64 = assert_ok!(
+        sqlx::query_scalar("SELECT max(version) FROM _sqlx_migrations")
+            .fetch_one(&app.db_pool)
+            .await
+    );
+    assert_ok!(
+        sqlx::query("DELETE FROM _sqlx_migrations WHERE version = $1")
+            .bind(latest)
+            .execute(&app.db_pool)
+            .await
+    );
+
+    let (status, report) = get_health(&app, "/ready").await;
+    assert_eq!(status, StatusCode::SERVICE_UNAVAILABLE);
+    assert_eq!(report["status"], json!("NotReady"));
+    assert_eq!(report["pending_migrations"], json!([latest]));
+
+    let (status, _) = get_health(&app, "/live").await;
+    assert_eq!(status, StatusCode::OK);
 }
@@ -1,46 +1,57 @@
 use axum::http::StatusCode;
 use bankaccount::application::Version;
 pub use bankaccount::tracing::TEST_TRACING;
-use bankaccount::AccountId;
+use bankaccount::{AccountId, ApiTokenSettings, BankAccountView, CustomerId};
 use claim::assert_ok;
 use once_cell::sync::Lazy;
 use pretty_assertions::assert_eq;
 use pretty_snowflake::LabeledRealtimeIdGenerator;
 use reqwest::header;
-use secrecy::ExposeSecret;
+use secrecy::{ExposeSecret, Secret};
 use settings_loader::common::database::DatabaseSettings;
 use settings_loader::SettingsLoader;
 use sqlx::migrate::MigrateDatabase;
 use sqlx::{Connection, Executor, PgConnection, PgPool};
+use std::net::IpAddr;
 
 static ID_GEN: Lazy<std::sync::RwLock<LabeledRealtimeIdGenerator<()>>> =
     Lazy::new(|| std::sync::RwLock::new(LabeledRealtimeIdGenerator::default()));
 
 pub const X_REAL_IP: &str = "x-real-ip";
 
+/// Token of the bank teller, who is granted the admin scope and so acts with the bank's authority.
+pub const TELLER_TOKEN: &str = "teller-token";
+
 pub async fn spawn_latest_app() -> TestApp {
     spawn_app(Version::latest()).await
 }
 
 pub async fn spawn_app(version: Version) -> TestApp {
-    Lazy::force(&TEST_TRACING);
+    spawn_app_with(version, |_| {}).await
+}
 
-    let mut settings = {
-        let mut options = bankaccount::CliOptions::default();
-        options.config = Some("./tests/data/settings.yaml".into());
-        assert_ok!(
-            bankaccount::Settings::load(&options),
-            "Failed to load configuration."
-        )
-    };
+/// Spawns the latest app recognizing the `tokens` besides the teller's.
+pub async fn spawn_auth_app(tokens: impl IntoIterator<Item = ApiTokenSettings>) -> TestApp {
+    spawn_app_with(Version::latest(), |settings| {
+        settings.auth.tokens.extend(tokens)
+    })
+    .await
+}
 
-    let db_name = ID_GEN.read().unwrap().next_id().pretty().to_string();
-    tracing::info!(%db_name, "DATABASE name is generated");
-    settings.database.database_name = db_name.clone();
-    assert_eq!(settings.database.database_name, db_name);
+/// The API `token` of the `subject`, granted the `scopes`.
+pub fn api_token(subject: &str, token: &str, scopes: &[&str]) -> ApiTokenSettings {
+    ApiTokenSettings {
+        subject: subject.to_string(),
+        token: Secret::new(token.to_string()),
+        scopes: scopes.iter().map(|scope| scope.to_string()).collect(),
+    }
+}
 
-    settings.http_api.server.port = 0;
-    assert_eq!(settings.http_api.server.port, 0);
+pub async fn spawn_app_with(
+    version: Version, customize: impl FnOnce(&mut bankaccount::Settings),
+) -> TestApp {
+    let mut settings = test_settings();
+    customize(&mut settings);
 
     configure_database(&settings.database).await;
 
@@ -51,7 +62,7 @@ pub async fn spawn_app(version: Version) -> TestApp {
     let application_port = application.port();
     pretty_assertions::assert_ne!(application_port, 0);
 
-    let _ = tokio::spawn(application.run_until_stopped());
+    tokio::spawn(application.run_until_stopped());
 
     let api_client = reqwest::Client::builder()
         .redirect(reqwest::redirect::Policy::none())
@@ -72,8 +83,45 @@ pub async fn spawn_app(version: Version) -> TestApp {
     test_app
 }
 
+/// Loads the test settings with a freshly named database, an ephemeral HTTP port, database
+/// migrations applied as the application starts, the teller's token recognized, and the loopback
+/// address trusted as a proxy so tests can set the client's address in `X-Real-IP`.
+pub fn test_settings() -> bankaccount::Settings {
+    Lazy::force(&TEST_TRACING);
+
+    let mut settings = {
+        let options = bankaccount::CliOptions {
+            config: Some("./tests/data/settings.yaml".into()),
+            ..bankaccount::CliOptions::default()
+        };
+        assert_ok!(
+            bankaccount::Settings::load(&options),
+            "Failed to load configuration."
+        )
+    };
+
+    let db_name = ID_GEN.read().unwrap().next_id().pretty().to_string();
+    tracing::info!(%db_name, "DATABASE name is generated");
+    settings.database.database_name = db_name.clone();
+    assert_eq!(settings.database.database_name, db_name);
+
+    settings.http_api.server.port = 0;
+    assert_eq!(settings.http_api.server.port, 0);
+
+    settings.migrations.run_on_startup = true;
+    settings.http_api.trusted_proxies = vec![IpAddr::from([127, 0, 0, 1])];
+
+    settings.auth.tokens.extend([ApiTokenSettings {
+        subject: "teller".to_string(),
+        token: Secret::new(TELLER_TOKEN.to_string()),
+        scopes: vec!["admin:account".to_string()],
+    }]);
+    settings
+}
+
+/// Creates the test database. Its schema is migrated when the application is built.
 #[tracing::instrument(level = "info")]
-async fn configure_database(settings: &DatabaseSettings) -> PgPool {
+pub async fn configure_database(settings: &DatabaseSettings) -> PgPool {
     let mut connection = assert_ok!(
         PgConnection::connect_with(&settings.pg_connect_options_without_db()).await,
         "Failed to connect to Postgres."
@@ -92,17 +140,10 @@ async fn configure_database(settings: &DatabaseSettings) -> PgPool {
         );
     }
 
-    let connection_pool = assert_ok!(
+    assert_ok!(
         PgPool::connect_with(settings.pg_connect_options_with_db()).await,
         "Failed to connect to Postgres."
-    );
-
-    assert_ok!(
-        sqlx::migrate!("./migrations").run(&connection_pool).await,
-        "Failed to migrate the database."
-    );
-
-    connection_pool
+    )
 }
 
 pub struct TestApp {
@@ -119,11 +160,201 @@ impl TestApp {
         format!("{}/api/{}/bank", self.http_address, self.version)
     }
 
+    #[inline]
+    pub fn commands_url(&self) -> String {
+        format!("{}/api/{}/commands", self.http_address, self.version)
+    }
+
+    #[inline]
+    pub fn customers_url(&self) -> String {
+        format!("{}/api/{}/customers", self.http_address, self.version)
+    }
+
+    #[inline]
+    pub fn disputes_url(&self) -> String {
+        format!("{}/api/{}/disputes", self.http_address, self.version)
+    }
+
+    #[inline]
+    pub fn standing_orders_url(&self) -> String {
+        format!("{}/api/{}/standing_orders", self.http_address, self.version)
+    }
+
+    #[inline]
+    pub fn webhooks_url(&self) -> String {
+        format!("{}/api/{}/webhooks", self.http_address, self.version)
+    }
+
+    #[tracing::instrument(skip(self))]
+    pub async fn post_webhook_subscription(
+        &self, body: serde_json::Value, token: Option<&str>,
+    ) -> reqwest::Response {
+        let mut my_request = self
+            .api_client
+            .post(self.webhooks_url())
+            .header(header::CONTENT_TYPE, "application/json")
+            .header(X_REAL_IP, "127.0.0.1")
+            .json(&body);
+        if let Some(token) = token {
+            my_request = my_request.bearer_auth(token);
+        }
+        assert_ok!(my_request.send().await)
+    }
+
+    #[tracing::instrument(skip(self))]
+    pub async fn get_webhook_deliveries(
+        &self, subscription_id: i64, query: &[(&str, &str)], token: &str,
+    ) -> reqwest::Response {
+        let my_request = self
+            .api_client
+            .get(format!(
+                "{}/{}/deliveries",
+                self.webhooks_url(),
+                subscription_id
+            ))
+            .query(query)
+            .header(X_REAL_IP, "127.0.0.1")
+            .bearer_auth(token);
+        assert_ok!(my_request.send().await)
+    }
+
+    #[tracing::instrument(skip(self))]
+    pub async fn get_command(&self, command_id: i64) -> reqwest::Response {
+        let my_request = self
+            .api_client
+            .get(format!("{}/{}", self.commands_url(), command_id))
+            .header(X_REAL_IP, "127.0.0.1")
+            .bearer_auth(TELLER_TOKEN);
+        assert_ok!(my_request.send().await)
+    }
+
+    /// Registers the customer, who signs in as the `name` subject, and verifies their identity from
+    /// a submitted passport. A verified customer already registered for the subject is reused.
+    #[tracing::instrument(skip(self))]
+    pub async fn register_verified_customer(&self, name: &str) -> CustomerId {
+        let registered: Option<i64> = assert_ok!(
+            sqlx::query_scalar(
+                "SELECT (payload->>'customer_id')::bigint FROM customer_query WHERE \
+                 payload->>'subject' = $1 AND payload->>'kyc_status' = 'verified'"
+            )
+            .bind(name)
+            .fetch_optional(&self.db_pool)
+            .await
+        );
+        if let Some(customer_id) = registered {
+            return CustomerId::new(customer_id);
+        }
+
+        let response = assert_ok!(
+            self.api_client
+                .post(self.customers_url())
+                .header(X_REAL_IP, "127.0.0.1")
+                .bearer_auth(TELLER_TOKEN)
+                .json(&serde_json::json!({
+                    "subject": name,
+                    "name": name,
+                    "email": format!("{name}@example.com"),
+                    "mailing_address": "12 Seahawks Way, Renton, WA 98056, USA",
+                }))
+                .send()
+                .await
+        );
+        assert_eq!(response.status(), StatusCode::OK);
+        let customer_id: CustomerId = assert_ok!(response.json().await);
+
+        let response = assert_ok!(
+            self.api_client
+                .post(format!(
+                    "{}/{}/documents",
+                    self.customers_url(),
+                    customer_id
+                ))
+                .header(X_REAL_IP, "127.0.0.1")
+                .bearer_auth(TELLER_TOKEN)
+                .json(&serde_json::json!({
+                    "kind": "passport",
+                    "reference": format!("docs/{customer_id}/passport.pdf"),
+                }))
+                .send()
+                .await
+        );
+        assert_eq!(response.status(), StatusCode::OK);
+
+        let response = assert_ok!(
+            self.api_client
+                .post(format!("{}/{}/verify", self.customers_url(), customer_id))
+                .header(X_REAL_IP, "127.0.0.1")
+                .bearer_auth(TELLER_TOKEN)
+                .send()
+                .await
+        );
+        assert_eq!(response.status(), StatusCode::OK);
+        customer_id
+    }
+
+    /// The view of the account, which must be served.
+    pub async fn account_view(&self, account_id: AccountId) -> BankAccountView {
+        let response = self.get_serve_bank_account(account_id).await;
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_ok!(response.json().await)
+    }
+
+    /// Opens neo's checking account.
+    pub async fn open_account(&self) -> AccountId {
+        self.open_account_with(serde_json::json!({}), None).await
+    }
+
+    /// Opens neo's checking account with the `deposit` amount in USD.
+    pub async fn open_funded_account(&self, deposit: &str) -> AccountId {
+        self.open_account_with(serde_json::json!({}), Some(deposit)).await
+    }
+
+    /// Opens neo's account from the [account_application] with the given `fields` replaced, and
+    /// deposits the `deposit` amount in USD, if any.
     #[tracing::instrument(skip(self))]
-    pub async fn post_create_bank_account(&self, body: serde_json::Value) -> reqwest::Response {
+    pub async fn open_account_with(
+        &self, fields: serde_json::Value, deposit: Option<&str>,
+    ) -> AccountId {
+        let mut application = account_application();
+        if let (Some(application), Some(fields)) = (application.as_object_mut(), fields.as_object())
+        {
+            application.extend(fields.clone());
+        }
+
+        let response = self.post_create_bank_account(application).await;
+        assert_eq!(response.status(), StatusCode::OK);
+        let account_id: AccountId = assert_ok!(response.json().await);
+
+        if let Some(deposit) = deposit {
+            let response = self
+                .post_deposit_amount(
+                    account_id,
+                    serde_json::json!({ "amount": deposit, "currency": "USD" }),
+                )
+                .await;
+            assert_eq!(response.status(), StatusCode::OK);
+        }
+
+        account_id
+    }
+
+    /// Opens the account for the `customer_id` the body names, or else for a verified customer
+    /// registered under the body's `user_name`, which is taken out of the application.
+    #[tracing::instrument(skip(self))]
+    pub async fn post_create_bank_account(&self, mut body: serde_json::Value) -> reqwest::Response {
+        if body.get("customer_id").is_none() {
+            let name = body
+                .as_object_mut()
+                .and_then(|application| application.remove("user_name"))
+                .and_then(|name| name.as_str().map(|name| name.to_string()))
+                .unwrap_or_else(|| "neo".to_string());
+            let customer_id = self.register_verified_customer(&name).await;
+            body["customer_id"] = serde_json::json!(customer_id);
+        }
+
         let my_request = self
             .api_client
-            .post(&format!("{}", self.bank_url()))
+            .post(self.bank_url())
             .header(header::CONTENT_TYPE, "application/json")
             .header(X_REAL_IP, "127.0.0.1")
             .json(&body);
@@ -134,18 +365,69 @@ impl TestApp {
     pub async fn get_serve_bank_account(&self, account_id: AccountId) -> reqwest::Response {
         let my_request = self
             .api_client
-            .get(&format!("{}/{}", self.bank_url(), account_id))
+            .get(format!("{}/{}", self.bank_url(), account_id))
             .header(X_REAL_IP, "127.0.0.1");
         assert_ok!(my_request.send().await)
     }
 
+    #[tracing::instrument(skip(self))]
+    pub async fn get_serve_bank_account_as_of(
+        &self, account_id: AccountId, as_of: &str,
+    ) -> reqwest::Response {
+        let my_request = self
+            .api_client
+            .get(format!("{}/{}", self.bank_url(), account_id))
+            .query(&[("as_of", as_of)])
+            .header(X_REAL_IP, "127.0.0.1")
+            .bearer_auth(TELLER_TOKEN);
+        assert_ok!(my_request.send().await)
+    }
+
+    #[tracing::instrument(skip(self))]
+    pub async fn get_account_events(
+        &self, account_id: AccountId, query: &[(&str, &str)],
+    ) -> reqwest::Response {
+        let my_request = self
+            .api_client
+            .get(format!("{}/{}/events", self.bank_url(), account_id))
+            .query(query)
+            .header(X_REAL_IP, "127.0.0.1")
+            .bearer_auth(TELLER_TOKEN);
+        assert_ok!(my_request.send().await)
+    }
+
+    #[tracing::instrument(skip(self))]
+    pub async fn post_update_email(&self, account_id: AccountId, email: &str) -> reqwest::Response {
+        let my_request = self
+            .api_client
+            .post(format!("{}/email/{}", self.bank_url(), account_id))
+            .header(X_REAL_IP, "127.0.0.1")
+            .json(&email);
+        assert_ok!(my_request.send().await)
+    }
+
+    #[tracing::instrument(skip(self))]
+    pub async fn put_notification_preferences(
+        &self, account_id: AccountId, body: serde_json::Value, token: Option<&str>,
+    ) -> reqwest::Response {
+        let mut my_request = self
+            .api_client
+            .put(format!("{}/{}/notifications", self.bank_url(), account_id))
+            .header(X_REAL_IP, "127.0.0.1")
+            .json(&body);
+        if let Some(token) = token {
+            my_request = my_request.bearer_auth(token);
+        }
+        assert_ok!(my_request.send().await)
+    }
+
     #[tracing::instrument(skip(self))]
     pub async fn post_deposit_amount(
         &self, account_id: AccountId, body: serde_json::Value,
     ) -> reqwest::Response {
         let my_request = self
             .api_client
-            .post(&format!("{}/deposit/{}", self.bank_url(), account_id))
+            .post(format!("{}/deposit/{}", self.bank_url(), account_id))
             .header(X_REAL_IP, "127.0.0.1")
             .json(&body);
         assert_ok!(my_request.send().await)
@@ -157,11 +439,7 @@ impl TestApp {
     ) -> reqwest::Response {
         let my_request = self
             .api_client
-            .post(&format!(
-                "{}/atm/withdrawal/{}",
-                self.bank_url(),
-                account_id
-            ))
+            .post(format!("{}/atm/withdrawal/{}", self.bank_url(), account_id))
             .header(X_REAL_IP, "127.0.0.1")
             .json(&body);
         tracing::info!("atm withdrawal request: {my_request:?}");
@@ -174,7 +452,7 @@ impl TestApp {
     ) -> reqwest::Response {
         let my_request = self
             .api_client
-            .post(&format!(
+            .post(format!(
                 "{}/check/withdrawal/{}",
                 self.bank_url(),
                 account_id
@@ -185,6 +463,15 @@ impl TestApp {
     }
 }
 
+/// Application to open a checking account for neo.
+pub fn account_application() -> serde_json::Value {
+    serde_json::json!({
+        "user_name": "neo",
+        "mailing_address": "12 Seahawks Way, Renton, WA 98056, USA",
+        "email": "neo@example.com",
+    })
+}
+
 #[allow(dead_code)]
 pub fn assert_is_redirect_to(response: &reqwest::Response, location: &str) {
     assert_eq!(response.status(), StatusCode::SEE_OTHER);
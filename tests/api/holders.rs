@@ -0,0 +1,328 @@
+use crate::helpers::{spawn_app_with, TestApp, TELLER_TOKEN, X_REAL_IP};
+use axum::http::StatusCode;
+use bankaccount::application::Version;
+use bankaccount::{
+    AccountHolding, AccountId, ApiTokenSettings, CustomerId, FeeSettings, HolderRole,
+};
+use claim::assert_ok;
+use money2::{Currency, Money};
+use pretty_assertions::assert_eq;
+use secrecy::Secret;
+use serde_json::json;
+
+const CUSTOMERS: [&str; 4] = ["neo", "trinity", "morpheus", "tank"];
+
+fn token_for(customer: &str) -> String {
+    format!("{customer}-token")
+}
+
+async fn spawn_holders_app() -> TestApp {
+    spawn_app_with(Version::latest(), |settings| {
+        settings
+            .auth
+            .tokens
+            .extend(CUSTOMERS.iter().map(|customer| ApiTokenSettings {
+                subject: customer.to_string(),
+                token: Secret::new(token_for(customer)),
+                scopes: Vec::new(),
+            }));
+    })
+    .await
+}
+
+/// The customer registered for the subject signing in as `name`.
+async fn customer_id(app: &TestApp, name: &str) -> CustomerId {
+    app.register_verified_customer(name).await
+}
+
+/// The holders as listed for the account: owner first, then by customer.
+fn owner_first(mut holders: Vec<(CustomerId, HolderRole)>) -> Vec<(CustomerId, HolderRole)> {
+    holders.sort_by_key(|(customer_id, role)| (*role != HolderRole::Owner, *customer_id));
+    holders
+}
+
+/// Opens neo's account with trinity as joint owner and morpheus as viewer.
+async fn open_joint_account(app: &TestApp) -> AccountId {
+    let response = app
+        .post_create_bank_account(json!({
+            "customer_id": customer_id(app, "neo").await,
+            "mailing_address": "12 Seahawks Way, Renton, WA 98056, USA",
+            "email": "neo@example.com",
+            "joint_holders": [
+                { "customer_id": customer_id(app, "trinity").await, "role": "joint_owner" },
+                { "customer_id": customer_id(app, "morpheus").await, "role": "viewer" },
+            ],
+        }))
+        .await;
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_ok!(response.json().await)
+}
+
+async fn post_as(
+    app: &TestApp, url: &str, customer: &str, body: serde_json::Value,
+) -> reqwest::Response {
+    assert_ok!(
+        app.api_client
+            .post(url)
+            .header(X_REAL_IP, "127.0.0.1")
+            .bearer_auth(token_for(customer))
+            .json(&body)
+            .send()
+            .await
+    )
+}
+
+async fn get_as(app: &TestApp, url: &str, customer: Option<&str>) -> reqwest::Response {
+    let mut request = app.api_client.get(url).header(X_REAL_IP, "127.0.0.1");
+    if let Some(customer) = customer {
+        request = request.bearer_auth(token_for(customer));
+    }
+    assert_ok!(request.send().await)
+}
+
+async fn account_holders(app: &TestApp, account_id: AccountId) -> Vec<(CustomerId, HolderRole)> {
+    let holders_url = format!("{}/{}/holders", app.bank_url(), account_id);
+    let response = get_as(app, &holders_url, Some("neo")).await;
+    assert_eq!(response.status(), StatusCode::OK);
+    let holders: Vec<AccountHolding> = assert_ok!(response.json().await);
+    holders
+        .into_iter()
+        .map(|holding| (holding.customer_id, holding.role))
+        .collect()
+}
+
+#[tokio::test]
+async fn account_opened_with_joint_holders_lists_them() {
+    let app = spawn_holders_app().await;
+    let account_id = open_joint_account(&app).await;
+
+    assert_eq!(
+        account_holders(&app, account_id).await,
+        owner_first(vec![
+            (customer_id(&app, "neo").await, HolderRole::Owner),
+            (customer_id(&app, "morpheus").await, HolderRole::Viewer),
+            (customer_id(&app, "trinity").await, HolderRole::JointOwner),
+        ])
+    );
+}
+
+#[tokio::test]
+async fn replay_rebuilds_account_holders() {
+    let app = spawn_holders_app().await;
+    let account_id = open_joint_account(&app).await;
+    let holders = account_holders(&app, account_id).await;
+
+    assert_ok!(sqlx::query("DELETE FROM account_holders").execute(&app.db_pool).await);
+    assert_ok!(bankaccount::replay_projections(&app.db_pool, &FeeSettings::default()).await);
+
+    assert_eq!(account_holders(&app, account_id).await, holders);
+}
+
+#[tokio::test]
+async fn holders_transact_as_their_role_permits() {
+    let app = spawn_holders_app().await;
+    let account_id = open_joint_account(&app).await;
+    let deposit_url = format!("{}/deposit/{}", app.bank_url(), account_id);
+    let amount = json!({ "amount": "25.00", "currency": "USD" });
+
+    let response = post_as(&app, &deposit_url, "trinity", amount.clone()).await;
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let response = post_as(&app, &deposit_url, "morpheus", amount.clone()).await;
+    assert_eq!(response.status(), StatusCode::FORBIDDEN);
+
+    let response = post_as(&app, &deposit_url, "tank", amount).await;
+    assert_eq!(response.status(), StatusCode::FORBIDDEN);
+
+    assert_eq!(
+        app.account_view(account_id).await.balance,
+        Money::new(25_00, 2, Currency::Usd)
+    );
+}
+
+#[tokio::test]
+async fn holders_are_recognized_by_customer_rather_than_name() {
+    let app = spawn_holders_app().await;
+    let account_id = open_joint_account(&app).await;
+
+    // tank registers under trinity's name, but is not the customer holding the account
+    let response = assert_ok!(
+        app.api_client
+            .post(app.customers_url())
+            .header(X_REAL_IP, "127.0.0.1")
+            .bearer_auth(TELLER_TOKEN)
+            .json(&json!({
+                "subject": "tank",
+                "name": "trinity",
+                "email": "tank@example.com",
+                "mailing_address": "12 Seahawks Way, Renton, WA 98056, USA",
+            }))
+            .send()
+            .await
+    );
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let deposit_url = format!("{}/deposit/{}", app.bank_url(), account_id);
+    let amount = json!({ "amount": "25.00", "currency": "USD" });
+    let response = post_as(&app, &deposit_url, "tank", amount).await;
+    assert_eq!(response.status(), StatusCode::FORBIDDEN);
+    assert_eq!(
+        app.account_view(account_id).await.balance,
+        Money::new(0, 2, Currency::Usd)
+    );
+}
+
+#[tokio::test]
+async fn anonymous_withdrawal_keeps_the_bank_authority_but_an_unrecognized_token_is_rejected() {
+    let app = spawn_holders_app().await;
+    let account_id = open_joint_account(&app).await;
+    let response = app
+        .post_deposit_amount(account_id, json!({ "amount": "50.00", "currency": "USD" }))
+        .await;
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let withdrawal_url = format!("{}/atm/withdrawal/{}", app.bank_url(), account_id);
+    let withdrawal =
+        json!({ "atm_id": "abc_123", "amount": { "amount": "20.00", "currency": "USD" } });
+    let response = assert_ok!(
+        app.api_client
+            .post(&withdrawal_url)
+            .header(X_REAL_IP, "127.0.0.1")
+            .bearer_auth("bogus")
+            .json(&withdrawal)
+            .send()
+            .await
+    );
+    assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+
+    let response = app.post_atm_withdrawal(account_id, withdrawal.clone()).await;
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let response = post_as(&app, &withdrawal_url, "trinity", withdrawal).await;
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(
+        app.account_view(account_id).await.balance,
+        Money::new(10_00, 2, Currency::Usd)
+    );
+}
+
+#[tokio::test]
+async fn joint_owner_holder_change_waits_for_owner_approval() {
+    let app = spawn_holders_app().await;
+    let account_id = open_joint_account(&app).await;
+    let holders_url = format!("{}/{}/holders", app.bank_url(), account_id);
+    let tank = customer_id(&app, "tank").await;
+
+    let response = post_as(
+        &app,
+        &holders_url,
+        "trinity",
+        json!({ "customer_id": tank, "role": "authorized_signer" }),
+    )
+    .await;
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(account_holders(&app, account_id).await.len(), 3);
+
+    let pending = app.account_view(account_id).await.pending_holder_changes;
+    assert_eq!(pending.len(), 1);
+    assert_eq!(pending[0].requested_by, customer_id(&app, "trinity").await);
+    let approve_url = format!(
+        "{}/{}/holder_changes/{}/approve",
+        app.bank_url(),
+        account_id,
+        pending[0].change_id
+    );
+
+    let response = post_as(&app, &approve_url, "trinity", json!({})).await;
+    assert_eq!(response.status(), StatusCode::FORBIDDEN);
+
+    let response = post_as(&app, &approve_url, "neo", json!({})).await;
+    assert_eq!(response.status(), StatusCode::OK);
+    assert!(app.account_view(account_id).await.pending_holder_changes.is_empty());
+    assert!(account_holders(&app, account_id)
+        .await
+        .contains(&(tank, HolderRole::AuthorizedSigner)));
+}
+
+#[tokio::test]
+async fn owner_changes_holders_directly() {
+    let app = spawn_holders_app().await;
+    let account_id = open_joint_account(&app).await;
+    let holders_url = format!("{}/{}/holders", app.bank_url(), account_id);
+    let tank = customer_id(&app, "tank").await;
+
+    let response = post_as(
+        &app,
+        &holders_url,
+        "neo",
+        json!({ "customer_id": tank, "role": "viewer" }),
+    )
+    .await;
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let morpheus = customer_id(&app, "morpheus").await;
+    let response = assert_ok!(
+        app.api_client
+            .delete(format!("{holders_url}/{morpheus}"))
+            .header(X_REAL_IP, "127.0.0.1")
+            .bearer_auth(token_for("neo"))
+            .send()
+            .await
+    );
+    assert_eq!(response.status(), StatusCode::OK);
+
+    assert_eq!(
+        account_holders(&app, account_id).await,
+        owner_first(vec![
+            (customer_id(&app, "neo").await, HolderRole::Owner),
+            (tank, HolderRole::Viewer),
+            (customer_id(&app, "trinity").await, HolderRole::JointOwner),
+        ])
+    );
+}
+
+#[tokio::test]
+async fn customer_lookup_lists_held_accounts() {
+    let app = spawn_holders_app().await;
+    let first = open_joint_account(&app).await;
+    let second = open_joint_account(&app).await;
+
+    let morpheus = customer_id(&app, "morpheus").await;
+    let accounts_url = format!("{}/customers/{}/accounts", app.bank_url(), morpheus);
+    let response = get_as(&app, &accounts_url, Some("morpheus")).await;
+    assert_eq!(response.status(), StatusCode::OK);
+    let accounts: Vec<AccountHolding> = assert_ok!(response.json().await);
+
+    let mut expected = vec![first, second];
+    expected.sort();
+    assert_eq!(
+        accounts
+            .iter()
+            .map(|holding| (holding.account_id, holding.role))
+            .collect::<Vec<_>>(),
+        expected
+            .into_iter()
+            .map(|account_id| (account_id, HolderRole::Viewer))
+            .collect::<Vec<_>>()
+    );
+}
+
+#[tokio::test]
+async fn holdings_are_restricted_to_holders_and_the_customer() {
+    let app = spawn_holders_app().await;
+    let account_id = open_joint_account(&app).await;
+
+    let holders_url = format!("{}/{}/holders", app.bank_url(), account_id);
+    let morpheus = customer_id(&app, "morpheus").await;
+    let accounts_url = format!("{}/customers/{}/accounts", app.bank_url(), morpheus);
+    for (url, customer, expected) in [
+        (&holders_url, None, StatusCode::UNAUTHORIZED),
+        (&holders_url, Some("tank"), StatusCode::FORBIDDEN),
+        (&holders_url, Some("morpheus"), StatusCode::OK),
+        (&accounts_url, None, StatusCode::UNAUTHORIZED),
+        (&accounts_url, Some("neo"), StatusCode::FORBIDDEN),
+    ] {
+        let response = get_as(&app, url, customer).await;
+        assert_eq!(response.status(), expected, "{url} as {customer:?}");
+    }
+}
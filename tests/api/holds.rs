@@ -0,0 +1,196 @@
+use crate::helpers::{spawn_app_with, spawn_latest_app, TestApp, TELLER_TOKEN, X_REAL_IP};
+use axum::http::StatusCode;
+use bankaccount::application::Version;
+use bankaccount::{AccountId, FeeSettings, HoldId};
+use chrono::{Duration, Utc};
+use claim::assert_ok;
+use money2::{Currency, Money};
+use pretty_assertions::assert_eq;
+use serde_json::json;
+
+async fn post_hold(
+    app: &TestApp, account_id: AccountId, hold_id: &str, amount: &str, expires_in: Duration,
+) -> reqwest::Response {
+    assert_ok!(
+        app.api_client
+            .post(format!("{}/{}/holds", app.bank_url(), account_id))
+            .header(X_REAL_IP, "127.0.0.1")
+            .bearer_auth(TELLER_TOKEN)
+            .json(&json!({
+                "hold_id": hold_id,
+                "amount": { "amount": amount, "currency": "USD" },
+                "expires_at": Utc::now() + expires_in,
+            }))
+            .send()
+            .await
+    )
+}
+
+async fn post_hold_action(
+    app: &TestApp, account_id: AccountId, hold_id: &str, action: &str,
+) -> reqwest::Response {
+    assert_ok!(
+        app.api_client
+            .post(format!(
+                "{}/{}/holds/{}/{}",
+                app.bank_url(),
+                account_id,
+                hold_id,
+                action
+            ))
+            .header(X_REAL_IP, "127.0.0.1")
+            .bearer_auth(TELLER_TOKEN)
+            .send()
+            .await
+    )
+}
+
+fn usd(amount: i64) -> Money {
+    Money::new(amount * 100, 2, Currency::Usd)
+}
+
+#[tokio::test]
+async fn hold_reserves_available_balance_until_captured() {
+    let app = spawn_latest_app().await;
+    let account_id = app.open_funded_account("500.00").await;
+
+    let response = post_hold(&app, account_id, "auth_1", "300.00", Duration::hours(1)).await;
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let view = app.account_view(account_id).await;
+    assert_eq!(view.balance, usd(500));
+    assert_eq!(view.available_balance, usd(200));
+    assert_eq!(view.holds.len(), 1);
+    assert_eq!(view.holds[0].hold_id, HoldId::new("auth_1"));
+
+    let response = app
+        .post_atm_withdrawal(
+            account_id,
+            json!({ "atm_id": "abc_123", "amount": { "amount": "250.00", "currency": "USD" } }),
+        )
+        .await;
+    assert_eq!(response.status(), StatusCode::UNPROCESSABLE_ENTITY);
+
+    let response = post_hold(&app, account_id, "auth_1", "10.00", Duration::hours(1)).await;
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+
+    let response = post_hold_action(&app, account_id, "auth_1", "capture").await;
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let view = app.account_view(account_id).await;
+    assert_eq!(view.balance, usd(200));
+    assert_eq!(view.available_balance, usd(200));
+    assert!(view.holds.is_empty());
+    let last_entry = assert_ok!(view.ledger.last().ok_or("no ledger entry"));
+    assert_eq!(last_entry.description, "Hold auth_1 captured");
+    assert_eq!(last_entry.amount, usd(-300));
+
+    let response = post_hold_action(&app, account_id, "auth_1", "release").await;
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn released_hold_returns_available_balance() {
+    let app = spawn_latest_app().await;
+    let account_id = app.open_funded_account("500.00").await;
+
+    let response = post_hold(&app, account_id, "auth_2", "450.00", Duration::hours(1)).await;
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(
+        app.account_view(account_id).await.available_balance,
+        usd(50)
+    );
+
+    let response = post_hold_action(&app, account_id, "auth_2", "release").await;
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let view = app.account_view(account_id).await;
+    assert_eq!(view.balance, usd(500));
+    assert_eq!(view.available_balance, usd(500));
+    assert!(view.holds.is_empty());
+}
+
+#[tokio::test]
+async fn expired_hold_is_released_by_background_task() {
+    let app = spawn_app_with(Version::latest(), |settings| {
+        settings.holds.poll_interval = std::time::Duration::from_secs(1);
+    })
+    .await;
+    let account_id = app.open_funded_account("500.00").await;
+
+    let response = post_hold(&app, account_id, "auth_3", "100.00", Duration::seconds(1)).await;
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(
+        app.account_view(account_id).await.available_balance,
+        usd(400)
+    );
+
+    let mut view = app.account_view(account_id).await;
+    for _ in 0..40 {
+        if view.holds.is_empty() {
+            break;
+        }
+        tokio::time::sleep(std::time::Duration::from_millis(250)).await;
+        view = app.account_view(account_id).await;
+    }
+
+    assert!(view.holds.is_empty());
+    assert_eq!(view.available_balance, usd(500));
+}
+
+/// The rows of the worker index table as JSON, ordered by the columns.
+async fn index_rows(app: &TestApp, table: &str, columns: &str) -> String {
+    let select_sql = format!(
+        "SELECT COALESCE(json_agg(json_build_array({columns}) ORDER BY {columns})::text, '[]')
+        FROM {table}"
+    );
+    assert_ok!(sqlx::query_scalar(&select_sql).fetch_one(&app.db_pool).await)
+}
+
+#[tokio::test]
+async fn replay_rebuilds_worker_indexes_as_they_were() {
+    let app = spawn_latest_app().await;
+    let account_id = app.open_funded_account("500.00").await;
+    let response = post_hold(&app, account_id, "auth_4", "100.00", Duration::hours(1)).await;
+    assert_eq!(response.status(), StatusCode::OK);
+    let response = app
+        .post_atm_withdrawal(
+            account_id,
+            json!({ "atm_id": "abc_123", "amount": { "amount": "20.00", "currency": "USD" } }),
+        )
+        .await;
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let indexes = [
+        ("account_holds", "aggregate_id, hold_id, expires_at"),
+        ("fee_assessment", "aggregate_id, next_assessment_at"),
+        (
+            "account_activity",
+            "aggregate_id, last_activity_at, dormant_since",
+        ),
+        (
+            "fraud_activity",
+            "aggregate_id, sequence, kind, atm_id, occurred_at",
+        ),
+    ];
+    let mut before = Vec::new();
+    for (table, columns) in indexes {
+        before.push(index_rows(&app, table, columns).await);
+    }
+    assert!(
+        before.iter().all(|rows| rows != "[]"),
+        "empty index: {before:?}"
+    );
+
+    assert_ok!(bankaccount::replay_projections(&app.db_pool, &FeeSettings::default()).await);
+
+    for ((table, columns), rows) in indexes.into_iter().zip(before) {
+        assert_eq!(index_rows(&app, table, columns).await, rows, "{table}");
+    }
+    let email: String = assert_ok!(
+        sqlx::query_scalar("SELECT email FROM notification_recipients")
+            .fetch_one(&app.db_pool)
+            .await
+    );
+    assert_eq!(email, "neo@example.com");
+}
@@ -1,3 +1,22 @@
+mod account_types;
 mod bank;
+mod command_retry;
+mod commands;
+mod customers;
+mod disputes;
+mod dormancy;
+mod fees;
+mod fraud;
+mod freeze;
 mod health_check;
 mod helpers;
+mod holders;
+mod holds;
+mod migrations;
+mod notifications;
+mod outbox;
+mod problem_details;
+mod rate_limits;
+mod reversal;
+mod standing_orders;
+mod webhooks;
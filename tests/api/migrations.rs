@@ -0,0 +1,49 @@
+use crate::helpers::{configure_database, test_settings};
+use bankaccount::application::{ApiError, MIGRATOR};
+use bankaccount::Application;
+use claim::{assert_ok, assert_some};
+use pretty_assertions::assert_eq;
+
+const FUTURE_VERSION: i64 = 99_990_101_000_000;
+
+#[tokio::test]
+async fn replicas_starting_together_apply_migrations_once() {
+    let settings = test_settings();
+    let pool = configure_database(&settings.database).await;
+
+    let (first, second) =
+        tokio::join!(Application::build(&settings), Application::build(&settings));
+    assert_ok!(first);
+    assert_ok!(second);
+
+    let nr_applied: i64 = assert_ok!(
+        sqlx::query_scalar("SELECT count(*) FROM _sqlx_migrations WHERE success")
+            .fetch_one(&pool)
+            .await
+    );
+    assert_eq!(nr_applied, MIGRATOR.iter().count() as i64);
+}
+
+#[tokio::test]
+async fn startup_fails_when_schema_is_ahead_of_binary() {
+    let settings = test_settings();
+    let pool = configure_database(&settings.database).await;
+    assert_ok!(bankaccount::application::migrate_database(&pool).await);
+
+    assert_ok!(
+        sqlx::query(
+            r##"INSERT INTO _sqlx_migrations
+            (version, description, success, checksum, execution_time)
+            VALUES ($1, 'from a newer build', true, '\x00', 0)"##
+        )
+        .bind(FUTURE_VERSION)
+        .execute(&pool)
+        .await
+    );
+
+    let error = assert_some!(Application::build(&settings).await.err());
+    assert!(
+        matches!(error, ApiError::SchemaAhead(version) if version == FUTURE_VERSION),
+        "unexpected error: {error}"
+    );
+}
@@ -0,0 +1,231 @@
+use crate::helpers::{spawn_app_with, TestApp, X_REAL_IP};
+use axum::http::StatusCode;
+use bankaccount::application::Version;
+use bankaccount::{ApiTokenSettings, EmailMessage, MailerSettings};
+use claim::assert_ok;
+use money2::{Currency, Money};
+use pretty_assertions::assert_eq;
+use secrecy::Secret;
+use serde_json::json;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+const HOLDER_TOKEN: &str = "neo-token";
+const STRANGER_TOKEN: &str = "smith-token";
+
+async fn spawn_notifying_app(outbox_path: &Path) -> TestApp {
+    let path = outbox_path.to_path_buf();
+    spawn_app_with(Version::latest(), move |settings| {
+        settings.auth.tokens.extend([
+            ApiTokenSettings {
+                subject: "neo".to_string(),
+                token: Secret::new(HOLDER_TOKEN.to_string()),
+                scopes: vec![],
+            },
+            ApiTokenSettings {
+                subject: "smith".to_string(),
+                token: Secret::new(STRANGER_TOKEN.to_string()),
+                scopes: vec![],
+            },
+        ]);
+        settings.notifications.enabled = true;
+        settings.notifications.poll_interval = Duration::from_millis(50);
+        settings.notifications.large_withdrawal_threshold = Money::new(100, 0, Currency::Usd);
+        settings.notifications.mailer = MailerSettings::File { path };
+    })
+    .await
+}
+
+fn mail_path(test_name: &str) -> PathBuf {
+    let path = std::env::temp_dir().join(format!(
+        "{test_name}-{}.jsonl",
+        chrono::Utc::now().timestamp_nanos()
+    ));
+    let _ = std::fs::remove_file(&path);
+    path
+}
+
+async fn await_mail(path: &Path, expected: usize) -> Vec<EmailMessage> {
+    let mut sent = Vec::new();
+    for _ in 0..100 {
+        sent = std::fs::read_to_string(path)
+            .unwrap_or_default()
+            .lines()
+            .map(|line| assert_ok!(serde_json::from_str::<EmailMessage>(line)))
+            .collect();
+        if expected <= sent.len() {
+            break;
+        }
+        tokio::time::sleep(Duration::from_millis(50)).await;
+    }
+
+    // order mail by content rather than by when the worker happened to send it
+    sent.sort_by(|lhs, rhs| (&lhs.subject, &lhs.to).cmp(&(&rhs.subject, &rhs.to)));
+    sent
+}
+
+#[tokio::test]
+async fn notifications_are_sent_for_notable_account_events() {
+    let path = mail_path("notifications_are_sent");
+    let app = spawn_notifying_app(&path).await;
+    let account_id = app.open_funded_account("1000.00").await;
+
+    let response = app
+        .post_atm_withdrawal(
+            account_id,
+            json!({ "atm_id": "abc_123", "amount": { "amount": "5.00", "currency": "USD" } }),
+        )
+        .await;
+    assert_eq!(response.status(), StatusCode::OK);
+    let response = app
+        .post_atm_withdrawal(
+            account_id,
+            json!({ "atm_id": "abc_123", "amount": { "amount": "250.00", "currency": "USD" } }),
+        )
+        .await;
+    assert_eq!(response.status(), StatusCode::OK);
+    let response = app.post_update_email(account_id, "the.one@example.com").await;
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let withdrawal_subject = format!(
+        "Withdrawal of {} from your account",
+        Money::new(25_000, 2, Currency::Usd)
+    );
+    let sent = await_mail(&path, 4).await;
+    let actual: Vec<(&str, &str)> = sent
+        .iter()
+        .map(|message| (message.subject.as_str(), message.to.as_str()))
+        .collect();
+    assert_eq!(
+        actual,
+        vec![
+            (
+                "The email address on your account changed",
+                "neo@example.com"
+            ),
+            (
+                "The email address on your account changed",
+                "the.one@example.com"
+            ),
+            ("Welcome to the bank", "neo@example.com"),
+            (withdrawal_subject.as_str(), "neo@example.com"),
+        ]
+    );
+
+    let _ = std::fs::remove_file(&path);
+}
+
+#[tokio::test]
+async fn notifications_respect_account_preferences() {
+    let path = mail_path("notifications_respect_preferences");
+    let app = spawn_notifying_app(&path).await;
+    let account_id = app.open_funded_account("1000.00").await;
+    assert_eq!(await_mail(&path, 1).await.len(), 1);
+
+    let response = app
+        .put_notification_preferences(
+            account_id,
+            json!({ "large_withdrawal": false }),
+            Some(HOLDER_TOKEN),
+        )
+        .await;
+    assert_eq!(response.status(), StatusCode::OK);
+    let preferences: serde_json::Value = assert_ok!(response.json().await);
+    assert_eq!(
+        preferences,
+        json!({ "account_opened": true, "large_withdrawal": false, "email_changed": true })
+    );
+
+    let response = app
+        .post_atm_withdrawal(
+            account_id,
+            json!({ "atm_id": "abc_123", "amount": { "amount": "250.00", "currency": "USD" } }),
+        )
+        .await;
+    assert_eq!(response.status(), StatusCode::OK);
+    let response = app.post_update_email(account_id, "the.one@example.com").await;
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let subjects: Vec<String> = await_mail(&path, 3)
+        .await
+        .into_iter()
+        .map(|message| message.subject)
+        .collect();
+    assert_eq!(
+        subjects,
+        vec![
+            "The email address on your account changed".to_string(),
+            "The email address on your account changed".to_string(),
+            "Welcome to the bank".to_string(),
+        ]
+    );
+
+    let _ = std::fs::remove_file(&path);
+}
+
+#[tokio::test]
+async fn notification_preferences_require_an_account_holder() {
+    let path = mail_path("notification_preferences_require_holder");
+    let app = spawn_notifying_app(&path).await;
+    let account_id = app.open_account().await;
+    let preferences = json!({ "account_opened": false });
+
+    let response = app
+        .put_notification_preferences(account_id, preferences.clone(), None)
+        .await;
+    assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+
+    let response = app
+        .put_notification_preferences(account_id, preferences, Some(STRANGER_TOKEN))
+        .await;
+    assert_eq!(response.status(), StatusCode::FORBIDDEN);
+
+    let response = assert_ok!(
+        app.api_client
+            .get(format!("{}/{}/notifications", app.bank_url(), account_id))
+            .header(X_REAL_IP, "127.0.0.1")
+            .bearer_auth(HOLDER_TOKEN)
+            .send()
+            .await
+    );
+    assert_eq!(response.status(), StatusCode::OK);
+    let preferences: serde_json::Value = assert_ok!(response.json().await);
+    assert_eq!(preferences["account_opened"], json!(true));
+
+    let _ = std::fs::remove_file(&path);
+}
+
+#[tokio::test]
+async fn notification_failing_every_attempt_is_marked_failed() {
+    let path = std::env::temp_dir()
+        .join(format!(
+            "missing-mail-dir-{}",
+            chrono::Utc::now().timestamp_nanos()
+        ))
+        .join("mail.jsonl");
+    let app = spawn_app_with(Version::latest(), move |settings| {
+        settings.notifications.enabled = true;
+        settings.notifications.poll_interval = Duration::from_millis(50);
+        settings.notifications.max_attempts = 2;
+        settings.notifications.backoff.initial_delay = Duration::from_millis(10);
+        settings.notifications.mailer = MailerSettings::File { path };
+    })
+    .await;
+    app.open_account().await;
+
+    let mut outcome = None;
+    for _ in 0..100 {
+        outcome = assert_ok!(
+            sqlx::query_as::<_, (String, i32)>(
+                "SELECT status, attempts FROM notification_messages WHERE status <> 'pending'"
+            )
+            .fetch_optional(&app.db_pool)
+            .await
+        );
+        if outcome.is_some() {
+            break;
+        }
+        tokio::time::sleep(Duration::from_millis(50)).await;
+    }
+    assert_eq!(outcome, Some(("failed".to_string(), 2)));
+}
@@ -0,0 +1,236 @@
+use crate::helpers::spawn_app_with;
+use axum::http::StatusCode;
+use bankaccount::application::Version;
+use bankaccount::{AccountId, PublisherSettings};
+use claim::assert_ok;
+use pretty_assertions::assert_eq;
+use serde_json::json;
+use std::time::Duration;
+use wiremock::matchers::{body_partial_json, method, path};
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+#[tokio::test]
+async fn outbox_relays_account_events_in_order() {
+    let consumer = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path("/events"))
+        .respond_with(ResponseTemplate::new(200))
+        .mount(&consumer)
+        .await;
+
+    let consumer_url = assert_ok!(url::Url::parse(&format!("{}/events", consumer.uri())));
+    let app = spawn_app_with(Version::latest(), |settings| {
+        settings.outbox.enabled = true;
+        settings.outbox.poll_interval = Duration::from_millis(50);
+        settings.outbox.publisher = PublisherSettings::Webhook { url: consumer_url };
+    })
+    .await;
+
+    let response = app
+        .post_create_bank_account(json!({
+            "user_name": "neo",
+            "mailing_address": "12 Seahawks Way, Renton, WA 98056, USA",
+            "email": "neo@example.com",
+        }))
+        .await;
+    assert_eq!(response.status(), StatusCode::OK);
+    let account_id: AccountId = assert_ok!(response.json().await);
+    let response = app
+        .post_deposit_amount(account_id, json!({ "amount": "12.34", "currency": "USD" }))
+        .await;
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let mut nr_unpublished = -1_i64;
+    for _ in 0..100 {
+        nr_unpublished = assert_ok!(
+            sqlx::query_scalar("SELECT count(*) FROM event_outbox WHERE published_at IS NULL")
+                .fetch_one(&app.db_pool)
+                .await
+        );
+        if nr_unpublished == 0 {
+            break;
+        }
+        tokio::time::sleep(Duration::from_millis(50)).await;
+    }
+    assert_eq!(nr_unpublished, 0);
+
+    let received = consumer.received_requests().await.unwrap_or_default();
+    let delivered: Vec<serde_json::Value> = received
+        .iter()
+        .map(|request| assert_ok!(request.body_json::<serde_json::Value>()))
+        .map(|message| json!([message["sequence"], message["event_type"]]))
+        .collect();
+    assert_eq!(
+        delivered,
+        vec![
+            json!([1, "account_opened"]),
+            json!([2, "balance_deposited"]),
+        ]
+    );
+}
+
+#[tokio::test]
+async fn outbox_does_not_publish_customer_events() {
+    let consumer = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path("/events"))
+        .respond_with(ResponseTemplate::new(200))
+        .mount(&consumer)
+        .await;
+
+    let consumer_url = assert_ok!(url::Url::parse(&format!("{}/events", consumer.uri())));
+    let app = spawn_app_with(Version::latest(), |settings| {
+        settings.outbox.enabled = true;
+        settings.outbox.poll_interval = Duration::from_millis(50);
+        settings.outbox.publisher = PublisherSettings::Webhook { url: consumer_url };
+    })
+    .await;
+
+    let customer_id = app.register_verified_customer("neo").await;
+    let nr_customer_events: i64 = assert_ok!(
+        sqlx::query_scalar("SELECT count(*) FROM events WHERE aggregate_id = $1")
+            .bind(customer_id.to_string())
+            .fetch_one(&app.db_pool)
+            .await
+    );
+    assert_eq!(nr_customer_events, 3);
+
+    tokio::time::sleep(Duration::from_millis(250)).await;
+    let nr_queued: i64 = assert_ok!(
+        sqlx::query_scalar("SELECT count(*) FROM event_outbox")
+            .fetch_one(&app.db_pool)
+            .await
+    );
+    assert_eq!(nr_queued, 0);
+    let received = consumer.received_requests().await.unwrap_or_default();
+    assert!(
+        received.is_empty(),
+        "published customer events: {received:?}"
+    );
+}
+
+/// Waits for the outbox rows matching the `filter` to be published, returning how many are left.
+async fn await_published(app: &crate::helpers::TestApp, filter: &str) -> i64 {
+    let count_sql =
+        format!("SELECT count(*) FROM event_outbox WHERE published_at IS NULL AND {filter}");
+    let mut nr_unpublished = -1_i64;
+    for _ in 0..100 {
+        nr_unpublished = assert_ok!(sqlx::query_scalar(&count_sql).fetch_one(&app.db_pool).await);
+        if nr_unpublished == 0 {
+            break;
+        }
+        tokio::time::sleep(Duration::from_millis(50)).await;
+    }
+    nr_unpublished
+}
+
+#[tokio::test]
+async fn outbox_failing_aggregate_does_not_hold_back_others() {
+    let consumer = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path("/events"))
+        .respond_with(ResponseTemplate::new(200))
+        .mount(&consumer)
+        .await;
+
+    let consumer_url = assert_ok!(url::Url::parse(&format!("{}/events", consumer.uri())));
+    let app = spawn_app_with(Version::latest(), |settings| {
+        settings.outbox.enabled = true;
+        settings.outbox.poll_interval = Duration::from_millis(50);
+        settings.outbox.batch_size = 1;
+        settings.outbox.backoff.initial_delay = Duration::from_secs(3600);
+        settings.outbox.backoff.max_delay = Duration::from_secs(3600);
+        settings.outbox.publisher = PublisherSettings::Webhook { url: consumer_url };
+    })
+    .await;
+
+    let failing_id = app.open_account().await;
+    assert_eq!(await_published(&app, "true").await, 0);
+
+    Mock::given(method("POST"))
+        .and(path("/events"))
+        .and(body_partial_json(
+            json!({ "aggregate_id": failing_id.to_string() }),
+        ))
+        .respond_with(ResponseTemplate::new(503))
+        .with_priority(1)
+        .mount(&consumer)
+        .await;
+
+    for amount in ["12.34", "5.00"] {
+        let response = app
+            .post_deposit_amount(failing_id, json!({ "amount": amount, "currency": "USD" }))
+            .await;
+        assert_eq!(response.status(), StatusCode::OK);
+    }
+
+    let account_id = app.open_funded_account("12.34").await;
+    let others = format!("aggregate_id = '{account_id}'");
+    assert_eq!(await_published(&app, &others).await, 0);
+
+    let failing_rows: Vec<(i32, Option<chrono::DateTime<chrono::Utc>>)> = assert_ok!(
+        sqlx::query_as(
+            "SELECT attempts, published_at FROM event_outbox WHERE aggregate_id = $1 AND \
+             published_at IS NULL ORDER BY id"
+        )
+        .bind(failing_id.to_string())
+        .fetch_all(&app.db_pool)
+        .await
+    );
+    assert_eq!(failing_rows, vec![(1, None), (0, None)]);
+}
+
+#[tokio::test]
+async fn outbox_prunes_rows_past_retention_once_published() {
+    let consumer = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path("/events"))
+        .respond_with(ResponseTemplate::new(503))
+        .mount(&consumer)
+        .await;
+
+    let consumer_url = assert_ok!(url::Url::parse(&format!("{}/events", consumer.uri())));
+    let app = spawn_app_with(Version::latest(), |settings| {
+        settings.outbox.enabled = true;
+        settings.outbox.poll_interval = Duration::from_millis(50);
+        settings.outbox.backoff.initial_delay = Duration::from_millis(50);
+        settings.outbox.backoff.max_delay = Duration::from_millis(50);
+        settings.outbox.publisher = PublisherSettings::Webhook { url: consumer_url };
+        settings.outbox.retention = Duration::ZERO;
+        settings.outbox.prune_interval = Duration::from_millis(50);
+    })
+    .await;
+
+    let account_id = app.open_account().await;
+    let count_sql = "SELECT count(*) FROM event_outbox WHERE aggregate_id = $1";
+    tokio::time::sleep(Duration::from_millis(300)).await;
+    let nr_queued: i64 = assert_ok!(
+        sqlx::query_scalar(count_sql)
+            .bind(account_id.to_string())
+            .fetch_one(&app.db_pool)
+            .await
+    );
+    assert_eq!(nr_queued, 1, "unpublished row must not be pruned");
+
+    Mock::given(method("POST"))
+        .and(path("/events"))
+        .respond_with(ResponseTemplate::new(200))
+        .with_priority(1)
+        .mount(&consumer)
+        .await;
+
+    let mut nr_queued = -1_i64;
+    for _ in 0..100 {
+        nr_queued = assert_ok!(
+            sqlx::query_scalar(count_sql)
+                .bind(account_id.to_string())
+                .fetch_one(&app.db_pool)
+                .await
+        );
+        if nr_queued == 0 {
+            break;
+        }
+        tokio::time::sleep(Duration::from_millis(50)).await;
+    }
+    assert_eq!(nr_queued, 0);
+}
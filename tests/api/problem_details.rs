@@ -0,0 +1,160 @@
+use crate::helpers::{spawn_latest_app, TELLER_TOKEN, X_REAL_IP};
+use axum::http::header::CONTENT_TYPE;
+use axum::http::StatusCode;
+use bankaccount::application::{ProblemDetails, PROBLEM_JSON};
+use bankaccount::AccountId;
+use claim::{assert_ok, assert_some};
+use pretty_assertions::assert_eq;
+use serde_json::json;
+
+async fn problem_of(response: reqwest::Response, status: StatusCode) -> ProblemDetails {
+    assert_eq!(response.status(), status);
+    let content_type = assert_some!(response.headers().get(CONTENT_TYPE));
+    assert_eq!(assert_ok!(content_type.to_str()), PROBLEM_JSON);
+    assert_ok!(response.json().await)
+}
+
+#[tokio::test]
+async fn insufficient_funds_is_reported_as_problem_details() {
+    let app = spawn_latest_app().await;
+    let account_id = app.open_account().await;
+
+    let response = app
+        .post_atm_withdrawal(
+            account_id,
+            json!({ "atm_id": "abc_123", "amount": { "amount": "20.00", "currency": "USD" } }),
+        )
+        .await;
+    let request_id = assert_some!(response.headers().get("x-request-id"));
+    let request_id = assert_ok!(request_id.to_str()).to_string();
+
+    let problem = problem_of(response, StatusCode::UNPROCESSABLE_ENTITY).await;
+    assert_eq!(problem.code, "insufficient_funds");
+    assert_eq!(
+        problem.problem_type,
+        "urn:bankaccount:problem:insufficient_funds"
+    );
+    assert_eq!(problem.title, "Insufficient funds");
+    assert_eq!(problem.status, 422);
+    assert_eq!(
+        problem.instance,
+        Some(format!("/api/v1/bank/atm/withdrawal/{account_id}"))
+    );
+    assert_eq!(problem.request_id, Some(request_id));
+}
+
+#[tokio::test]
+async fn rejected_command_and_missing_account_have_distinct_codes() {
+    let app = spawn_latest_app().await;
+    let account_id = app.open_account().await;
+
+    let response = assert_ok!(
+        app.api_client
+            .post(format!("{}/{}/account_type", app.bank_url(), account_id))
+            .header(X_REAL_IP, "127.0.0.1")
+            .bearer_auth(TELLER_TOKEN)
+            .json(&json!({ "account_type": "checking" }))
+            .send()
+            .await
+    );
+    let problem = problem_of(response, StatusCode::BAD_REQUEST).await;
+    assert_eq!(problem.code, "rejected_command");
+    assert!(problem.detail.contains("already a checking account"));
+
+    let response = app.get_account_events(AccountId::new(42), &[]).await;
+    let problem = problem_of(response, StatusCode::NOT_FOUND).await;
+    assert_eq!(problem.code, "account_not_found");
+    assert_eq!(problem.status, 404);
+}
+
+#[tokio::test]
+async fn command_on_unopened_account_returns_a_404() {
+    let app = spawn_latest_app().await;
+
+    let response = app
+        .post_deposit_amount(
+            AccountId::new(42),
+            json!({ "amount": "25.00", "currency": "USD" }),
+        )
+        .await;
+    let problem = problem_of(response, StatusCode::NOT_FOUND).await;
+    assert_eq!(problem.code, "account_not_found");
+    assert!(problem.detail.contains("Unopened account"));
+}
+
+#[tokio::test]
+async fn malformed_request_is_a_bad_request() {
+    let app = spawn_latest_app().await;
+    let account_id = app.open_account().await;
+
+    let response = assert_ok!(
+        app.api_client
+            .put(format!("{}/{}/notifications", app.bank_url(), account_id))
+            .header(X_REAL_IP, "127.0.0.1")
+            .header(CONTENT_TYPE, "application/json")
+            .bearer_auth(TELLER_TOKEN)
+            .body(r#"{ "email": "#)
+            .send()
+            .await
+    );
+    let problem = problem_of(response, StatusCode::BAD_REQUEST).await;
+    assert_eq!(problem.code, "invalid_request");
+    assert_eq!(problem.status, 400);
+
+    let response = assert_ok!(
+        app.api_client
+            .put(format!("{}/not-an-account/notifications", app.bank_url()))
+            .header(X_REAL_IP, "127.0.0.1")
+            .bearer_auth(TELLER_TOKEN)
+            .json(&json!({}))
+            .send()
+            .await
+    );
+    let problem = problem_of(response, StatusCode::BAD_REQUEST).await;
+    assert_eq!(problem.code, "invalid_request");
+}
+
+#[tokio::test]
+async fn missing_resources_are_reported_as_problem_details() {
+    let app = spawn_latest_app().await;
+
+    let response = app.get_serve_bank_account(AccountId::new(42)).await;
+    let problem = problem_of(response, StatusCode::NOT_FOUND).await;
+    assert_eq!(problem.code, "account_not_found");
+
+    for (url, code) in [
+        (app.customers_url(), "customer_not_found"),
+        (app.standing_orders_url(), "standing_order_not_found"),
+        (app.commands_url(), "command_not_found"),
+    ] {
+        let response = assert_ok!(
+            app.api_client
+                .get(format!("{url}/7006077196242653184"))
+                .header(X_REAL_IP, "127.0.0.1")
+                .bearer_auth(TELLER_TOKEN)
+                .send()
+                .await
+        );
+        let problem = problem_of(response, StatusCode::NOT_FOUND).await;
+        assert_eq!(problem.code, code);
+        assert!(problem.detail.contains("7006077196242653184"));
+    }
+}
+
+#[tokio::test]
+async fn malformed_account_application_is_a_bad_request() {
+    let app = spawn_latest_app().await;
+
+    let response = assert_ok!(
+        app.api_client
+            .post(app.bank_url())
+            .header(X_REAL_IP, "127.0.0.1")
+            .header(CONTENT_TYPE, "application/json")
+            .bearer_auth(TELLER_TOKEN)
+            .body(r#"{ "user_name": "#)
+            .send()
+            .await
+    );
+    let problem = problem_of(response, StatusCode::BAD_REQUEST).await;
+    assert_eq!(problem.code, "invalid_request");
+}
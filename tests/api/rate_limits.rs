@@ -0,0 +1,132 @@
+use crate::helpers::{spawn_app_with, TestApp, X_REAL_IP};
+use axum::http::StatusCode;
+use bankaccount::application::Version;
+use bankaccount::{AccountId, ApiTokenSettings, RateLimitQuota};
+use claim::{assert_ok, assert_some};
+use pretty_assertions::assert_eq;
+use secrecy::Secret;
+use serde_json::json;
+use std::time::Duration;
+
+const CLIENT_TOKEN: &str = "client-token";
+
+async fn spawn_limited_app() -> TestApp {
+    spawn_app_with(Version::latest(), |settings| {
+        // opening the account makes three writes with the teller's token registering its customer
+        settings.http_api.rate_limit.writes = Some(RateLimitQuota {
+            nr_requests: 4,
+            per_duration: Duration::from_secs(60),
+        });
+        settings.auth.tokens.extend([ApiTokenSettings {
+            subject: "client".to_string(),
+            token: Secret::new(CLIENT_TOKEN.to_string()),
+            scopes: vec!["admin:account".to_string()],
+        }]);
+    })
+    .await
+}
+
+/// Attempts to register a customer without a token, which is rejected but still counted against
+/// the address's write quota.
+async fn register_from(app: &TestApp, ip: &str) -> reqwest::Response {
+    assert_ok!(
+        app.api_client
+            .post(app.customers_url())
+            .header(X_REAL_IP, ip)
+            .json(&json!({
+                "name": "neo",
+                "email": "neo@example.com",
+                "mailing_address": "12 Seahawks Way, Renton, WA 98056, USA",
+            }))
+            .send()
+            .await
+    )
+}
+
+async fn deposit_from(app: &TestApp, account_id: AccountId, ip: &str) -> reqwest::Response {
+    assert_ok!(
+        app.api_client
+            .post(format!("{}/deposit/{}", app.bank_url(), account_id))
+            .header(X_REAL_IP, ip)
+            .bearer_auth(CLIENT_TOKEN)
+            .json(&json!({ "amount": "10.00", "currency": "USD" }))
+            .send()
+            .await
+    )
+}
+
+fn header<'r>(response: &'r reqwest::Response, name: &str) -> &'r str {
+    assert_ok!(assert_some!(response.headers().get(name)).to_str())
+}
+
+#[tokio::test]
+async fn writes_are_limited_per_client_ip_apart_from_reads() {
+    let app = spawn_limited_app().await;
+    let account_id = app.open_account().await;
+
+    let response = register_from(&app, "10.0.0.1").await;
+    assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+    assert_eq!(header(&response, "ratelimit-limit"), "4");
+    assert_eq!(header(&response, "ratelimit-remaining"), "3");
+
+    for remaining in ["2", "1", "0"] {
+        let response = register_from(&app, "10.0.0.1").await;
+        assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+        assert_eq!(header(&response, "ratelimit-remaining"), remaining);
+    }
+
+    let response = register_from(&app, "10.0.0.1").await;
+    assert_eq!(response.status(), StatusCode::TOO_MANY_REQUESTS);
+    assert_eq!(header(&response, "retry-after"), "15");
+
+    let response = assert_ok!(
+        app.api_client
+            .get(format!("{}/{}", app.bank_url(), account_id))
+            .header(X_REAL_IP, "10.0.0.1")
+            .bearer_auth(CLIENT_TOKEN)
+            .send()
+            .await
+    );
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(header(&response, "ratelimit-limit"), "100");
+
+    let response = register_from(&app, "10.0.0.2").await;
+    assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+}
+
+#[tokio::test]
+async fn authenticated_client_is_limited_across_addresses() {
+    let app = spawn_limited_app().await;
+    let account_id = app.open_account().await;
+
+    for ip in ["10.0.0.3", "10.0.0.4", "10.0.0.5", "10.0.0.6"] {
+        let response = deposit_from(&app, account_id, ip).await;
+        assert_eq!(response.status(), StatusCode::OK);
+    }
+
+    let response = deposit_from(&app, account_id, "10.0.0.7").await;
+    assert_eq!(response.status(), StatusCode::TOO_MANY_REQUESTS);
+
+    let response = register_from(&app, "10.0.0.7").await;
+    assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+}
+
+#[tokio::test]
+async fn forwarded_address_is_ignored_unless_the_peer_is_a_trusted_proxy() {
+    let app = spawn_app_with(Version::latest(), |settings| {
+        settings.http_api.rate_limit.writes = Some(RateLimitQuota {
+            nr_requests: 2,
+            per_duration: Duration::from_secs(60),
+        });
+        settings.http_api.trusted_proxies.clear();
+    })
+    .await;
+
+    for ip in ["10.0.0.8", "10.0.0.9"] {
+        let response = register_from(&app, ip).await;
+        assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+    }
+
+    let response = register_from(&app, "10.0.0.10").await;
+    assert_eq!(response.status(), StatusCode::TOO_MANY_REQUESTS);
+}
@@ -0,0 +1,108 @@
+use crate::helpers::{api_token, spawn_auth_app, TestApp, X_REAL_IP};
+use axum::http::StatusCode;
+use bankaccount::{AccountId, ApiTokenSettings, BankAccountView};
+use claim::assert_ok;
+use money2::{Currency, Money};
+use pretty_assertions::assert_eq;
+use serde_json::json;
+
+const ADMIN_TOKEN: &str = "ledger-ops-token";
+
+fn auth_tokens() -> [ApiTokenSettings; 1] {
+    [api_token("ledger-ops", ADMIN_TOKEN, &["admin:account"])]
+}
+
+async fn post_reversal(
+    app: &TestApp, account_id: AccountId, sequence: usize, token: Option<&str>,
+) -> reqwest::Response {
+    let mut request = app
+        .api_client
+        .post(format!(
+            "{}/{}/transactions/{}/reverse",
+            app.bank_url(),
+            account_id,
+            sequence
+        ))
+        .header(X_REAL_IP, "127.0.0.1")
+        .json(&json!({ "reason": "duplicate posting" }));
+    if let Some(token) = token {
+        request = request.bearer_auth(token);
+    }
+    assert_ok!(request.send().await)
+}
+
+#[tokio::test]
+async fn reversal_posts_a_linked_compensating_entry() {
+    let app = spawn_auth_app(auth_tokens()).await;
+    let account_id = app.open_funded_account("500.00").await;
+
+    let response = post_reversal(&app, account_id, 2, Some(ADMIN_TOKEN)).await;
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let response = app.get_serve_bank_account(account_id).await;
+    assert_eq!(response.status(), StatusCode::OK);
+    let view: BankAccountView = assert_ok!(response.json().await);
+    assert_eq!(view.balance, Money::new(0, 2, Currency::Usd));
+    assert_eq!(view.ledger.len(), 2);
+
+    let original = &view.ledger[0];
+    assert_eq!(original.sequence, Some(2));
+    assert_eq!(original.reversed_by, Some(3));
+
+    let reversal = &view.ledger[1];
+    assert_eq!(
+        reversal.description,
+        "Reversal of deposit: duplicate posting"
+    );
+    assert_eq!(reversal.amount, Money::new(-50000, 2, Currency::Usd));
+    assert_eq!(reversal.sequence, Some(3));
+    assert_eq!(reversal.reverses, Some(2));
+
+    let response = post_reversal(&app, account_id, 2, Some(ADMIN_TOKEN)).await;
+    assert_eq!(response.status(), StatusCode::CONFLICT);
+}
+
+#[tokio::test]
+async fn reversal_of_unknown_transaction_returns_a_404() {
+    let app = spawn_auth_app(auth_tokens()).await;
+    let account_id = app.open_funded_account("500.00").await;
+
+    let response = post_reversal(&app, account_id, 1, Some(ADMIN_TOKEN)).await;
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+
+    let response = post_reversal(&app, account_id, 42, Some(ADMIN_TOKEN)).await;
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn reversal_requires_an_admin_token() {
+    let app = spawn_auth_app(auth_tokens()).await;
+    let account_id = app.open_funded_account("500.00").await;
+
+    let response = post_reversal(&app, account_id, 2, None).await;
+    assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+}
+
+#[tokio::test]
+async fn reversal_of_a_spent_credit_is_rejected_for_insufficient_funds() {
+    let app = spawn_auth_app(auth_tokens()).await;
+    let account_id = app.open_funded_account("500.00").await;
+    let response = app
+        .post_atm_withdrawal(
+            account_id,
+            json!({ "atm_id": "abc_123", "amount": { "amount": "300.00", "currency": "USD" } }),
+        )
+        .await;
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let response = post_reversal(&app, account_id, 2, Some(ADMIN_TOKEN)).await;
+    assert_eq!(response.status(), StatusCode::UNPROCESSABLE_ENTITY);
+
+    let response = app.get_serve_bank_account(account_id).await;
+    assert_eq!(response.status(), StatusCode::OK);
+    let view: BankAccountView = assert_ok!(response.json().await);
+    assert_eq!(view.balance, Money::new(20000, 2, Currency::Usd));
+
+    let response = post_reversal(&app, account_id, 3, Some(ADMIN_TOKEN)).await;
+    assert_eq!(response.status(), StatusCode::OK);
+}
@@ -0,0 +1,478 @@
+use crate::helpers::{spawn_app_with, TestApp, TELLER_TOKEN, X_REAL_IP};
+use axum::http::StatusCode;
+use bankaccount::application::Version;
+use bankaccount::{
+    AccountId, ApiTokenSettings, StandingOrderId, StandingOrderStatus, StandingOrderView,
+};
+use chrono::{Duration, Utc};
+use claim::{assert_none, assert_ok, assert_some};
+use money2::{Currency, Money};
+use pretty_assertions::assert_eq;
+use secrecy::Secret;
+use serde_json::json;
+
+const HOLDER_TOKEN: &str = "neo-token";
+const STRANGER_TOKEN: &str = "smith-token";
+
+async fn spawn_scheduler_app(retry_window: std::time::Duration) -> TestApp {
+    spawn_app_with(Version::latest(), move |settings| {
+        settings.auth.tokens.extend([
+            ApiTokenSettings {
+                subject: "neo".to_string(),
+                token: Secret::new(HOLDER_TOKEN.to_string()),
+                scopes: vec![],
+            },
+            ApiTokenSettings {
+                subject: "smith".to_string(),
+                token: Secret::new(STRANGER_TOKEN.to_string()),
+                scopes: vec![],
+            },
+        ]);
+        settings.standing_orders.poll_interval = std::time::Duration::from_millis(250);
+        settings.standing_orders.retry_interval = std::time::Duration::from_secs(60);
+        settings.standing_orders.retry_window = retry_window;
+    })
+    .await
+}
+
+async fn post_standing_order(
+    app: &TestApp, account_id: AccountId, amount: &str, beneficiary: serde_json::Value,
+    schedule: serde_json::Value, token: Option<&str>,
+) -> reqwest::Response {
+    let mut request = app
+        .api_client
+        .post(app.standing_orders_url())
+        .header(X_REAL_IP, "127.0.0.1")
+        .json(&json!({
+            "account_id": account_id,
+            "amount": { "amount": amount, "currency": "USD" },
+            "beneficiary": beneficiary,
+            "schedule": schedule,
+        }));
+    if let Some(token) = token {
+        request = request.bearer_auth(token);
+    }
+    assert_ok!(request.send().await)
+}
+
+async fn create_standing_order(
+    app: &TestApp, account_id: AccountId, amount: &str, beneficiary: serde_json::Value,
+    schedule: serde_json::Value,
+) -> StandingOrderId {
+    let response = post_standing_order(
+        app,
+        account_id,
+        amount,
+        beneficiary,
+        schedule,
+        Some(HOLDER_TOKEN),
+    )
+    .await;
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_ok!(response.json().await)
+}
+
+async fn cancel_standing_order(
+    app: &TestApp, order_id: StandingOrderId, token: Option<&str>,
+) -> reqwest::Response {
+    let mut request = app
+        .api_client
+        .post(format!("{}/{}/cancel", app.standing_orders_url(), order_id))
+        .header(X_REAL_IP, "127.0.0.1");
+    if let Some(token) = token {
+        request = request.bearer_auth(token);
+    }
+    assert_ok!(request.send().await)
+}
+
+async fn get_standing_order(
+    app: &TestApp, order_id: StandingOrderId, token: Option<&str>,
+) -> reqwest::Response {
+    let mut request = app
+        .api_client
+        .get(format!("{}/{}", app.standing_orders_url(), order_id))
+        .header(X_REAL_IP, "127.0.0.1");
+    if let Some(token) = token {
+        request = request.bearer_auth(token);
+    }
+    assert_ok!(request.send().await)
+}
+
+async fn list_standing_orders(
+    app: &TestApp, account_id: Option<AccountId>, token: Option<&str>,
+) -> reqwest::Response {
+    let mut request = app
+        .api_client
+        .get(app.standing_orders_url())
+        .header(X_REAL_IP, "127.0.0.1");
+    if let Some(account_id) = account_id {
+        request = request.query(&[("account_id", account_id.to_string())]);
+    }
+    if let Some(token) = token {
+        request = request.bearer_auth(token);
+    }
+    assert_ok!(request.send().await)
+}
+
+async fn standing_order_view(app: &TestApp, order_id: StandingOrderId) -> StandingOrderView {
+    let response = get_standing_order(app, order_id, Some(HOLDER_TOKEN)).await;
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_ok!(response.json().await)
+}
+
+/// Polls the standing order until the scheduler has attempted payment of its first occurrence.
+async fn await_first_attempt(app: &TestApp, order_id: StandingOrderId) -> StandingOrderView {
+    let mut view = standing_order_view(app, order_id).await;
+    for _ in 0..40 {
+        if view.updated_at != view.created_at {
+            break;
+        }
+        tokio::time::sleep(std::time::Duration::from_millis(250)).await;
+        view = standing_order_view(app, order_id).await;
+    }
+    view
+}
+
+fn usd(amount: i64) -> Money {
+    Money::new(amount * 100, 2, Currency::Usd)
+}
+
+fn payee() -> serde_json::Value {
+    json!({ "payee": { "name": "Renton Water District" } })
+}
+
+#[tokio::test]
+async fn future_dated_payment_is_paid_once_due() {
+    let app = spawn_scheduler_app(std::time::Duration::from_secs(3600)).await;
+    let account_id = app.open_funded_account("500.00").await;
+
+    let order_id = create_standing_order(
+        &app,
+        account_id,
+        "120.00",
+        payee(),
+        json!({ "once": { "at": Utc::now() + Duration::seconds(1) } }),
+    )
+    .await;
+    assert_eq!(
+        standing_order_view(&app, order_id).await.status,
+        StandingOrderStatus::Scheduled
+    );
+
+    let view = await_first_attempt(&app, order_id).await;
+    assert_eq!(view.status, StandingOrderStatus::Completed);
+    assert_some!(view.last_paid_at);
+    assert_none!(view.next_due_at);
+
+    let account = app.account_view(account_id).await;
+    assert_eq!(account.balance, usd(380));
+    let entry = assert_some!(account.ledger.last());
+    assert_eq!(
+        entry.description,
+        format!("Standing order {order_id} to Renton Water District")
+    );
+    assert_eq!(entry.amount, usd(-120));
+}
+
+#[tokio::test]
+async fn recurring_transfer_credits_beneficiary_account() {
+    let app = spawn_scheduler_app(std::time::Duration::from_secs(3600)).await;
+    let from_account = app.open_funded_account("500.00").await;
+    let to_account = app.open_funded_account("10.00").await;
+
+    let starting = Utc::now() + Duration::seconds(1);
+    let order_id = create_standing_order(
+        &app,
+        from_account,
+        "75.00",
+        json!({ "account": { "account_id": to_account } }),
+        json!({ "weekly": { "starting": starting } }),
+    )
+    .await;
+
+    let view = await_first_attempt(&app, order_id).await;
+    assert_eq!(view.status, StandingOrderStatus::Scheduled);
+    assert_eq!(
+        assert_some!(view.next_due_at).timestamp(),
+        (starting + Duration::weeks(1)).timestamp()
+    );
+
+    assert_eq!(app.account_view(from_account).await.balance, usd(425));
+    let beneficiary = app.account_view(to_account).await;
+    assert_eq!(beneficiary.balance, usd(85));
+    assert_eq!(
+        assert_some!(beneficiary.ledger.last()).description,
+        format!("Standing order {order_id} from account {from_account}")
+    );
+}
+
+#[tokio::test]
+async fn declined_payment_is_retried_within_retry_window() {
+    let app = spawn_scheduler_app(std::time::Duration::from_secs(3600)).await;
+    let account_id = app.open_funded_account("50.00").await;
+
+    let order_id = create_standing_order(
+        &app,
+        account_id,
+        "120.00",
+        payee(),
+        json!({ "once": { "at": Utc::now() + Duration::seconds(1) } }),
+    )
+    .await;
+
+    let view = await_first_attempt(&app, order_id).await;
+    assert_eq!(view.status, StandingOrderStatus::Scheduled);
+    assert_some!(view.retry_at);
+    assert!(assert_some!(view.last_failure).contains("funds not available"));
+    assert_eq!(app.account_view(account_id).await.balance, usd(50));
+}
+
+#[tokio::test]
+async fn declined_payment_fails_after_retry_window() {
+    let app = spawn_scheduler_app(std::time::Duration::ZERO).await;
+    let account_id = app.open_funded_account("50.00").await;
+
+    let order_id = create_standing_order(
+        &app,
+        account_id,
+        "120.00",
+        payee(),
+        json!({ "once": { "at": Utc::now() + Duration::seconds(1) } }),
+    )
+    .await;
+
+    let view = await_first_attempt(&app, order_id).await;
+    assert_eq!(view.status, StandingOrderStatus::Failed);
+    assert_none!(view.retry_at);
+    assert_some!(view.last_failure);
+    assert_eq!(app.account_view(account_id).await.balance, usd(50));
+}
+
+#[tokio::test]
+async fn cancelled_standing_order_is_not_paid() {
+    let app = spawn_scheduler_app(std::time::Duration::from_secs(3600)).await;
+    let account_id = app.open_funded_account("500.00").await;
+
+    let order_id = create_standing_order(
+        &app,
+        account_id,
+        "120.00",
+        payee(),
+        json!({ "cron": { "expression": "0 0 9 * * Mon" } }),
+    )
+    .await;
+
+    let response = cancel_standing_order(&app, order_id, Some(HOLDER_TOKEN)).await;
+    assert_eq!(response.status(), StatusCode::OK);
+    let view = standing_order_view(&app, order_id).await;
+    assert_eq!(view.status, StandingOrderStatus::Cancelled);
+    assert_none!(view.next_due_at);
+
+    let response = cancel_standing_order(&app, order_id, Some(HOLDER_TOKEN)).await;
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+
+    let response = list_standing_orders(&app, Some(account_id), Some(HOLDER_TOKEN)).await;
+    assert_eq!(response.status(), StatusCode::OK);
+    let orders: Vec<StandingOrderView> = assert_ok!(response.json().await);
+    assert_eq!(orders, vec![view]);
+}
+
+/// Moves the standing order's schedule entry back to an hour ago, as if the index had fallen behind.
+async fn backdate_schedule_entry(app: &TestApp) -> String {
+    let aggregate_id: String = assert_ok!(
+        sqlx::query_scalar(
+            "SELECT aggregate_id FROM events WHERE aggregate_type = 'standing_order' LIMIT 1"
+        )
+        .fetch_one(&app.db_pool)
+        .await
+    );
+    assert_ok!(
+        sqlx::query(
+            "INSERT INTO standing_order_schedule (aggregate_id, next_attempt_at) VALUES ($1, \
+             now() - interval '1 hour') ON CONFLICT (aggregate_id) DO UPDATE SET \
+             next_attempt_at = EXCLUDED.next_attempt_at"
+        )
+        .bind(&aggregate_id)
+        .execute(&app.db_pool)
+        .await
+    );
+    aggregate_id
+}
+
+/// Polls the schedule entry of the standing order until it is no longer due, returning its next
+/// attempt time, if it is still scheduled.
+async fn await_schedule_settled(
+    app: &TestApp, aggregate_id: &str,
+) -> Option<chrono::DateTime<Utc>> {
+    let mut next_attempt_at = None;
+    for _ in 0..40 {
+        next_attempt_at = assert_ok!(
+            sqlx::query_scalar(
+                "SELECT next_attempt_at FROM standing_order_schedule WHERE aggregate_id = $1"
+            )
+            .bind(aggregate_id)
+            .fetch_optional(&app.db_pool)
+            .await
+        );
+        if next_attempt_at.is_none_or(|next_attempt_at| Utc::now() < next_attempt_at) {
+            break;
+        }
+        tokio::time::sleep(std::time::Duration::from_millis(250)).await;
+    }
+    next_attempt_at
+}
+
+#[tokio::test]
+async fn stale_schedule_entry_is_deferred_until_the_order_is_due() {
+    let app = spawn_scheduler_app(std::time::Duration::from_secs(3600)).await;
+    let account_id = app.open_funded_account("500.00").await;
+
+    let due_at = Utc::now() + Duration::days(1);
+    let order_id = create_standing_order(
+        &app,
+        account_id,
+        "120.00",
+        payee(),
+        json!({ "once": { "at": due_at } }),
+    )
+    .await;
+
+    let aggregate_id = backdate_schedule_entry(&app).await;
+    let next_attempt_at = assert_some!(await_schedule_settled(&app, &aggregate_id).await);
+    assert_eq!(next_attempt_at.timestamp(), due_at.timestamp());
+    assert_eq!(
+        standing_order_view(&app, order_id).await.status,
+        StandingOrderStatus::Scheduled
+    );
+    assert_eq!(app.account_view(account_id).await.balance, usd(500));
+}
+
+#[tokio::test]
+async fn schedule_entry_of_cancelled_order_is_removed() {
+    let app = spawn_scheduler_app(std::time::Duration::from_secs(3600)).await;
+    let account_id = app.open_funded_account("500.00").await;
+
+    let order_id = create_standing_order(
+        &app,
+        account_id,
+        "120.00",
+        payee(),
+        json!({ "cron": { "expression": "0 0 9 * * Mon" } }),
+    )
+    .await;
+    let response = cancel_standing_order(&app, order_id, Some(HOLDER_TOKEN)).await;
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let aggregate_id = backdate_schedule_entry(&app).await;
+    assert_none!(await_schedule_settled(&app, &aggregate_id).await);
+    assert_eq!(app.account_view(account_id).await.balance, usd(500));
+}
+
+#[tokio::test]
+async fn invalid_standing_orders_are_rejected() {
+    let app = spawn_scheduler_app(std::time::Duration::from_secs(3600)).await;
+    let account_id = app.open_funded_account("500.00").await;
+
+    let response = post_standing_order(
+        &app,
+        account_id,
+        "120.00",
+        json!({ "account": { "account_id": account_id } }),
+        json!({ "monthly": { "starting": Utc::now() + Duration::days(1) } }),
+        Some(HOLDER_TOKEN),
+    )
+    .await;
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+
+    let response = post_standing_order(
+        &app,
+        account_id,
+        "120.00",
+        payee(),
+        json!({ "cron": { "expression": "every monday" } }),
+        Some(HOLDER_TOKEN),
+    )
+    .await;
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+
+    let response = post_standing_order(
+        &app,
+        account_id,
+        "120.00",
+        payee(),
+        json!({ "once": { "at": Utc::now() - Duration::days(1) } }),
+        Some(HOLDER_TOKEN),
+    )
+    .await;
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+}
+
+#[tokio::test]
+async fn standing_orders_require_a_signer_of_open_accounts() {
+    let app = spawn_scheduler_app(std::time::Duration::from_secs(3600)).await;
+    let account_id = app.open_funded_account("500.00").await;
+    let schedule = json!({ "monthly": { "starting": Utc::now() + Duration::days(1) } });
+
+    let response =
+        post_standing_order(&app, account_id, "120.00", payee(), schedule.clone(), None).await;
+    assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+
+    let response = post_standing_order(
+        &app,
+        account_id,
+        "120.00",
+        payee(),
+        schedule.clone(),
+        Some(STRANGER_TOKEN),
+    )
+    .await;
+    assert_eq!(response.status(), StatusCode::FORBIDDEN);
+
+    let response = post_standing_order(
+        &app,
+        account_id,
+        "120.00",
+        json!({ "account": { "account_id": 7006077196242653184_u64 } }),
+        schedule.clone(),
+        Some(HOLDER_TOKEN),
+    )
+    .await;
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+
+    let order_id = create_standing_order(&app, account_id, "120.00", payee(), schedule).await;
+    let response = cancel_standing_order(&app, order_id, None).await;
+    assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+    let response = cancel_standing_order(&app, order_id, Some(STRANGER_TOKEN)).await;
+    assert_eq!(response.status(), StatusCode::FORBIDDEN);
+    assert_eq!(
+        standing_order_view(&app, order_id).await.status,
+        StandingOrderStatus::Scheduled
+    );
+}
+
+#[tokio::test]
+async fn standing_orders_are_served_only_to_holders_and_admins() {
+    let app = spawn_scheduler_app(std::time::Duration::from_secs(3600)).await;
+    let account_id = app.open_funded_account("500.00").await;
+    let schedule = json!({ "monthly": { "starting": Utc::now() + Duration::days(1) } });
+    let order_id = create_standing_order(&app, account_id, "120.00", payee(), schedule).await;
+
+    let response = get_standing_order(&app, order_id, None).await;
+    assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+    let response = get_standing_order(&app, order_id, Some(STRANGER_TOKEN)).await;
+    assert_eq!(response.status(), StatusCode::FORBIDDEN);
+    let response = get_standing_order(&app, order_id, Some(TELLER_TOKEN)).await;
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let response = list_standing_orders(&app, Some(account_id), None).await;
+    assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+    let response = list_standing_orders(&app, Some(account_id), Some(STRANGER_TOKEN)).await;
+    assert_eq!(response.status(), StatusCode::FORBIDDEN);
+
+    let response = list_standing_orders(&app, None, Some(HOLDER_TOKEN)).await;
+    assert_eq!(response.status(), StatusCode::FORBIDDEN);
+    let response = list_standing_orders(&app, None, Some(TELLER_TOKEN)).await;
+    assert_eq!(response.status(), StatusCode::OK);
+    let orders: Vec<StandingOrderView> = assert_ok!(response.json().await);
+    assert!(orders.iter().any(|order| order.order_id == Some(order_id)));
+}
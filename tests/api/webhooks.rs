@@ -0,0 +1,256 @@
+use crate::helpers::{spawn_app_with, TestApp};
+use axum::http::StatusCode;
+use bankaccount::application::Version;
+use bankaccount::ApiTokenSettings;
+use claim::assert_ok;
+use hmac::{Hmac, Mac};
+use pretty_assertions::assert_eq;
+use secrecy::Secret;
+use serde_json::json;
+use sha2::Sha256;
+use std::time::Duration;
+use wiremock::matchers::{method, path};
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+const SECRET: &str = "partner-shared-secret";
+const ADMIN_TOKEN: &str = "partner-desk-token";
+
+async fn spawn_webhook_app() -> TestApp {
+    spawn_app_with(Version::latest(), |settings| {
+        settings.auth.tokens.extend([ApiTokenSettings {
+            subject: "partner-desk".to_string(),
+            token: Secret::new(ADMIN_TOKEN.to_string()),
+            scopes: vec!["admin:account".to_string()],
+        }]);
+        settings.webhooks.enabled = true;
+        settings.webhooks.poll_interval = Duration::from_millis(50);
+        settings.webhooks.max_attempts = 2;
+        settings.webhooks.backoff.initial_delay = Duration::from_millis(10);
+        settings.webhooks.backoff.max_delay = Duration::from_millis(50);
+    })
+    .await
+}
+
+fn header_value(request: &wiremock::Request, name: &str) -> String {
+    request
+        .headers
+        .iter()
+        .find(|(header_name, _)| header_name.as_str() == name)
+        .map(|(_, values)| values.last().as_str().to_string())
+        .unwrap_or_else(|| panic!("missing {name} header"))
+}
+
+async fn await_deliveries(
+    app: &TestApp, subscription_id: i64, status: &str, expected: usize,
+) -> Vec<serde_json::Value> {
+    let mut deliveries = Vec::new();
+    for _ in 0..100 {
+        let response = app
+            .get_webhook_deliveries(subscription_id, &[("status", status)], ADMIN_TOKEN)
+            .await;
+        assert_eq!(response.status(), StatusCode::OK);
+        deliveries = assert_ok!(response.json().await);
+        if deliveries.len() == expected {
+            break;
+        }
+        tokio::time::sleep(Duration::from_millis(50)).await;
+    }
+    deliveries
+}
+
+#[tokio::test]
+async fn webhook_deliveries_are_signed_and_filtered_by_event_type() {
+    let subscriber = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path("/hooks"))
+        .respond_with(ResponseTemplate::new(204))
+        .mount(&subscriber)
+        .await;
+
+    let app = spawn_webhook_app().await;
+    let account_id = app.open_account().await;
+
+    let response = app
+        .post_webhook_subscription(
+            json!({
+                "target_url": format!("{}/hooks", subscriber.uri()),
+                "event_types": ["balance_deposited"],
+                "account_ids": [account_id],
+                "secret": SECRET,
+            }),
+            Some(ADMIN_TOKEN),
+        )
+        .await;
+    assert_eq!(response.status(), StatusCode::CREATED);
+    let subscription: serde_json::Value = assert_ok!(response.json().await);
+    assert_eq!(subscription.get("secret"), None);
+    let subscription_id = subscription["id"].as_i64().expect("subscription id");
+
+    let response = app
+        .post_deposit_amount(account_id, json!({ "amount": "12.34", "currency": "USD" }))
+        .await;
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let delivered = await_deliveries(&app, subscription_id, "delivered", 1).await;
+    assert_eq!(delivered.len(), 1);
+    assert_eq!(delivered[0]["event_type"], json!("balance_deposited"));
+    assert_eq!(delivered[0]["last_status_code"], json!(204));
+
+    let received = subscriber.received_requests().await.unwrap_or_default();
+    assert_eq!(received.len(), 1);
+    let request = &received[0];
+    let timestamp = header_value(request, "x-bank-timestamp");
+    let signature = header_value(request, "x-bank-signature");
+
+    let mut mac = assert_ok!(Hmac::<Sha256>::new_from_slice(SECRET.as_bytes()));
+    mac.update(timestamp.as_bytes());
+    mac.update(b".");
+    mac.update(&request.body);
+    let expected = format!("sha256={}", hex::encode(mac.finalize().into_bytes()));
+    assert_eq!(signature, expected);
+
+    let body: serde_json::Value = assert_ok!(request.body_json());
+    assert_eq!(body["aggregate_type"], json!("account"));
+    assert_eq!(body["event_type"], json!("balance_deposited"));
+    assert_eq!(body["sequence"], json!(2));
+}
+
+#[tokio::test]
+async fn webhook_deliveries_are_limited_to_account_events() {
+    let subscriber = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path("/hooks"))
+        .respond_with(ResponseTemplate::new(204))
+        .mount(&subscriber)
+        .await;
+
+    let app = spawn_webhook_app().await;
+    let response = app
+        .post_webhook_subscription(
+            json!({ "target_url": format!("{}/hooks", subscriber.uri()), "secret": SECRET }),
+            Some(ADMIN_TOKEN),
+        )
+        .await;
+    assert_eq!(response.status(), StatusCode::CREATED);
+    let subscription: serde_json::Value = assert_ok!(response.json().await);
+    let subscription_id = subscription["id"].as_i64().expect("subscription id");
+
+    // opening the account also records the events registering and verifying its customer
+    app.open_account().await;
+
+    for _ in 0..100 {
+        let undispatched: i64 = assert_ok!(
+            sqlx::query_scalar(
+                "SELECT count(*) FROM event_outbox WHERE webhooks_dispatched_at IS NULL"
+            )
+            .fetch_one(&app.db_pool)
+            .await
+        );
+        if undispatched == 0 {
+            break;
+        }
+        tokio::time::sleep(Duration::from_millis(50)).await;
+    }
+
+    let delivered = await_deliveries(&app, subscription_id, "delivered", 1).await;
+    assert_eq!(delivered.len(), 1);
+    assert_eq!(delivered[0]["aggregate_type"], json!("account"));
+    assert_eq!(delivered[0]["event_type"], json!("account_opened"));
+}
+
+#[tokio::test]
+async fn failing_webhook_deliveries_are_dead_lettered_and_can_be_redelivered() {
+    let subscriber = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path("/hooks"))
+        .respond_with(ResponseTemplate::new(503))
+        .mount(&subscriber)
+        .await;
+
+    let app = spawn_webhook_app().await;
+    let response = app
+        .post_webhook_subscription(
+            json!({
+                "target_url": format!("{}/hooks", subscriber.uri()),
+                "event_types": ["account_opened"],
+                "secret": SECRET,
+            }),
+            Some(ADMIN_TOKEN),
+        )
+        .await;
+    assert_eq!(response.status(), StatusCode::CREATED);
+    let subscription: serde_json::Value = assert_ok!(response.json().await);
+    let subscription_id = subscription["id"].as_i64().expect("subscription id");
+
+    app.open_account().await;
+
+    let dead = await_deliveries(&app, subscription_id, "dead", 1).await;
+    assert_eq!(dead.len(), 1);
+    assert_eq!(dead[0]["attempts"], json!(2));
+    assert_eq!(dead[0]["last_status_code"], json!(503));
+
+    let response = assert_ok!(
+        app.api_client
+            .get(format!("{}/dead_letters", app.webhooks_url()))
+            .header("x-real-ip", "127.0.0.1")
+            .bearer_auth(ADMIN_TOKEN)
+            .send()
+            .await
+    );
+    assert_eq!(response.status(), StatusCode::OK);
+    let dead_letters: Vec<serde_json::Value> = assert_ok!(response.json().await);
+    assert_eq!(dead_letters.len(), 1);
+    assert_eq!(dead_letters[0]["id"], dead[0]["id"]);
+
+    let response = assert_ok!(
+        app.api_client
+            .post(format!(
+                "{}/deliveries/{}/redeliver",
+                app.webhooks_url(),
+                dead[0]["id"]
+            ))
+            .header("x-real-ip", "127.0.0.1")
+            .bearer_auth(ADMIN_TOKEN)
+            .send()
+            .await
+    );
+    assert_eq!(response.status(), StatusCode::OK);
+    let requeued: serde_json::Value = assert_ok!(response.json().await);
+    assert_eq!(requeued["status"], json!("pending"));
+}
+
+#[tokio::test]
+async fn webhook_subscription_requires_a_strong_secret() {
+    let app = spawn_webhook_app().await;
+    let response = app
+        .post_webhook_subscription(
+            json!({
+                "target_url": "https://partner.example.com/hooks",
+                "secret": "short",
+            }),
+            Some(ADMIN_TOKEN),
+        )
+        .await;
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+}
+
+#[tokio::test]
+async fn webhook_subscription_requires_an_admin_token() {
+    let app = spawn_webhook_app().await;
+    let subscription = json!({
+        "target_url": "https://partner.example.com/hooks",
+        "secret": SECRET,
+    });
+
+    let response = app.post_webhook_subscription(subscription.clone(), None).await;
+    assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+
+    let response = assert_ok!(
+        app.api_client
+            .get(app.webhooks_url())
+            .header("x-real-ip", "127.0.0.1")
+            .send()
+            .await
+    );
+    assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+}